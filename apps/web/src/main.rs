@@ -7,7 +7,9 @@ use futures::StreamExt;
 use prsnl_core::{AppEvent, ConnectionStatus, SharedEventBus, SharedTransport};
 use prsnl_platform_web::{WebEventBus, WebTransport};
 use prsnl_ui::{
-    provide_chat_feature, provide_conversations_feature, provide_settings_feature, ResponsiveApp,
+    provide_chat_feature, provide_conversations_feature, provide_settings_feature,
+    provide_notifications_feature, provide_search_feature, provide_speech_feature, ResponsiveApp,
+    DEFAULT_MESSAGE_LIMIT,
 };
 use tracing::info;
 
@@ -37,24 +39,44 @@ fn App() -> Element {
     // Initialize features
     // ============================================
 
-    // Conversations feature
-    let (conv_state, conv_service) = use_hook(|| {
-        provide_conversations_feature(event_bus.clone(), transport.clone())
-    });
-    use_context_provider(|| conv_state.clone());
-    use_context_provider(|| conv_service.clone());
-
     // Chat feature
     let (chat_state, chat_service) =
-        use_hook(|| provide_chat_feature(event_bus.clone(), transport.clone()));
+        use_hook(|| provide_chat_feature(event_bus.clone(), transport.clone(), DEFAULT_MESSAGE_LIMIT));
     use_context_provider(|| chat_state.clone());
     use_context_provider(|| chat_service.clone());
 
+    // Conversations feature (reads loaded messages for list previews)
+    let (conv_state, conv_service) = use_hook(|| {
+        provide_conversations_feature(event_bus.clone(), transport.clone(), chat_state.clone())
+    });
+    use_context_provider(|| conv_state.clone());
+    use_context_provider(|| conv_service.clone());
+
     // Settings feature
     let (settings_state, settings_service) =
         use_hook(|| provide_settings_feature(event_bus.clone()));
     use_context_provider(|| settings_state.clone());
     use_context_provider(|| settings_service.clone());
+    // Pre-fill the server from a setup link before the first connection
+    use_hook(|| settings_service.apply_launch_config());
+
+    // Speech feature (reads replies aloud based on settings)
+    let speech_service =
+        use_hook(|| provide_speech_feature(event_bus.clone(), settings_state.clone()));
+    use_context_provider(|| speech_service.clone());
+
+    // Notifications feature (toasts for background conversations)
+    let (notifications_state, notifications_service) = use_hook(|| {
+        provide_notifications_feature(event_bus.clone(), chat_state.clone(), conv_state.clone())
+    });
+    use_context_provider(|| notifications_state);
+    use_context_provider(|| notifications_service.clone());
+
+    // Search feature (message search across conversations)
+    let (search_state, search_service) =
+        use_hook(|| provide_search_feature(event_bus.clone(), transport.clone()));
+    use_context_provider(|| search_state);
+    use_context_provider(|| search_service.clone());
 
     // ============================================
     // Subscribe features to events
@@ -64,10 +86,16 @@ fn App() -> Element {
         let conv_service = conv_service.clone();
         let chat_service = chat_service.clone();
         let settings_service = settings_service.clone();
+        let speech_service = speech_service.clone();
+        let notifications_service = notifications_service.clone();
+        let search_service = search_service.clone();
         use_effect(move || {
             conv_service.subscribe_to_events();
             chat_service.subscribe_to_events();
             settings_service.subscribe_to_events();
+            speech_service.subscribe_to_events();
+            notifications_service.subscribe_to_events();
+            search_service.subscribe_to_events();
         });
     }
 
@@ -103,27 +131,33 @@ fn App() -> Element {
         let settings_state = settings_state.clone();
         move || {
             let _trigger = reconnect_trigger.read(); // Subscribe to trigger
-            let url = settings_state.server_url();
+            let (url, auth_token) = settings_state.connection_target();
             let transport = transport.clone();
             let event_bus = event_bus.clone();
 
             spawn(async move {
+                // Stop the previous connection and its reconnect loop first
+                let _ = transport.disconnect().await;
                 info!("Connecting to WebSocket server: {}", url);
-                if let Err(e) = transport.connect(url, event_bus).await {
+                if let Err(e) = transport.connect(url, auth_token, event_bus).await {
                     info!("Connection error: {}", e);
                 }
             });
         }
     });
 
-    // Listen for server URL changes to trigger reconnection
+    // Listen for server URL/token changes and manual reconnects to trigger reconnection
+    // (live only: a replayed request must not reconnect again)
     use_effect({
         let event_bus = event_bus.clone();
         move || {
-            let mut rx = event_bus.subscribe();
+            let mut rx = event_bus.subscribe_live();
             spawn(async move {
                 while let Some(event) = rx.next().await {
-                    if let AppEvent::ServerUrlChanged(_) = event {
+                    if matches!(
+                        event,
+                        AppEvent::ServerUrlChanged(_) | AppEvent::AuthTokenChanged | AppEvent::ReconnectRequested
+                    ) {
                         let current = *reconnect_trigger.read();
                         reconnect_trigger.set(current.wrapping_add(1));
                     }
@@ -3,32 +3,289 @@
 //! This module defines the event types only. Platform-specific implementations
 //! of the event bus are provided by platform-native and platform-web crates.
 
-use crate::types::{ConnectionStatus, Conversation, Message};
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+
+use crate::protocol::HISTORY_BATCH_SIZE;
+use crate::types::{ConnectionStatus, Conversation, ConversationLabels, Message, SearchResult};
+
+/// Default number of recent events event buses replay to new subscribers
+pub const DEFAULT_REPLAY_LEN: usize = 16;
 
 /// Application-wide events for cross-feature communication
 #[derive(Debug, Clone)]
 pub enum AppEvent {
+    // Lifecycle events
+    /// The first conversation list has been applied and every feature service
+    /// is subscribed (published once per app run)
+    AppReady,
+
     // Connection events
     ConnectionChanged(ConnectionStatus),
+    /// The user asked to reconnect now (e.g. from the offline banner)
+    ReconnectRequested,
+    /// The server rejected the auth token; the transport won't reconnect
+    /// until the token changes (the status becomes `ConnectionStatus::AuthError`)
+    AuthFailed { reason: Option<String> },
 
     // Conversation events
     ConversationSelected(String),
-    ConversationCreated { id: String, title: Option<String> },
+    /// The user opened a conversation that had `unread` messages waiting
+    /// (published just before its `ConversationSelected`)
+    ConversationOpened { id: String, unread: u32 },
+    /// A conversation was created; `reply_to` is the request id (as returned
+    /// by `send_create_conversation`) when this client asked for it
+    ConversationCreated { id: String, title: Option<String>, reply_to: Option<String> },
+    /// A conversation created on this client got its server id
+    ConversationConfirmed { local_id: String, id: String },
+    /// A conversation created on this client could not be created
+    ConversationCreateFailed { local_id: String, error: String },
+    /// The server rejected a request that names no message, like a
+    /// conversation creation (`request_id` is the id the `send_*` call returned)
+    RequestFailed { request_id: String, error: String },
     ConversationDeleted(String),
     ConversationsLoaded(Vec<Conversation>),
+    /// Server confirmed new tags/color for a conversation
+    ConversationLabelsChanged { conv_id: String, labels: ConversationLabels },
+    /// A conversation got a new title (by this client, or confirmed by the server)
+    ConversationRenamed { conv_id: String, title: String },
+    /// Server confirmed a conversation was pinned or unpinned
+    ConversationPinned { conv_id: String, pinned: bool },
 
     // Chat events
     MessageSent { conv_id: String, message: Message },
-    MessageReceived { conv_id: String, message: Message },
+    /// A reply arrived; `reply_to` is the wire id (the id given to `send_chat`)
+    /// of the message it answers, when the server said
+    MessageReceived { conv_id: String, message: Message, reply_to: Option<String> },
     MessageError { conv_id: String, msg_id: String, error: String },
+    /// A chat message queued while disconnected went out after reconnecting
+    /// (`wire_id` is the id it was given to `send_chat` with)
+    QueuedMessageSent { conv_id: String, wire_id: String },
+    /// The server acknowledged receiving a chat message, ahead of its reply
+    /// (`wire_id` is the id it was given to `send_chat` with; `received_at` is the
+    /// server's time for it, when given)
+    MessageAcked { conv_id: String, wire_id: String, received_at: Option<DateTime<Utc>> },
+    /// A sent message's body was changed (by this client or confirmed by the server)
+    MessageEdited { conv_id: String, msg_id: String, new_body: String },
+    /// A message was deleted (by this client, or confirmed by the server)
+    MessageDeleted { conv_id: String, msg_id: String },
+    /// An emoji was added to or taken off a message; `count` is the new
+    /// total for it (zero removes it)
+    MessageReaction { conv_id: String, msg_id: String, emoji: String, count: u32 },
+    /// The server read this client's messages up to and including `up_to_msg_id`
+    ReadReceipt { conv_id: String, up_to_msg_id: String },
     TypingChanged { conv_id: String, is_typing: bool },
-    HistoryLoaded { conv_id: String, messages: Vec<Message> },
+    /// A history page; `has_more` says whether older messages exist (None if the server didn't say)
+    HistoryLoaded { conv_id: String, messages: Vec<Message>, has_more: Option<bool> },
+    /// Part of a large history response, delivered newest batch first so the
+    /// latest messages show right away; each batch is older than the previous one
+    HistoryBatch { conv_id: String, messages: Vec<Message>, first: bool, last: bool, has_more: Option<bool> },
+
+    // Search events
+    /// Messages matching `query`, in the order the server ranked them
+    SearchResultsLoaded { query: String, results: Vec<SearchResult> },
+    /// The user opened a search result; the chat scrolls to its message once
+    /// loaded (published before the conversation is selected)
+    SearchResultOpened(SearchResult),
+
+    // Notification events
+    /// A notification pushed by the server (e.g. a reminder)
+    NotificationReceived { title: String, body: String, category: String },
 
     // Settings events
     ServerUrlChanged(String),
+    /// The auth token was changed or cleared (the token itself stays in settings)
+    AuthTokenChanged,
     SettingsModalToggled(bool),
 
     // Navigation events
     NavigateToList,
     NavigateToChat(String),
+    /// Open a conversation scrolled to a specific message
+    NavigateToMessage { conv_id: String, msg_id: String },
+}
+
+/// Events replayed to late subscribers
+///
+/// The transport may publish `ConnectionChanged` and `ConversationsLoaded`
+/// before every feature has subscribed (especially on web, where effects run
+/// after the first render). Event buses record published events here and
+/// hand the snapshot to each new subscriber.
+///
+/// Two kinds of events are kept:
+/// - "state" events (`ConnectionChanged`, `ConversationsLoaded`, `AppReady`):
+///   only the latest of each, so a replay never walks the connection status
+///   through outdated values
+/// - with `with_recent(n)`, the last `n` events of every other kind
+///
+/// Ordering guarantee: a new subscriber first receives the kept events, in
+/// the order they were originally published, followed by every event
+/// published after it subscribed. No event is delivered twice.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayBuffer {
+    /// Latest event of each state kind, with its publish sequence number
+    latest: Vec<(u64, AppEvent)>,
+    /// Most recent other events, oldest first
+    recent: VecDeque<(u64, AppEvent)>,
+    recent_len: usize,
+    next_seq: u64,
+}
+
+impl ReplayBuffer {
+    /// Buffer that replays state events only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer that also replays the last `recent_len` events of other kinds
+    pub fn with_recent(recent_len: usize) -> Self {
+        Self {
+            recent_len,
+            ..Self::default()
+        }
+    }
+
+    /// Record a published event
+    pub fn record(&mut self, event: &AppEvent) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if matches!(
+            event,
+            AppEvent::ConnectionChanged(_) | AppEvent::ConversationsLoaded(_) | AppEvent::AppReady
+        ) {
+            let kind = std::mem::discriminant(event);
+            self.latest.retain(|(_, e)| std::mem::discriminant(e) != kind);
+            self.latest.push((seq, event.clone()));
+        } else if self.recent_len > 0 {
+            if self.recent.len() == self.recent_len {
+                self.recent.pop_front();
+            }
+            self.recent.push_back((seq, event.clone()));
+        }
+    }
+
+    /// Events to deliver to a new subscriber, oldest first
+    pub fn snapshot(&self) -> Vec<AppEvent> {
+        self.snapshot_matching(|_| true)
+    }
+
+    /// Events to deliver to a filtered subscriber, oldest first
+    ///
+    /// Only the events that match are copied.
+    pub fn snapshot_matching(&self, filter: impl Fn(&AppEvent) -> bool) -> Vec<AppEvent> {
+        let mut events: Vec<&(u64, AppEvent)> = self
+            .latest
+            .iter()
+            .chain(self.recent.iter())
+            .filter(|(_, e)| filter(e))
+            .collect();
+        events.sort_by_key(|(seq, _)| *seq);
+        events.into_iter().map(|(_, e)| e.clone()).collect()
+    }
+}
+
+/// Split a history too long for one `HistoryLoaded` into `HistoryBatch`
+/// events, newest batch first, each in chronological order
+///
+/// Transports publish them all before handling their next frame, so a
+/// reply or another history can't land between the batches.
+pub fn history_batches(conv_id: &str, mut messages: Vec<Message>, has_more: Option<bool>) -> Vec<AppEvent> {
+    let mut batches = Vec::new();
+    while !messages.is_empty() {
+        let start = messages.len().saturating_sub(HISTORY_BATCH_SIZE);
+        let batch = messages.split_off(start);
+        batches.push(AppEvent::HistoryBatch {
+            conv_id: conv_id.to_string(),
+            messages: batch,
+            first: batches.is_empty(),
+            last: messages.is_empty(),
+            has_more,
+        });
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_batches_come_newest_first_in_order() {
+        let messages = (0..HISTORY_BATCH_SIZE * 2 + 10)
+            .map(|i| Message::new_assistant(i.to_string(), String::new(), None))
+            .collect();
+
+        let batches: Vec<_> = history_batches("c1", messages, Some(true))
+            .into_iter()
+            .map(|event| match event {
+                AppEvent::HistoryBatch { conv_id, messages, first, last, has_more } => {
+                    assert_eq!((conv_id.as_str(), has_more), ("c1", Some(true)));
+                    let ids: Vec<usize> = messages.iter().map(|m| m.id.parse().unwrap()).collect();
+                    assert!(ids.windows(2).all(|w| w[0] + 1 == w[1]), "each batch is chronological");
+                    (ids[0], ids.len(), first, last)
+                }
+                other => panic!("expected a history batch, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            batches,
+            [
+                (HISTORY_BATCH_SIZE + 10, HISTORY_BATCH_SIZE, true, false),
+                (10, HISTORY_BATCH_SIZE, false, false),
+                (0, 10, false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_keeps_latest_of_each_kind_in_publish_order() {
+        let mut buffer = ReplayBuffer::new();
+        buffer.record(&AppEvent::ConnectionChanged(ConnectionStatus::Connecting));
+        buffer.record(&AppEvent::ConversationsLoaded(Vec::new()));
+        buffer.record(&AppEvent::NavigateToList);
+        buffer.record(&AppEvent::ConnectionChanged(ConnectionStatus::Connected));
+
+        let replayed = buffer.snapshot();
+        assert_eq!(replayed.len(), 2);
+        assert!(matches!(replayed[0], AppEvent::ConversationsLoaded(_)));
+        assert!(matches!(
+            replayed[1],
+            AppEvent::ConnectionChanged(ConnectionStatus::Connected)
+        ));
+    }
+
+    #[test]
+    fn test_replay_includes_app_ready() {
+        let mut buffer = ReplayBuffer::new();
+        buffer.record(&AppEvent::ConversationsLoaded(Vec::new()));
+        buffer.record(&AppEvent::AppReady);
+        buffer.record(&AppEvent::AppReady);
+
+        let replayed = buffer.snapshot();
+        assert_eq!(replayed.len(), 2);
+        assert!(matches!(replayed[1], AppEvent::AppReady));
+    }
+
+    #[test]
+    fn test_recent_events_are_bounded_and_interleaved_with_latest_state() {
+        let mut buffer = ReplayBuffer::with_recent(2);
+        buffer.record(&AppEvent::ConnectionChanged(ConnectionStatus::Connecting));
+        buffer.record(&AppEvent::NavigateToChat("a".to_string()));
+        buffer.record(&AppEvent::ConnectionChanged(ConnectionStatus::Disconnected));
+        buffer.record(&AppEvent::NavigateToChat("b".to_string()));
+        buffer.record(&AppEvent::ConversationsLoaded(Vec::new()));
+        buffer.record(&AppEvent::NavigateToList);
+
+        let replayed = buffer.snapshot();
+        assert_eq!(replayed.len(), 4);
+        assert!(matches!(
+            replayed[0],
+            AppEvent::ConnectionChanged(ConnectionStatus::Disconnected)
+        ));
+        assert!(matches!(&replayed[1], AppEvent::NavigateToChat(id) if id == "b"));
+        assert!(matches!(replayed[2], AppEvent::ConversationsLoaded(_)));
+        assert!(matches!(replayed[3], AppEvent::NavigateToList));
+    }
 }
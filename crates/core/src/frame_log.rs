@@ -0,0 +1,106 @@
+//! Ring buffer of raw protocol frames, for debugging delivery problems
+//!
+//! Transports built with their `debug-log` feature record every text frame
+//! they send and receive here, so the recent traffic can be attached to a
+//! bug report.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+
+/// Default number of frames kept
+pub const DEFAULT_FRAME_LOG_LEN: usize = 200;
+
+/// Whether a frame went to or came from the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+/// A raw JSON frame and when it passed through the transport
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedFrame {
+    pub at: DateTime<Utc>,
+    pub direction: FrameDirection,
+    pub json: String,
+}
+
+/// The most recent frames, dropping the oldest once full
+#[derive(Debug, Clone)]
+pub struct FrameLog {
+    frames: VecDeque<LoggedFrame>,
+    capacity: usize,
+}
+
+impl FrameLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Record a frame passing through now
+    pub fn record(&mut self, direction: FrameDirection, json: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(LoggedFrame {
+            at: Utc::now(),
+            direction,
+            json: json.to_string(),
+        });
+    }
+
+    /// Copy of the kept frames, oldest first
+    pub fn frames(&self) -> Vec<LoggedFrame> {
+        self.frames.iter().cloned().collect()
+    }
+}
+
+impl Default for FrameLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_FRAME_LOG_LEN)
+    }
+}
+
+/// One line per frame, e.g. `12:00:01.250 >> {"type":"chat",...}`
+pub fn format_frames(frames: &[LoggedFrame]) -> String {
+    frames
+        .iter()
+        .map(|frame| {
+            let arrow = match frame.direction {
+                FrameDirection::Sent => ">>",
+                FrameDirection::Received => "<<",
+            };
+            format!("{} {} {}", frame.at.format("%H:%M:%S%.3f"), arrow, frame.json)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_the_most_recent_frames() {
+        let mut log = FrameLog::new(2);
+        log.record(FrameDirection::Sent, "{\"n\":1}");
+        log.record(FrameDirection::Received, "{\"n\":2}");
+        log.record(FrameDirection::Sent, "{\"n\":3}");
+
+        let frames = log.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].direction, FrameDirection::Received);
+        assert_eq!(frames[1].json, "{\"n\":3}");
+
+        let text = format_frames(&frames);
+        assert!(text.lines().next().unwrap().ends_with("<< {\"n\":2}"));
+        assert_eq!(text.lines().count(), 2);
+    }
+}
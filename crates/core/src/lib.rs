@@ -3,18 +3,31 @@
 //! This crate provides platform-agnostic types, protocol definitions,
 //! and trait abstractions used by all platform implementations.
 
+pub mod clock;
 pub mod events;
+pub mod frame_log;
+pub mod outbound;
 pub mod protocol;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod traits;
 pub mod types;
 
 // Re-export commonly used types at crate root
-pub use events::AppEvent;
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use events::{history_batches, AppEvent, ReplayBuffer, DEFAULT_REPLAY_LEN};
+pub use frame_log::{format_frames, FrameDirection, FrameLog, LoggedFrame, DEFAULT_FRAME_LOG_LEN};
+pub use outbound::{OutboundQueue, DEFAULT_OUTBOUND_QUEUE_CAPACITY, QUEUE_FULL_ERROR};
 pub use protocol::{
-    ConversationInfo, HistoryMessage, ImagePayload, WSClientMessage, WSServerMessage,
+    frame_type, AttachmentPayload, ConversationInfo, HistoryMessage, ImagePayload, SearchHit, WSClientMessage, WSServerMessage,
+    HISTORY_BATCH_SIZE, READ_ONLY_CATEGORIES,
 };
 pub use traits::{
-    EventBus, EventStream, SharedEventBus, SharedTransport, Transport, TransportResult,
+    EventBus, EventFilter, EventStream, SharedEventBus, SharedTransport, Transport, TransportDebug, TransportError, TransportResult,
     TransportResultVoid,
 };
-pub use types::{ConnectionStatus, Conversation, ImageData, Message, MessageSender, MessageStatus};
+pub use types::{
+    base64_decoded_len, is_auth_close_code, server_time, Attachment, AttachmentKind, ConnectionStatus, Conversation, ConversationLabels,
+    ImageData, ImageValidationError, Message, MessageSender, MessageStatus, SearchResult, AUTH_CLOSE_CODES,
+    MAX_ATTACHMENT_BYTES,
+};
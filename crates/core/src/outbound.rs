@@ -0,0 +1,123 @@
+//! Bounded buffer for chat frames sent while disconnected
+//!
+//! Transports queue `Chat` frames here instead of failing when the socket
+//! is down, then flush them in order after reconnecting.
+
+use std::collections::VecDeque;
+
+use crate::protocol::WSClientMessage;
+
+/// Default number of frames kept while disconnected
+pub const DEFAULT_OUTBOUND_QUEUE_CAPACITY: usize = 50;
+
+/// Error reported for a queued frame dropped to make room for newer ones
+pub const QUEUE_FULL_ERROR: &str = "Not sent: too many messages were waiting for the connection";
+
+/// FIFO of unsent frames that drops the oldest once full
+#[derive(Debug, Clone)]
+pub struct OutboundQueue {
+    frames: VecDeque<WSClientMessage>,
+    capacity: usize,
+}
+
+impl OutboundQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Queue a frame, returning the oldest one if it had to be dropped
+    ///
+    /// With a capacity of zero nothing is kept and the frame itself is returned.
+    pub fn push(&mut self, frame: WSClientMessage) -> Option<WSClientMessage> {
+        if self.capacity == 0 {
+            return Some(frame);
+        }
+        let dropped = if self.frames.len() >= self.capacity {
+            self.frames.pop_front()
+        } else {
+            None
+        };
+        self.frames.push_back(frame);
+        dropped
+    }
+
+    /// Take every queued frame, oldest first
+    pub fn drain(&mut self) -> Vec<WSClientMessage> {
+        self.frames.drain(..).collect()
+    }
+
+    /// Put frames that could not be flushed back in front of newer ones
+    ///
+    /// Returns the oldest frames that no longer fit.
+    pub fn restore(&mut self, frames: Vec<WSClientMessage>) -> Vec<WSClientMessage> {
+        for frame in frames.into_iter().rev() {
+            self.frames.push_front(frame);
+        }
+        let excess = self.frames.len().saturating_sub(self.capacity);
+        self.frames.drain(..excess).collect()
+    }
+}
+
+impl Default for OutboundQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat(id: &str) -> WSClientMessage {
+        WSClientMessage::Chat {
+            id: id.to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            body: id.to_string(),
+            image: None,
+            files: Vec::new(),
+            reply_to: None,
+        }
+    }
+
+    fn ids(frames: &[WSClientMessage]) -> Vec<String> {
+        frames
+            .iter()
+            .map(|f| match f {
+                WSClientMessage::Chat { id, .. } => id.clone(),
+                other => panic!("unexpected frame {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drops_oldest_when_full_and_restores_in_order() {
+        let mut queue = OutboundQueue::new(2);
+        assert!(queue.push(chat("1")).is_none());
+        assert!(queue.push(chat("2")).is_none());
+        assert_eq!(ids(&queue.push(chat("3")).into_iter().collect::<Vec<_>>()), ["1"]);
+
+        let mut drained = queue.drain();
+        assert_eq!(ids(&drained), ["2", "3"]);
+        assert!(queue.is_empty());
+
+        // "2" went out, "3" failed, and "4" was queued meanwhile
+        drained.remove(0);
+        queue.push(chat("4"));
+        assert!(queue.restore(drained).is_empty());
+        assert_eq!(ids(&queue.drain()), ["3", "4"]);
+
+        assert_eq!(ids(&OutboundQueue::new(0).push(chat("5")).into_iter().collect::<Vec<_>>()), ["5"]);
+    }
+}
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::types::Attachment;
+
 // ============================================
 // Client -> Server message types
 // ============================================
@@ -21,12 +23,22 @@ pub enum WSClientMessage {
         body: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         image: Option<ImagePayload>,
+        /// Attached files other than the image
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files: Vec<AttachmentPayload>,
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "replyTo")]
         reply_to: Option<String>,
     },
     #[serde(rename = "ping")]
     Ping { id: String, timestamp: i64 },
+    /// Sent first on a connection when the client has an auth token
+    #[serde(rename = "authenticate")]
+    Authenticate {
+        id: String,
+        timestamp: i64,
+        token: String,
+    },
     #[serde(rename = "subscribe")]
     Subscribe {
         id: String,
@@ -43,6 +55,9 @@ pub enum WSClientMessage {
         conversation_id: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         limit: Option<u32>,
+        /// Only return messages older than this timestamp (ms), for paging back
+        #[serde(skip_serializing_if = "Option::is_none")]
+        before: Option<i64>,
     },
     #[serde(rename = "create_conversation")]
     CreateConversation {
@@ -58,6 +73,96 @@ pub enum WSClientMessage {
         #[serde(rename = "conversationId")]
         conversation_id: String,
     },
+    #[serde(rename = "set_conversation_labels")]
+    SetConversationLabels {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        tags: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+    },
+    #[serde(rename = "rename_conversation")]
+    RenameConversation {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        title: String,
+    },
+    #[serde(rename = "pin_conversation")]
+    PinConversation {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        pinned: bool,
+    },
+    #[serde(rename = "edit_message")]
+    EditMessage {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+        #[serde(rename = "newBody")]
+        new_body: String,
+    },
+    #[serde(rename = "delete_message")]
+    DeleteMessage {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+    },
+    #[serde(rename = "react_message")]
+    ReactMessage {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+        emoji: String,
+        /// Take the user's reaction back instead of adding it
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        remove: bool,
+    },
+    #[serde(rename = "mark_read")]
+    MarkRead {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        /// Newest message the user has seen; everything before it counts as read too
+        #[serde(rename = "upToMessageId")]
+        up_to_message_id: String,
+    },
+    #[serde(rename = "typing")]
+    Typing {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "isTyping")]
+        is_typing: bool,
+    },
+    #[serde(rename = "search_messages")]
+    SearchMessages {
+        id: String,
+        timestamp: i64,
+        query: String,
+        /// Search one conversation instead of all of them
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "conversationId")]
+        conversation_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+    },
 }
 
 /// Image payload for messages
@@ -67,6 +172,26 @@ pub struct ImagePayload {
     pub mimetype: String,
 }
 
+/// File payload for messages (documents and other non-image files)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentPayload {
+    pub filename: String,
+    pub mimetype: String,
+    pub data: String,
+}
+
+impl From<AttachmentPayload> for Attachment {
+    fn from(file: AttachmentPayload) -> Self {
+        Self { filename: file.filename, mimetype: file.mimetype, data: file.data }
+    }
+}
+
+impl From<Attachment> for AttachmentPayload {
+    fn from(file: Attachment) -> Self {
+        Self { filename: file.filename, mimetype: file.mimetype, data: file.data }
+    }
+}
+
 // ============================================
 // Server -> Client message types
 // ============================================
@@ -86,9 +211,29 @@ pub enum WSServerMessage {
         body: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         image: Option<ImagePayload>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files: Vec<AttachmentPayload>,
+    },
+    /// The server received a chat message; its reply follows later
+    #[serde(rename = "ack")]
+    Ack {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "replyTo")]
+        reply_to: String,
+        #[serde(rename = "conversationId")]
+        conversation_id: Option<String>,
     },
     #[serde(rename = "pong")]
     Pong { id: String, timestamp: i64 },
+    /// The auth token was missing or rejected; the server closes the connection
+    #[serde(rename = "auth_failed")]
+    AuthFailed {
+        id: String,
+        timestamp: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
     #[serde(rename = "notification")]
     Notification {
         id: String,
@@ -132,11 +277,17 @@ pub enum WSServerMessage {
         #[serde(rename = "conversationId")]
         conversation_id: String,
         messages: Vec<HistoryMessage>,
+        /// Whether older messages exist beyond this page (absent from older servers)
+        #[serde(default, rename = "hasMore", skip_serializing_if = "Option::is_none")]
+        has_more: Option<bool>,
     },
     #[serde(rename = "conversation_created")]
     ConversationCreated {
         id: String,
         timestamp: i64,
+        /// Id of the `create_conversation` request this answers
+        #[serde(rename = "replyTo")]
+        reply_to: Option<String>,
         #[serde(rename = "conversationId")]
         conversation_id: String,
         title: Option<String>,
@@ -148,20 +299,145 @@ pub enum WSServerMessage {
         #[serde(rename = "conversationId")]
         conversation_id: String,
     },
+    #[serde(rename = "conversation_labels_updated")]
+    ConversationLabelsUpdated {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        color: Option<String>,
+    },
+    #[serde(rename = "conversation_renamed")]
+    ConversationRenamed {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        title: String,
+    },
+    #[serde(rename = "conversation_pinned")]
+    ConversationPinned {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        pinned: bool,
+    },
+    #[serde(rename = "message_edited")]
+    MessageEdited {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+        #[serde(rename = "newBody")]
+        new_body: String,
+    },
+    #[serde(rename = "message_deleted")]
+    MessageDeleted {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+    },
+    #[serde(rename = "message_reaction")]
+    MessageReaction {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "messageId")]
+        message_id: String,
+        emoji: String,
+        /// New total for the emoji; zero when the last one was taken back
+        count: u32,
+    },
+    #[serde(rename = "read_receipt")]
+    ReadReceipt {
+        id: String,
+        timestamp: i64,
+        #[serde(rename = "conversationId")]
+        conversation_id: String,
+        #[serde(rename = "upToMessageId")]
+        up_to_message_id: String,
+    },
+    #[serde(rename = "search_results")]
+    SearchResults {
+        id: String,
+        timestamp: i64,
+        query: String,
+        #[serde(default)]
+        results: Vec<SearchHit>,
+    },
+    /// A `type` this client doesn't know (e.g. from a newer server); use
+    /// `frame_type` on the raw frame to see which
+    #[serde(other)]
+    Unknown,
+}
+
+/// Just the `type` tag of a frame
+#[derive(Deserialize)]
+struct FrameType {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// The `type` tag of a raw frame, for logging frames parsed as `Unknown`
+pub fn frame_type(text: &str) -> Option<String> {
+    serde_json::from_str::<FrameType>(text).ok().map(|frame| frame.kind)
 }
 
 /// Conversation info from list response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationInfo {
     pub id: String,
+    /// Title set by a rename; absent for untitled conversations
+    #[serde(default)]
+    pub title: Option<String>,
     #[serde(rename = "lastMessage")]
     pub last_message: Option<String>,
     #[serde(rename = "lastMessageTime")]
     pub last_message_time: Option<i64>,
     #[serde(rename = "messageCount")]
     pub message_count: u32,
+    /// Absent on servers without label support
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Server-assigned position (lower first); absent when the server doesn't order
+    #[serde(rename = "sortIndex", default)]
+    pub sort_index: Option<i64>,
+    /// Server flag for conversations that don't accept input
+    #[serde(rename = "readOnly", default)]
+    pub read_only: bool,
+    /// Kind of conversation (e.g. "broadcast"); absent for ordinary chats
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
+/// Conversation categories that are read-only even without the server flag
+pub const READ_ONLY_CATEGORIES: &[&str] = &["broadcast", "reminders", "feed"];
+
+impl ConversationInfo {
+    /// Check if the conversation should not accept input, by flag or category
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+            || self
+                .category
+                .as_deref()
+                .is_some_and(|c| READ_ONLY_CATEGORIES.iter().any(|ro| ro.eq_ignore_ascii_case(c)))
+    }
+}
+
+/// History responses longer than this are parsed and delivered in batches
+pub const HISTORY_BATCH_SIZE: usize = 200;
+
 /// Message from history response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryMessage {
@@ -169,3 +445,77 @@ pub struct HistoryMessage {
     pub content: String,
     pub timestamp: Option<i64>,
 }
+
+/// Message matching a search, in the same shape as a history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    #[serde(rename = "conversationId")]
+    pub conversation_id: String,
+    #[serde(flatten)]
+    pub message: HistoryMessage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(json: &str) -> ConversationInfo {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_read_only_from_flag_or_category() {
+        let base = r#""id":"c1","lastMessage":null,"lastMessageTime":null,"messageCount":0"#;
+
+        assert!(!info(&format!("{{{base}}}")).is_read_only());
+        assert!(info(&format!(r#"{{{base},"readOnly":true}}"#)).is_read_only());
+        assert!(info(&format!(r#"{{{base},"category":"Broadcast"}}"#)).is_read_only());
+        assert!(!info(&format!(r#"{{{base},"category":"chat"}}"#)).is_read_only());
+    }
+
+    #[test]
+    fn test_search_results_carry_conversation_and_message() {
+        let json = r#"{"type":"search_results","id":"s1","timestamp":0,"query":"tea",
+            "results":[{"conversationId":"c1","role":"user","content":"green tea?","timestamp":5}]}"#;
+
+        let WSServerMessage::SearchResults { query, results, .. } = serde_json::from_str(json).unwrap() else {
+            panic!("expected search results");
+        };
+        assert_eq!(query, "tea");
+        assert_eq!(results[0].conversation_id, "c1");
+        assert_eq!(results[0].message.content, "green tea?");
+        assert_eq!(results[0].message.timestamp, Some(5));
+    }
+
+    #[test]
+    fn test_unknown_message_types_parse_as_unknown() {
+        let json = r#"{"type":"presence","id":"p1","timestamp":0,"online":["c1"]}"#;
+
+        assert!(matches!(serde_json::from_str(json).unwrap(), WSServerMessage::Unknown));
+        assert_eq!(frame_type(json).as_deref(), Some("presence"));
+        // A known type with missing fields is still an error
+        assert!(serde_json::from_str::<WSServerMessage>(r#"{"type":"pong"}"#).is_err());
+    }
+
+    #[test]
+    fn test_history_paging_fields_are_optional() {
+        let request = WSClientMessage::GetHistory {
+            id: "h1".to_string(),
+            timestamp: 0,
+            conversation_id: "c1".to_string(),
+            limit: Some(50),
+            before: Some(1_000),
+        };
+        assert!(serde_json::to_string(&request).unwrap().contains(r#""before":1000"#));
+
+        let page = |extra: &str| {
+            let json = format!(r#"{{"type":"history","id":"h1","timestamp":0,"conversationId":"c1","messages":[]{extra}}}"#);
+            match serde_json::from_str(&json).unwrap() {
+                WSServerMessage::History { has_more, .. } => has_more,
+                other => panic!("expected history, got {:?}", other),
+            }
+        };
+        assert_eq!(page(r#","hasMore":true"#), Some(true));
+        assert_eq!(page(""), None);
+    }
+}
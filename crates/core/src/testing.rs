@@ -0,0 +1,582 @@
+//! In-memory transport for driving services in tests
+//!
+//! Enabled with the `testing` feature. `MockTransport` records every
+//! outgoing frame instead of sending it, and turns injected server frames
+//! into the same `AppEvent`s the platform transports publish, so feature
+//! services can be tested without a WebSocket server.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use uuid::Uuid;
+
+use crate::events::AppEvent;
+use crate::outbound::{OutboundQueue, QUEUE_FULL_ERROR};
+use crate::protocol::{AttachmentPayload, HistoryMessage, ImagePayload, WSClientMessage, WSServerMessage};
+use crate::traits::{EventBus, SharedEventBus, Transport, TransportDebug, TransportError, TransportResult, TransportResultVoid};
+use crate::types::{
+    server_time, ConnectionStatus, Conversation, ConversationLabels, ImageData, Message, MessageSender, MessageStatus,
+    SearchResult,
+};
+
+/// Transport that records outgoing frames and lets tests inject server frames
+///
+/// Starts out connected. Like the real transports, chat messages sent
+/// while not connected are queued and go out (with `QueuedMessageSent`)
+/// when the status returns to `Connected`, and ones dropped from a full
+/// queue get a `MessageError`; other sends fail with
+/// `TransportError::NotConnected` and are not recorded.
+pub struct MockTransport {
+    event_bus: SharedEventBus,
+    sent: Mutex<Vec<WSClientMessage>>,
+    outbound: Mutex<OutboundQueue>,
+    next_id: AtomicUsize,
+    status: RwLock<ConnectionStatus>,
+    active_conversation: RwLock<Option<String>>,
+}
+
+impl MockTransport {
+    /// Create a transport that publishes to `event_bus`
+    pub fn new(event_bus: SharedEventBus) -> Self {
+        Self {
+            event_bus,
+            sent: Mutex::new(Vec::new()),
+            outbound: Mutex::new(OutboundQueue::default()),
+            next_id: AtomicUsize::new(1),
+            status: RwLock::new(ConnectionStatus::Connected),
+            active_conversation: RwLock::new(None),
+        }
+    }
+
+    /// Keep at most `max` chat messages while disconnected (the oldest is dropped beyond that)
+    ///
+    /// Replaces the queue, dropping anything already in it.
+    pub fn set_max_queued_messages(&self, max: usize) {
+        *self.outbound.lock().unwrap() = OutboundQueue::new(max);
+    }
+
+    /// Snapshot of all frames sent so far
+    pub fn sent_messages(&self) -> Vec<WSClientMessage> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Forget the frames sent so far
+    pub fn clear_sent(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+
+    /// Current simulated connection status
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    /// Move to a connection status and publish `ConnectionChanged`
+    ///
+    /// Becoming connected flushes the queued chat messages.
+    pub fn set_status(&self, status: ConnectionStatus) {
+        *self.status.write().unwrap() = status.clone();
+        self.event_bus.publish(AppEvent::ConnectionChanged(status));
+        if self.is_connected() {
+            let queued = self.outbound.lock().unwrap().drain();
+            for frame in queued {
+                if let WSClientMessage::Chat { id, conversation_id, .. } = &frame {
+                    self.event_bus.publish(AppEvent::QueuedMessageSent {
+                        conv_id: conversation_id.clone(),
+                        wire_id: id.clone(),
+                    });
+                }
+                self.sent.lock().unwrap().push(frame);
+            }
+        }
+    }
+
+    /// Deliver a server frame as the platform transports would
+    ///
+    /// Responses, acks and typing updates without a conversation id go to
+    /// the active conversation. History is always delivered in one piece.
+    pub fn inject(&self, frame: WSServerMessage) {
+        let active_conversation = self.active_conversation.read().unwrap().clone();
+        match frame {
+            WSServerMessage::Response { id, timestamp, reply_to, conversation_id, body, image, files } => {
+                let image = image.map(|img| ImageData { data: img.data, mimetype: img.mimetype });
+                if let Some(conv_id) = conversation_id.or(active_conversation) {
+                    let files = files.into_iter().map(Into::into).collect();
+                    let message = Message::new_assistant(id, body, image).with_files(files).at_server_time(timestamp);
+                    self.event_bus.publish(AppEvent::MessageReceived { conv_id, message, reply_to: Some(reply_to) });
+                }
+            }
+            WSServerMessage::Ack { timestamp, reply_to, conversation_id, .. } => {
+                if let Some(conv_id) = conversation_id.or(active_conversation) {
+                    let received_at = server_time(timestamp);
+                    self.event_bus.publish(AppEvent::MessageAcked { conv_id, wire_id: reply_to, received_at });
+                }
+            }
+            WSServerMessage::Typing { conversation_id, is_typing, .. } => {
+                if let Some(conv_id) = conversation_id.or(active_conversation) {
+                    self.event_bus.publish(AppEvent::TypingChanged { conv_id, is_typing });
+                }
+            }
+            WSServerMessage::Error { reply_to, conversation_id, message, .. } => match (reply_to, conversation_id) {
+                (Some(msg_id), Some(conv_id)) => {
+                    self.event_bus.publish(AppEvent::MessageError { conv_id, msg_id, error: message });
+                }
+                (Some(request_id), None) => {
+                    self.event_bus.publish(AppEvent::RequestFailed { request_id, error: message });
+                }
+                _ => {}
+            },
+            WSServerMessage::ConversationsList { conversations, .. } => {
+                let convs = conversations
+                    .into_iter()
+                    .map(|c| {
+                        let read_only = c.is_read_only();
+                        let mut conv =
+                            Conversation::from_server(c.id, c.last_message, c.last_message_time, c.message_count);
+                        conv.set_labels(ConversationLabels { tags: c.tags, color: c.color });
+                        if let Some(title) = c.title {
+                            conv.title = title;
+                        }
+                        conv.sort_index = c.sort_index;
+                        conv.read_only = read_only;
+                        conv
+                    })
+                    .collect();
+                self.event_bus.publish(AppEvent::ConversationsLoaded(convs));
+            }
+            WSServerMessage::History { conversation_id, messages, has_more, .. } => {
+                self.event_bus.publish(AppEvent::HistoryLoaded {
+                    conv_id: conversation_id,
+                    messages: messages.into_iter().filter_map(history_message).collect(),
+                    has_more,
+                });
+            }
+            WSServerMessage::ConversationCreated { conversation_id, title, reply_to, .. } => {
+                self.event_bus.publish(AppEvent::ConversationCreated { id: conversation_id, title, reply_to });
+            }
+            WSServerMessage::ConversationDeleted { conversation_id, .. } => {
+                self.event_bus.publish(AppEvent::ConversationDeleted(conversation_id));
+            }
+            WSServerMessage::ConversationLabelsUpdated { conversation_id, tags, color, .. } => {
+                self.event_bus.publish(AppEvent::ConversationLabelsChanged {
+                    conv_id: conversation_id,
+                    labels: ConversationLabels { tags, color },
+                });
+            }
+            WSServerMessage::ReadReceipt { conversation_id, up_to_message_id, .. } => {
+                self.event_bus.publish(AppEvent::ReadReceipt {
+                    conv_id: conversation_id,
+                    up_to_msg_id: up_to_message_id,
+                });
+            }
+            WSServerMessage::ConversationRenamed { conversation_id, title, .. } => {
+                self.event_bus.publish(AppEvent::ConversationRenamed { conv_id: conversation_id, title });
+            }
+            WSServerMessage::ConversationPinned { conversation_id, pinned, .. } => {
+                self.event_bus.publish(AppEvent::ConversationPinned { conv_id: conversation_id, pinned });
+            }
+            WSServerMessage::MessageEdited { conversation_id, message_id, new_body, .. } => {
+                self.event_bus.publish(AppEvent::MessageEdited {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                    new_body,
+                });
+            }
+            WSServerMessage::MessageDeleted { conversation_id, message_id, .. } => {
+                self.event_bus.publish(AppEvent::MessageDeleted {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                });
+            }
+            WSServerMessage::MessageReaction { conversation_id, message_id, emoji, count, .. } => {
+                self.event_bus.publish(AppEvent::MessageReaction {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                    emoji,
+                    count,
+                });
+            }
+            WSServerMessage::SearchResults { query, results, .. } => {
+                let results = results
+                    .into_iter()
+                    .filter_map(|hit| {
+                        let message = history_message(hit.message)?;
+                        Some(SearchResult { conv_id: hit.conversation_id, message })
+                    })
+                    .collect();
+                self.event_bus.publish(AppEvent::SearchResultsLoaded { query, results });
+            }
+            WSServerMessage::Notification { title, body, category, .. } => {
+                self.event_bus.publish(AppEvent::NotificationReceived { title, body, category });
+            }
+            WSServerMessage::AuthFailed { reason, .. } => {
+                self.event_bus.publish(AppEvent::AuthFailed { reason: reason.clone() });
+                self.set_status(ConnectionStatus::auth_error(reason));
+            }
+            WSServerMessage::Pong { .. } | WSServerMessage::Unknown => {}
+        }
+    }
+
+    /// Record a frame built with the next frame id, or fail while offline
+    fn record(&self, build: impl FnOnce(String) -> WSClientMessage) -> Result<String, TransportError> {
+        if !self.is_connected() {
+            return Err(TransportError::NotConnected);
+        }
+        let id = format!("wire-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        self.sent.lock().unwrap().push(build(id.clone()));
+        Ok(id)
+    }
+
+    fn record_void(&self, build: impl FnOnce(String) -> WSClientMessage) -> TransportResultVoid {
+        let result = self.record(build).map(|_| ());
+        Box::pin(async move { result })
+    }
+}
+
+impl TransportDebug for MockTransport {}
+
+impl Transport for MockTransport {
+    fn connect(
+        &self,
+        _url: String,
+        _auth_token: Option<String>,
+        _event_bus: Arc<dyn EventBus>,
+    ) -> TransportResultVoid {
+        self.set_status(ConnectionStatus::Connecting);
+        self.set_status(ConnectionStatus::Connected);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn disconnect(&self) -> TransportResultVoid {
+        self.set_status(ConnectionStatus::Disconnected);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn send_chat(
+        &self,
+        msg_id: String,
+        conv_id: String,
+        text: String,
+        image: Option<ImagePayload>,
+        files: Vec<AttachmentPayload>,
+        reply_to: Option<String>,
+    ) -> TransportResultVoid {
+        let chat = WSClientMessage::Chat {
+            id: msg_id.clone(),
+            timestamp: 0,
+            conversation_id: conv_id,
+            body: text,
+            image,
+            files,
+            reply_to,
+        };
+        let result = if self.is_connected() {
+            self.sent.lock().unwrap().push(chat);
+            Ok(())
+        } else {
+            let dropped = self.outbound.lock().unwrap().push(chat);
+            match dropped {
+                Some(WSClientMessage::Chat { id, .. }) if id == msg_id => Err(TransportError::NotConnected),
+                Some(WSClientMessage::Chat { id, conversation_id, .. }) => {
+                    self.event_bus.publish(AppEvent::MessageError {
+                        conv_id: conversation_id,
+                        msg_id: id,
+                        error: QUEUE_FULL_ERROR.to_string(),
+                    });
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        };
+        Box::pin(async move { result })
+    }
+
+    fn send_list_conversations(&self) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::ListConversations { id, timestamp: 0 })
+    }
+
+    fn send_get_history(&self, conv_id: String, limit: Option<u32>, before: Option<i64>) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::GetHistory {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            limit,
+            before,
+        })
+    }
+
+    fn send_create_conversation(&self, title: Option<String>) -> TransportResult<String> {
+        let result = self.record(|id| WSClientMessage::CreateConversation { id, timestamp: 0, title });
+        Box::pin(async move { result })
+    }
+
+    fn send_delete_conversation(&self, conv_id: String) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::DeleteConversation {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+        })
+    }
+
+    fn send_set_conversation_labels(
+        &self,
+        conv_id: String,
+        tags: Vec<String>,
+        color: Option<String>,
+    ) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::SetConversationLabels {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            tags,
+            color,
+        })
+    }
+
+    fn send_rename_conversation(&self, conv_id: String, title: String) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::RenameConversation {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            title,
+        })
+    }
+
+    fn send_pin_conversation(&self, conv_id: String, pinned: bool) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::PinConversation {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            pinned,
+        })
+    }
+
+    fn send_edit_message(&self, conv_id: String, message_id: String, new_body: String) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::EditMessage {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            message_id,
+            new_body,
+        })
+    }
+
+    fn send_delete_message(&self, conv_id: String, message_id: String) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::DeleteMessage {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            message_id,
+        })
+    }
+
+    fn send_reaction(&self, conv_id: String, message_id: String, emoji: String, remove: bool) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::ReactMessage {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            message_id,
+            emoji,
+            remove,
+        })
+    }
+
+    fn send_mark_read(&self, conv_id: String, up_to_message_id: String) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::MarkRead {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            up_to_message_id,
+        })
+    }
+
+    fn send_typing(&self, conv_id: String, is_typing: bool) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::Typing {
+            id,
+            timestamp: 0,
+            conversation_id: conv_id,
+            is_typing,
+        })
+    }
+
+    fn send_search(&self, query: String, conv_id: Option<String>, limit: Option<u32>) -> TransportResultVoid {
+        self.record_void(|id| WSClientMessage::SearchMessages {
+            id,
+            timestamp: 0,
+            query,
+            conversation_id: conv_id,
+            limit,
+        })
+    }
+
+    fn is_connected(&self) -> bool {
+        self.status() == ConnectionStatus::Connected
+    }
+
+    fn set_active_conversation(&self, conv_id: Option<String>) {
+        *self.active_conversation.write().unwrap() = conv_id;
+    }
+}
+
+/// Convert a history entry, skipping unknown roles
+fn history_message(m: HistoryMessage) -> Option<Message> {
+    let sender = match m.role.as_str() {
+        "user" => MessageSender::User,
+        "assistant" => MessageSender::Assistant,
+        "system" => MessageSender::System,
+        _ => return None,
+    };
+    Some(Message {
+        id: Uuid::new_v4().to_string(),
+        body: m.content,
+        timestamp: m
+            .timestamp
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .unwrap_or_else(chrono::Utc::now),
+        sender,
+        status: MessageStatus::Delivered,
+        image: None,
+        delivered_at: None,
+        edited: false,
+        reply_to: None,
+        files: Vec::new(),
+        reactions: HashMap::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::EventStream;
+
+    /// Bus that keeps every published event
+    #[derive(Default)]
+    struct RecordingBus(Mutex<Vec<AppEvent>>);
+
+    impl EventBus for RecordingBus {
+        fn publish(&self, event: AppEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn subscribe(&self) -> EventStream {
+            Box::pin(futures::stream::iter(self.0.lock().unwrap().clone()))
+        }
+    }
+
+    #[test]
+    fn test_default_filtered_subscription_drops_other_events() {
+        let bus = RecordingBus::default();
+        bus.publish(AppEvent::NavigateToList);
+        bus.publish(AppEvent::SettingsModalToggled(true));
+        bus.publish(AppEvent::ConversationSelected("a".to_string()));
+
+        let received: Vec<AppEvent> = futures::executor::block_on(futures::StreamExt::collect(
+            bus.subscribe_filtered(Box::new(|e| matches!(e, AppEvent::SettingsModalToggled(_)))),
+        ));
+        assert!(matches!(received.as_slice(), [AppEvent::SettingsModalToggled(true)]));
+    }
+
+    #[test]
+    fn test_records_sends_and_simulates_connection_changes() {
+        let bus = Arc::new(RecordingBus::default());
+        let transport = MockTransport::new(bus.clone());
+
+        let sent = futures::executor::block_on(transport.send_chat(
+            "m1".to_string(),
+            "a".to_string(),
+            "hi".to_string(),
+            None,
+            Vec::new(),
+            None,
+        ));
+        assert_eq!(sent, Ok(()));
+        futures::executor::block_on(transport.send_get_history("a".to_string(), Some(50), None)).unwrap();
+        assert!(matches!(
+            transport.sent_messages().as_slice(),
+            [WSClientMessage::Chat { .. }, WSClientMessage::GetHistory { limit: Some(50), .. }]
+        ));
+
+        transport.set_status(ConnectionStatus::Disconnected);
+        assert!(!transport.is_connected());
+        assert_eq!(
+            futures::executor::block_on(transport.send_list_conversations()),
+            Err(TransportError::NotConnected)
+        );
+        let queued = futures::executor::block_on(transport.send_chat(
+            "m2".to_string(),
+            "a".to_string(),
+            "later".to_string(),
+            None,
+            Vec::new(),
+            None,
+        ));
+        assert_eq!(queued, Ok(()));
+        assert_eq!(transport.sent_messages().len(), 2);
+
+        transport.set_active_conversation(Some("a".to_string()));
+        transport.inject(WSServerMessage::Typing {
+            id: "t".to_string(),
+            timestamp: 0,
+            reply_to: String::new(),
+            conversation_id: None,
+            is_typing: true,
+        });
+
+        transport.set_status(ConnectionStatus::Connected);
+        assert_eq!(transport.sent_messages().len(), 3);
+
+        let events = bus.0.lock().unwrap().clone();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::ConnectionChanged(ConnectionStatus::Disconnected),
+                AppEvent::TypingChanged { is_typing: true, .. },
+                AppEvent::ConnectionChanged(ConnectionStatus::Connected),
+                AppEvent::QueuedMessageSent { wire_id, .. },
+            ] if wire_id == "m2"
+        ));
+    }
+
+    #[test]
+    fn test_full_queue_fails_the_dropped_message() {
+        let bus = Arc::new(RecordingBus::default());
+        let transport = MockTransport::new(bus.clone());
+        transport.set_max_queued_messages(1);
+        transport.set_status(ConnectionStatus::Disconnected);
+
+        let send = |body: &str| {
+            futures::executor::block_on(transport.send_chat(
+                body.to_string(),
+                "a".to_string(),
+                body.to_string(),
+                None,
+                Vec::new(),
+                None,
+            ))
+        };
+        assert_eq!(send("one"), Ok(()));
+        assert_eq!(send("two"), Ok(()));
+
+        let events = bus.0.lock().unwrap().clone();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::ConnectionChanged(ConnectionStatus::Disconnected),
+                AppEvent::MessageError { msg_id, error, .. },
+            ] if msg_id == "one" && error == QUEUE_FULL_ERROR
+        ));
+
+        let nothing_kept = MockTransport::new(bus);
+        nothing_kept.set_max_queued_messages(0);
+        nothing_kept.set_status(ConnectionStatus::Disconnected);
+        let result = futures::executor::block_on(nothing_kept.send_chat(
+            "x".to_string(),
+            "a".to_string(),
+            "x".to_string(),
+            None,
+            Vec::new(),
+            None,
+        ));
+        assert_eq!(result, Err(TransportError::NotConnected));
+    }
+}
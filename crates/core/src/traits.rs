@@ -4,11 +4,52 @@
 //! of transport and event bus functionality.
 
 use crate::events::AppEvent;
-use crate::protocol::ImagePayload;
+use crate::frame_log::LoggedFrame;
+use crate::protocol::{AttachmentPayload, ImagePayload};
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
+/// Why a transport operation failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransportError {
+    /// There is no open connection to send on
+    NotConnected,
+    /// The frame couldn't be encoded
+    Serialization(String),
+    /// The socket reported an error (or could not be opened)
+    Io(String),
+    /// The operation took too long
+    Timeout,
+    /// The connection was closed while sending
+    Closed,
+    /// The server rejected the auth token (with its reason, if it gave one)
+    AuthFailed(Option<String>),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "Not connected"),
+            Self::Serialization(e) => write!(f, "Serialization error: {}", e),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Timeout => write!(f, "Timed out"),
+            Self::Closed => write!(f, "Connection closed"),
+            Self::AuthFailed(Some(reason)) => write!(f, "Authentication failed: {}", reason),
+            Self::AuthFailed(None) => write!(f, "Authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+impl From<serde_json::Error> for TransportError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
+
 // Conditional Send bounds based on target platform
 // Native targets require Send for multi-threaded async runtimes
 // WASM is single-threaded and cannot use Send
@@ -17,10 +58,10 @@ mod bounds {
     use super::*;
 
     /// Result type for async transport operations
-    pub type TransportResult<T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send>>;
+    pub type TransportResult<T> = Pin<Box<dyn Future<Output = Result<T, TransportError>> + Send>>;
 
     /// Result type for async transport operations (no return value)
-    pub type TransportResultVoid = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+    pub type TransportResultVoid = Pin<Box<dyn Future<Output = Result<(), TransportError>> + Send>>;
 
     /// Stream of application events
     pub type EventStream = Pin<Box<dyn futures::Stream<Item = AppEvent> + Send>>;
@@ -31,10 +72,10 @@ mod bounds {
     use super::*;
 
     /// Result type for async transport operations (WASM - no Send required)
-    pub type TransportResult<T> = Pin<Box<dyn Future<Output = Result<T, String>>>>;
+    pub type TransportResult<T> = Pin<Box<dyn Future<Output = Result<T, TransportError>>>>;
 
     /// Result type for async transport operations (no return value, WASM - no Send required)
-    pub type TransportResultVoid = Pin<Box<dyn Future<Output = Result<(), String>>>>;
+    pub type TransportResultVoid = Pin<Box<dyn Future<Output = Result<(), TransportError>>>>;
 
     /// Stream of application events (WASM - no Send required)
     pub type EventStream = Pin<Box<dyn futures::Stream<Item = AppEvent>>>;
@@ -42,41 +83,113 @@ mod bounds {
 
 pub use bounds::*;
 
+/// Predicate choosing which events a filtered subscription receives
+pub type EventFilter = Box<dyn Fn(&AppEvent) -> bool + Send + Sync>;
+
+/// Debugging hooks every transport offers
+pub trait TransportDebug {
+    /// Raw frames recently sent and received, oldest first
+    ///
+    /// Empty unless the transport was built with its `debug-log` feature.
+    fn recent_frames(&self) -> Vec<LoggedFrame> {
+        Vec::new()
+    }
+}
+
 /// Platform-agnostic transport for server communication
 ///
-/// Implemented by platform-native (tokio-tungstenite) and platform-web (web-sys::WebSocket)
+/// Implemented by platform-native (yawc) and platform-web (web-sys::WebSocket)
 ///
 /// Note: Methods return boxed futures to avoid async_trait lifetime issues while
 /// supporting both Send (native) and !Send (web) implementations.
-pub trait Transport: Send + Sync + 'static {
+pub trait Transport: TransportDebug + Send + Sync + 'static {
     /// Connect to the server at the given URL
-    fn connect(&self, url: String, event_bus: Arc<dyn EventBus>) -> TransportResultVoid;
+    ///
+    /// With an `auth_token`, an `authenticate` frame carrying it is the first
+    /// thing sent on every connection. If the server answers `auth_failed`,
+    /// the transport stops reconnecting until `connect` is called again.
+    fn connect(
+        &self,
+        url: String,
+        auth_token: Option<String>,
+        event_bus: Arc<dyn EventBus>,
+    ) -> TransportResultVoid;
 
     /// Disconnect from the server
     fn disconnect(&self) -> TransportResultVoid;
 
-    /// Send a chat message
+    /// Send a chat message, optionally as a reply to an earlier message
+    ///
+    /// `msg_id` is the frame id; acks and replies from the server refer to
+    /// it. `files` are attachments other than the inline image.
     fn send_chat(
         &self,
+        msg_id: String,
         conv_id: String,
         text: String,
         image: Option<ImagePayload>,
-    ) -> TransportResult<String>;
+        files: Vec<AttachmentPayload>,
+        reply_to: Option<String>,
+    ) -> TransportResultVoid;
 
     /// Request the list of conversations
     fn send_list_conversations(&self) -> TransportResultVoid;
 
     /// Request message history for a conversation
-    fn send_get_history(&self, conv_id: String, limit: Option<u32>) -> TransportResultVoid;
+    ///
+    /// Returns the newest `limit` messages, or with `before` (a timestamp in
+    /// ms) the newest ones older than it.
+    fn send_get_history(&self, conv_id: String, limit: Option<u32>, before: Option<i64>) -> TransportResultVoid;
 
     /// Create a new conversation
-    fn send_create_conversation(&self, title: Option<String>) -> TransportResultVoid;
+    ///
+    /// Returns the request id, which the server's `conversation_created`
+    /// or `error` answer names in `replyTo`.
+    fn send_create_conversation(&self, title: Option<String>) -> TransportResult<String>;
 
     /// Delete a conversation
     fn send_delete_conversation(&self, conv_id: String) -> TransportResultVoid;
 
+    /// Set the tags and color of a conversation
+    fn send_set_conversation_labels(
+        &self,
+        conv_id: String,
+        tags: Vec<String>,
+        color: Option<String>,
+    ) -> TransportResultVoid;
+
+    /// Change the title of a conversation
+    fn send_rename_conversation(&self, conv_id: String, title: String) -> TransportResultVoid;
+
+    /// Pin a conversation to the top of the list, or unpin it
+    fn send_pin_conversation(&self, conv_id: String, pinned: bool) -> TransportResultVoid;
+
+    /// Change the body of a sent message
+    fn send_edit_message(&self, conv_id: String, message_id: String, new_body: String) -> TransportResultVoid;
+
+    /// Delete a single message
+    fn send_delete_message(&self, conv_id: String, message_id: String) -> TransportResultVoid;
+
+    /// React to a message with an emoji, or take the reaction back (`remove`)
+    fn send_reaction(&self, conv_id: String, message_id: String, emoji: String, remove: bool) -> TransportResultVoid;
+
+    /// Tell the server the user has seen a conversation up to a message
+    fn send_mark_read(&self, conv_id: String, up_to_message_id: String) -> TransportResultVoid;
+
+    /// Tell the server whether the user is typing in a conversation
+    fn send_typing(&self, conv_id: String, is_typing: bool) -> TransportResultVoid;
+
+    /// Search message bodies, in one conversation or (with `None`) all of them
+    fn send_search(&self, query: String, conv_id: Option<String>, limit: Option<u32>) -> TransportResultVoid;
+
     /// Check if currently connected
     fn is_connected(&self) -> bool;
+
+    /// Set the conversation open in the UI
+    ///
+    /// Used as the fallback target for responses and typing updates that
+    /// arrive without a `conversationId` (e.g. from single-conversation servers).
+    fn set_active_conversation(&self, conv_id: Option<String>);
 }
 
 /// Platform-agnostic event bus for cross-feature communication
@@ -87,7 +200,29 @@ pub trait EventBus: Send + Sync + 'static {
     fn publish(&self, event: AppEvent);
 
     /// Subscribe to events, returning a stream of events
+    ///
+    /// Buses with a `ReplayBuffer` start the stream with its snapshot.
     fn subscribe(&self) -> EventStream;
+
+    /// Subscribe to events published from now on, without any replay
+    ///
+    /// For listeners that act on events (e.g. reconnect requests) rather than
+    /// track state, so an old event is not acted on twice. Buses that don't
+    /// replay can keep the default.
+    fn subscribe_live(&self) -> EventStream {
+        self.subscribe()
+    }
+
+    /// Subscribe to the events matching `filter` (replay included)
+    ///
+    /// For listeners that care about a few kinds of event, so the rest of the
+    /// traffic never reaches them. The default filters `subscribe`; buses
+    /// can override it to skip events before copying them to the subscriber.
+    fn subscribe_filtered(&self, filter: EventFilter) -> EventStream {
+        Box::pin(futures::StreamExt::filter(self.subscribe(), move |event| {
+            std::future::ready(filter(event))
+        }))
+    }
 }
 
 /// Shared transport handle
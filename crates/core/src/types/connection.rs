@@ -10,4 +10,25 @@ pub enum ConnectionStatus {
     #[default]
     Disconnected,
     Reconnecting,
+    /// The server rejected the client's credentials; no retries until the
+    /// auth token changes (holds the server's reason)
+    AuthError(String),
+}
+
+impl ConnectionStatus {
+    /// `AuthError` with the server's reason, or a generic one if it gave none
+    pub fn auth_error(reason: Option<String>) -> Self {
+        Self::AuthError(reason.unwrap_or_else(|| "The server rejected the credentials".to_string()))
+    }
+}
+
+/// WebSocket close codes servers use to turn away unauthenticated clients
+///
+/// 1008 is the standard policy-violation code; 4001/4003 and 4401/4403 are
+/// the common application-range stand-ins for HTTP 401/403.
+pub const AUTH_CLOSE_CODES: &[u16] = &[1008, 4001, 4003, 4401, 4403];
+
+/// Check if a close code means the server rejected the client's credentials
+pub fn is_auth_close_code(code: u16) -> bool {
+    AUTH_CLOSE_CODES.contains(&code)
 }
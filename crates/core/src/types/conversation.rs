@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use uuid::Uuid;
 
 use super::message::{Message, MessageStatus};
 
@@ -14,8 +15,80 @@ pub struct Conversation {
     pub messages: Vec<Message>,
     pub last_message_time: Option<DateTime<Utc>>,
     pub last_message_preview: Option<String>,
+    /// Total messages in the conversation; history is paged, so this can
+    /// exceed `messages.len()` but never falls below it
     pub message_count: u32,
     pub pending_messages: HashSet<String>,
+    /// User-assigned tags for organizing conversations
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Optional label color (CSS color, e.g. "#ef4444")
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Pinned conversations are listed first, ordered by `pin_order`
+    #[serde(default)]
+    pub pinned: bool,
+    /// Position within the pinned group (ignored when not pinned)
+    #[serde(default)]
+    pub pin_order: u32,
+    /// Archived conversations are tucked away below the rest of the list
+    #[serde(default)]
+    pub archived: bool,
+    /// Messages received since the conversation was last read
+    #[serde(default)]
+    pub unread_count: u32,
+    /// When the conversation was last opened (None if never, or marked unread)
+    #[serde(default)]
+    pub last_read_at: Option<DateTime<Utc>>,
+    /// Server-assigned position; conversations with one are listed before
+    /// those without, lowest first
+    #[serde(default)]
+    pub sort_index: Option<i64>,
+    /// Read-only conversations (e.g. broadcast feeds) don't accept input
+    #[serde(default)]
+    pub read_only: bool,
+    /// Created on this client and not yet confirmed by the server; the id
+    /// is a temporary one until then
+    #[serde(skip)]
+    pub pending: bool,
+}
+
+/// Prefix of the temporary ids given to conversations awaiting the server
+const PENDING_ID_PREFIX: &str = "pending-";
+
+/// Longest message excerpt used as a fallback title
+const TITLE_FROM_MESSAGE_CHARS: usize = 40;
+
+/// "Chat <short id>" title derived from a conversation id
+fn id_title(id: &str) -> String {
+    // Extract a short ID from the full conversation ID for display
+    let short_id = id
+        .split('-')
+        .nth(1)
+        .map(|s| s.chars().take(8).collect::<String>())
+        .unwrap_or_else(|| id.chars().take(8).collect());
+    format!("Chat {}", short_id)
+}
+
+/// Check for a `#rgb` or `#rrggbb` color, the only form safe to put in a style
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Tags and color assigned to a conversation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationLabels {
+    pub tags: Vec<String>,
+    pub color: Option<String>,
+}
+
+impl ConversationLabels {
+    /// Check if no tags or color are set
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.color.is_none()
+    }
 }
 
 impl Conversation {
@@ -29,9 +102,32 @@ impl Conversation {
             last_message_preview: None,
             message_count: 0,
             pending_messages: HashSet::new(),
+            tags: Vec::new(),
+            color: None,
+            pinned: false,
+            pin_order: 0,
+            archived: false,
+            unread_count: 0,
+            last_read_at: None,
+            sort_index: None,
+            read_only: false,
+            pending: false,
+        }
+    }
+
+    /// Create a conversation shown while the server is still creating it
+    pub fn new_pending(title: Option<String>) -> Self {
+        Self {
+            pending: true,
+            ..Self::new(format!("{}{}", PENDING_ID_PREFIX, Uuid::new_v4()), title)
         }
     }
 
+    /// Whether `id` is a temporary id from `new_pending`
+    pub fn is_pending_id(id: &str) -> bool {
+        id.starts_with(PENDING_ID_PREFIX)
+    }
+
     /// Create a conversation from server data (for conversation list)
     pub fn from_server(
         id: String,
@@ -39,27 +135,79 @@ impl Conversation {
         last_message_time: Option<i64>,
         message_count: u32,
     ) -> Self {
-        // Extract a short ID from the full conversation ID for display
-        let short_id = id
-            .split('-')
-            .nth(1)
-            .map(|s| s.chars().take(8).collect::<String>())
-            .unwrap_or_else(|| id.chars().take(8).collect());
-
         Self {
+            title: id_title(&id),
             id,
-            title: format!("Chat {}", short_id),
             messages: Vec::new(),
             last_message_time: last_message_time
                 .and_then(DateTime::from_timestamp_millis),
             last_message_preview: last_message,
             message_count,
             pending_messages: HashSet::new(),
+            tags: Vec::new(),
+            color: None,
+            pinned: false,
+            pin_order: 0,
+            archived: false,
+            unread_count: 0,
+            last_read_at: None,
+            sort_index: None,
+            read_only: false,
+            pending: false,
+        }
+    }
+
+    /// Title to show, never blank
+    ///
+    /// Falls back to the start of the last message preview, then to
+    /// "Chat <id>", when the title is empty or whitespace.
+    pub fn display_title(&self) -> String {
+        let title = self.title.trim();
+        if !title.is_empty() {
+            return title.to_string();
+        }
+        self.last_message_preview
+            .as_deref()
+            .and_then(|preview| preview.lines().map(str::trim).find(|line| !line.is_empty()))
+            .map(|line| match line.char_indices().nth(TITLE_FROM_MESSAGE_CHARS) {
+                Some((cut, _)) => format!("{}…", line[..cut].trim_end()),
+                None => line.to_string(),
+            })
+            .unwrap_or_else(|| id_title(&self.id))
+    }
+
+    /// Get the tags and color assigned to this conversation
+    pub fn labels(&self) -> ConversationLabels {
+        ConversationLabels {
+            tags: self.tags.clone(),
+            color: self.color.clone(),
         }
     }
 
-    /// Add a user message to the conversation
+    /// Replace the tags and color
+    ///
+    /// The color ends up in an inline style, so anything other than a
+    /// `#rgb`/`#rrggbb` hex color is dropped.
+    pub fn set_labels(&mut self, labels: ConversationLabels) {
+        self.tags = labels.tags;
+        self.color = labels.color.filter(|c| is_hex_color(c));
+    }
+
+    /// Check if the conversation has a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Check if a message with this id is already loaded
+    fn has_message(&self, id: &str) -> bool {
+        self.messages.iter().any(|m| m.id == id)
+    }
+
+    /// Add a user message to the conversation (ignored if already added)
     pub fn add_user_message(&mut self, message: Message) {
+        if self.has_message(&message.id) {
+            return;
+        }
         self.pending_messages.insert(message.id.clone());
         self.last_message_time = Some(message.timestamp);
         self.last_message_preview = Some(message.body.clone());
@@ -67,13 +215,16 @@ impl Conversation {
         self.messages.push(message);
     }
 
-    /// Add a response to a pending message
+    /// Add a response to a pending message (ignored if already added)
     pub fn add_response(&mut self, reply_to: &str, response: Message) {
+        if self.has_message(&response.id) {
+            return;
+        }
         self.pending_messages.remove(reply_to);
 
         // Mark the original message as delivered
         if let Some(msg) = self.messages.iter_mut().find(|m| m.id == reply_to) {
-            msg.status = MessageStatus::Delivered;
+            msg.mark_delivered(response.timestamp);
         }
 
         self.last_message_time = Some(response.timestamp);
@@ -97,12 +248,187 @@ impl Conversation {
         }
     }
 
+    /// Replace the body of a message and flag it as edited
+    ///
+    /// Returns false if the message is not loaded.
+    pub fn edit_message(&mut self, id: &str, new_body: String) -> bool {
+        let is_last = self.messages.last().is_some_and(|m| m.id == id);
+        let Some(msg) = self.messages.iter_mut().find(|m| m.id == id) else {
+            return false;
+        };
+        msg.edit(new_body);
+        if is_last {
+            self.last_message_preview = Some(msg.body.clone());
+        }
+        true
+    }
+
+    /// Remove a deleted message, returning whether it was loaded
+    ///
+    /// The count drops either way, since list entries carry the server's
+    /// count without the messages, so call this once per deletion. The
+    /// preview and time move to the new last loaded message; with none
+    /// loaded they are left as they are.
+    pub fn remove_message(&mut self, id: &str) -> bool {
+        let loaded = self.has_message(id);
+        self.messages.retain(|m| m.id != id);
+        self.pending_messages.remove(id);
+        self.message_count = self.message_count.saturating_sub(1);
+        if let Some(last) = self.messages.last() {
+            self.last_message_time = Some(last.timestamp);
+            self.last_message_preview = Some(last.body.clone());
+        }
+        loaded
+    }
+
     /// Set messages from history
+    ///
+    /// History may be only the latest page, so the count is raised to the
+    /// loaded length rather than replaced by it. Live messages are already
+    /// counted and reappear in later history loads with new ids, so they
+    /// are not added on top.
     pub fn set_messages(&mut self, messages: Vec<Message>) {
         self.messages = messages;
+        self.message_count = self.message_count.max(self.messages.len() as u32);
         if let Some(last) = self.messages.last() {
             self.last_message_time = Some(last.timestamp);
             self.last_message_preview = Some(last.body.clone());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::clock::FixedClock;
+
+    fn history(n: usize) -> Vec<Message> {
+        (0..n).map(|i| Message::new_user(i.to_string())).collect()
+    }
+
+    #[test]
+    fn test_count_after_history_then_send() {
+        let mut conv = Conversation::from_server("native-a".to_string(), None, None, 0);
+        conv.set_messages(history(3));
+        assert_eq!(conv.message_count, 3);
+
+        let sent = Message::new_user("hi".to_string());
+        let sent_id = sent.id.clone();
+        conv.add_user_message(sent.clone());
+        conv.add_user_message(sent);
+        conv.add_response(&sent_id, Message::new_assistant("r1".to_string(), "hello".to_string(), None));
+        conv.add_response(&sent_id, Message::new_assistant("r1".to_string(), "hello".to_string(), None));
+
+        assert_eq!(conv.message_count, 5);
+        assert_eq!(conv.messages.len(), 5);
+    }
+
+    #[test]
+    fn test_count_after_send_then_history() {
+        // Server reports more messages than the loaded page
+        let mut conv = Conversation::from_server("native-a".to_string(), None, None, 120);
+        conv.add_user_message(Message::new_user("hi".to_string()));
+        assert_eq!(conv.message_count, 121);
+
+        // Reloaded page includes the sent message under a new id
+        conv.set_messages(history(50));
+        assert_eq!(conv.message_count, 121);
+
+        let mut fresh = Conversation::new("native-b".to_string(), None);
+        fresh.add_user_message(Message::new_user("hi".to_string()));
+        fresh.set_messages(history(2));
+        assert_eq!(fresh.message_count, 2);
+    }
+
+    #[test]
+    fn test_response_records_delivery_time() {
+        let sent_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let clock = FixedClock::new(sent_at);
+        let mut conv = Conversation::new("native-a".to_string(), None);
+        let sent = Message::new_user_at("hi".to_string(), &clock);
+        let sent_id = sent.id.clone();
+        conv.add_user_message(sent);
+
+        clock.advance(chrono::Duration::seconds(3));
+        let reply = Message::new_assistant_at("r1".to_string(), "hello".to_string(), None, &clock);
+        conv.add_response(&sent_id, reply);
+
+        let sent = &conv.messages[0];
+        assert_eq!(sent.status, MessageStatus::Delivered);
+        assert_eq!(sent.delivered_at, Some(sent_at + chrono::Duration::seconds(3)));
+        assert_eq!(sent.delivery_time(), Some(chrono::Duration::seconds(3)));
+        assert_eq!(conv.messages[1].delivery_time(), None);
+    }
+
+    #[test]
+    fn test_display_title_falls_back_when_blank() {
+        let mut conv = Conversation::new("native-abcdef123".to_string(), Some("  Plans  ".to_string()));
+        assert_eq!(conv.display_title(), "Plans");
+
+        conv.title = " \t ".to_string();
+        assert_eq!(conv.display_title(), "Chat abcdef12");
+
+        // Listed conversations carry a preview but no messages
+        let mut listed = Conversation::from_server(
+            "native-abcdef123".to_string(),
+            Some("\n  What should we cook tonight for the whole family?".to_string()),
+            None,
+            4,
+        );
+        listed.title = String::new();
+        assert!(listed.messages.is_empty());
+        assert_eq!(listed.display_title(), "What should we cook tonight for the whol…");
+    }
+
+    #[test]
+    fn test_edit_message_flags_and_updates_preview() {
+        let mut conv = Conversation::new("native-a".to_string(), None);
+        let sent = Message::new_user("helo".to_string());
+        let id = sent.id.clone();
+        conv.add_user_message(sent);
+
+        assert!(conv.edit_message(&id, "hello".to_string()));
+        assert!(!conv.edit_message("missing", "x".to_string()));
+        assert_eq!(conv.messages[0].body, "hello");
+        assert!(conv.messages[0].edited);
+        assert_eq!(conv.last_message_preview.as_deref(), Some("hello"));
+
+        // The flag survives a round trip; unedited messages omit it
+        let json = serde_json::to_string(&conv.messages[0]).unwrap();
+        assert!(json.contains("\"edited\":true"));
+        let plain = serde_json::to_string(&Message::new_user("x".to_string())).unwrap();
+        assert!(!plain.contains("edited"));
+    }
+
+    #[test]
+    fn test_remove_message_updates_count_and_preview() {
+        let mut conv = Conversation::from_server("native-a".to_string(), Some("1".to_string()), None, 10);
+        conv.set_messages(history(2));
+        let last_id = conv.messages[1].id.clone();
+
+        assert!(conv.remove_message(&last_id));
+        assert_eq!(conv.message_count, 9);
+        assert_eq!(conv.last_message_preview.as_deref(), Some("0"));
+
+        // An older, unloaded message still counts
+        assert!(!conv.remove_message("older"));
+        assert_eq!(conv.message_count, 8);
+        assert_eq!(conv.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_set_labels_keeps_only_hex_colors() {
+        let mut conv = Conversation::new("native-a".to_string(), None);
+        let with_color = |color: &str| ConversationLabels { tags: Vec::new(), color: Some(color.to_string()) };
+
+        for color in ["#ef4444", "#FFF"] {
+            conv.set_labels(with_color(color));
+            assert_eq!(conv.color.as_deref(), Some(color));
+        }
+        for color in ["red; background:url(x)", "#ef4444; x:y", "ef4444", "#ef44", "#gggggg", ""] {
+            conv.set_labels(with_color(color));
+            assert_eq!(conv.color, None, "{:?} was kept", color);
+        }
+    }
+}
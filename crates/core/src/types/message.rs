@@ -1,9 +1,20 @@
 //! Message types for chat functionality
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use uuid::Uuid;
 
+use crate::clock::{Clock, SystemClock};
+
+/// Maximum decoded size accepted for an inline image (10 MiB)
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Maximum decoded size accepted for an attached file (10 MiB)
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
 /// Sender type for messages
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MessageSender {
@@ -18,6 +29,8 @@ pub enum MessageStatus {
     Sending,
     Sent,
     Delivered,
+    /// The server reported the message as read
+    Read,
     Error(String),
 }
 
@@ -28,6 +41,162 @@ pub struct ImageData {
     pub mimetype: String,
 }
 
+/// Broad kind of an attached file, deciding how it is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttachmentKind {
+    /// Rendered inline
+    Image,
+    /// PDFs, text and office documents
+    Document,
+    Other,
+}
+
+impl AttachmentKind {
+    /// Kind of a file from its mimetype
+    pub fn from_mimetype(mimetype: &str) -> Self {
+        let mimetype = mimetype.to_ascii_lowercase();
+        if mimetype.starts_with("image/") {
+            Self::Image
+        } else if mimetype.starts_with("text/")
+            || matches!(
+                mimetype.as_str(),
+                "application/pdf"
+                    | "application/json"
+                    | "application/rtf"
+                    | "application/msword"
+                    | "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            )
+        {
+            Self::Document
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A file attached to a message, other than its inline image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub mimetype: String,
+    pub data: String,      // Base64 encoded
+}
+
+impl Attachment {
+    /// Kind of the file, from its mimetype
+    pub fn kind(&self) -> AttachmentKind {
+        AttachmentKind::from_mimetype(&self.mimetype)
+    }
+
+    /// Size of the file in bytes, worked out from the base64 length
+    pub fn size(&self) -> usize {
+        base64_decoded_len(&self.data)
+    }
+}
+
+/// Time a server frame was stamped with (ms since the epoch)
+///
+/// None when the server left it out (sent as 0) or it is out of range.
+pub fn server_time(millis: i64) -> Option<DateTime<Utc>> {
+    if millis <= 0 {
+        return None;
+    }
+    DateTime::from_timestamp_millis(millis)
+}
+
+/// Number of bytes a base64 string decodes to (without decoding it)
+pub fn base64_decoded_len(data: &str) -> usize {
+    let data = data.trim();
+    let padding = data.bytes().rev().take_while(|&b| b == b'=').count();
+    (data.len() / 4 * 3).saturating_sub(padding)
+}
+
+/// Reasons an image payload is rejected before rendering
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageValidationError {
+    /// Payload is not valid base64
+    InvalidBase64,
+    /// Decoded payload exceeds `MAX_IMAGE_BYTES`
+    TooLarge(usize),
+    /// Declared mimetype is not an image type we render
+    UnsupportedMimetype(String),
+    /// Decoded bytes don't match the declared mimetype
+    MimetypeMismatch { declared: String, detected: Option<String> },
+}
+
+impl fmt::Display for ImageValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBase64 => write!(f, "invalid base64 data"),
+            Self::TooLarge(size) => write!(f, "image too large ({} bytes)", size),
+            Self::UnsupportedMimetype(mime) => write!(f, "unsupported image type {}", mime),
+            Self::MimetypeMismatch { declared, detected } => write!(
+                f,
+                "declared {} but data is {}",
+                declared,
+                detected.as_deref().unwrap_or("unknown")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageValidationError {}
+
+impl ImageData {
+    /// Decode the base64 payload, checking size and that the bytes match the mimetype
+    pub fn decode(&self) -> Result<Vec<u8>, ImageValidationError> {
+        let mimetype = self.mimetype.to_ascii_lowercase();
+        if !matches!(
+            mimetype.as_str(),
+            "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "image/bmp"
+        ) {
+            return Err(ImageValidationError::UnsupportedMimetype(self.mimetype.clone()));
+        }
+
+        // Reject oversized payloads before paying for the decode
+        let estimated = self.data.len() / 4 * 3;
+        if estimated > MAX_IMAGE_BYTES {
+            return Err(ImageValidationError::TooLarge(estimated));
+        }
+
+        let bytes = BASE64
+            .decode(self.data.trim())
+            .map_err(|_| ImageValidationError::InvalidBase64)?;
+
+        let detected = sniff_image_mimetype(&bytes);
+        if detected != Some(mimetype.as_str()) {
+            return Err(ImageValidationError::MimetypeMismatch {
+                declared: self.mimetype.clone(),
+                detected: detected.map(str::to_string),
+            });
+        }
+
+        Ok(bytes)
+    }
+
+    /// Check that the payload is safe to render
+    pub fn validate(&self) -> Result<(), ImageValidationError> {
+        self.decode().map(|_| ())
+    }
+}
+
+/// Detect an image mimetype from the file signature
+pub fn sniff_image_mimetype(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if bytes.starts_with(b"BM") {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
 /// A chat message
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
@@ -37,54 +206,267 @@ pub struct Message {
     pub sender: MessageSender,
     pub status: MessageStatus,
     pub image: Option<ImageData>,
+    /// When the message was confirmed delivered (for user messages, when the reply arrived)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// The body was changed after sending
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub edited: bool,
+    /// Id of the earlier message this one replies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+    /// Attached files that aren't shown inline (documents and the like)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<Attachment>,
+    /// Emoji reactions and how many times each was given
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub reactions: HashMap<String, u32>,
 }
 
 impl Message {
     /// Create a new user message
     pub fn new_user(body: String) -> Self {
+        Self::new_user_at(body, &SystemClock)
+    }
+
+    /// Like `new_user`, stamped with the time from `clock`
+    pub fn new_user_at(body: String, clock: &dyn Clock) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             body,
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             sender: MessageSender::User,
             status: MessageStatus::Sending,
             image: None,
+            delivered_at: None,
+            edited: false,
+            reply_to: None,
+            files: Vec::new(),
+            reactions: HashMap::new(),
         }
     }
 
     /// Create a new user message with an image attachment
     pub fn new_user_with_image(body: String, image: ImageData) -> Self {
+        Self::new_user_with_image_at(body, image, &SystemClock)
+    }
+
+    /// Like `new_user_with_image`, stamped with the time from `clock`
+    pub fn new_user_with_image_at(body: String, image: ImageData, clock: &dyn Clock) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             body,
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             sender: MessageSender::User,
             status: MessageStatus::Sending,
             image: Some(image),
+            delivered_at: None,
+            edited: false,
+            reply_to: None,
+            files: Vec::new(),
+            reactions: HashMap::new(),
         }
     }
 
     /// Create a new assistant message
     pub fn new_assistant(id: String, body: String, image: Option<ImageData>) -> Self {
+        Self::new_assistant_at(id, body, image, &SystemClock)
+    }
+
+    /// Like `new_assistant`, stamped with the time from `clock`
+    pub fn new_assistant_at(id: String, body: String, image: Option<ImageData>, clock: &dyn Clock) -> Self {
         Self {
             id,
             body,
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             sender: MessageSender::Assistant,
             status: MessageStatus::Delivered,
             image,
+            delivered_at: None,
+            edited: false,
+            reply_to: None,
+            files: Vec::new(),
+            reactions: HashMap::new(),
         }
     }
 
     /// Create a new system message
     pub fn new_system(body: String) -> Self {
+        Self::new_system_at(body, &SystemClock)
+    }
+
+    /// Like `new_system`, stamped with the time from `clock`
+    pub fn new_system_at(body: String, clock: &dyn Clock) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             body,
-            timestamp: Utc::now(),
+            timestamp: clock.now(),
             sender: MessageSender::System,
             status: MessageStatus::Delivered,
             image: None,
+            delivered_at: None,
+            edited: false,
+            reply_to: None,
+            files: Vec::new(),
+            reactions: HashMap::new(),
+        }
+    }
+
+    /// Attach files to the message
+    pub fn with_files(mut self, files: Vec<Attachment>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Stamp the message with the server's time (ms) instead of the local one
+    ///
+    /// History carries the server's times, so live messages stamped this way
+    /// line up with their history copies. Keeps the local time if the server
+    /// gave none.
+    pub fn at_server_time(mut self, millis: i64) -> Self {
+        if let Some(at) = server_time(millis) {
+            self.timestamp = at;
+        }
+        self
+    }
+
+    /// Mark the message delivered, keeping the first delivery time if already set
+    ///
+    /// A message already marked read stays read.
+    pub fn mark_delivered(&mut self, at: DateTime<Utc>) {
+        if self.status != MessageStatus::Read {
+            self.status = MessageStatus::Delivered;
+        }
+        self.delivered_at.get_or_insert(at);
+    }
+
+    /// Replace the body and flag the message as edited
+    pub fn edit(&mut self, new_body: String) {
+        self.body = new_body;
+        self.edited = true;
+    }
+
+    /// Time from sending to delivery, if the message has been delivered
+    pub fn delivery_time(&self) -> Option<chrono::Duration> {
+        self.delivered_at.map(|at| at - self.timestamp)
+    }
+
+    /// Set how many times an emoji was given; zero removes it
+    pub fn set_reaction_count(&mut self, emoji: &str, count: u32) {
+        if count == 0 {
+            self.reactions.remove(emoji);
+        } else {
+            self.reactions.insert(emoji.to_string(), count);
+        }
+    }
+
+    /// Reactions for display, most given first (ties by emoji, so the order is stable)
+    pub fn sorted_reactions(&self) -> Vec<(String, u32)> {
+        let mut reactions: Vec<(String, u32)> =
+            self.reactions.iter().map(|(emoji, count)| (emoji.clone(), *count)).collect();
+        reactions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        reactions
+    }
+}
+
+/// A message found by a search, with the conversation it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub conv_id: String,
+    pub message: Message,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+
+    fn image(bytes: &[u8], mimetype: &str) -> ImageData {
+        ImageData {
+            data: BASE64.encode(bytes),
+            mimetype: mimetype.to_string(),
         }
     }
+
+    #[test]
+    fn test_attachment_kind_and_size() {
+        assert_eq!(AttachmentKind::from_mimetype("image/PNG"), AttachmentKind::Image);
+        assert_eq!(AttachmentKind::from_mimetype("application/pdf"), AttachmentKind::Document);
+        assert_eq!(AttachmentKind::from_mimetype("text/plain"), AttachmentKind::Document);
+        assert_eq!(AttachmentKind::from_mimetype("application/zip"), AttachmentKind::Other);
+
+        for len in 0..6 {
+            let data = BASE64.encode(vec![0u8; len]);
+            assert_eq!(base64_decoded_len(&data), len);
+        }
+    }
+
+    #[test]
+    fn test_server_time_replaces_local_time_when_given() {
+        let local = Message::new_assistant("a1".to_string(), "hello".to_string(), None);
+        let stamped = local.clone().at_server_time(1_700_000_000_000);
+        assert_eq!(stamped.timestamp.timestamp_millis(), 1_700_000_000_000);
+
+        // Frames without a time send 0
+        assert_eq!(local.clone().at_server_time(0).timestamp, local.timestamp);
+        assert_eq!(server_time(-5), None);
+    }
+
+    #[test]
+    fn test_reactions_sort_by_count_and_drop_at_zero() {
+        let mut message = Message::new_assistant("a1".to_string(), "hello".to_string(), None);
+        message.set_reaction_count("👍", 1);
+        message.set_reaction_count("❤️", 3);
+        message.set_reaction_count("😂", 1);
+        assert_eq!(
+            message.sorted_reactions(),
+            [("❤️".to_string(), 3), ("👍".to_string(), 1), ("😂".to_string(), 1)]
+        );
+
+        message.set_reaction_count("❤️", 0);
+        assert!(!message.reactions.contains_key("❤️"));
+        assert_eq!(message.reactions.len(), 2);
+    }
+
+    #[test]
+    fn test_valid_image_decodes() {
+        assert_eq!(image(PNG_HEADER, "image/png").decode().unwrap(), PNG_HEADER);
+    }
+
+    #[test]
+    fn test_invalid_base64_rejected() {
+        let img = ImageData {
+            data: "not base64!!".to_string(),
+            mimetype: "image/png".to_string(),
+        };
+        assert_eq!(img.validate(), Err(ImageValidationError::InvalidBase64));
+    }
+
+    #[test]
+    fn test_mimetype_mismatch_rejected() {
+        let err = image(PNG_HEADER, "image/jpeg").validate().unwrap_err();
+        assert_eq!(
+            err,
+            ImageValidationError::MimetypeMismatch {
+                declared: "image/jpeg".to_string(),
+                detected: Some("image/png".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_non_image_mimetype_rejected() {
+        let err = image(b"<svg/>", "image/svg+xml").validate().unwrap_err();
+        assert!(matches!(err, ImageValidationError::UnsupportedMimetype(_)));
+    }
+
+    #[test]
+    fn test_oversized_payload_rejected() {
+        let img = ImageData {
+            data: "A".repeat(MAX_IMAGE_BYTES / 3 * 4 + 8),
+            mimetype: "image/png".to_string(),
+        };
+        assert!(matches!(img.validate(), Err(ImageValidationError::TooLarge(_))));
+    }
 }
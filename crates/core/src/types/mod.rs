@@ -4,6 +4,9 @@ pub mod message;
 pub mod conversation;
 pub mod connection;
 
-pub use message::{Message, MessageSender, MessageStatus, ImageData};
-pub use conversation::Conversation;
-pub use connection::ConnectionStatus;
+pub use message::{
+    base64_decoded_len, server_time, Attachment, AttachmentKind, Message, MessageSender, MessageStatus, ImageData, ImageValidationError,
+    SearchResult, MAX_ATTACHMENT_BYTES,
+};
+pub use conversation::{Conversation, ConversationLabels};
+pub use connection::{is_auth_close_code, ConnectionStatus, AUTH_CLOSE_CODES};
@@ -1,19 +1,33 @@
 //! Native event bus using tokio::sync::broadcast
 
+use std::sync::Mutex;
+
 use futures::StreamExt;
-use prsnl_core::{AppEvent, EventBus, EventStream};
+use prsnl_core::{AppEvent, EventBus, EventFilter, EventStream, ReplayBuffer, DEFAULT_REPLAY_LEN};
 use tokio::sync::broadcast;
 use tokio_stream::wrappers::BroadcastStream;
 
 /// Native event bus implementation using tokio broadcast channels
+///
+/// New subscribers first receive the replayed state events (see `ReplayBuffer`).
 pub struct NativeEventBus {
     tx: broadcast::Sender<AppEvent>,
+    replay: Mutex<ReplayBuffer>,
 }
 
 impl NativeEventBus {
+    /// Bus that replays the last `DEFAULT_REPLAY_LEN` events to new subscribers
     pub fn new() -> Self {
+        Self::with_replay(DEFAULT_REPLAY_LEN)
+    }
+
+    /// Bus that replays the last `recent` events (0 for state events only)
+    pub fn with_replay(recent: usize) -> Self {
         let (tx, _) = broadcast::channel(256);
-        Self { tx }
+        Self {
+            tx,
+            replay: Mutex::new(ReplayBuffer::with_recent(recent)),
+        }
     }
 }
 
@@ -25,11 +39,96 @@ impl Default for NativeEventBus {
 
 impl EventBus for NativeEventBus {
     fn publish(&self, event: AppEvent) {
+        // Hold the replay lock across record + send so a concurrent subscribe
+        // sees each event either in its snapshot or on its receiver, never both
+        let mut replay = self.replay.lock().unwrap();
+        replay.record(&event);
         let _ = self.tx.send(event);
     }
 
     fn subscribe(&self) -> EventStream {
+        let replay = self.replay.lock().unwrap();
         let rx = self.tx.subscribe();
-        Box::pin(BroadcastStream::new(rx).filter_map(|r| async { r.ok() }))
+        let replayed = futures::stream::iter(replay.snapshot());
+        Box::pin(replayed.chain(BroadcastStream::new(rx).filter_map(|r| async { r.ok() })))
+    }
+
+    fn subscribe_live(&self) -> EventStream {
+        Box::pin(BroadcastStream::new(self.tx.subscribe()).filter_map(|r| async { r.ok() }))
+    }
+
+    /// Only matching replayed events are copied; live events are still cloned
+    /// by the broadcast receiver, then dropped before reaching the subscriber
+    fn subscribe_filtered(&self, filter: EventFilter) -> EventStream {
+        let replay = self.replay.lock().unwrap();
+        let rx = self.tx.subscribe();
+        let replayed = futures::stream::iter(replay.snapshot_matching(&filter));
+        let live = BroadcastStream::new(rx).filter_map(move |r| {
+            let event = r.ok().filter(|e| filter(e));
+            async move { event }
+        });
+        Box::pin(replayed.chain(live))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prsnl_core::ConnectionStatus;
+
+    #[tokio::test]
+    async fn test_late_subscriber_gets_replay_then_live_events() {
+        let bus = NativeEventBus::with_replay(0);
+        bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
+        bus.publish(AppEvent::ConversationsLoaded(Vec::new()));
+        bus.publish(AppEvent::NavigateToList);
+
+        let mut rx = bus.subscribe();
+        bus.publish(AppEvent::ConversationSelected("native-a".to_string()));
+
+        assert!(matches!(
+            rx.next().await,
+            Some(AppEvent::ConnectionChanged(ConnectionStatus::Connected))
+        ));
+        assert!(matches!(rx.next().await, Some(AppEvent::ConversationsLoaded(_))));
+        assert!(matches!(rx.next().await, Some(AppEvent::ConversationSelected(_))));
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_replay_unless_subscribing_live() {
+        let bus = NativeEventBus::new();
+        bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Disconnected));
+        bus.publish(AppEvent::ReconnectRequested);
+        bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
+
+        let mut rx = bus.subscribe();
+        let mut live = bus.subscribe_live();
+        bus.publish(AppEvent::NavigateToList);
+
+        assert!(matches!(rx.next().await, Some(AppEvent::ReconnectRequested)));
+        assert!(matches!(
+            rx.next().await,
+            Some(AppEvent::ConnectionChanged(ConnectionStatus::Connected))
+        ));
+        assert!(matches!(rx.next().await, Some(AppEvent::NavigateToList)));
+        assert!(matches!(live.next().await, Some(AppEvent::NavigateToList)));
+    }
+
+    #[tokio::test]
+    async fn test_filtered_subscription_skips_other_events() {
+        let bus = NativeEventBus::new();
+        bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
+        bus.publish(AppEvent::SettingsModalToggled(true));
+
+        let rx = bus.subscribe_filtered(Box::new(|e| matches!(e, AppEvent::SettingsModalToggled(_))));
+        bus.publish(AppEvent::NavigateToList);
+        bus.publish(AppEvent::ConversationSelected("native-a".to_string()));
+        bus.publish(AppEvent::SettingsModalToggled(false));
+        drop(bus);
+
+        let received: Vec<AppEvent> = rx.collect().await;
+        assert_eq!(received.len(), 2);
+        assert!(matches!(received[0], AppEvent::SettingsModalToggled(true)));
+        assert!(matches!(received[1], AppEvent::SettingsModalToggled(false)));
     }
 }
@@ -1,9 +1,12 @@
 //! Native platform adapter for PrsnlAssistant
 //!
-//! Provides Transport and EventBus implementations using tokio and tungstenite.
+//! Provides Transport and EventBus implementations using tokio and yawc.
 
 pub mod events;
+pub mod reconnect;
 pub mod transport;
+pub mod wire;
 
 pub use events::NativeEventBus;
-pub use transport::NativeTransport;
+pub use reconnect::{ReconnectConfig, ReconnectPolicy};
+pub use transport::{NativeTransport, NativeTransportConfig};
@@ -0,0 +1,279 @@
+//! Reconnection backoff policy
+//!
+//! Pure attempt counting and exponential backoff, kept separate from the
+//! WebSocket I/O loop so it can be tested without a server.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Maximum reconnection attempts
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Delay before the first reconnection attempt
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound for the delay between attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Factor applied to the delay after each attempt
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+
+/// Tunables for reconnection backoff
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectConfig {
+    pub initial_delay: Duration,
+    /// Cap on the delay between attempts, jitter included
+    pub max_delay: Duration,
+    /// Attempts before giving up; None keeps retrying forever
+    pub max_attempts: Option<u32>,
+    /// Growth factor per attempt (2.0 doubles). Values below 1.0 are treated as 1.0.
+    pub multiplier: f64,
+    /// Fraction by which each delay is randomly stretched or shortened
+    /// (0.2 = ±20%). Only applies when `full_jitter` is false; the default
+    /// is 0.0. Clamped to 0.0..=1.0.
+    pub jitter: f64,
+    /// Sleep a random time between zero and the capped delay instead
+    /// ("full jitter"), so clients don't all reconnect at the same instant
+    /// after a server restart. On by default; the delay then only grows as
+    /// a ceiling. Turn off for exact delays, or for `jitter` instead.
+    pub full_jitter: bool,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: INITIAL_RECONNECT_DELAY,
+            max_delay: MAX_RECONNECT_DELAY,
+            max_attempts: Some(MAX_RECONNECT_ATTEMPTS),
+            multiplier: DEFAULT_MULTIPLIER,
+            jitter: 0.0,
+            full_jitter: true,
+        }
+    }
+}
+
+/// Exponential backoff with a capped delay, jitter, and a limited number of attempts
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    config: ReconnectConfig,
+    attempts: u32,
+    /// Un-jittered delay for the next attempt
+    delay: Duration,
+}
+
+impl ReconnectPolicy {
+    pub fn new(config: ReconnectConfig) -> Self {
+        Self {
+            attempts: 0,
+            delay: config.initial_delay.min(config.max_delay),
+            config,
+        }
+    }
+
+    /// Delay before the next attempt, or None once all attempts are used up
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.config.max_attempts.is_some_and(|max| self.attempts >= max) {
+            return None;
+        }
+        self.attempts = self.attempts.saturating_add(1);
+        let base = self.delay;
+        self.delay = self.grown(base);
+        if self.config.full_jitter {
+            return Some(full_jittered(base.min(self.config.max_delay)));
+        }
+        Some(self.jittered(base).min(self.config.max_delay))
+    }
+
+    /// Start over after a successful connection
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+        self.delay = self.config.initial_delay.min(self.config.max_delay);
+    }
+
+    /// Number of attempts handed out since the last reset
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Attempt limit, or None when retrying forever
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.config.max_attempts
+    }
+
+    /// Next un-jittered delay, saturating at `max_delay` instead of overflowing
+    fn grown(&self, delay: Duration) -> Duration {
+        let multiplier = self.config.multiplier.max(1.0);
+        Duration::try_from_secs_f64(delay.as_secs_f64() * multiplier)
+            .unwrap_or(self.config.max_delay)
+            .min(self.config.max_delay)
+    }
+
+    fn jittered(&self, base: Duration) -> Duration {
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return base;
+        }
+        let factor = 1.0 + rand::thread_rng().gen_range(-jitter..=jitter);
+        base.mul_f64(factor)
+    }
+}
+
+/// Random delay between zero and `ceiling`
+fn full_jittered(ceiling: Duration) -> Duration {
+    ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(ReconnectConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    /// Policy without jitter so delays follow the base schedule exactly
+    fn exact(max_attempts: u32) -> ReconnectPolicy {
+        ReconnectPolicy::new(ReconnectConfig {
+            initial_delay: secs(1),
+            max_delay: secs(30),
+            max_attempts: Some(max_attempts),
+            multiplier: 2.0,
+            jitter: 0.0,
+            full_jitter: false,
+        })
+    }
+
+    #[test]
+    fn test_delay_doubles_each_attempt() {
+        let mut policy = exact(4);
+
+        assert_eq!(policy.next_delay(), Some(secs(1)));
+        assert_eq!(policy.next_delay(), Some(secs(2)));
+        assert_eq!(policy.next_delay(), Some(secs(4)));
+        assert_eq!(policy.next_delay(), Some(secs(8)));
+        assert_eq!(policy.attempts(), 4);
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let mut policy = exact(10);
+        let delays: Vec<_> = std::iter::from_fn(|| policy.next_delay()).collect();
+
+        assert_eq!(delays[4], secs(16));
+        assert_eq!(delays[5], secs(30));
+        assert_eq!(delays[9], secs(30));
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let mut policy = ReconnectPolicy::default();
+
+        for _ in 0..MAX_RECONNECT_ATTEMPTS {
+            assert!(policy.next_delay().is_some());
+        }
+        assert_eq!(policy.next_delay(), None);
+        assert_eq!(policy.next_delay(), None);
+        assert_eq!(policy.attempts(), MAX_RECONNECT_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_unlimited_attempts_never_give_up() {
+        let mut policy = ReconnectPolicy::new(ReconnectConfig {
+            max_attempts: None,
+            jitter: 0.0,
+            full_jitter: false,
+            ..ReconnectConfig::default()
+        });
+
+        for _ in 0..1000 {
+            assert!(policy.next_delay().is_some());
+        }
+        assert_eq!(policy.next_delay(), Some(secs(30)));
+        assert_eq!(policy.max_attempts(), None);
+    }
+
+    #[test]
+    fn test_reset_restarts_progression() {
+        let mut policy = exact(3);
+        while policy.next_delay().is_some() {}
+
+        policy.reset();
+
+        assert_eq!(policy.attempts(), 0);
+        assert_eq!(policy.next_delay(), Some(secs(1)));
+        assert_eq!(policy.next_delay(), Some(secs(2)));
+    }
+
+    #[test]
+    fn test_jittered_delays_stay_within_bounds() {
+        let config = ReconnectConfig {
+            max_attempts: Some(8),
+            jitter: 0.25,
+            full_jitter: false,
+            ..ReconnectConfig::default()
+        };
+        let base = [1, 2, 4, 8, 16, 30, 30, 30];
+
+        for _ in 0..50 {
+            let mut policy = ReconnectPolicy::new(config.clone());
+            for expected in base {
+                let delay = policy.next_delay().unwrap();
+                let expected = secs(expected);
+                assert!(delay >= expected.mul_f64(0.75), "{:?} below {:?}", delay, expected);
+                assert!(delay <= expected.mul_f64(1.25), "{:?} above {:?}", delay, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_cap_and_multiplier_saturate() {
+        let config = ReconnectConfig {
+            initial_delay: secs(1),
+            max_delay: secs(10),
+            max_attempts: Some(200),
+            multiplier: 3.0,
+            jitter: 0.5,
+            full_jitter: false,
+        };
+        let mut policy = ReconnectPolicy::new(ReconnectConfig { jitter: 0.0, ..config.clone() });
+        let delays: Vec<_> = std::iter::from_fn(|| policy.next_delay()).collect();
+
+        assert_eq!(delays[..4], [secs(1), secs(3), secs(9), secs(10)]);
+        assert!(delays[4..].iter().all(|d| *d == secs(10)));
+
+        // Jitter and a huge multiplier must never push a delay past the cap
+        let mut policy = ReconnectPolicy::new(ReconnectConfig { multiplier: 1e300, ..config });
+        while let Some(delay) = policy.next_delay() {
+            assert!(delay <= secs(10), "{:?} above cap", delay);
+        }
+    }
+
+    #[test]
+    fn test_full_jitter_stays_below_growing_ceiling() {
+        let ceilings = [1, 2, 4, 8, 16, 30, 30, 30];
+        let mut delays = Vec::new();
+
+        for _ in 0..50 {
+            let mut policy = ReconnectPolicy::new(ReconnectConfig {
+                max_attempts: Some(8),
+                ..ReconnectConfig::default()
+            });
+            for ceiling in ceilings {
+                let delay = policy.next_delay().unwrap();
+                assert!(delay <= secs(ceiling), "{:?} above {:?}", delay, secs(ceiling));
+                delays.push(delay);
+            }
+        }
+
+        // Later attempts may wait longer than the first one ever could
+        assert!(delays.iter().any(|d| *d > secs(1)));
+        assert!(delays.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+}
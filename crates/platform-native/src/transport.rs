@@ -1,58 +1,149 @@
-//! Native WebSocket transport using tokio-tungstenite
+//! Native WebSocket transport using yawc
 //!
 //! This module provides a full WebSocket transport implementation for native platforms.
 //! It handles connection management, message dispatch, ping/pong keep-alive, and reconnection.
+//! Connections offer permessage-deflate unless `NativeTransportConfig::compression` is off.
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use chrono::Utc;
 use futures_util::{stream::SplitSink, SinkExt, StreamExt};
-use tokio::sync::Mutex;
-use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
-use tracing::info;
+use tokio::sync::{Mutex, Notify};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
+use yawc::{close::CloseCode, Frame, OpCode, WebSocketError};
 
 use prsnl_core::{
-    AppEvent, ConnectionStatus, Conversation, EventBus, HistoryMessage, ImageData, ImagePayload,
-    Message, MessageSender, MessageStatus, Transport, TransportResult, TransportResultVoid,
-    WSClientMessage, WSServerMessage,
+    frame_type, history_batches, is_auth_close_code, server_time, AppEvent, AttachmentPayload, ConnectionStatus, Conversation, ConversationLabels, EventBus,
+    HistoryMessage, ImageData, ImagePayload, Message, MessageSender, MessageStatus, OutboundQueue,
+    SearchResult, Transport, TransportDebug, TransportError, TransportResult, TransportResultVoid,
+    WSClientMessage, WSServerMessage, DEFAULT_OUTBOUND_QUEUE_CAPACITY, HISTORY_BATCH_SIZE, QUEUE_FULL_ERROR,
 };
+#[cfg(feature = "debug-log")]
+use prsnl_core::{FrameDirection, FrameLog, LoggedFrame};
+
+use crate::reconnect::{ReconnectConfig, ReconnectPolicy};
+use crate::wire::{self, WireStats, WsConnection};
+
+/// Frames kept for `TransportDebug::recent_frames`
+#[cfg(feature = "debug-log")]
+type SharedFrameLog = Arc<std::sync::Mutex<FrameLog>>;
+
+/// Write half of the socket; text frames are noted in the debug log
+struct FrameSink {
+    sink: SplitSink<WsConnection, Frame>,
+    stats: Arc<WireStats>,
+    #[cfg(feature = "debug-log")]
+    log: SharedFrameLog,
+}
 
-/// WebSocket connection type alias
-pub type WsConnection = tokio_tungstenite::WebSocketStream<
-    tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
->;
+impl FrameSink {
+    async fn send_text(&mut self, text: String) -> Result<(), WebSocketError> {
+        #[cfg(feature = "debug-log")]
+        self.log.lock().unwrap().record(FrameDirection::Sent, &text);
+        self.stats.sent_text(text.len());
+        self.sink.send(Frame::text(text)).await
+    }
+
+    async fn close(&mut self) -> Result<(), WebSocketError> {
+        self.sink.send(Frame::close(CloseCode::Normal, "")).await
+    }
+}
 
 /// Ping interval for keep-alive
 const PING_INTERVAL: Duration = Duration::from_secs(30);
 
-/// Maximum reconnection attempts
-const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// How long a connection attempt may take before it counts as failed
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ping intervals without any incoming frame before the connection is
+/// considered dead (catches half-open TCP connections)
+const MISSED_PINGS_BEFORE_TIMEOUT: u32 = 2;
+
+/// Keep-alive and reconnection tunables for `NativeTransport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeTransportConfig {
+    /// Time between keep-alive pings
+    pub ping_interval: Duration,
+    /// Limit on each connection attempt, so a bad host can't hang on "Connecting"
+    pub connect_timeout: Duration,
+    /// Backoff, jitter and attempt limit for reconnection
+    pub reconnect: ReconnectConfig,
+    /// Chat messages kept while disconnected; the oldest is dropped beyond this
+    pub max_queued_messages: usize,
+    /// Offer permessage-deflate; servers without it fall back to plain frames
+    pub compression: bool,
+}
 
-/// Delay between reconnection attempts (starts at this and increases exponentially)
-const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+impl Default for NativeTransportConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: PING_INTERVAL,
+            connect_timeout: CONNECT_TIMEOUT,
+            reconnect: ReconnectConfig::default(),
+            max_queued_messages: DEFAULT_OUTBOUND_QUEUE_CAPACITY,
+            compression: true,
+        }
+    }
+}
 
-/// Native transport implementation using tokio-tungstenite
+/// Native transport implementation using yawc
 pub struct NativeTransport {
     /// WebSocket sender for outgoing messages
-    sender: Arc<Mutex<Option<SplitSink<WsConnection, WsMessage>>>>,
+    sender: Arc<Mutex<Option<FrameSink>>>,
     /// Connection state flag
     connected: Arc<AtomicBool>,
-    /// Flag to signal shutdown
-    shutdown: Arc<AtomicBool>,
+    /// Bumped by every connect and disconnect, so an older reconnect loop stops
+    generation: Arc<AtomicU64>,
+    /// Conversation open in the UI (fallback for responses without a conversation id)
+    active_conversation: Arc<RwLock<Option<String>>>,
+    /// Chat frames sent while disconnected, flushed after reconnecting
+    outbound: Arc<Mutex<OutboundQueue>>,
+    /// Event bus of the latest connect, to fail frames dropped from a full queue
+    event_bus: Arc<RwLock<Option<Arc<dyn EventBus>>>>,
+    /// Ping interval and reconnection settings
+    config: NativeTransportConfig,
+    /// Recent frames in both directions
+    #[cfg(feature = "debug-log")]
+    frame_log: SharedFrameLog,
 }
 
 impl NativeTransport {
-    /// Create a new native transport
+    /// Create a new native transport with the default settings
     pub fn new() -> Self {
+        Self::with_config(NativeTransportConfig::default())
+    }
+
+    /// Create a new native transport with custom ping and reconnection settings
+    pub fn with_config(config: NativeTransportConfig) -> Self {
         Self {
             sender: Arc::new(Mutex::new(None)),
             connected: Arc::new(AtomicBool::new(false)),
-            shutdown: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(0)),
+            active_conversation: Arc::new(RwLock::new(None)),
+            outbound: Arc::new(Mutex::new(OutboundQueue::new(config.max_queued_messages))),
+            event_bus: Arc::new(RwLock::new(None)),
+            config,
+            #[cfg(feature = "debug-log")]
+            frame_log: Arc::default(),
         }
     }
+
+    /// Use custom reconnection backoff settings
+    pub fn with_reconnect_config(mut self, config: ReconnectConfig) -> Self {
+        self.config.reconnect = config;
+        self
+    }
+
+    /// Offer permessage-deflate when connecting, or not
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.config.compression = enabled;
+        self
+    }
 }
 
 impl Default for NativeTransport {
@@ -61,35 +152,86 @@ impl Default for NativeTransport {
     }
 }
 
+impl TransportDebug for NativeTransport {
+    #[cfg(feature = "debug-log")]
+    fn recent_frames(&self) -> Vec<LoggedFrame> {
+        self.frame_log.lock().unwrap().frames()
+    }
+}
+
 impl Transport for NativeTransport {
-    fn connect(&self, url: String, event_bus: Arc<dyn EventBus>) -> TransportResultVoid {
+    fn connect(
+        &self,
+        url: String,
+        auth_token: Option<String>,
+        event_bus: Arc<dyn EventBus>,
+    ) -> TransportResultVoid {
         let sender = self.sender.clone();
         let connected = self.connected.clone();
-        let shutdown = self.shutdown.clone();
-
-        // Reset shutdown flag
-        shutdown.store(false, Ordering::SeqCst);
+        let generation = self.generation.clone();
+        let active_conversation = self.active_conversation.clone();
+        let reconnect_config = self.config.reconnect.clone();
+        let ping_interval = self.config.ping_interval;
+        let connect_timeout = self.config.connect_timeout;
+        let compression = self.config.compression;
+        let outbound = self.outbound.clone();
+        #[cfg(feature = "debug-log")]
+        let frame_log = self.frame_log.clone();
+
+        // Take over from any earlier connection; it stops once it notices
+        let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let stopped = move || generation.load(Ordering::SeqCst) != current;
+        *self.event_bus.write().unwrap() = Some(event_bus.clone());
 
         Box::pin(async move {
             info!("Attempting WebSocket connection to {}", url);
             event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connecting));
 
-            let mut reconnect_attempts = 0;
-            let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+            let mut policy = ReconnectPolicy::new(reconnect_config);
 
             loop {
-                match connect_async(&url).await {
-                    Ok((ws_stream, _)) => {
+                let stats = Arc::new(WireStats::default());
+                let attempt = tokio::time::timeout(connect_timeout, wire::open(&url, compression, stats.clone()))
+                    .await
+                    .map_err(|_| TransportError::Timeout)
+                    .and_then(|result| result.map_err(connect_error));
+                // Why this attempt failed, reported if reconnecting gives up
+                let failure = match attempt {
+                    Ok(ws_stream) => {
+                        if stopped() {
+                            return Ok(());
+                        }
                         let (write, mut read) = ws_stream.split();
 
                         // Store sender for outgoing messages
-                        *sender.lock().await = Some(write);
-                        connected.store(true, Ordering::SeqCst);
-                        reconnect_attempts = 0;
-                        reconnect_delay = INITIAL_RECONNECT_DELAY;
+                        *sender.lock().await = Some(FrameSink {
+                            sink: write,
+                            stats: stats.clone(),
+                            #[cfg(feature = "debug-log")]
+                            log: frame_log.clone(),
+                        });
+                        policy.reset();
 
                         event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
-                        info!("WebSocket connection established");
+                        info!(
+                            "WebSocket connection established (permessage-deflate {})",
+                            if stats.deflate() { "on" } else { "off" }
+                        );
+
+                        // Authenticate before anything else
+                        if let Some(token) = &auth_token {
+                            let msg = WSClientMessage::Authenticate {
+                                id: Uuid::new_v4().to_string(),
+                                timestamp: Utc::now().timestamp_millis(),
+                                token: token.clone(),
+                            };
+                            if let Ok(json) = serde_json::to_string(&msg) {
+                                let mut guard = sender.lock().await;
+                                if let Some(s) = guard.as_mut() {
+                                    let _ = s.send_text(json).await;
+                                }
+                            }
+                        }
 
                         // Subscribe to notifications
                         {
@@ -104,7 +246,7 @@ impl Transport for NativeTransport {
                             if let Ok(json) = serde_json::to_string(&msg) {
                                 let mut guard = sender.lock().await;
                                 if let Some(s) = guard.as_mut() {
-                                    let _ = s.send(WsMessage::Text(json.into())).await;
+                                    let _ = s.send_text(json).await;
                                 }
                             }
                         }
@@ -118,22 +260,36 @@ impl Transport for NativeTransport {
                             if let Ok(json) = serde_json::to_string(&msg) {
                                 let mut guard = sender.lock().await;
                                 if let Some(s) = guard.as_mut() {
-                                    let _ = s.send(WsMessage::Text(json.into())).await;
+                                    let _ = s.send_text(json).await;
                                 }
                             }
                         }
 
+                        // Send what was queued while disconnected; only then is the connection usable
+                        flush_outbound(&sender, &outbound, &connected, &event_bus).await;
+
+                        // Any frame from the server counts as a sign of life
+                        let last_activity = Arc::new(std::sync::Mutex::new(Instant::now()));
+                        // Signalled by the ping task when the server went silent
+                        let timed_out = Arc::new(Notify::new());
+
                         // Spawn ping task for keep-alive
                         let ping_sender = sender.clone();
                         let ping_connected = connected.clone();
-                        let ping_shutdown = shutdown.clone();
-
-                        tokio::spawn(async move {
-                            let mut interval = tokio::time::interval(PING_INTERVAL);
+                        let ping_stopped = stopped.clone();
+                        let ping_event_bus = event_bus.clone();
+                        let ping_last_activity = last_activity.clone();
+                        let ping_timed_out = timed_out.clone();
+                        #[cfg(feature = "debug-log")]
+                        let ping_stats = stats.clone();
+
+                        let ping_task = tokio::spawn(async move {
+                            let timeout = ping_interval * MISSED_PINGS_BEFORE_TIMEOUT;
+                            let mut interval = tokio::time::interval(ping_interval);
                             loop {
                                 interval.tick().await;
 
-                                if ping_shutdown.load(Ordering::SeqCst) {
+                                if ping_stopped() {
                                     break;
                                 }
 
@@ -141,6 +297,21 @@ impl Transport for NativeTransport {
                                     break;
                                 }
 
+                                let silent_for = ping_last_activity.lock().unwrap().elapsed();
+                                if silent_for >= timeout {
+                                    warn!("No frames from server for {:?}, dropping connection", silent_for);
+                                    ping_connected.store(false, Ordering::SeqCst);
+                                    *ping_sender.lock().await = None;
+                                    ping_event_bus.publish(AppEvent::ConnectionChanged(
+                                        ConnectionStatus::Disconnected,
+                                    ));
+                                    ping_timed_out.notify_one();
+                                    break;
+                                }
+
+                                #[cfg(feature = "debug-log")]
+                                debug!("Compression so far: {}", ping_stats.summary());
+
                                 let msg = WSClientMessage::Ping {
                                     id: Uuid::new_v4().to_string(),
                                     timestamp: Utc::now().timestamp_millis(),
@@ -149,7 +320,7 @@ impl Transport for NativeTransport {
                                 let mut guard = ping_sender.lock().await;
                                 if let Some(s) = guard.as_mut() {
                                     if let Ok(json) = serde_json::to_string(&msg) {
-                                        if s.send(WsMessage::Text(json.into())).await.is_err() {
+                                        if s.send_text(json).await.is_err() {
                                             break;
                                         }
                                     }
@@ -157,19 +328,58 @@ impl Transport for NativeTransport {
                             }
                         });
 
-                        // Process incoming messages
-                        while let Some(msg_result) = read.next().await {
-                            if shutdown.load(Ordering::SeqCst) {
-                                info!("Shutdown requested, closing connection");
+                        // Set when the server rejects the auth token
+                        let mut auth_failure = None;
+
+                        // Process incoming messages until the connection ends or times out
+                        loop {
+                            let frame = tokio::select! {
+                                frame = read.next() => frame,
+                                _ = timed_out.notified() => break,
+                            };
+                            *last_activity.lock().unwrap() = Instant::now();
+
+                            if stopped() {
+                                info!("Disconnect requested, closing connection");
                                 break;
                             }
 
-                            match msg_result {
-                                Ok(WsMessage::Text(text)) => {
+                            // Errors end the stream; pings are answered by yawc
+                            let Some(frame) = frame else {
+                                info!("WebSocket connection lost");
+                                connected.store(false, Ordering::SeqCst);
+                                event_bus.publish(AppEvent::ConnectionChanged(
+                                    ConnectionStatus::Disconnected,
+                                ));
+                                break;
+                            };
+                            match frame.opcode() {
+                                OpCode::Text => {
+                                    let Ok(text) = std::str::from_utf8(frame.payload()) else {
+                                        info!("Ignoring server message that isn't valid UTF-8");
+                                        continue;
+                                    };
+                                    stats.received_text(text.len());
+                                    #[cfg(feature = "debug-log")]
+                                    frame_log.lock().unwrap().record(FrameDirection::Received, text);
+
                                     // Parse and dispatch the message
-                                    match serde_json::from_str::<WSServerMessage>(&text) {
+                                    match serde_json::from_str::<WSServerMessage>(text) {
+                                        Ok(WSServerMessage::Unknown) => {
+                                            debug!(
+                                                "Ignoring server message of unknown type {}",
+                                                frame_type(text).unwrap_or_default()
+                                            );
+                                        }
                                         Ok(msg) => {
-                                            dispatch_server_message(msg, &event_bus);
+                                            if let WSServerMessage::AuthFailed { reason, .. } = &msg {
+                                                auth_failure = Some(reason.clone());
+                                            }
+                                            let active = active_conversation.read().unwrap().clone();
+                                            dispatch_server_message(msg, &event_bus, active);
+                                            if auth_failure.is_some() {
+                                                break;
+                                            }
                                         }
                                         Err(e) => {
                                             info!(
@@ -179,13 +389,20 @@ impl Transport for NativeTransport {
                                         }
                                     }
                                 }
-                                Ok(WsMessage::Ping(data)) => {
-                                    let mut guard = sender.lock().await;
-                                    if let Some(s) = guard.as_mut() {
-                                        let _ = s.send(WsMessage::Pong(data)).await;
+                                OpCode::Close => {
+                                    let code = frame.close_code().map(u16::from);
+                                    if let Some(code) = code.filter(|code| is_auth_close_code(*code)) {
+                                        info!("Server closed the connection over credentials ({})", code);
+                                        let reason = frame
+                                            .close_reason()
+                                            .ok()
+                                            .flatten()
+                                            .filter(|reason| !reason.is_empty())
+                                            .map(str::to_string);
+                                        event_bus.publish(AppEvent::AuthFailed { reason: reason.clone() });
+                                        auth_failure = Some(reason);
+                                        break;
                                     }
-                                }
-                                Ok(WsMessage::Close(_)) => {
                                     info!("WebSocket connection closed by server");
                                     connected.store(false, Ordering::SeqCst);
                                     event_bus.publish(AppEvent::ConnectionChanged(
@@ -193,62 +410,79 @@ impl Transport for NativeTransport {
                                     ));
                                     break;
                                 }
-                                Err(e) => {
-                                    info!("WebSocket error: {:?}", e);
-                                    connected.store(false, Ordering::SeqCst);
-                                    event_bus.publish(AppEvent::ConnectionChanged(
-                                        ConnectionStatus::Disconnected,
-                                    ));
-                                    break;
-                                }
                                 _ => {}
                             }
                         }
+                        #[cfg(feature = "debug-log")]
+                        debug!("Compression over the connection: {}", stats.summary());
 
-                        // Clear sender on disconnect
-                        *sender.lock().await = None;
+                        // Clear sender on disconnect; the next connection gets its own ping task
+                        ping_task.abort();
+                        {
+                            let mut guard = sender.lock().await;
+                            // After a disconnect the sender may already belong to a newer connection
+                            if stopped() {
+                                return Ok(());
+                            }
+                            *guard = None;
+                        }
                         connected.store(false, Ordering::SeqCst);
 
-                        // If shutdown was requested, exit the reconnect loop
-                        if shutdown.load(Ordering::SeqCst) {
-                            return Ok(());
+                        // Retrying with the same token would only fail again
+                        if let Some(reason) = auth_failure {
+                            event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::auth_error(reason.clone())));
+                            return Err(TransportError::AuthFailed(reason));
                         }
+                        None
+                    }
+                    Err(TransportError::AuthFailed(reason)) => {
+                        info!("Server refused the connection: {}", TransportError::AuthFailed(reason.clone()));
+                        connected.store(false, Ordering::SeqCst);
+                        event_bus.publish(AppEvent::AuthFailed { reason: reason.clone() });
+                        event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::auth_error(reason.clone())));
+                        return Err(TransportError::AuthFailed(reason));
                     }
                     Err(e) => {
-                        info!("Failed to connect: {:?}", e);
+                        info!("Failed to connect: {}", e);
                         connected.store(false, Ordering::SeqCst);
                         event_bus.publish(AppEvent::ConnectionChanged(
                             ConnectionStatus::Disconnected,
                         ));
+                        Some(e)
                     }
-                }
+                };
 
                 // Reconnection logic
-                reconnect_attempts += 1;
-                if reconnect_attempts > MAX_RECONNECT_ATTEMPTS {
+                let Some(reconnect_delay) = policy.next_delay() else {
                     info!(
                         "Max reconnection attempts ({}) reached, giving up",
-                        MAX_RECONNECT_ATTEMPTS
+                        policy.attempts()
                     );
-                    return Err(format!(
-                        "Failed to connect after {} attempts",
-                        MAX_RECONNECT_ATTEMPTS
-                    ));
-                }
+                    if failure == Some(TransportError::Timeout) {
+                        return Err(TransportError::Timeout);
+                    }
+                    return Err(TransportError::Io(format!(
+                        "failed to connect after {} attempts",
+                        policy.attempts()
+                    )));
+                };
 
-                if shutdown.load(Ordering::SeqCst) {
+                if stopped() {
                     return Ok(());
                 }
 
+                let limit = policy.max_attempts().map_or("unlimited".to_string(), |max| max.to_string());
                 info!(
                     "Reconnecting in {:?} (attempt {}/{})",
-                    reconnect_delay, reconnect_attempts, MAX_RECONNECT_ATTEMPTS
+                    reconnect_delay,
+                    policy.attempts(),
+                    limit
                 );
                 event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connecting));
                 tokio::time::sleep(reconnect_delay).await;
-
-                // Exponential backoff
-                reconnect_delay = std::cmp::min(reconnect_delay * 2, Duration::from_secs(30));
+                if stopped() {
+                    return Ok(());
+                }
             }
         })
     }
@@ -256,16 +490,16 @@ impl Transport for NativeTransport {
     fn disconnect(&self) -> TransportResultVoid {
         let sender = self.sender.clone();
         let connected = self.connected.clone();
-        let shutdown = self.shutdown.clone();
+        let generation = self.generation.clone();
 
         Box::pin(async move {
             info!("Disconnecting WebSocket");
-            shutdown.store(true, Ordering::SeqCst);
+            generation.fetch_add(1, Ordering::SeqCst);
 
             // Send close frame if connected
             let mut guard = sender.lock().await;
             if let Some(s) = guard.as_mut() {
-                let _ = s.send(WsMessage::Close(None)).await;
+                let _ = s.close().await;
             }
             *guard = None;
 
@@ -276,33 +510,51 @@ impl Transport for NativeTransport {
 
     fn send_chat(
         &self,
+        msg_id: String,
         conv_id: String,
         text: String,
         image: Option<ImagePayload>,
-    ) -> TransportResult<String> {
+        files: Vec<AttachmentPayload>,
+        reply_to: Option<String>,
+    ) -> TransportResultVoid {
         let sender = self.sender.clone();
+        let connected = self.connected.clone();
+        let outbound = self.outbound.clone();
+        let event_bus = self.event_bus.clone();
 
         Box::pin(async move {
-            let msg_id = Uuid::new_v4().to_string();
             let msg = WSClientMessage::Chat {
                 id: msg_id.clone(),
                 timestamp: Utc::now().timestamp_millis(),
                 conversation_id: conv_id,
                 body: text,
                 image,
-                reply_to: None,
+                files,
+                reply_to,
             };
 
-            let json =
-                serde_json::to_string(&msg).map_err(|e| format!("Serialization error: {}", e))?;
+            // Checked under the queue lock, so a send can't overtake the flush after reconnecting
+            let mut queue = outbound.lock().await;
+            if !connected.load(Ordering::SeqCst) {
+                info!("Not connected, queueing message {}", msg_id);
+                match queue.push(msg) {
+                    // Nothing can be queued; fail this send instead
+                    Some(WSClientMessage::Chat { id, .. }) if id == msg_id => return Err(TransportError::NotConnected),
+                    dropped => fail_dropped(dropped, event_bus.read().unwrap().as_ref()),
+                }
+                return Ok(());
+            }
+            drop(queue);
+
+            let json = serde_json::to_string(&msg)?;
 
             let mut guard = sender.lock().await;
-            let s = guard.as_mut().ok_or("WebSocket not connected")?;
-            s.send(WsMessage::Text(json.into()))
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
                 .await
-                .map_err(|e| format!("Send error: {}", e))?;
+                .map_err(send_error)?;
 
-            Ok(msg_id)
+            Ok(())
         })
     }
 
@@ -315,20 +567,19 @@ impl Transport for NativeTransport {
                 timestamp: Utc::now().timestamp_millis(),
             };
 
-            let json =
-                serde_json::to_string(&msg).map_err(|e| format!("Serialization error: {}", e))?;
+            let json = serde_json::to_string(&msg)?;
 
             let mut guard = sender.lock().await;
-            let s = guard.as_mut().ok_or("WebSocket not connected")?;
-            s.send(WsMessage::Text(json.into()))
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
                 .await
-                .map_err(|e| format!("Send error: {}", e))?;
+                .map_err(send_error)?;
 
             Ok(())
         })
     }
 
-    fn send_get_history(&self, conv_id: String, limit: Option<u32>) -> TransportResultVoid {
+    fn send_get_history(&self, conv_id: String, limit: Option<u32>, before: Option<i64>) -> TransportResultVoid {
         let sender = self.sender.clone();
 
         Box::pin(async move {
@@ -337,41 +588,41 @@ impl Transport for NativeTransport {
                 timestamp: Utc::now().timestamp_millis(),
                 conversation_id: conv_id,
                 limit,
+                before,
             };
 
-            let json =
-                serde_json::to_string(&msg).map_err(|e| format!("Serialization error: {}", e))?;
+            let json = serde_json::to_string(&msg)?;
 
             let mut guard = sender.lock().await;
-            let s = guard.as_mut().ok_or("WebSocket not connected")?;
-            s.send(WsMessage::Text(json.into()))
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
                 .await
-                .map_err(|e| format!("Send error: {}", e))?;
+                .map_err(send_error)?;
 
             Ok(())
         })
     }
 
-    fn send_create_conversation(&self, title: Option<String>) -> TransportResultVoid {
+    fn send_create_conversation(&self, title: Option<String>) -> TransportResult<String> {
         let sender = self.sender.clone();
 
         Box::pin(async move {
+            let request_id = Uuid::new_v4().to_string();
             let msg = WSClientMessage::CreateConversation {
-                id: Uuid::new_v4().to_string(),
+                id: request_id.clone(),
                 timestamp: Utc::now().timestamp_millis(),
                 title,
             };
 
-            let json =
-                serde_json::to_string(&msg).map_err(|e| format!("Serialization error: {}", e))?;
+            let json = serde_json::to_string(&msg)?;
 
             let mut guard = sender.lock().await;
-            let s = guard.as_mut().ok_or("WebSocket not connected")?;
-            s.send(WsMessage::Text(json.into()))
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
                 .await
-                .map_err(|e| format!("Send error: {}", e))?;
+                .map_err(send_error)?;
 
-            Ok(())
+            Ok(request_id)
         })
     }
 
@@ -385,14 +636,230 @@ impl Transport for NativeTransport {
                 conversation_id: conv_id,
             };
 
-            let json =
-                serde_json::to_string(&msg).map_err(|e| format!("Serialization error: {}", e))?;
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_set_conversation_labels(
+        &self,
+        conv_id: String,
+        tags: Vec<String>,
+        color: Option<String>,
+    ) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::SetConversationLabels {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                tags,
+                color,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_rename_conversation(&self, conv_id: String, title: String) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::RenameConversation {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                title,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_pin_conversation(&self, conv_id: String, pinned: bool) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::PinConversation {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                pinned,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_edit_message(&self, conv_id: String, message_id: String, new_body: String) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::EditMessage {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+                new_body,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_delete_message(&self, conv_id: String, message_id: String) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::DeleteMessage {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_reaction(&self, conv_id: String, message_id: String, emoji: String, remove: bool) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::ReactMessage {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+                emoji,
+                remove,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_mark_read(&self, conv_id: String, up_to_message_id: String) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::MarkRead {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                up_to_message_id,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_typing(&self, conv_id: String, is_typing: bool) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::Typing {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                conversation_id: conv_id,
+                is_typing,
+            };
+
+            let json = serde_json::to_string(&msg)?;
+
+            let mut guard = sender.lock().await;
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
+                .await
+                .map_err(send_error)?;
+
+            Ok(())
+        })
+    }
+
+    fn send_search(&self, query: String, conv_id: Option<String>, limit: Option<u32>) -> TransportResultVoid {
+        let sender = self.sender.clone();
+
+        Box::pin(async move {
+            let msg = WSClientMessage::SearchMessages {
+                id: Uuid::new_v4().to_string(),
+                timestamp: Utc::now().timestamp_millis(),
+                query,
+                conversation_id: conv_id,
+                limit,
+            };
+
+            let json = serde_json::to_string(&msg)?;
 
             let mut guard = sender.lock().await;
-            let s = guard.as_mut().ok_or("WebSocket not connected")?;
-            s.send(WsMessage::Text(json.into()))
+            let s = guard.as_mut().ok_or(TransportError::NotConnected)?;
+            s.send_text(json)
                 .await
-                .map_err(|e| format!("Send error: {}", e))?;
+                .map_err(send_error)?;
 
             Ok(())
         })
@@ -401,18 +868,106 @@ impl Transport for NativeTransport {
     fn is_connected(&self) -> bool {
         self.connected.load(Ordering::SeqCst)
     }
+
+    fn set_active_conversation(&self, conv_id: Option<String>) {
+        *self.active_conversation.write().unwrap() = conv_id;
+    }
+}
+
+/// Map a failed handshake; a 401 or 403 answer means the credentials were refused
+fn connect_error(e: WebSocketError) -> TransportError {
+    match e {
+        WebSocketError::InvalidStatusCode(status @ (401 | 403)) => {
+            TransportError::AuthFailed(Some(format!("Server answered HTTP {}", status)))
+        }
+        e => TransportError::Io(e.to_string()),
+    }
+}
+
+/// Classify a failed socket send
+fn send_error(e: WebSocketError) -> TransportError {
+    match e {
+        WebSocketError::ConnectionClosed => TransportError::Closed,
+        e => TransportError::Io(e.to_string()),
+    }
+}
+
+/// Fail chat frames dropped from a full outbound queue, so they show as not sent
+fn fail_dropped(dropped: impl IntoIterator<Item = WSClientMessage>, event_bus: Option<&Arc<dyn EventBus>>) {
+    for frame in dropped {
+        if let WSClientMessage::Chat { id, conversation_id, .. } = frame {
+            warn!("Outbound queue full, dropping oldest queued message {}", id);
+            if let Some(event_bus) = event_bus {
+                event_bus.publish(AppEvent::MessageError {
+                    conv_id: conversation_id,
+                    msg_id: id,
+                    error: QUEUE_FULL_ERROR.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Send queued chat frames in order, announcing each one that went out,
+/// then mark the connection usable
+///
+/// The queue stays locked throughout, so new sends wait behind it. Stops at
+/// the first failure and puts the unsent frames back in front.
+async fn flush_outbound(
+    sender: &Mutex<Option<FrameSink>>,
+    outbound: &Mutex<OutboundQueue>,
+    connected: &AtomicBool,
+    event_bus: &Arc<dyn EventBus>,
+) {
+    let mut queue = outbound.lock().await;
+    let frames = queue.drain();
+    if !frames.is_empty() {
+        info!("Flushing {} queued messages", frames.len());
+    }
+    let mut frames = frames.into_iter();
+    while let Some(frame) = frames.next() {
+        let WSClientMessage::Chat { id, conversation_id, .. } = &frame else {
+            continue;
+        };
+        let (wire_id, conv_id) = (id.clone(), conversation_id.clone());
+        let sent = match serde_json::to_string(&frame) {
+            Ok(json) => {
+                let mut guard = sender.lock().await;
+                match guard.as_mut() {
+                    Some(s) => s.send_text(json).await.is_ok(),
+                    None => false,
+                }
+            }
+            Err(_) => false,
+        };
+        if !sent {
+            let unsent = std::iter::once(frame).chain(frames).collect();
+            let dropped = queue.restore(unsent);
+            fail_dropped(dropped, Some(event_bus));
+            return;
+        }
+        event_bus.publish(AppEvent::QueuedMessageSent { conv_id, wire_id });
+    }
+    connected.store(true, Ordering::SeqCst);
 }
 
 /// Dispatch a server message to the event bus (standalone function for use in async context)
-fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
+///
+/// `active_conversation` is used when a response, ack or typing update omits its conversation id.
+fn dispatch_server_message(
+    msg: WSServerMessage,
+    event_bus: &Arc<dyn EventBus>,
+    active_conversation: Option<String>,
+) {
     match msg {
         WSServerMessage::Response {
             id,
+            timestamp,
             reply_to,
             conversation_id,
             body,
             image,
-            ..
+            files,
         } => {
             info!(
                 "Received response for message {} in {:?}",
@@ -424,10 +979,38 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
                 mimetype: img.mimetype,
             });
 
-            let message = Message::new_assistant(id, body, image_data);
+            let files = files.into_iter().map(Into::into).collect();
+            let message = Message::new_assistant(id, body, image_data)
+                .with_files(files)
+                .at_server_time(timestamp);
+
+            // Single-conversation servers omit the id; deliver to the open chat
+            match conversation_id.or(active_conversation) {
+                Some(conv_id) => {
+                    event_bus.publish(AppEvent::MessageReceived {
+                        conv_id,
+                        message,
+                        reply_to: Some(reply_to),
+                    });
+                }
+                None => {
+                    info!("Dropping response {}: no conversation id and none active", reply_to);
+                }
+            }
+        }
 
-            if let Some(conv_id) = conversation_id {
-                event_bus.publish(AppEvent::MessageReceived { conv_id, message });
+        WSServerMessage::Ack {
+            timestamp,
+            reply_to,
+            conversation_id,
+            ..
+        } => {
+            if let Some(conv_id) = conversation_id.or(active_conversation) {
+                event_bus.publish(AppEvent::MessageAcked {
+                    conv_id,
+                    wire_id: reply_to,
+                    received_at: server_time(timestamp),
+                });
             }
         }
 
@@ -436,7 +1019,7 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
             is_typing,
             ..
         } => {
-            if let Some(conv_id) = conversation_id {
+            if let Some(conv_id) = conversation_id.or(active_conversation) {
                 event_bus.publish(AppEvent::TypingChanged { conv_id, is_typing });
             }
         }
@@ -448,6 +1031,7 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
             ..
         } => {
             info!("Notification [{}]: {} - {}", category, title, body);
+            event_bus.publish(AppEvent::NotificationReceived { title, body, category });
         }
 
         WSServerMessage::Error {
@@ -457,12 +1041,21 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
             ..
         } => {
             info!("Error received: {}", message);
-            if let (Some(msg_id), Some(conv_id)) = (reply_to, conversation_id) {
-                event_bus.publish(AppEvent::MessageError {
-                    conv_id,
-                    msg_id,
-                    error: message,
-                });
+            match (reply_to, conversation_id) {
+                (Some(msg_id), Some(conv_id)) => {
+                    event_bus.publish(AppEvent::MessageError {
+                        conv_id,
+                        msg_id,
+                        error: message,
+                    });
+                }
+                (Some(request_id), None) => {
+                    event_bus.publish(AppEvent::RequestFailed {
+                        request_id,
+                        error: message,
+                    });
+                }
+                _ => {}
             }
         }
 
@@ -472,12 +1065,23 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
             let convs: Vec<Conversation> = conversations
                 .into_iter()
                 .map(|c| {
-                    Conversation::from_server(
+                    let read_only = c.is_read_only();
+                    let mut conv = Conversation::from_server(
                         c.id,
                         c.last_message,
                         c.last_message_time,
                         c.message_count,
-                    )
+                    );
+                    conv.set_labels(ConversationLabels {
+                        tags: c.tags,
+                        color: c.color,
+                    });
+                    if let Some(title) = c.title {
+                        conv.title = title;
+                    }
+                    conv.sort_index = c.sort_index;
+                    conv.read_only = read_only;
+                    conv
                 })
                 .collect();
 
@@ -487,6 +1091,7 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
         WSServerMessage::History {
             conversation_id,
             messages,
+            has_more,
             ..
         } => {
             info!(
@@ -500,21 +1105,33 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
                 .filter_map(parse_history_message)
                 .collect();
 
+            if parsed_messages.len() > HISTORY_BATCH_SIZE {
+                // Batching lets the UI apply it a slice at a time. Publishing
+                // inline keeps later frames behind the last batch.
+                for batch in history_batches(&conversation_id, parsed_messages, has_more) {
+                    event_bus.publish(batch);
+                }
+                return;
+            }
+
             event_bus.publish(AppEvent::HistoryLoaded {
                 conv_id: conversation_id,
                 messages: parsed_messages,
+                has_more,
             });
         }
 
         WSServerMessage::ConversationCreated {
             conversation_id,
             title,
+            reply_to,
             ..
         } => {
             info!("Conversation created: {} ({:?})", conversation_id, title);
             event_bus.publish(AppEvent::ConversationCreated {
                 id: conversation_id,
                 title,
+                reply_to,
             });
         }
 
@@ -525,8 +1142,122 @@ fn dispatch_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>)
             event_bus.publish(AppEvent::ConversationDeleted(conversation_id));
         }
 
+        WSServerMessage::ConversationLabelsUpdated {
+            conversation_id,
+            tags,
+            color,
+            ..
+        } => {
+            info!("Conversation labels updated: {}", conversation_id);
+            event_bus.publish(AppEvent::ConversationLabelsChanged {
+                conv_id: conversation_id,
+                labels: ConversationLabels { tags, color },
+            });
+        }
+
+        WSServerMessage::ConversationRenamed {
+            conversation_id,
+            title,
+            ..
+        } => {
+            info!("Conversation renamed: {}", conversation_id);
+            event_bus.publish(AppEvent::ConversationRenamed {
+                conv_id: conversation_id,
+                title,
+            });
+        }
+
+        WSServerMessage::ConversationPinned {
+            conversation_id,
+            pinned,
+            ..
+        } => {
+            info!("Conversation pinned: {} ({})", conversation_id, pinned);
+            event_bus.publish(AppEvent::ConversationPinned {
+                conv_id: conversation_id,
+                pinned,
+            });
+        }
+
+        WSServerMessage::MessageEdited {
+            conversation_id,
+            message_id,
+            new_body,
+            ..
+        } => {
+            info!("Message {} edited in {}", message_id, conversation_id);
+            event_bus.publish(AppEvent::MessageEdited {
+                conv_id: conversation_id,
+                msg_id: message_id,
+                new_body,
+            });
+        }
+
+        WSServerMessage::MessageDeleted {
+            conversation_id,
+            message_id,
+            ..
+        } => {
+            info!("Message {} deleted in {}", message_id, conversation_id);
+            event_bus.publish(AppEvent::MessageDeleted {
+                conv_id: conversation_id,
+                msg_id: message_id,
+            });
+        }
+
+        WSServerMessage::MessageReaction {
+            conversation_id,
+            message_id,
+            emoji,
+            count,
+            ..
+        } => {
+            info!("Reaction {} on {} now at {}", emoji, message_id, count);
+            event_bus.publish(AppEvent::MessageReaction {
+                conv_id: conversation_id,
+                msg_id: message_id,
+                emoji,
+                count,
+            });
+        }
+
+        WSServerMessage::ReadReceipt {
+            conversation_id,
+            up_to_message_id,
+            ..
+        } => {
+            event_bus.publish(AppEvent::ReadReceipt {
+                conv_id: conversation_id,
+                up_to_msg_id: up_to_message_id,
+            });
+        }
+
+        WSServerMessage::SearchResults { query, results, .. } => {
+            info!("Received {} search results for {:?}", results.len(), query);
+            let results = results
+                .into_iter()
+                .filter_map(|hit| {
+                    let message = parse_history_message(hit.message)?;
+                    Some(SearchResult {
+                        conv_id: hit.conversation_id,
+                        message,
+                    })
+                })
+                .collect();
+            event_bus.publish(AppEvent::SearchResultsLoaded { query, results });
+        }
+
         WSServerMessage::Pong { .. } => {
-            // Heartbeat response, nothing to do
+            // Heartbeat response; the read loop already noted the activity
+        }
+
+        WSServerMessage::AuthFailed { reason, .. } => {
+            warn!("Server rejected the auth token: {}", reason.as_deref().unwrap_or("no reason given"));
+            event_bus.publish(AppEvent::AuthFailed { reason });
+        }
+
+        WSServerMessage::Unknown => {
+            // Logged with its type where the frame is parsed
         }
     }
 }
@@ -562,5 +1293,526 @@ fn parse_history_message(m: HistoryMessage) -> Option<Message> {
         sender,
         status: MessageStatus::Delivered,
         image: None,
+        delivered_at: None,
+        edited: false,
+        reply_to: None,
+        files: Vec::new(),
+        reactions: HashMap::new(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NativeEventBus;
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+    /// Serves WebSocket connections that accept permessage-deflate (the
+    /// tungstenite test servers don't), greets each with `greeting` and
+    /// reports the extensions every client offered
+    async fn deflate_server(greeting: String) -> (String, tokio::sync::mpsc::UnboundedReceiver<Option<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (offers_tx, offers) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let offers_tx = offers_tx.clone();
+                let greeting = greeting.clone();
+                let service = hyper::service::service_fn(move |mut req: hyper::Request<hyper::body::Incoming>| {
+                    let offered = req
+                        .headers()
+                        .get("sec-websocket-extensions")
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    let _ = offers_tx.send(offered);
+                    let greeting = greeting.clone();
+                    async move {
+                        let options = yawc::Options::default().with_balanced_compression();
+                        let (response, upgrade) = yawc::WebSocket::upgrade_with_options(&mut req, options)?;
+                        tokio::spawn(async move {
+                            let Ok(mut ws) = upgrade.await else {
+                                return;
+                            };
+                            let _ = ws.send(Frame::text(greeting)).await;
+                            while ws.next().await.is_some() {}
+                        });
+                        Ok::<_, WebSocketError>(response)
+                    }
+                });
+                let io = hyper_util::rt::TokioIo::new(stream);
+                tokio::spawn(hyper::server::conn::http1::Builder::new().serve_connection(io, service).with_upgrades());
+            }
+        });
+        (url, offers)
+    }
+
+    #[tokio::test]
+    async fn test_response_without_conversation_id_uses_active_conversation() {
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+
+        let msg = WSServerMessage::Response {
+            id: "resp-1".to_string(),
+            timestamp: 0,
+            reply_to: "msg-1".to_string(),
+            conversation_id: None,
+            body: "hello".to_string(),
+            image: None,
+            files: Vec::new(),
+        };
+        dispatch_server_message(msg, &event_bus, Some("native-a".to_string()));
+
+        match rx.next().await {
+            Some(AppEvent::MessageReceived { conv_id, message, reply_to }) => {
+                assert_eq!(conv_id, "native-a");
+                assert_eq!(message.body, "hello");
+                assert_eq!(reply_to.as_deref(), Some("msg-1"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_history_is_published_newest_batch_first_and_in_order() {
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+
+        let total = HISTORY_BATCH_SIZE * 2 + 10;
+        let messages = (0..total)
+            .map(|i| HistoryMessage {
+                role: "assistant".to_string(),
+                content: i.to_string(),
+                timestamp: None,
+            })
+            .collect();
+        let msg = WSServerMessage::History {
+            id: "hist-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            messages,
+            has_more: None,
+        };
+        dispatch_server_message(msg, &event_bus, None);
+        let reply = WSServerMessage::Response {
+            id: "resp-1".to_string(),
+            timestamp: 0,
+            reply_to: "msg-1".to_string(),
+            conversation_id: Some("native-a".to_string()),
+            body: "live".to_string(),
+            image: None,
+            files: Vec::new(),
+        };
+        dispatch_server_message(reply, &event_bus, None);
+
+        let mut batches = Vec::new();
+        while let Some(AppEvent::HistoryBatch { messages, first, last, .. }) = rx.next().await {
+            batches.push((messages.len(), messages[0].body.clone(), first, last));
+            if last {
+                break;
+            }
+        }
+        assert_eq!(
+            batches,
+            [
+                (HISTORY_BATCH_SIZE, (total - HISTORY_BATCH_SIZE).to_string(), true, false),
+                (HISTORY_BATCH_SIZE, "10".to_string(), false, false),
+                (10, "0".to_string(), false, true),
+            ]
+        );
+        // A frame read after the history is published after its last batch
+        assert!(matches!(rx.next().await, Some(AppEvent::MessageReceived { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_silent_server_times_out_the_connection() {
+        // Accepts WebSocket connections but never sends a frame back
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+        let transport = NativeTransport::with_config(NativeTransportConfig {
+            ping_interval: Duration::from_millis(50),
+            ..NativeTransportConfig::default()
+        });
+        let connection = tokio::spawn(transport.connect(url, None, event_bus));
+
+        let statuses = async {
+            let mut statuses = Vec::new();
+            while let Some(event) = rx.next().await {
+                if let AppEvent::ConnectionChanged(status) = event {
+                    let disconnected = status == ConnectionStatus::Disconnected;
+                    statuses.push(status);
+                    if disconnected {
+                        return statuses;
+                    }
+                }
+            }
+            statuses
+        };
+        let statuses = tokio::time::timeout(Duration::from_secs(5), statuses)
+            .await
+            .expect("the silent connection should time out");
+        assert_eq!(
+            statuses,
+            [ConnectionStatus::Connecting, ConnectionStatus::Connected, ConnectionStatus::Disconnected]
+        );
+        assert!(!transport.is_connected());
+
+        connection.abort();
+    }
+
+    #[tokio::test]
+    async fn test_connect_attempt_times_out() {
+        // Accepts TCP connections but never answers the WebSocket handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let event_bus: Arc<dyn EventBus> = Arc::new(NativeEventBus::new());
+        let transport = NativeTransport::with_config(NativeTransportConfig {
+            connect_timeout: Duration::from_millis(50),
+            reconnect: ReconnectConfig {
+                max_attempts: Some(0),
+                ..ReconnectConfig::default()
+            },
+            ..NativeTransportConfig::default()
+        });
+
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.connect(url, None, event_bus))
+            .await
+            .expect("the attempt should give up on its own");
+        assert_eq!(result, Err(TransportError::Timeout));
+        assert!(!transport.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_auth_token_stops_reconnecting() {
+        // Rejects whatever token each connection opens with
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (first_frames_tx, mut first_frames) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let first_frames_tx = first_frames_tx.clone();
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    if let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                        let _ = first_frames_tx.send(text.to_string());
+                    }
+                    let rejection = r#"{"type":"auth_failed","id":"a1","timestamp":0,"reason":"bad token"}"#;
+                    let _ = ws.send(WsMessage::Text(rejection.into())).await;
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+        let transport = NativeTransport::new();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            transport.connect(url, Some("secret".to_string()), event_bus),
+        )
+        .await
+        .expect("a rejected token should end the connection");
+        assert_eq!(result, Err(TransportError::AuthFailed(Some("bad token".to_string()))));
+
+        let first = first_frames.recv().await.unwrap();
+        assert!(first.contains(r#""type":"authenticate""#) && first.contains(r#""token":"secret""#));
+        assert!(first_frames.try_recv().is_err(), "no second connection should be attempted");
+
+        let mut auth_failed = false;
+        let mut last_status = None;
+        while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(50), rx.next()).await {
+            match event {
+                AppEvent::AuthFailed { reason } => auth_failed = reason.as_deref() == Some("bad token"),
+                AppEvent::ConnectionChanged(status) => last_status = Some(status),
+                _ => {}
+            }
+        }
+        assert!(auth_failed);
+        assert_eq!(last_status, Some(ConnectionStatus::AuthError("bad token".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_fails_the_dropped_message() {
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+        let transport = NativeTransport::with_config(NativeTransportConfig {
+            max_queued_messages: 1,
+            ..NativeTransportConfig::default()
+        });
+        // Never polled: only hands the transport its event bus
+        drop(transport.connect("ws://127.0.0.1:9".to_string(), None, event_bus));
+
+        let send = |body: &str| transport.send_chat(body.to_string(), "c1".to_string(), body.to_string(), None, Vec::new(), None);
+        send("one").await.unwrap();
+        send("two").await.unwrap();
+
+        match rx.next().await {
+            Some(AppEvent::MessageError { conv_id, msg_id, error }) => {
+                assert_eq!((conv_id.as_str(), msg_id, error.as_str()), ("c1", "one".to_string(), QUEUE_FULL_ERROR));
+            }
+            other => panic!("expected MessageError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_go_out_before_new_ones() {
+        // Reports the body of every chat frame received
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (bodies_tx, mut bodies) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let bodies_tx = bodies_tx.clone();
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                        let frame: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        if frame["type"] == "chat" {
+                            let _ = bodies_tx.send(frame["body"].as_str().unwrap_or_default().to_string());
+                        }
+                    }
+                });
+            }
+        });
+
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+        let transport = NativeTransport::new();
+        let send = |body: &str| transport.send_chat(body.to_string(), "c1".to_string(), body.to_string(), None, Vec::new(), None);
+        send("queued").await.unwrap();
+
+        let connection = tokio::spawn(transport.connect(url, None, event_bus));
+        while !matches!(rx.next().await, Some(AppEvent::ConnectionChanged(ConnectionStatus::Connected))) {}
+        // Sent as soon as the connection is announced, possibly mid-flush
+        send("new").await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            vec![bodies.recv().await.unwrap(), bodies.recv().await.unwrap()]
+        })
+        .await
+        .expect("both messages should arrive");
+        assert_eq!(received, ["queued", "new"]);
+
+        connection.abort();
+    }
+
+    #[tokio::test]
+    async fn test_reconnecting_stops_the_previous_connection() {
+        // Hands each accepted connection to the test, which decides when it ends
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (accepted_tx, mut accepted) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Ok(ws) = tokio_tungstenite::accept_async(stream).await {
+                    let _ = accepted_tx.send(ws);
+                }
+            }
+        });
+        let wait_connected = |transport: &NativeTransport| {
+            let connected = transport.connected.clone();
+            async move {
+                while !connected.load(Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        };
+
+        let event_bus: Arc<dyn EventBus> = Arc::new(NativeEventBus::new());
+        let transport = NativeTransport::new();
+        let first = tokio::spawn(transport.connect(url.clone(), None, event_bus.clone()));
+        let first_server = accepted.recv().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), wait_connected(&transport))
+            .await
+            .unwrap();
+
+        transport.disconnect().await.unwrap();
+        let second = tokio::spawn(transport.connect(url, None, event_bus));
+        let _second_server = accepted.recv().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), wait_connected(&transport))
+            .await
+            .unwrap();
+
+        // The old connection ending must not touch the new one or reconnect
+        drop(first_server);
+        let result = tokio::time::timeout(Duration::from_secs(5), first)
+            .await
+            .expect("the previous connection should stop");
+        assert_eq!(result.unwrap(), Ok(()));
+        assert!(transport.is_connected());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), accepted.recv()).await.is_err(),
+            "no third connection should be made"
+        );
+
+        second.abort();
+    }
+
+    #[tokio::test]
+    async fn test_compression_is_negotiated_with_a_supporting_server() {
+        let greeting = "the same words over and over ".repeat(2000);
+        let (url, mut offers) = deflate_server(greeting.clone()).await;
+
+        let stats = Arc::new(WireStats::default());
+        let mut ws = wire::open(&url, true, stats.clone()).await.unwrap();
+        let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .unwrap()
+            .expect("the greeting should arrive");
+        stats.received_text(frame.payload().len());
+
+        assert_eq!(frame.payload().as_ref(), greeting.as_bytes());
+        assert!(offers.recv().await.unwrap().unwrap().contains("permessage-deflate"));
+        assert!(stats.deflate());
+        assert!(stats.ratio().unwrap() > 10.0, "ratio was {:?}", stats.ratio());
+    }
+
+    #[tokio::test]
+    async fn test_compression_falls_back_when_the_server_lacks_it() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.send(WsMessage::Text("plain".into())).await;
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let stats = Arc::new(WireStats::default());
+        let mut ws = wire::open(&url, true, stats.clone()).await.unwrap();
+        let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .unwrap()
+            .expect("the greeting should arrive");
+
+        assert_eq!(frame.payload().as_ref(), b"plain");
+        assert!(!stats.deflate());
+    }
+
+    #[tokio::test]
+    async fn test_compression_can_be_turned_off() {
+        let (url, mut offers) = deflate_server("hello".to_string()).await;
+
+        let stats = Arc::new(WireStats::default());
+        let mut ws = wire::open(&url, false, stats.clone()).await.unwrap();
+        let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .unwrap()
+            .expect("the greeting should arrive");
+
+        assert_eq!(frame.payload().as_ref(), b"hello");
+        assert_eq!(offers.recv().await.unwrap(), None);
+        assert!(!stats.deflate());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_response_is_dispatched() {
+        let body = "a long and repetitive answer ".repeat(5000);
+        let response = WSServerMessage::Response {
+            id: "resp-1".to_string(),
+            timestamp: 0,
+            reply_to: "msg-1".to_string(),
+            conversation_id: Some("c1".to_string()),
+            body: body.clone(),
+            image: None,
+            files: Vec::new(),
+        };
+        let (url, _offers) = deflate_server(serde_json::to_string(&response).unwrap()).await;
+
+        let bus = NativeEventBus::new();
+        let mut rx = bus.subscribe();
+        let event_bus: Arc<dyn EventBus> = Arc::new(bus);
+        let transport = NativeTransport::new();
+        let connection = tokio::spawn(transport.connect(url, None, event_bus));
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(AppEvent::MessageReceived { message, .. }) = rx.next().await {
+                    return message.body;
+                }
+            }
+        })
+        .await
+        .expect("the response should be dispatched");
+        assert_eq!(received, body);
+
+        connection.abort();
+    }
+
+    #[cfg(feature = "debug-log")]
+    #[tokio::test]
+    async fn test_frames_are_logged_in_both_directions() {
+        // Greets each connection with one frame, then reads whatever comes
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    let _ = ws.send(WsMessage::Text("{\"type\":\"hello\"}".into())).await;
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        let event_bus: Arc<dyn EventBus> = Arc::new(NativeEventBus::new());
+        let transport = NativeTransport::new();
+        let connection = tokio::spawn(transport.connect(url, None, event_bus));
+
+        let frames = async {
+            loop {
+                let frames = transport.recent_frames();
+                if frames.iter().any(|f| f.direction == FrameDirection::Received) {
+                    return frames;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        let frames = tokio::time::timeout(Duration::from_secs(5), frames)
+            .await
+            .expect("the greeting should be logged");
+        let received: Vec<_> = frames
+            .iter()
+            .filter(|f| f.direction == FrameDirection::Received)
+            .map(|f| f.json.as_str())
+            .collect();
+        assert_eq!(received, ["{\"type\":\"hello\"}"]);
+        assert!(frames
+            .iter()
+            .any(|f| f.direction == FrameDirection::Sent && f.json.contains("\"type\":\"subscribe\"")));
+
+        connection.abort();
+    }
+}
@@ -0,0 +1,201 @@
+//! Opening the socket for the native transport
+//!
+//! Dials TCP (with TLS for `wss://`), then runs the WebSocket handshake,
+//! offering permessage-deflate (RFC 7692) when compression is enabled. A
+//! server without the extension simply leaves it out of its answer and the
+//! connection goes on uncompressed.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+use url::Url;
+use yawc::{HttpRequest, MaybeTlsStream, Options, WebSocket, WebSocketError};
+
+/// Largest message accepted from the server (history with images can be big)
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// Most of the handshake answer kept to look for the extension
+const MAX_HEAD_SIZE: usize = 16 << 10;
+
+/// An open WebSocket connection
+pub type WsConnection = WebSocket<MeteredStream<MaybeTlsStream<TcpStream>>>;
+
+/// What went over the socket compared to the text it carried
+#[derive(Debug, Default)]
+pub struct WireStats {
+    /// Whether the server accepted permessage-deflate
+    deflate: AtomicBool,
+    /// Frame bytes read and written after the handshake
+    wire_read: AtomicU64,
+    wire_written: AtomicU64,
+    /// Text payload received and sent
+    text_received: AtomicU64,
+    text_sent: AtomicU64,
+}
+
+impl WireStats {
+    /// Whether the server accepted permessage-deflate
+    pub fn deflate(&self) -> bool {
+        self.deflate.load(Ordering::Relaxed)
+    }
+
+    /// Count a text message taken off the socket
+    pub fn received_text(&self, len: usize) {
+        self.text_received.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// Count a text message put on the socket
+    pub fn sent_text(&self, len: usize) {
+        self.text_sent.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// Text bytes per wire byte in both directions, if anything was sent yet
+    pub fn ratio(&self) -> Option<f64> {
+        let text = self.text_received.load(Ordering::Relaxed) + self.text_sent.load(Ordering::Relaxed);
+        let wire = self.wire_read.load(Ordering::Relaxed) + self.wire_written.load(Ordering::Relaxed);
+        (wire > 0).then(|| text as f64 / wire as f64)
+    }
+
+    /// One line for the log, e.g. "deflate on, 2.51x (12034 text bytes in 4794)"
+    #[cfg(feature = "debug-log")]
+    pub fn summary(&self) -> String {
+        let text = self.text_received.load(Ordering::Relaxed) + self.text_sent.load(Ordering::Relaxed);
+        let wire = self.wire_read.load(Ordering::Relaxed) + self.wire_written.load(Ordering::Relaxed);
+        let deflate = if self.deflate() { "on" } else { "off" };
+        match self.ratio() {
+            Some(ratio) => format!("deflate {}, {:.2}x ({} text bytes in {})", deflate, ratio, text, wire),
+            None => format!("deflate {}, nothing sent yet", deflate),
+        }
+    }
+}
+
+/// Socket wrapper that reads the handshake answer, then counts frame bytes
+pub struct MeteredStream<S> {
+    inner: S,
+    stats: Arc<WireStats>,
+    /// Start of the HTTP answer, until its end has been seen
+    head: Option<Vec<u8>>,
+}
+
+impl<S> MeteredStream<S> {
+    fn new(inner: S, stats: Arc<WireStats>) -> Self {
+        Self { inner, stats, head: Some(Vec::new()) }
+    }
+
+    /// Note whether the handshake answer agreed to permessage-deflate
+    ///
+    /// Frames may arrive in the same read as the answer; their bytes are
+    /// counted once the answer ends.
+    fn watch_head(&mut self, read: &[u8]) {
+        let Some(head) = self.head.as_mut() else {
+            self.stats.wire_read.fetch_add(read.len() as u64, Ordering::Relaxed);
+            return;
+        };
+        head.extend_from_slice(read);
+        let end = head.windows(4).position(|w| w == b"\r\n\r\n").map(|at| at + 4);
+        if end.is_none() && head.len() < MAX_HEAD_SIZE {
+            return;
+        }
+        let (head, frames) = head.split_at(end.unwrap_or(head.len()));
+        self.stats.wire_read.fetch_add(frames.len() as u64, Ordering::Relaxed);
+        let deflate = String::from_utf8_lossy(head).lines().any(|line| {
+            let line = line.to_ascii_lowercase();
+            line.starts_with("sec-websocket-extensions:") && line.contains("permessage-deflate")
+        });
+        self.stats.deflate.store(deflate, Ordering::Relaxed);
+        self.head = None;
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for MeteredStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.watch_head(&buf.filled()[before..]);
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for MeteredStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        // Only the handshake request is written before the answer arrives
+        if let (Poll::Ready(Ok(written)), None) = (&result, &self.head) {
+            self.stats.wire_written.fetch_add(*written as u64, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Connect to `url` and complete the WebSocket handshake
+///
+/// With `compression` the handshake offers permessage-deflate; `stats`
+/// learns whether the server took it and counts the traffic afterwards.
+pub async fn open(url: &str, compression: bool, stats: Arc<WireStats>) -> Result<WsConnection, WebSocketError> {
+    let url = Url::parse(url)?;
+    let tls = match url.scheme() {
+        "ws" => false,
+        "wss" => true,
+        scheme => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unsupported URL scheme {}", scheme)).into())
+        }
+    };
+    let host = url
+        .host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URL has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(if tls { 443 } else { 80 });
+
+    let tcp = TcpStream::connect((host.as_str(), port)).await?;
+    tcp.set_nodelay(true)?;
+    let stream = if tls {
+        let name = ServerName::try_from(host).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        MaybeTlsStream::from(tls_connector().connect(name, tcp).await?)
+    } else {
+        MaybeTlsStream::Plain(tcp)
+    };
+
+    let options = Options::default().with_max_payload_read(MAX_MESSAGE_SIZE);
+    let options = if compression {
+        options.with_balanced_compression()
+    } else {
+        options.without_compression()
+    };
+    let metered = MeteredStream::new(stream, stats);
+    WebSocket::handshake_with_request(url, metered, options, HttpRequest::builder()).await
+}
+
+/// TLS client trusting the bundled web PKI roots
+fn tls_connector() -> TlsConnector {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    let config = CONFIG.get_or_init(|| {
+        let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+        let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+        let mut config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default TLS versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Arc::new(config)
+    });
+    TlsConnector::from(config.clone())
+}
@@ -1,21 +1,48 @@
 //! Web event bus using futures-channel
 
 use futures_channel::mpsc;
-use prsnl_core::{AppEvent, EventBus, EventStream};
+use prsnl_core::{AppEvent, EventBus, EventFilter, EventStream, ReplayBuffer, DEFAULT_REPLAY_LEN};
 use std::sync::Mutex;
 
 /// Web event bus implementation using futures-channel
 ///
 /// Uses unbounded channels since we're in a single-threaded WASM environment
-/// and don't need backpressure.
+/// and don't need backpressure. New subscribers first receive the replayed
+/// state events (see `ReplayBuffer`).
 pub struct WebEventBus {
-    senders: Mutex<Vec<mpsc::UnboundedSender<AppEvent>>>,
+    senders: Mutex<Vec<Subscriber>>,
+    replay: Mutex<ReplayBuffer>,
+}
+
+/// A subscriber's channel, with the filter of a filtered subscription
+struct Subscriber {
+    tx: mpsc::UnboundedSender<AppEvent>,
+    filter: Option<EventFilter>,
+}
+
+impl Subscriber {
+    /// Deliver an event if the subscriber wants it; false once it is gone
+    ///
+    /// Events that don't match are never cloned.
+    fn deliver(&self, event: &AppEvent) -> bool {
+        match &self.filter {
+            Some(filter) if !filter(event) => !self.tx.is_closed(),
+            _ => self.tx.unbounded_send(event.clone()).is_ok(),
+        }
+    }
 }
 
 impl WebEventBus {
+    /// Bus that replays the last `DEFAULT_REPLAY_LEN` events to new subscribers
     pub fn new() -> Self {
+        Self::with_replay(DEFAULT_REPLAY_LEN)
+    }
+
+    /// Bus that replays the last `recent` events (0 for state events only)
+    pub fn with_replay(recent: usize) -> Self {
         Self {
             senders: Mutex::new(Vec::new()),
+            replay: Mutex::new(ReplayBuffer::with_recent(recent)),
         }
     }
 }
@@ -33,15 +60,35 @@ unsafe impl Sync for WebEventBus {}
 
 impl EventBus for WebEventBus {
     fn publish(&self, event: AppEvent) {
+        self.replay.lock().unwrap().record(&event);
         let mut senders = self.senders.lock().unwrap();
         // Remove closed senders and send to remaining ones
-        senders.retain(|sender| sender.unbounded_send(event.clone()).is_ok());
+        senders.retain(|sender| sender.deliver(&event));
     }
 
     fn subscribe(&self) -> EventStream {
         let (tx, rx) = mpsc::unbounded();
-        self.senders.lock().unwrap().push(tx);
+        // Queue the replayed events ahead of anything published from now on
+        for event in self.replay.lock().unwrap().snapshot() {
+            let _ = tx.unbounded_send(event);
+        }
+        self.senders.lock().unwrap().push(Subscriber { tx, filter: None });
         // In WASM, EventStream doesn't require Send, so we can return the receiver directly
         Box::pin(rx)
     }
+
+    fn subscribe_live(&self) -> EventStream {
+        let (tx, rx) = mpsc::unbounded();
+        self.senders.lock().unwrap().push(Subscriber { tx, filter: None });
+        Box::pin(rx)
+    }
+
+    fn subscribe_filtered(&self, filter: EventFilter) -> EventStream {
+        let (tx, rx) = mpsc::unbounded();
+        for event in self.replay.lock().unwrap().snapshot_matching(&filter) {
+            let _ = tx.unbounded_send(event);
+        }
+        self.senders.lock().unwrap().push(Subscriber { tx, filter: Some(filter) });
+        Box::pin(rx)
+    }
 }
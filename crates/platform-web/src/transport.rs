@@ -4,33 +4,64 @@
 //! using the browser's native WebSocket API via web-sys.
 
 use prsnl_core::{
-    AppEvent, ConnectionStatus, Conversation, EventBus, HistoryMessage, ImageData, ImagePayload,
-    Message, MessageSender, MessageStatus, Transport, TransportResult, TransportResultVoid,
-    WSClientMessage, WSServerMessage,
+    frame_type, history_batches, is_auth_close_code, server_time, AppEvent, AttachmentPayload, ConnectionStatus, Conversation, ConversationLabels, EventBus,
+    HistoryMessage, ImageData, ImagePayload, Message, MessageSender, MessageStatus, OutboundQueue,
+    SearchResult, Transport, TransportDebug, TransportError, TransportResult, TransportResultVoid, WSClientMessage,
+    WSServerMessage, HISTORY_BATCH_SIZE, QUEUE_FULL_ERROR,
 };
+#[cfg(feature = "debug-log")]
+use prsnl_core::{FrameDirection, FrameLog, LoggedFrame};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CloseEvent, ErrorEvent, MessageEvent, WebSocket};
 
 /// Reconnection configuration
 const RECONNECT_DELAY_MS: u32 = 3000;
+const MAX_RECONNECT_DELAY_MS: u32 = 30_000;
 const MAX_RECONNECT_ATTEMPTS: u32 = 5;
 
+/// How long a connection attempt may take before it is closed and retried
+const CONNECT_TIMEOUT_MS: u32 = 10_000;
+
 /// Internal state shared between callbacks
 struct WebTransportInner {
     ws: Option<WebSocket>,
     event_bus: Option<Arc<dyn EventBus>>,
     url: Option<String>,
+    /// Sent in an `authenticate` frame at the start of each connection
+    auth_token: Option<String>,
+    /// `AuthError` status once the server rejected `auth_token`; while set,
+    /// no reconnects until `connect` is called again
+    auth_error: Option<ConnectionStatus>,
     reconnect_attempts: u32,
+    /// Wait a random part of each reconnect delay so clients don't reconnect in lockstep
+    reconnect_jitter: bool,
+    /// Limit on each connection attempt, so a bad host can't hang on "Connecting"
+    connect_timeout_ms: u32,
+    /// Closes the socket if it hasn't opened in time (dropping it cancels that)
+    connect_timer: Option<gloo_timers::callback::Timeout>,
+    /// Next reconnect attempt (dropping it cancels that)
+    reconnect_timer: Option<gloo_timers::callback::Timeout>,
+    /// Conversation open in the UI (fallback for responses without a conversation id)
+    active_conversation: Option<String>,
+    /// Chat frames sent while disconnected, flushed after reconnecting
+    outbound: OutboundQueue,
+    /// Recent frames in both directions
+    #[cfg(feature = "debug-log")]
+    frame_log: FrameLog,
     // Store closures to prevent them from being dropped
     _onmessage: Option<Closure<dyn FnMut(MessageEvent)>>,
     _onerror: Option<Closure<dyn FnMut(ErrorEvent)>>,
     _onclose: Option<Closure<dyn FnMut(CloseEvent)>>,
     _onopen: Option<Closure<dyn FnMut()>>,
+    /// Window `online`/`offline` listeners, kept to remove them on disconnect
+    ononline: Option<Closure<dyn FnMut()>>,
+    onoffline: Option<Closure<dyn FnMut()>>,
 }
 
 impl WebTransportInner {
@@ -39,11 +70,23 @@ impl WebTransportInner {
             ws: None,
             event_bus: None,
             url: None,
+            auth_token: None,
+            auth_error: None,
             reconnect_attempts: 0,
+            reconnect_jitter: true,
+            connect_timeout_ms: CONNECT_TIMEOUT_MS,
+            connect_timer: None,
+            reconnect_timer: None,
+            active_conversation: None,
+            outbound: OutboundQueue::default(),
+            #[cfg(feature = "debug-log")]
+            frame_log: FrameLog::default(),
             _onmessage: None,
             _onerror: None,
             _onclose: None,
             _onopen: None,
+            ononline: None,
+            onoffline: None,
         }
     }
 
@@ -71,15 +114,33 @@ impl WebTransport {
         }
     }
 
+    /// Keep at most `max` chat messages while disconnected (the oldest is dropped beyond that)
+    pub fn with_max_queued_messages(self, max: usize) -> Self {
+        self.inner.borrow_mut().outbound = OutboundQueue::new(max);
+        self
+    }
+
+    /// Randomize reconnect delays (on by default); off waits the full backoff delay
+    pub fn with_reconnect_jitter(self, jitter: bool) -> Self {
+        self.inner.borrow_mut().reconnect_jitter = jitter;
+        self
+    }
+
+    /// Give up on a connection attempt that hasn't opened after `timeout` (10s by default)
+    pub fn with_connect_timeout(self, timeout: std::time::Duration) -> Self {
+        self.inner.borrow_mut().connect_timeout_ms = timeout.as_millis().min(u32::MAX as u128) as u32;
+        self
+    }
+
     /// Connect to the WebSocket server
-    fn connect_internal(inner: Rc<RefCell<WebTransportInner>>) -> Result<(), String> {
+    fn connect_internal(inner: Rc<RefCell<WebTransportInner>>) -> Result<(), TransportError> {
         let (url, event_bus) = {
             let state = inner.borrow();
-            let url = state.url.clone().ok_or("URL not set")?;
+            let url = state.url.clone().ok_or(TransportError::NotConnected)?;
             let event_bus = state
                 .event_bus
                 .clone()
-                .ok_or("Event bus not set")?;
+                .ok_or(TransportError::NotConnected)?;
             (url, event_bus)
         };
 
@@ -87,7 +148,8 @@ impl WebTransport {
         event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connecting));
 
         // Create the WebSocket
-        let ws = WebSocket::new(&url).map_err(|e| format!("Failed to create WebSocket: {:?}", e))?;
+        let ws = WebSocket::new(&url)
+            .map_err(|e| TransportError::Io(format!("failed to create WebSocket: {:?}", e)))?;
 
         // Set binary type
         ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
@@ -105,31 +167,52 @@ impl WebTransport {
             {
                 let mut state = inner_onopen.borrow_mut();
                 state.reconnect_attempts = 0;
+                state.connect_timer = None;
             }
 
             event_bus_open.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
 
-            // Subscribe to notifications and request conversations
-            let inner = inner_onopen.clone();
-            wasm_bindgen_futures::spawn_local(async move {
-                // Send subscribe message
-                if let Err(e) = send_subscribe_internal(&inner) {
-                    warn!("Failed to subscribe: {}", e);
-                }
+            // Authenticate, subscribe to notifications and request conversations.
+            // All of it runs within this callback: the socket already counts as
+            // open, so a send let in before the flush would overtake the queue.
+            let inner = &inner_onopen;
 
-                // Request conversations list
-                if let Err(e) = send_list_conversations_internal(&inner) {
-                    warn!("Failed to request conversations: {}", e);
-                }
-            });
+            // Authenticate before anything else
+            if let Err(e) = send_authenticate_internal(inner) {
+                warn!("Failed to authenticate: {}", e);
+            }
+
+            // Send subscribe message
+            if let Err(e) = send_subscribe_internal(inner) {
+                warn!("Failed to subscribe: {}", e);
+            }
+
+            // Request conversations list
+            if let Err(e) = send_list_conversations_internal(inner) {
+                warn!("Failed to request conversations: {}", e);
+            }
+
+            // Send what was queued while disconnected
+            flush_outbound(inner);
         }) as Box<dyn FnMut()>);
 
         // Set up onmessage callback
         let event_bus_msg = event_bus.clone();
+        let inner_onmessage = inner.clone();
         let onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
             if let Ok(text) = e.data().dyn_into::<js_sys::JsString>() {
                 let text: String = text.into();
-                dispatch_message(&text, &event_bus_msg);
+                #[cfg(feature = "debug-log")]
+                inner_onmessage.borrow_mut().frame_log.record(FrameDirection::Received, &text);
+                let active = inner_onmessage.borrow().active_conversation.clone();
+                if let Some(auth_error) = dispatch_message(&text, &event_bus_msg, active) {
+                    // Retrying with the same token would only fail again
+                    let mut state = inner_onmessage.borrow_mut();
+                    state.auth_error = Some(auth_error);
+                    if let Some(ws) = state.ws.as_ref() {
+                        let _ = ws.close();
+                    }
+                }
             }
         }) as Box<dyn FnMut(MessageEvent)>);
 
@@ -148,7 +231,13 @@ impl WebTransport {
                 e.code(),
                 e.reason()
             );
-            event_bus_close.publish(AppEvent::ConnectionChanged(ConnectionStatus::Disconnected));
+            if is_auth_close_code(e.code()) && inner_onclose.borrow().auth_error.is_none() {
+                let reason = (!e.reason().is_empty()).then(|| e.reason());
+                event_bus_close.publish(AppEvent::AuthFailed { reason: reason.clone() });
+                inner_onclose.borrow_mut().auth_error = Some(ConnectionStatus::auth_error(reason));
+            }
+            let status = inner_onclose.borrow().auth_error.clone();
+            event_bus_close.publish(AppEvent::ConnectionChanged(status.unwrap_or(ConnectionStatus::Disconnected)));
 
             // Attempt reconnection
             let inner = inner_onclose.clone();
@@ -161,10 +250,23 @@ impl WebTransport {
         ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
         ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
 
+        // Closing a socket that is still connecting fires onclose, which
+        // schedules the reconnect as for any other failed attempt
+        let inner_timeout = inner.clone();
+        let timeout_ms = inner.borrow().connect_timeout_ms;
+        let connect_timer = gloo_timers::callback::Timeout::new(timeout_ms, move || {
+            let state = inner_timeout.borrow();
+            if let Some(ws) = state.ws.as_ref().filter(|ws| ws.ready_state() == WebSocket::CONNECTING) {
+                warn!("Connection attempt failed: {} after {}ms", TransportError::Timeout, timeout_ms);
+                let _ = ws.close();
+            }
+        });
+
         // Store WebSocket and closures in state
         {
             let mut state = inner.borrow_mut();
             state.ws = Some(ws);
+            state.connect_timer = Some(connect_timer);
             state._onopen = Some(onopen);
             state._onmessage = Some(onmessage);
             state._onerror = Some(onerror);
@@ -180,16 +282,15 @@ impl WebTransport {
 
         if let Some(ws) = state.ws.take() {
             // Clear callbacks to prevent reconnect attempts
-            ws.set_onopen(None);
-            ws.set_onmessage(None);
-            ws.set_onerror(None);
-            ws.set_onclose(None);
+            detach_handlers(&ws);
 
             // Close the WebSocket
             let _ = ws.close();
         }
 
         // Clear stored closures
+        state.connect_timer = None;
+        state.reconnect_timer = None;
         state._onopen = None;
         state._onmessage = None;
         state._onerror = None;
@@ -202,19 +303,26 @@ impl WebTransport {
     }
 
     /// Send a message over the WebSocket
-    fn send_internal(inner: &Rc<RefCell<WebTransportInner>>, msg: &WSClientMessage) -> Result<(), String> {
+    fn send_internal(inner: &Rc<RefCell<WebTransportInner>>, msg: &WSClientMessage) -> Result<(), TransportError> {
         let state = inner.borrow();
-        let ws = state.ws.as_ref().ok_or("WebSocket not connected")?;
+        let ws = state.ws.as_ref().ok_or(TransportError::NotConnected)?;
 
-        if ws.ready_state() != WebSocket::OPEN {
-            return Err("WebSocket not open".to_string());
+        match ws.ready_state() {
+            WebSocket::OPEN => {}
+            WebSocket::CLOSING | WebSocket::CLOSED => return Err(TransportError::Closed),
+            _ => return Err(TransportError::NotConnected),
         }
 
-        let json =
-            serde_json::to_string(msg).map_err(|e| format!("Serialization error: {}", e))?;
+        let json = serde_json::to_string(msg)?;
 
         ws.send_with_str(&json)
-            .map_err(|e| format!("Send error: {:?}", e))?;
+            .map_err(|e| TransportError::Io(format!("{:?}", e)))?;
+
+        #[cfg(feature = "debug-log")]
+        {
+            drop(state);
+            inner.borrow_mut().frame_log.record(FrameDirection::Sent, &json);
+        }
 
         Ok(())
     }
@@ -231,16 +339,33 @@ impl Default for WebTransport {
 unsafe impl Send for WebTransport {}
 unsafe impl Sync for WebTransport {}
 
+impl TransportDebug for WebTransport {
+    #[cfg(feature = "debug-log")]
+    fn recent_frames(&self) -> Vec<LoggedFrame> {
+        self.inner.borrow().frame_log.frames()
+    }
+}
+
 impl Transport for WebTransport {
-    fn connect(&self, url: String, event_bus: Arc<dyn EventBus>) -> TransportResultVoid {
+    fn connect(
+        &self,
+        url: String,
+        auth_token: Option<String>,
+        event_bus: Arc<dyn EventBus>,
+    ) -> TransportResultVoid {
         let inner = self.inner.clone();
 
-        // Store URL and event bus for reconnection
+        // Store URL, token and event bus for reconnection
         {
             let mut state = inner.borrow_mut();
             state.url = Some(url);
+            state.auth_token = auth_token;
+            state.auth_error = None;
             state.event_bus = Some(event_bus);
+            // A previous disconnect maxed out the attempts to stop reconnecting
+            state.reconnect_attempts = 0;
         }
+        watch_network(&inner);
 
         Box::pin(async move { WebTransport::connect_internal(inner) })
     }
@@ -253,6 +378,7 @@ impl Transport for WebTransport {
                 let mut state = inner.borrow_mut();
                 state.reconnect_attempts = MAX_RECONNECT_ATTEMPTS;
             }
+            unwatch_network(&inner);
             WebTransport::disconnect_internal(&inner);
             Ok(())
         })
@@ -260,24 +386,37 @@ impl Transport for WebTransport {
 
     fn send_chat(
         &self,
+        msg_id: String,
         conv_id: String,
         text: String,
         image: Option<ImagePayload>,
-    ) -> TransportResult<String> {
+        files: Vec<AttachmentPayload>,
+        reply_to: Option<String>,
+    ) -> TransportResultVoid {
         let inner = self.inner.clone();
         Box::pin(async move {
-            let msg_id = generate_uuid();
             let msg = WSClientMessage::Chat {
                 id: msg_id.clone(),
                 timestamp: current_timestamp_millis(),
                 conversation_id: conv_id,
                 body: text,
                 image,
-                reply_to: None,
+                files,
+                reply_to,
             };
 
-            WebTransport::send_internal(&inner, &msg)?;
-            Ok(msg_id)
+            if !inner.borrow().is_connected() {
+                info!("Not connected, queueing message {}", msg_id);
+                let dropped = inner.borrow_mut().outbound.push(msg);
+                // Nothing can be queued; fail this send instead
+                if matches!(&dropped, Some(WSClientMessage::Chat { id, .. }) if *id == msg_id) {
+                    return Err(TransportError::NotConnected);
+                }
+                fail_dropped(&inner, dropped);
+                return Ok(());
+            }
+
+            WebTransport::send_internal(&inner, &msg)
         })
     }
 
@@ -286,7 +425,7 @@ impl Transport for WebTransport {
         Box::pin(async move { send_list_conversations_internal(&inner) })
     }
 
-    fn send_get_history(&self, conv_id: String, limit: Option<u32>) -> TransportResultVoid {
+    fn send_get_history(&self, conv_id: String, limit: Option<u32>, before: Option<i64>) -> TransportResultVoid {
         let inner = self.inner.clone();
         Box::pin(async move {
             let msg = WSClientMessage::GetHistory {
@@ -294,22 +433,25 @@ impl Transport for WebTransport {
                 timestamp: current_timestamp_millis(),
                 conversation_id: conv_id,
                 limit,
+                before,
             };
 
             WebTransport::send_internal(&inner, &msg)
         })
     }
 
-    fn send_create_conversation(&self, title: Option<String>) -> TransportResultVoid {
+    fn send_create_conversation(&self, title: Option<String>) -> TransportResult<String> {
         let inner = self.inner.clone();
         Box::pin(async move {
+            let request_id = generate_uuid();
             let msg = WSClientMessage::CreateConversation {
-                id: generate_uuid(),
+                id: request_id.clone(),
                 timestamp: current_timestamp_millis(),
                 title,
             };
 
-            WebTransport::send_internal(&inner, &msg)
+            WebTransport::send_internal(&inner, &msg)?;
+            Ok(request_id)
         })
     }
 
@@ -326,9 +468,149 @@ impl Transport for WebTransport {
         })
     }
 
+    fn send_set_conversation_labels(
+        &self,
+        conv_id: String,
+        tags: Vec<String>,
+        color: Option<String>,
+    ) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::SetConversationLabels {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                tags,
+                color,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_rename_conversation(&self, conv_id: String, title: String) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::RenameConversation {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                title,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_pin_conversation(&self, conv_id: String, pinned: bool) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::PinConversation {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                pinned,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_edit_message(&self, conv_id: String, message_id: String, new_body: String) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::EditMessage {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+                new_body,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_delete_message(&self, conv_id: String, message_id: String) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::DeleteMessage {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_reaction(&self, conv_id: String, message_id: String, emoji: String, remove: bool) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::ReactMessage {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                message_id,
+                emoji,
+                remove,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_mark_read(&self, conv_id: String, up_to_message_id: String) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::MarkRead {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                up_to_message_id,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_typing(&self, conv_id: String, is_typing: bool) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::Typing {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                conversation_id: conv_id,
+                is_typing,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
+    fn send_search(&self, query: String, conv_id: Option<String>, limit: Option<u32>) -> TransportResultVoid {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let msg = WSClientMessage::SearchMessages {
+                id: generate_uuid(),
+                timestamp: current_timestamp_millis(),
+                query,
+                conversation_id: conv_id,
+                limit,
+            };
+
+            WebTransport::send_internal(&inner, &msg)
+        })
+    }
+
     fn is_connected(&self) -> bool {
         self.inner.borrow().is_connected()
     }
+
+    fn set_active_conversation(&self, conv_id: Option<String>) {
+        self.inner.borrow_mut().active_conversation = conv_id;
+    }
 }
 
 // ============================================
@@ -346,7 +628,7 @@ fn current_timestamp_millis() -> i64 {
 }
 
 /// Send subscribe message
-fn send_subscribe_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(), String> {
+fn send_subscribe_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(), TransportError> {
     let msg = WSClientMessage::Subscribe {
         id: generate_uuid(),
         timestamp: current_timestamp_millis(),
@@ -356,8 +638,22 @@ fn send_subscribe_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(),
     WebTransport::send_internal(inner, &msg)
 }
 
+/// Send the auth token, if there is one
+fn send_authenticate_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(), TransportError> {
+    let Some(token) = inner.borrow().auth_token.clone() else {
+        return Ok(());
+    };
+    let msg = WSClientMessage::Authenticate {
+        id: generate_uuid(),
+        timestamp: current_timestamp_millis(),
+        token,
+    };
+
+    WebTransport::send_internal(inner, &msg)
+}
+
 /// Send list conversations request
-fn send_list_conversations_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(), String> {
+fn send_list_conversations_internal(inner: &Rc<RefCell<WebTransportInner>>) -> Result<(), TransportError> {
     let msg = WSClientMessage::ListConversations {
         id: generate_uuid(),
         timestamp: current_timestamp_millis(),
@@ -366,11 +662,60 @@ fn send_list_conversations_internal(inner: &Rc<RefCell<WebTransportInner>>) -> R
     WebTransport::send_internal(inner, &msg)
 }
 
+/// Fail chat frames dropped from a full outbound queue, so they show as not sent
+fn fail_dropped(inner: &Rc<RefCell<WebTransportInner>>, dropped: impl IntoIterator<Item = WSClientMessage>) {
+    let event_bus = inner.borrow().event_bus.clone();
+    for frame in dropped {
+        if let WSClientMessage::Chat { id, conversation_id, .. } = frame {
+            warn!("Outbound queue full, dropping oldest queued message {}", id);
+            if let Some(event_bus) = &event_bus {
+                event_bus.publish(AppEvent::MessageError {
+                    conv_id: conversation_id,
+                    msg_id: id,
+                    error: QUEUE_FULL_ERROR.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Send queued chat frames in order, announcing each one that went out
+///
+/// Stops at the first failure and puts the unsent frames back in front.
+fn flush_outbound(inner: &Rc<RefCell<WebTransportInner>>) {
+    let (frames, event_bus) = {
+        let mut state = inner.borrow_mut();
+        (state.outbound.drain(), state.event_bus.clone())
+    };
+    if !frames.is_empty() {
+        info!("Flushing {} queued messages", frames.len());
+    }
+    let mut frames = frames.into_iter();
+    while let Some(frame) = frames.next() {
+        if let Err(e) = WebTransport::send_internal(inner, &frame) {
+            warn!("Failed to flush queued message: {}", e);
+            let unsent = std::iter::once(frame).chain(frames).collect();
+            let dropped = inner.borrow_mut().outbound.restore(unsent);
+            fail_dropped(inner, dropped);
+            return;
+        }
+        if let (WSClientMessage::Chat { id, conversation_id, .. }, Some(event_bus)) = (frame, &event_bus) {
+            event_bus.publish(AppEvent::QueuedMessageSent {
+                conv_id: conversation_id,
+                wire_id: id,
+            });
+        }
+    }
+}
+
 /// Schedule a reconnection attempt
 fn schedule_reconnect(inner: Rc<RefCell<WebTransportInner>>) {
     let should_reconnect = {
         let mut state = inner.borrow_mut();
-        if state.reconnect_attempts >= MAX_RECONNECT_ATTEMPTS {
+        if state.auth_error.is_some() {
+            warn!("Auth token was rejected, not reconnecting until it changes");
+            false
+        } else if state.reconnect_attempts >= MAX_RECONNECT_ATTEMPTS {
             warn!(
                 "Max reconnect attempts ({}) reached, giving up",
                 MAX_RECONNECT_ATTEMPTS
@@ -386,10 +731,14 @@ fn schedule_reconnect(inner: Rc<RefCell<WebTransportInner>>) {
         return;
     }
 
-    let attempts = inner.borrow().reconnect_attempts;
+    let (attempts, jitter) = {
+        let state = inner.borrow();
+        (state.reconnect_attempts, state.reconnect_jitter)
+    };
+    let delay_ms = reconnect_delay_ms(attempts, jitter);
     info!(
         "Scheduling reconnect attempt {} in {}ms",
-        attempts, RECONNECT_DELAY_MS
+        attempts, delay_ms
     );
 
     // Publish reconnecting status
@@ -402,35 +751,174 @@ fn schedule_reconnect(inner: Rc<RefCell<WebTransportInner>>) {
 
     // Schedule reconnect using gloo-timers
     let inner_clone = inner.clone();
-    gloo_timers::callback::Timeout::new(RECONNECT_DELAY_MS, move || {
+    let reconnect_timer = gloo_timers::callback::Timeout::new(delay_ms, move || {
         info!("Attempting reconnection...");
         if let Err(e) = WebTransport::connect_internal(inner_clone) {
             warn!("Reconnection failed: {}", e);
         }
-    })
-    .forget();
+    });
+    inner.borrow_mut().reconnect_timer = Some(reconnect_timer);
+}
+
+/// Follow the browser's network status
+///
+/// After sleep or a network change the socket can take a long time to
+/// notice it is dead. `offline` shows the connection as lost right away and
+/// holds off retries; `online` starts a fresh retry sequence at once.
+/// Installed on the first connect and removed by `disconnect`.
+fn watch_network(inner: &Rc<RefCell<WebTransportInner>>) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    if inner.borrow().ononline.is_some() {
+        return;
+    }
+
+    let inner_online = inner.clone();
+    let ononline = Closure::wrap(Box::new(move || {
+        info!("Browser is back online");
+        reconnect_now(&inner_online);
+    }) as Box<dyn FnMut()>);
+
+    let inner_offline = inner.clone();
+    let onoffline = Closure::wrap(Box::new(move || {
+        info!("Browser went offline");
+        let mut state = inner_offline.borrow_mut();
+        // Attempts would only fail until the network is back
+        state.reconnect_timer = None;
+        if let Some(event_bus) = &state.event_bus {
+            event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Disconnected));
+        }
+    }) as Box<dyn FnMut()>);
+
+    for (event, listener) in [("online", &ononline), ("offline", &onoffline)] {
+        if let Err(e) = window.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref()) {
+            warn!("Failed to listen for {} events: {:?}", event, e);
+        }
+    }
+
+    let mut state = inner.borrow_mut();
+    state.ononline = Some(ononline);
+    state.onoffline = Some(onoffline);
+}
+
+/// Remove the network status listeners
+fn unwatch_network(inner: &Rc<RefCell<WebTransportInner>>) {
+    let mut state = inner.borrow_mut();
+    let (ononline, onoffline) = (state.ononline.take(), state.onoffline.take());
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    for (event, listener) in [("online", ononline), ("offline", onoffline)] {
+        if let Some(listener) = listener {
+            let _ = window.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        }
+    }
+}
+
+/// Reconnect right away with a fresh attempt count
+///
+/// A socket that is open or still connecting is left alone; an open one is
+/// reported connected again, in case `offline` said otherwise.
+fn reconnect_now(inner: &Rc<RefCell<WebTransportInner>>) {
+    {
+        let mut state = inner.borrow_mut();
+        if state.auth_error.is_some() {
+            return;
+        }
+        state.reconnect_timer = None;
+        state.reconnect_attempts = 0;
+        match state.ws.as_ref().map(|ws| ws.ready_state()) {
+            Some(WebSocket::OPEN) => {
+                if let Some(event_bus) = &state.event_bus {
+                    event_bus.publish(AppEvent::ConnectionChanged(ConnectionStatus::Connected));
+                }
+                return;
+            }
+            Some(WebSocket::CONNECTING) => return,
+            _ => {}
+        }
+        // A socket still closing must not call back into the new connection's state
+        if let Some(ws) = state.ws.take() {
+            detach_handlers(&ws);
+        }
+    }
+
+    if let Err(e) = WebTransport::connect_internal(inner.clone()) {
+        warn!("Reconnection failed: {}", e);
+    }
+}
+
+/// Stop a socket from calling its event handlers
+fn detach_handlers(ws: &WebSocket) {
+    ws.set_onopen(None);
+    ws.set_onmessage(None);
+    ws.set_onerror(None);
+    ws.set_onclose(None);
+}
+
+/// Delay before the given (1-based) attempt
+///
+/// The ceiling doubles per attempt up to `MAX_RECONNECT_DELAY_MS`; with
+/// jitter the actual wait is random between zero and that ceiling.
+fn reconnect_delay_ms(attempt: u32, jitter: bool) -> u32 {
+    let doublings = attempt.saturating_sub(1).min(16);
+    let ceiling = RECONNECT_DELAY_MS
+        .saturating_mul(1 << doublings)
+        .min(MAX_RECONNECT_DELAY_MS);
+    if jitter {
+        (js_sys::Math::random() * f64::from(ceiling)) as u32
+    } else {
+        ceiling
+    }
 }
 
 /// Dispatch a received message to the event bus
-fn dispatch_message(text: &str, event_bus: &Arc<dyn EventBus>) {
+///
+/// Returns the `AuthError` status to end the connection with when the
+/// server rejected the auth token.
+fn dispatch_message(
+    text: &str,
+    event_bus: &Arc<dyn EventBus>,
+    active_conversation: Option<String>,
+) -> Option<ConnectionStatus> {
     match serde_json::from_str::<WSServerMessage>(text) {
-        Ok(msg) => handle_server_message(msg, event_bus),
+        Ok(WSServerMessage::Unknown) => {
+            debug!("Ignoring server message of unknown type {}", frame_type(text).unwrap_or_default());
+            None
+        }
+        Ok(msg) => {
+            let auth_error = match &msg {
+                WSServerMessage::AuthFailed { reason, .. } => Some(ConnectionStatus::auth_error(reason.clone())),
+                _ => None,
+            };
+            handle_server_message(msg, event_bus, active_conversation);
+            auth_error
+        }
         Err(e) => {
             warn!("Failed to parse server message: {:?} - raw: {}", e, text);
+            None
         }
     }
 }
 
 /// Handle a parsed server message and publish appropriate events
-fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
+///
+/// `active_conversation` is used when a response, ack or typing update omits its conversation id.
+fn handle_server_message(
+    msg: WSServerMessage,
+    event_bus: &Arc<dyn EventBus>,
+    active_conversation: Option<String>,
+) {
     match msg {
         WSServerMessage::Response {
             id,
+            timestamp,
             reply_to,
             conversation_id,
             body,
             image,
-            ..
+            files,
         } => {
             info!(
                 "Received response for message {} in {:?}",
@@ -442,10 +930,38 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
                 mimetype: img.mimetype,
             });
 
-            let message = Message::new_assistant(id, body, image_data);
+            let files = files.into_iter().map(Into::into).collect();
+            let message = Message::new_assistant(id, body, image_data)
+                .with_files(files)
+                .at_server_time(timestamp);
+
+            // Single-conversation servers omit the id; deliver to the open chat
+            match conversation_id.or(active_conversation) {
+                Some(conv_id) => {
+                    event_bus.publish(AppEvent::MessageReceived {
+                        conv_id,
+                        message,
+                        reply_to: Some(reply_to),
+                    });
+                }
+                None => {
+                    info!("Dropping response {}: no conversation id and none active", reply_to);
+                }
+            }
+        }
 
-            if let Some(conv_id) = conversation_id {
-                event_bus.publish(AppEvent::MessageReceived { conv_id, message });
+        WSServerMessage::Ack {
+            timestamp,
+            reply_to,
+            conversation_id,
+            ..
+        } => {
+            if let Some(conv_id) = conversation_id.or(active_conversation) {
+                event_bus.publish(AppEvent::MessageAcked {
+                    conv_id,
+                    wire_id: reply_to,
+                    received_at: server_time(timestamp),
+                });
             }
         }
 
@@ -454,7 +970,7 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             is_typing,
             ..
         } => {
-            if let Some(conv_id) = conversation_id {
+            if let Some(conv_id) = conversation_id.or(active_conversation) {
                 event_bus.publish(AppEvent::TypingChanged { conv_id, is_typing });
             }
         }
@@ -466,7 +982,7 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             ..
         } => {
             info!("Notification [{}]: {} - {}", category, title, body);
-            // TODO: Publish notification event when notification feature is added
+            event_bus.publish(AppEvent::NotificationReceived { title, body, category });
         }
 
         WSServerMessage::Error {
@@ -476,12 +992,21 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             ..
         } => {
             warn!("Error received: {}", message);
-            if let (Some(msg_id), Some(conv_id)) = (reply_to, conversation_id) {
-                event_bus.publish(AppEvent::MessageError {
-                    conv_id,
-                    msg_id,
-                    error: message,
-                });
+            match (reply_to, conversation_id) {
+                (Some(msg_id), Some(conv_id)) => {
+                    event_bus.publish(AppEvent::MessageError {
+                        conv_id,
+                        msg_id,
+                        error: message,
+                    });
+                }
+                (Some(request_id), None) => {
+                    event_bus.publish(AppEvent::RequestFailed {
+                        request_id,
+                        error: message,
+                    });
+                }
+                _ => {}
             }
         }
 
@@ -491,7 +1016,15 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             let convs: Vec<Conversation> = conversations
                 .into_iter()
                 .map(|c| {
-                    Conversation::from_server(c.id, c.last_message, c.last_message_time, c.message_count)
+                    let read_only = c.is_read_only();
+                    let mut conv = Conversation::from_server(c.id, c.last_message, c.last_message_time, c.message_count);
+                    conv.set_labels(ConversationLabels { tags: c.tags, color: c.color });
+                    if let Some(title) = c.title {
+                        conv.title = title;
+                    }
+                    conv.sort_index = c.sort_index;
+                    conv.read_only = read_only;
+                    conv
                 })
                 .collect();
 
@@ -501,6 +1034,7 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
         WSServerMessage::History {
             conversation_id,
             messages,
+            has_more,
             ..
         } => {
             info!(
@@ -512,21 +1046,33 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             let parsed_messages: Vec<Message> =
                 messages.into_iter().filter_map(parse_history_message).collect();
 
+            if parsed_messages.len() > HISTORY_BATCH_SIZE {
+                // Batching lets the UI apply it a slice at a time. Publishing
+                // inline keeps later frames behind the last batch.
+                for batch in history_batches(&conversation_id, parsed_messages, has_more) {
+                    event_bus.publish(batch);
+                }
+                return;
+            }
+
             event_bus.publish(AppEvent::HistoryLoaded {
                 conv_id: conversation_id,
                 messages: parsed_messages,
+                has_more,
             });
         }
 
         WSServerMessage::ConversationCreated {
             conversation_id,
             title,
+            reply_to,
             ..
         } => {
             info!("Conversation created: {} ({:?})", conversation_id, title);
             event_bus.publish(AppEvent::ConversationCreated {
                 id: conversation_id,
                 title,
+                reply_to,
             });
         }
 
@@ -537,9 +1083,120 @@ fn handle_server_message(msg: WSServerMessage, event_bus: &Arc<dyn EventBus>) {
             event_bus.publish(AppEvent::ConversationDeleted(conversation_id));
         }
 
+        WSServerMessage::ConversationLabelsUpdated {
+            conversation_id,
+            tags,
+            color,
+            ..
+        } => {
+            info!("Conversation labels updated: {}", conversation_id);
+            event_bus.publish(AppEvent::ConversationLabelsChanged {
+                conv_id: conversation_id,
+                labels: ConversationLabels { tags, color },
+            });
+        }
+
+        WSServerMessage::ConversationRenamed {
+            conversation_id,
+            title,
+            ..
+        } => {
+            info!("Conversation renamed: {}", conversation_id);
+            event_bus.publish(AppEvent::ConversationRenamed {
+                conv_id: conversation_id,
+                title,
+            });
+        }
+
+        WSServerMessage::ConversationPinned {
+            conversation_id,
+            pinned,
+            ..
+        } => {
+            info!("Conversation pinned: {} ({})", conversation_id, pinned);
+            event_bus.publish(AppEvent::ConversationPinned {
+                conv_id: conversation_id,
+                pinned,
+            });
+        }
+
+        WSServerMessage::MessageEdited {
+            conversation_id,
+            message_id,
+            new_body,
+            ..
+        } => {
+            info!("Message {} edited in {}", message_id, conversation_id);
+            event_bus.publish(AppEvent::MessageEdited {
+                conv_id: conversation_id,
+                msg_id: message_id,
+                new_body,
+            });
+        }
+
+        WSServerMessage::MessageDeleted {
+            conversation_id,
+            message_id,
+            ..
+        } => {
+            info!("Message {} deleted in {}", message_id, conversation_id);
+            event_bus.publish(AppEvent::MessageDeleted {
+                conv_id: conversation_id,
+                msg_id: message_id,
+            });
+        }
+
+        WSServerMessage::MessageReaction {
+            conversation_id,
+            message_id,
+            emoji,
+            count,
+            ..
+        } => {
+            info!("Reaction {} on {} now at {}", emoji, message_id, count);
+            event_bus.publish(AppEvent::MessageReaction {
+                conv_id: conversation_id,
+                msg_id: message_id,
+                emoji,
+                count,
+            });
+        }
+
+        WSServerMessage::ReadReceipt {
+            conversation_id,
+            up_to_message_id,
+            ..
+        } => {
+            event_bus.publish(AppEvent::ReadReceipt {
+                conv_id: conversation_id,
+                up_to_msg_id: up_to_message_id,
+            });
+        }
+
+        WSServerMessage::SearchResults { query, results, .. } => {
+            info!("Received {} search results for {:?}", results.len(), query);
+            let results = results
+                .into_iter()
+                .filter_map(|hit| {
+                    let message = parse_history_message(hit.message)?;
+                    Some(SearchResult { conv_id: hit.conversation_id, message })
+                })
+                .collect();
+            event_bus.publish(AppEvent::SearchResultsLoaded { query, results });
+        }
+
         WSServerMessage::Pong { .. } => {
             // Heartbeat response, nothing to do
         }
+
+        WSServerMessage::AuthFailed { reason, .. } => {
+            warn!("Server rejected the auth token: {}", reason.as_deref().unwrap_or("no reason given"));
+            event_bus.publish(AppEvent::AuthFailed { reason });
+        }
+
+        WSServerMessage::Unknown => {
+            // Logged with its type in dispatch_message
+        }
     }
 }
 
@@ -577,5 +1234,10 @@ fn parse_history_message(m: HistoryMessage) -> Option<Message> {
         sender,
         status: MessageStatus::Delivered,
         image: None,
+        delivered_at: None,
+        edited: false,
+        reply_to: None,
+        files: Vec::new(),
+        reactions: HashMap::new(),
     })
 }
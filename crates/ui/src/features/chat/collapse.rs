@@ -0,0 +1,61 @@
+//! Collapsing long message bodies behind "Show more"
+
+/// Limits beyond which a message body is shown collapsed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollapseThreshold {
+    pub max_lines: usize,
+    pub max_chars: usize,
+}
+
+impl Default for CollapseThreshold {
+    fn default() -> Self {
+        Self {
+            max_lines: 12,
+            max_chars: 1200,
+        }
+    }
+}
+
+impl CollapseThreshold {
+    /// Check if a body is long enough to offer collapsing
+    pub fn exceeds(&self, body: &str) -> bool {
+        body.lines().count() > self.max_lines || body.chars().count() > self.max_chars
+    }
+
+    /// Cut a body down to the first `max_lines` lines and `max_chars` characters
+    ///
+    /// Returns the body unchanged when it is within the limits; otherwise
+    /// the preview ends with an ellipsis.
+    pub fn preview(&self, body: &str) -> String {
+        if !self.exceeds(body) {
+            return body.to_string();
+        }
+        let mut preview: String = body
+            .lines()
+            .take(self.max_lines)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .chars()
+            .take(self.max_chars)
+            .collect();
+        preview.truncate(preview.trim_end().len());
+        preview.push('…');
+        preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_respects_line_and_char_limits() {
+        let threshold = CollapseThreshold { max_lines: 2, max_chars: 10 };
+
+        assert!(!threshold.exceeds("short\ntext"));
+        assert_eq!(threshold.preview("short\ntext"), "short\ntext");
+        assert_eq!(threshold.preview("one\ntwo\nthree"), "one\ntwo…");
+        assert_eq!(threshold.preview("ünïcödé ünïcödé"), "ünïcödé ün…");
+        assert_eq!(threshold.preview("trailing   spaces"), "trailing…");
+    }
+}
@@ -0,0 +1,66 @@
+//! Sender avatars next to message bubbles
+
+use dioxus::prelude::*;
+use prsnl_core::MessageSender;
+
+/// Colored circle with the initial of a message's sender
+///
+/// `label` names the sender (e.g. an assistant persona); without it the
+/// circle shows U, A or S. The color follows from the name, so the same
+/// sender always gets the same one.
+#[component]
+pub fn SenderAvatar(sender: MessageSender, #[props(default)] label: Option<String>) -> Element {
+    let identity = label.unwrap_or_else(|| sender_name(&sender).to_string());
+    let initial = avatar_initial(&identity);
+    let hue = avatar_hue(&identity);
+
+    rsx! {
+        div {
+            class: "shrink-0 w-7 h-7 rounded-full flex items-center justify-center text-white text-xs font-semibold select-none",
+            style: "background-color: hsl({hue}, 55%, 42%);",
+            title: "{identity}",
+            "{initial}"
+        }
+    }
+}
+
+/// Default name for a sender
+fn sender_name(sender: &MessageSender) -> &'static str {
+    match sender {
+        MessageSender::User => "User",
+        MessageSender::Assistant => "Assistant",
+        MessageSender::System => "System",
+    }
+}
+
+/// Uppercased first letter or digit of a name ("?" when it has none)
+fn avatar_initial(name: &str) -> String {
+    name.chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Hue for a name, from an FNV-1a hash (stable across builds, unlike `DefaultHasher`)
+fn avatar_hue(name: &str) -> u32 {
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    hash % 360
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avatar_initial_and_hue() {
+        assert_eq!(avatar_initial(sender_name(&MessageSender::User)), "U");
+        assert_eq!(avatar_initial("  émile"), "É");
+        assert_eq!(avatar_initial("--"), "?");
+
+        assert_eq!(avatar_hue("Assistant"), avatar_hue("Assistant"));
+        assert_ne!(avatar_hue("Assistant"), avatar_hue("User"));
+        assert!(avatar_hue("Researcher") < 360);
+    }
+}
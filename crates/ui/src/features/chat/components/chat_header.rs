@@ -2,7 +2,18 @@
 
 use dioxus::prelude::*;
 use prsnl_core::ConnectionStatus;
-use crate::shared::ConnectionIndicator;
+use crate::features::conversations::ExportFormat;
+use crate::features::settings::components::EditableConnectionIndicator;
+use crate::shared::copy_to_clipboard;
+
+/// Details for bug reports, offered in the header's overflow menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationDebugInfo {
+    pub conversation_id: String,
+    pub server_url: String,
+    /// Messages loaded in the chat view
+    pub message_count: usize,
+}
 
 /// Chat header with back button and title
 #[component]
@@ -11,7 +22,51 @@ pub fn ChatHeader(
     status: ConnectionStatus,
     on_back: EventHandler<()>,
     on_status_tap: EventHandler<()>,
+    /// Re-send the last prompt into a new conversation (hidden when None)
+    #[props(default)]
+    on_start_fresh: Option<EventHandler<()>>,
+    /// Open the tag/color editor (hidden when None)
+    #[props(default)]
+    on_edit_labels: Option<EventHandler<()>>,
+    /// Whether the conversation is pinned to the top of the list
+    #[props(default)]
+    pinned: bool,
+    /// Pin or unpin the conversation (hidden when None)
+    #[props(default)]
+    on_toggle_pin: Option<EventHandler<()>>,
+    /// Show the connection indicator (off when it lives in the status bar)
+    #[props(default = true)]
+    show_status: bool,
+    /// Rename the conversation; clicking the title edits it (plain text when None)
+    #[props(default)]
+    on_rename: Option<EventHandler<String>>,
+    /// Save the conversation as a file; adds an export menu (hidden when None)
+    #[props(default)]
+    on_export: Option<EventHandler<ExportFormat>>,
+    /// Open find-in-conversation (hidden when None)
+    #[props(default)]
+    on_search: Option<EventHandler<()>>,
+    /// Conversation id and connection details; adds an overflow menu (hidden when None)
+    #[props(default)]
+    debug_info: Option<ConversationDebugInfo>,
 ) -> Element {
+    // Title being edited, if any
+    let mut draft = use_signal(|| Option::<String>::None);
+    let mut export_menu_open = use_signal(|| false);
+    let mut overflow_menu_open = use_signal(|| false);
+    let mut id_copied = use_signal(|| false);
+    let debug_status = format!("{:?}", status);
+
+    // Enter and blur both commit; whichever comes second finds nothing to do
+    let current_title = title.clone();
+    let mut commit = move || {
+        if let (Some(text), Some(on_rename)) = (draft.take(), on_rename) {
+            if text.trim() != current_title {
+                on_rename.call(text);
+            }
+        }
+    };
+
     rsx! {
         header {
             class: "shrink-0 py-3 px-4 bg-bg-secondary text-text-white flex items-center gap-3 border-b border-border",
@@ -32,15 +87,216 @@ pub fn ChatHeader(
             }
 
             // Title
-            h1 {
-                class: "m-0 text-lg flex-1 overflow-hidden text-ellipsis whitespace-nowrap",
-                "{title}"
+            if let Some(text) = draft() {
+                input {
+                    r#type: "text",
+                    value: "{text}",
+                    placeholder: "Conversation title",
+                    autofocus: true,
+                    oninput: move |e| draft.set(Some(e.value())),
+                    onkeydown: {
+                        let mut commit = commit.clone();
+                        move |e: KeyboardEvent| match e.key() {
+                            Key::Enter => commit(),
+                            Key::Escape => draft.set(None),
+                            _ => {}
+                        }
+                    },
+                    onblur: move |_| commit(),
+                    class: "flex-1 min-w-0 m-0 py-1 px-2 border border-border rounded-md bg-bg-primary text-text-white text-lg outline-none focus:border-accent",
+                }
+            } else if on_rename.is_some() {
+                h1 {
+                    onclick: {
+                        let title = title.clone();
+                        move |_| draft.set(Some(title.clone()))
+                    },
+                    class: "m-0 text-lg flex-1 overflow-hidden text-ellipsis whitespace-nowrap cursor-text",
+                    title: "Rename",
+                    "{title}"
+                }
+            } else {
+                h1 {
+                    class: "m-0 text-lg flex-1 overflow-hidden text-ellipsis whitespace-nowrap",
+                    "{title}"
+                }
+            }
+
+            // Find in this conversation
+            if let Some(on_search) = on_search {
+                button {
+                    onclick: move |_| on_search.call(()),
+                    class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1",
+                    title: "Find in conversation",
+                    svg {
+                        width: "20",
+                        height: "20",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M15.5 14h-.79l-.28-.27A6.471 6.471 0 0016 9.5 6.5 6.5 0 109.5 16c1.61 0 3.09-.59 4.23-1.57l.27.28v.79l5 4.99L20.49 19l-4.99-5zm-6 0C7.01 14 5 11.99 5 9.5S7.01 5 9.5 5 14 7.01 14 9.5 11.99 14 9.5 14z"
+                        }
+                    }
+                }
+            }
+
+            // Pin to the top of the list
+            if let Some(on_toggle_pin) = on_toggle_pin {
+                button {
+                    onclick: move |_| on_toggle_pin.call(()),
+                    class: if pinned {
+                        "bg-transparent border-none text-accent cursor-pointer p-2 -m-1"
+                    } else {
+                        "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1"
+                    },
+                    title: if pinned { "Unpin" } else { "Pin" },
+                    svg {
+                        width: "20",
+                        height: "20",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M16 9V4h1c.55 0 1-.45 1-1s-.45-1-1-1H7c-.55 0-1 .45-1 1s.45 1 1 1h1v5c0 1.66-1.34 3-3 3v2h5.97v7l1 1 1-1v-7H19v-2c-1.66 0-3-1.34-3-3z"
+                        }
+                    }
+                }
+            }
+
+            // Edit tags and color
+            if let Some(on_edit_labels) = on_edit_labels {
+                button {
+                    onclick: move |_| on_edit_labels.call(()),
+                    class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1",
+                    title: "Tags and color",
+                    svg {
+                        width: "20",
+                        height: "20",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M21.41 11.58l-9-9C12.05 2.22 11.55 2 11 2H4c-1.1 0-2 .9-2 2v7c0 .55.22 1.05.59 1.42l9 9c.36.36.86.58 1.41.58.55 0 1.05-.22 1.41-.59l7-7c.37-.36.59-.86.59-1.41 0-.55-.23-1.06-.59-1.42zM5.5 7C4.67 7 4 6.33 4 5.5S4.67 4 5.5 4 7 4.67 7 5.5 6.33 7 5.5 7z"
+                        }
+                    }
+                }
+            }
+
+            // Export as text or JSON
+            if let Some(on_export) = on_export {
+                div {
+                    class: "relative",
+                    button {
+                        onclick: move |_| export_menu_open.toggle(),
+                        class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1",
+                        title: "Export conversation",
+                        svg {
+                            width: "20",
+                            height: "20",
+                            view_box: "0 0 24 24",
+                            fill: "currentColor",
+                            path {
+                                d: "M19 9h-4V3H9v6H5l7 7 7-7zM5 18v2h14v-2H5z"
+                            }
+                        }
+                    }
+                    if export_menu_open() {
+                        // Closes the menu on any click outside it
+                        div {
+                            class: "fixed inset-0 z-40",
+                            onclick: move |_| export_menu_open.set(false),
+                        }
+                        div {
+                            class: "absolute right-0 top-full mt-2 z-50 min-w-[160px] py-1 rounded-lg bg-bg-secondary border border-border shadow-lg",
+                            for format in [ExportFormat::Text, ExportFormat::Json] {
+                                button {
+                                    key: "{format.extension()}",
+                                    onclick: move |_| {
+                                        export_menu_open.set(false);
+                                        on_export.call(format);
+                                    },
+                                    class: "w-full text-left px-4 py-2 bg-transparent border-none text-text-white text-sm cursor-pointer hover:bg-bg-hover",
+                                    "Export as {format.label()}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Start fresh with the last prompt
+            if let Some(on_start_fresh) = on_start_fresh {
+                button {
+                    onclick: move |_| on_start_fresh.call(()),
+                    class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1",
+                    title: "Start fresh with this prompt",
+                    svg {
+                        width: "20",
+                        height: "20",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M17.65 6.35A7.958 7.958 0 0012 4c-4.42 0-7.99 3.58-7.99 8s3.57 8 7.99 8c3.73 0 6.84-2.55 7.73-6h-2.08A5.99 5.99 0 0112 18c-3.31 0-6-2.69-6-6s2.69-6 6-6c1.66 0 3.14.69 4.22 1.78L13 11h7V4l-2.35 2.35z"
+                        }
+                    }
+                }
+            }
+
+            // Overflow menu with details for bug reports
+            if let Some(info) = debug_info {
+                div {
+                    class: "relative",
+                    button {
+                        onclick: move |_| {
+                            id_copied.set(false);
+                            overflow_menu_open.toggle();
+                        },
+                        class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-2 -m-1",
+                        title: "More",
+                        svg {
+                            width: "20",
+                            height: "20",
+                            view_box: "0 0 24 24",
+                            fill: "currentColor",
+                            path {
+                                d: "M12 8c1.1 0 2-.9 2-2s-.9-2-2-2-2 .9-2 2 .9 2 2 2zm0 2c-1.1 0-2 .9-2 2s.9 2 2 2 2-.9 2-2-.9-2-2-2zm0 6c-1.1 0-2 .9-2 2s.9 2 2 2 2-.9 2-2-.9-2-2-2z"
+                            }
+                        }
+                    }
+                    if overflow_menu_open() {
+                        // Closes the menu on any click outside it
+                        div {
+                            class: "fixed inset-0 z-40",
+                            onclick: move |_| overflow_menu_open.set(false),
+                        }
+                        div {
+                            class: "absolute right-0 top-full mt-2 z-50 w-[280px] py-1 rounded-lg bg-bg-secondary border border-border shadow-lg",
+                            button {
+                                onclick: {
+                                    let conversation_id = info.conversation_id.clone();
+                                    move |_| {
+                                        copy_to_clipboard(&conversation_id);
+                                        id_copied.set(true);
+                                    }
+                                },
+                                class: "w-full text-left px-4 py-2 bg-transparent border-none text-text-white text-sm cursor-pointer hover:bg-bg-hover",
+                                if id_copied() { "Copied conversation id" } else { "Copy conversation id" }
+                            }
+                            div {
+                                class: "px-4 py-2 border-t border-border text-text-muted text-xs font-mono break-all select-text",
+                                div { "{info.conversation_id}" }
+                                div { "{info.server_url}" }
+                                div { "{debug_status} · {info.message_count} messages loaded" }
+                            }
+                        }
+                    }
+                }
             }
 
             // Connection status
-            ConnectionIndicator {
-                status,
-                on_tap: on_status_tap,
+            if show_status {
+                EditableConnectionIndicator {
+                    status,
+                    on_tap: on_status_tap,
+                }
             }
         }
     }
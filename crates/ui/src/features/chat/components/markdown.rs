@@ -0,0 +1,224 @@
+//! Markdown rendering for message bodies (`markdown` feature)
+//!
+//! The body is parsed with pulldown-cmark into a small node tree and then
+//! rendered as regular elements. Raw HTML in the source is kept as text, so
+//! it is escaped like any other text instead of reaching the DOM. Bare URLs
+//! in text are linked, except inside links and code.
+
+use dioxus::prelude::*;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use super::message_bubble::linked_text;
+
+/// Elements the renderer knows how to emit
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Paragraph,
+    Heading,
+    Quote,
+    Pre,
+    List(Option<u64>),
+    Item,
+    Strong,
+    Emphasis,
+    Strikethrough,
+    /// Only http(s) and mailto targets are kept
+    Link(Option<String>),
+    /// Anything unsupported (tables, images, footnotes) keeps just its text
+    Plain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Code(String),
+    Break,
+    Rule,
+    Element(Block, Vec<Node>),
+}
+
+/// Parse markdown into a node tree
+fn parse(body: &str) -> Vec<Node> {
+    let mut stack: Vec<(Block, Vec<Node>)> = vec![(Block::Plain, Vec::new())];
+
+    for event in Parser::new_ext(body, Options::ENABLE_STRIKETHROUGH) {
+        let node = match event {
+            Event::Start(tag) => {
+                stack.push((block_for(tag), Vec::new()));
+                continue;
+            }
+            Event::End(TagEnd::Image) => {
+                // Keep the alt text only; remote images are not loaded from bodies
+                let (_, children) = stack.pop().unwrap_or((Block::Plain, Vec::new()));
+                Node::Element(Block::Plain, children)
+            }
+            Event::End(_) => {
+                let (block, children) = stack.pop().unwrap_or((Block::Plain, Vec::new()));
+                Node::Element(block, children)
+            }
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => Node::Text(text.into_string()),
+            Event::Code(code) => Node::Code(code.into_string()),
+            Event::SoftBreak => Node::Text("\n".to_string()),
+            Event::HardBreak => Node::Break,
+            Event::Rule => Node::Rule,
+            _ => continue,
+        };
+        if let Some((_, children)) = stack.last_mut() {
+            children.push(node);
+        }
+    }
+
+    stack.into_iter().next().map(|(_, nodes)| nodes).unwrap_or_default()
+}
+
+fn block_for(tag: Tag) -> Block {
+    match tag {
+        Tag::Paragraph => Block::Paragraph,
+        Tag::Heading { .. } => Block::Heading,
+        Tag::BlockQuote(_) => Block::Quote,
+        Tag::CodeBlock(CodeBlockKind::Fenced(_) | CodeBlockKind::Indented) => Block::Pre,
+        Tag::List(start) => Block::List(start),
+        Tag::Item => Block::Item,
+        Tag::Strong => Block::Strong,
+        Tag::Emphasis => Block::Emphasis,
+        Tag::Strikethrough => Block::Strikethrough,
+        Tag::Link { dest_url, .. } => Block::Link(safe_href(&dest_url)),
+        _ => Block::Plain,
+    }
+}
+
+/// Keep link targets that can't run script
+fn safe_href(url: &str) -> Option<String> {
+    let lower = url.trim().to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+        .then(|| url.trim().to_string())
+}
+
+/// Message body rendered from markdown
+#[component]
+pub fn MarkdownBody(body: String) -> Element {
+    let nodes = parse(&body);
+    rsx! {
+        div {
+            class: "markdown-body break-words",
+            {render_nodes(&nodes, true)}
+        }
+    }
+}
+
+/// Render sibling nodes, linking URLs in text when `linkify` is set
+///
+/// The parser may split one run of text into several events, so adjacent
+/// text nodes are joined first to keep URLs whole.
+fn render_nodes(nodes: &[Node], linkify: bool) -> Element {
+    let mut runs: Vec<Element> = Vec::new();
+    let mut text = String::new();
+    for node in nodes {
+        if let Node::Text(part) = node {
+            text.push_str(part);
+            continue;
+        }
+        if !text.is_empty() {
+            runs.push(render_text(&std::mem::take(&mut text), linkify));
+        }
+        runs.push(render_node(node, linkify));
+    }
+    if !text.is_empty() {
+        runs.push(render_text(&text, linkify));
+    }
+    rsx! { {runs.into_iter()} }
+}
+
+fn render_text(text: &str, linkify: bool) -> Element {
+    if linkify {
+        linked_text(text)
+    } else {
+        rsx! { "{text}" }
+    }
+}
+
+fn render_node(node: &Node, linkify: bool) -> Element {
+    match node {
+        Node::Text(text) => render_text(text, linkify),
+        Node::Code(code) => rsx! {
+            code { class: "px-1 rounded bg-black/20 font-mono text-[0.9em]", "{code}" }
+        },
+        Node::Break => rsx! { br {} },
+        Node::Rule => rsx! { hr { class: "my-2 border-current/20" } },
+        Node::Element(block, children) => {
+            // Links and code blocks are rendered as written
+            let linkify = linkify && !matches!(block, Block::Link(_) | Block::Pre);
+            let children = render_nodes(children, linkify);
+            match block {
+                Block::Paragraph => rsx! { p { class: "m-0 mb-2 last:mb-0 whitespace-pre-wrap", {children} } },
+                Block::Heading => rsx! { p { class: "m-0 mb-2 font-semibold", {children} } },
+                Block::Quote => rsx! {
+                    blockquote { class: "m-0 mb-2 pl-3 border-l-2 border-current/30 opacity-90", {children} }
+                },
+                Block::Pre => rsx! {
+                    pre {
+                        class: "m-0 mb-2 p-2 rounded-md bg-black/25 overflow-x-auto text-[0.85em]",
+                        code { class: "font-mono", {children} }
+                    }
+                },
+                Block::List(None) => rsx! { ul { class: "m-0 mb-2 pl-5 list-disc", {children} } },
+                Block::List(Some(start)) => rsx! {
+                    ol { class: "m-0 mb-2 pl-5 list-decimal", start: "{start}", {children} }
+                },
+                Block::Item => rsx! { li { {children} } },
+                Block::Strong => rsx! { strong { {children} } },
+                Block::Emphasis => rsx! { em { {children} } },
+                Block::Strikethrough => rsx! { del { {children} } },
+                Block::Link(Some(href)) => rsx! {
+                    a {
+                        href: "{href}",
+                        target: "_blank",
+                        rel: "noopener noreferrer",
+                        class: "underline text-inherit",
+                        {children}
+                    }
+                },
+                Block::Link(None) | Block::Plain => rsx! { {children} },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Node {
+        Node::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_parses_inline_markup_and_escapes_html() {
+        let nodes = parse("**bold** and `code` <script>alert(1)</script>\n\n- [ok](https://a.b)\n- [no](javascript:alert(1))");
+
+        assert_eq!(
+            nodes[0],
+            Node::Element(
+                Block::Paragraph,
+                vec![
+                    Node::Element(Block::Strong, vec![text("bold")]),
+                    text(" and "),
+                    Node::Code("code".to_string()),
+                    text(" "),
+                    text("<script>"),
+                    text("alert(1)"),
+                    text("</script>"),
+                ]
+            )
+        );
+        let Node::Element(Block::List(None), items) = &nodes[1] else {
+            panic!("expected a list, got {:?}", nodes[1]);
+        };
+        assert_eq!(
+            items[0],
+            Node::Element(Block::Item, vec![Node::Element(Block::Link(Some("https://a.b".to_string())), vec![text("ok")])])
+        );
+        assert_eq!(items[1], Node::Element(Block::Item, vec![Node::Element(Block::Link(None), vec![text("no")])]));
+    }
+}
@@ -0,0 +1,245 @@
+//! Per-message actions: hover row on desktop, long-press menu on touch
+
+use dioxus::prelude::*;
+use prsnl_core::{Message, MessageSender, MessageStatus};
+use super::reactions::QUICK_REACTIONS;
+use crate::features::speech::is_speech_output_supported;
+
+/// Something the user can do with a single message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageAction {
+    Copy,
+    Speak,
+    Reply,
+    Edit,
+    Delete,
+    Regenerate,
+    Retry,
+}
+
+impl MessageAction {
+    /// Every action, in menu order
+    pub const ALL: [MessageAction; 7] = [
+        MessageAction::Copy,
+        MessageAction::Speak,
+        MessageAction::Reply,
+        MessageAction::Edit,
+        MessageAction::Delete,
+        MessageAction::Regenerate,
+        MessageAction::Retry,
+    ];
+
+    /// Check if the action makes sense for a message
+    ///
+    /// Edit/delete are limited to the user's own messages,
+    /// speak/regenerate to assistant replies, and retry to messages that
+    /// failed to send. Speak needs a build with speech output.
+    pub fn applies_to(&self, message: &Message) -> bool {
+        let has_body = !message.body.is_empty();
+        match self {
+            MessageAction::Copy | MessageAction::Reply => {
+                has_body && message.sender != MessageSender::System
+            }
+            MessageAction::Speak => {
+                is_speech_output_supported() && has_body && message.sender == MessageSender::Assistant
+            }
+            MessageAction::Edit => has_body && message.sender == MessageSender::User,
+            MessageAction::Delete => message.sender == MessageSender::User,
+            MessageAction::Regenerate => message.sender == MessageSender::Assistant,
+            MessageAction::Retry => {
+                message.sender == MessageSender::User && matches!(message.status, MessageStatus::Error(_))
+            }
+        }
+    }
+
+    /// Check if the action has to name the message to the server
+    ///
+    /// Such actions are hidden for messages the server has no id for, like
+    /// those loaded from history.
+    pub fn needs_server_id(&self) -> bool {
+        matches!(self, MessageAction::Edit | MessageAction::Delete)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageAction::Copy => "Copy",
+            MessageAction::Speak => "Speak aloud",
+            MessageAction::Reply => "Reply",
+            MessageAction::Edit => "Edit",
+            MessageAction::Delete => "Delete",
+            MessageAction::Regenerate => "Regenerate",
+            MessageAction::Retry => "Retry",
+        }
+    }
+
+    /// SVG path for the action's 24x24 icon
+    fn icon(&self) -> &'static str {
+        match self {
+            MessageAction::Copy => "M16 1H4c-1.1 0-2 .9-2 2v14h2V3h12V1zm3 4H8c-1.1 0-2 .9-2 2v14c0 1.1.9 2 2 2h11c1.1 0 2-.9 2-2V7c0-1.1-.9-2-2-2zm0 16H8V7h11v14z",
+            MessageAction::Speak => "M3 9v6h4l5 5V4L7 9H3zm13.5 3A4.5 4.5 0 0014 7.97v8.05c1.48-.73 2.5-2.25 2.5-4.02z",
+            MessageAction::Reply => "M10 9V5l-7 7 7 7v-4.1c5 0 8.5 1.6 11 5.1-1-5-4-10-11-11z",
+            MessageAction::Edit => "M3 17.25V21h3.75L17.81 9.94l-3.75-3.75L3 17.25zM20.71 7.04a.996.996 0 000-1.41l-2.34-2.34a.996.996 0 00-1.41 0l-1.83 1.83 3.75 3.75 1.83-1.83z",
+            MessageAction::Delete => "M6 19c0 1.1.9 2 2 2h8c1.1 0 2-.9 2-2V7H6v12zM19 4h-3.5l-1-1h-5l-1 1H5v2h14V4z",
+            MessageAction::Regenerate => "M17.65 6.35A7.958 7.958 0 0012 4c-4.42 0-7.99 3.58-7.99 8s3.57 8 7.99 8c3.73 0 6.84-2.55 7.73-6h-2.08A5.99 5.99 0 0112 18c-3.31 0-6-2.69-6-6s2.69-6 6-6c1.66 0 3.14.69 4.22 1.78L13 11h7V4l-2.35 2.35z",
+            MessageAction::Retry => "M12 5V1L7 6l5 5V7c3.31 0 6 2.69 6 6s-2.69 6-6 6-6-2.69-6-6H4c0 4.42 3.58 8 8 8s8-3.58 8-8-3.58-8-8-8z",
+        }
+    }
+}
+
+/// Actions from `enabled` that apply to a message
+pub fn actions_for(message: &Message, enabled: &[MessageAction]) -> Vec<MessageAction> {
+    enabled.iter().copied().filter(|a| a.applies_to(message)).collect()
+}
+
+/// Floating row of icon buttons shown while hovering a bubble (pointer devices only)
+#[component]
+pub fn MessageActionBar(actions: Vec<MessageAction>, on_action: EventHandler<MessageAction>) -> Element {
+    rsx! {
+        div {
+            class: "absolute -top-4 right-2 z-10 hidden group-hover:flex gap-1 p-1 rounded-lg bg-bg-secondary border border-border shadow-lg",
+            for action in actions {
+                button {
+                    key: "{action.label()}",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_action.call(action);
+                    },
+                    class: "w-7 h-7 flex items-center justify-center rounded-md bg-transparent border-none text-text-secondary hover:text-text-white hover:bg-bg-hover cursor-pointer",
+                    title: action.label(),
+                    svg {
+                        width: "16",
+                        height: "16",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path { d: action.icon() }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bottom sheet listing the same actions, opened by long-press (or right-click)
+#[component]
+pub fn MessageContextMenu(
+    /// Shown above the actions (e.g. when the message was sent)
+    #[props(default)]
+    heading: Option<String>,
+    actions: Vec<MessageAction>,
+    on_action: EventHandler<MessageAction>,
+    /// Offer the quick reactions above the actions
+    #[props(default)]
+    on_react: Option<EventHandler<String>>,
+    on_close: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div {
+            onclick: move |_| on_close.call(()),
+            class: "fixed inset-0 bg-black/50 flex items-end justify-center z-[1000]",
+            div {
+                onclick: move |e| e.stop_propagation(),
+                class: "w-full max-w-[480px] bg-bg-secondary rounded-t-2xl py-2 shadow-2xl animate-slide-in",
+                if let Some(heading) = heading {
+                    p {
+                        class: "m-0 px-6 pt-2 pb-3 border-b border-border text-text-muted text-sm",
+                        "{heading}"
+                    }
+                }
+                if let Some(on_react) = on_react {
+                    div {
+                        class: "flex justify-around px-4 py-2 border-b border-border",
+                        for emoji in QUICK_REACTIONS {
+                            button {
+                                key: "{emoji}",
+                                onclick: move |_| {
+                                    on_close.call(());
+                                    on_react.call(emoji.to_string());
+                                },
+                                class: "w-10 h-10 flex items-center justify-center rounded-full bg-transparent border-none text-2xl leading-none cursor-pointer hover:bg-bg-hover",
+                                title: "React with {emoji}",
+                                "{emoji}"
+                            }
+                        }
+                    }
+                }
+                for action in actions {
+                    button {
+                        key: "{action.label()}",
+                        onclick: move |_| {
+                            on_close.call(());
+                            on_action.call(action);
+                        },
+                        class: "w-full flex items-center gap-4 px-6 py-3 bg-transparent border-none text-text-white text-base text-left cursor-pointer hover:bg-bg-hover",
+                        svg {
+                            width: "20",
+                            height: "20",
+                            view_box: "0 0 24 24",
+                            fill: "currentColor",
+                            path { d: action.icon() }
+                        }
+                        "{action.label()}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bottom sheet asking before a message is deleted
+#[component]
+pub fn DeleteConfirmSheet(on_confirm: EventHandler<()>, on_close: EventHandler<()>) -> Element {
+    rsx! {
+        div {
+            onclick: move |_| on_close.call(()),
+            class: "fixed inset-0 bg-black/50 flex items-end justify-center z-[1000]",
+            div {
+                onclick: move |e| e.stop_propagation(),
+                class: "w-full max-w-[480px] bg-bg-secondary rounded-t-2xl p-6 shadow-2xl animate-slide-in flex flex-col gap-4",
+                p {
+                    class: "m-0 text-text-white text-base",
+                    "Delete this message?"
+                }
+                div {
+                    class: "flex justify-end gap-2",
+                    button {
+                        onclick: move |_| on_close.call(()),
+                        class: "px-4 py-2 rounded-lg border-none bg-bg-tertiary text-text-white text-sm cursor-pointer",
+                        "Cancel"
+                    }
+                    button {
+                        onclick: move |_| on_confirm.call(()),
+                        class: "px-4 py-2 rounded-lg border-none bg-error text-white text-sm cursor-pointer",
+                        "Delete"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actions_depend_on_sender() {
+        let user = Message::new_user("hi".to_string());
+        let assistant = Message::new_assistant("a1".to_string(), "hello".to_string(), None);
+
+        assert_eq!(
+            actions_for(&user, &MessageAction::ALL),
+            [MessageAction::Copy, MessageAction::Reply, MessageAction::Edit, MessageAction::Delete]
+        );
+        // Speak only shows where the build can speak
+        let speak = is_speech_output_supported().then_some(MessageAction::Speak);
+        let expected: Vec<_> = [Some(MessageAction::Copy), speak, Some(MessageAction::Reply), Some(MessageAction::Regenerate)]
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(actions_for(&assistant, &MessageAction::ALL), expected);
+        assert_eq!(
+            actions_for(&assistant, &[MessageAction::Copy, MessageAction::Edit]),
+            [MessageAction::Copy]
+        );
+    }
+}
@@ -1,21 +1,124 @@
 //! Message bubble component
 
+use chrono::{DateTime, Local, TimeZone};
 use dioxus::prelude::*;
 use prsnl_core::{Message, MessageSender, MessageStatus};
 
+use crate::features::chat::{linkify, split_matches, ChatState, CollapseThreshold, TextSpan};
+use crate::features::media::{check_image, FileChip};
+use crate::features::settings::TimestampVisibility;
+use crate::features::speech::{is_speaking, speak, stop_speaking};
+use crate::shared::copy_to_clipboard;
+use super::avatar::SenderAvatar;
+use super::message_actions::{actions_for, DeleteConfirmSheet, MessageAction, MessageActionBar, MessageContextMenu};
+use super::reactions::{QuickReactPicker, ReactionBar};
+
 /// A single message bubble
+///
+/// Copy and speak are handled here, and delete is passed on once confirmed;
+/// the remaining actions are passed to `on_action`. Assistant replies can
+/// be reacted to when `on_react` is set.
 #[component]
-pub fn MessageBubble(message: Message) -> Element {
+pub fn MessageBubble(
+    message: Message,
+    /// Actions offered in the hover row and long-press menu
+    #[props(default = MessageAction::ALL.to_vec())]
+    actions: Vec<MessageAction>,
+    #[props(default)] on_action: EventHandler<(MessageAction, Message)>,
+    /// Toggles the user's reaction, with the message id and emoji
+    #[props(default)]
+    on_react: Option<EventHandler<(String, String)>>,
+    /// Bodies beyond this are shown collapsed behind "Show more"
+    #[props(default)]
+    collapse: CollapseThreshold,
+    /// Newest message in the conversation (long bodies start expanded)
+    #[props(default)]
+    latest: bool,
+    /// When the time in the footer is shown
+    #[props(default = TimestampVisibility::Always)]
+    timestamps: TimestampVisibility,
+    /// The message this one replies to, quoted above the body
+    #[props(default)]
+    quoted: Option<Message>,
+    /// Show the sender's avatar beside the bubble (on the right for the user)
+    #[props(default)]
+    show_avatar: bool,
+    /// Name for the avatar in place of the sender kind
+    #[props(default)]
+    avatar_label: Option<String>,
+    /// Find-in-conversation query to mark in the body
+    #[props(default)]
+    highlight: Option<String>,
+) -> Element {
     let is_user = message.sender == MessageSender::User;
     let is_system = message.sender == MessageSender::System;
+    // Set when the webview fails to load an image that passed validation
+    let mut image_failed = use_signal(|| false);
+    // Validation decodes the whole image, so it runs once per message and image
+    // rather than on every render
+    let image_check = use_memo(use_reactive(
+        (&message.id, &message.image),
+        |(_, image)| image.as_ref().map(check_image),
+    ));
+    let mut menu_open = use_signal(|| false);
+    let mut confirm_delete = use_signal(|| false);
+    // Touch has no hover, so tapping the bubble reveals an on-hover time
+    let mut time_revealed = use_signal(|| false);
+
+    // Long bodies start collapsed unless this is the newest message or the
+    // user already chose otherwise this session
+    let mut chat_state = use_context::<ChatState>();
+    let is_long = !is_system && collapse.exceeds(&message.body);
+    let mut expanded = use_signal(|| chat_state.message_expanded(&message.id).unwrap_or(latest));
+    // A match may be in the collapsed part, so matching bodies show in full
+    let body = if is_long && !expanded() && highlight.is_none() {
+        collapse.preview(&message.body)
+    } else {
+        message.body.clone()
+    };
+    let toggle_expanded = {
+        let msg_id = message.id.clone();
+        move |_| {
+            let next = !expanded();
+            expanded.set(next);
+            chat_state.set_message_expanded(&msg_id, next);
+        }
+    };
+
+    // History carries no message ids, so the server can't be told about
+    // changes to messages loaded from it
+    let known_to_server = chat_state.server_id(&message.id).is_some();
+    let actions: Vec<_> = actions_for(&message, &actions)
+        .into_iter()
+        .filter(|action| known_to_server || !action.needs_server_id())
+        .collect();
+    let react = on_react
+        .filter(|_| known_to_server && message.sender == MessageSender::Assistant)
+        .map(|on_react| {
+            let msg_id = message.id.clone();
+            EventHandler::new(move |emoji: String| on_react.call((msg_id.clone(), emoji)))
+        });
+    let has_actions = !actions.is_empty() || react.is_some();
+    let reactions = message.sorted_reactions();
+    let handle_action = {
+        let message = message.clone();
+        move |action: MessageAction| match action {
+            MessageAction::Copy => copy_to_clipboard(&message.body),
+            // Tapping again while speaking stops playback
+            MessageAction::Speak if is_speaking() => stop_speaking(),
+            MessageAction::Speak => speak(&message.body),
+            MessageAction::Delete => confirm_delete.set(true),
+            _ => on_action.call((action, message.clone())),
+        }
+    };
 
     // Use Tailwind component classes with conditional variants
     let container_class = if is_system {
-        "flex justify-center mb-3"
+        "flex justify-center items-end gap-2 mb-3"
     } else if is_user {
-        "flex justify-end mb-3"
+        "flex justify-end items-end gap-2 mb-3"
     } else {
-        "flex justify-start mb-3"
+        "flex justify-start items-end gap-2 mb-3"
     };
 
     let bubble_class = if is_system {
@@ -26,16 +129,33 @@ pub fn MessageBubble(message: Message) -> Element {
         "message-bubble message-bubble-assistant"
     };
 
-    // Format timestamp
-    let time = message.timestamp.format("%H:%M").to_string();
+    // Format timestamp: compact in the footer, in full on hover or long-press
+    let local_time = message.timestamp.with_timezone(&Local);
+    let time = local_time.format("%H:%M").to_string();
+    let full_time = format_full_timestamp(&local_time);
+    let time_class = match timestamps {
+        TimestampVisibility::OnHover if !time_revealed() => {
+            "text-[0.7rem] text-current/60 opacity-0 group-hover:opacity-100 transition-opacity"
+        }
+        _ => "text-[0.7rem] text-current/60",
+    };
+
+    // Round trip shown on hover, e.g. "Delivered in 2.4s"
+    let delivery = message
+        .delivery_time()
+        .filter(|_| is_user)
+        .map(|d| format!("Delivered in {:.1}s", d.num_milliseconds().max(0) as f64 / 1000.0))
+        .unwrap_or_default();
 
-    // Status indicator for user messages
+    // Status indicator for user messages: one check once the server has the
+    // message, two once it answered
     let status_icon = if is_user {
         match &message.status {
-            MessageStatus::Sending => Some("..."),
-            MessageStatus::Sent => Some("v"),
-            MessageStatus::Delivered => Some("vv"),
-            MessageStatus::Error(_) => Some("!"),
+            MessageStatus::Sending => Some(("...", "Sending")),
+            MessageStatus::Sent => Some(("✓", "Sent")),
+            MessageStatus::Delivered => Some(("✓✓", "Delivered")),
+            MessageStatus::Read => Some(("✓✓", "Read")),
+            MessageStatus::Error(_) => Some(("!", "Failed")),
         }
     } else {
         None
@@ -43,22 +163,91 @@ pub fn MessageBubble(message: Message) -> Element {
 
     rsx! {
         div {
+            id: "msg-{message.id}",
             class: "{container_class}",
 
+            if show_avatar && !is_user {
+                SenderAvatar { sender: message.sender.clone(), label: avatar_label.clone() }
+            }
+
             div {
-                class: "{bubble_class}",
+                class: "{bubble_class} relative group",
+                onclick: move |_| {
+                    if timestamps == TimestampVisibility::OnHover {
+                        time_revealed.toggle();
+                    }
+                },
+                oncontextmenu: move |e: MouseEvent| {
+                    // Long-press on touch fires contextmenu too
+                    if has_actions {
+                        e.prevent_default();
+                        menu_open.set(true);
+                    }
+                },
+
+                if !actions.is_empty() {
+                    MessageActionBar {
+                        actions: actions.clone(),
+                        on_action: handle_action.clone(),
+                    }
+                }
+
+                if let Some(react) = react {
+                    QuickReactPicker { on_pick: react }
+                }
 
-                // Image if present
-                if let Some(ref image) = message.image {
+                // Tapping the quote jumps to the original
+                if let Some(original) = quoted {
+                    div {
+                        class: "mb-2 cursor-pointer",
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            chat_state.set_focused_message(Some(original.id.clone()));
+                        },
+                        QuotedMessage { message: original.clone() }
+                    }
+                }
+
+                // Image if present, validated before it reaches the data: URI
+                if let (Some(ref image), Some(check)) = (&message.image, image_check()) {
                     {
-                        let img_src = format!("data:{};base64,{}", image.mimetype, image.data);
-                        rsx! {
-                            div {
-                                class: "mb-2",
-                                img {
-                                    src: "{img_src}",
-                                    class: "max-w-full max-h-[200px] rounded-lg",
+                        match check {
+                            Ok(()) if !image_failed() => {
+                                let img_src = format!("data:{};base64,{}", image.mimetype, image.data);
+                                rsx! {
+                                    div {
+                                        class: "mb-2",
+                                        img {
+                                            src: "{img_src}",
+                                            class: "max-w-full max-h-[200px] rounded-lg",
+                                            onerror: move |_| image_failed.set(true),
+                                        }
+                                    }
+                                }
+                            }
+                            result => {
+                                if let Err(ref e) = result {
+                                    tracing::warn!("Rejected image in message {}: {}", message.id, e);
                                 }
+                                rsx! {
+                                    ImageErrorPlaceholder {}
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Other attachments, each opening or saving the file
+                if !message.files.is_empty() {
+                    div {
+                        class: "mb-2 flex flex-col gap-1",
+                        for file in message.files.iter() {
+                            FileChip {
+                                key: "{file.filename}",
+                                filename: file.filename.clone(),
+                                mimetype: file.mimetype.clone(),
+                                size: file.size(),
+                                data: file.data.clone(),
                             }
                         }
                     }
@@ -66,31 +255,56 @@ pub fn MessageBubble(message: Message) -> Element {
 
                 // Message body
                 if !message.body.is_empty() {
-                    p {
-                        class: "m-0 whitespace-pre-wrap break-words",
-                        "{message.body}"
+                    {message_body(&body, &message.sender, highlight.as_deref())}
+                    if is_long && highlight.is_none() {
+                        button {
+                            class: "mt-1 p-0 bg-transparent border-none cursor-pointer text-xs text-white/70 hover:text-text-white underline",
+                            onclick: toggle_expanded,
+                            if expanded() { "Show less" } else { "Show more" }
+                        }
+                    }
+                }
+
+                if !reactions.is_empty() {
+                    ReactionBar {
+                        reactions,
+                        own: chat_state.own_reactions(&message.id),
+                        on_toggle: react,
                     }
                 }
 
                 // Footer with time and status
                 div {
                     class: "flex justify-end items-center gap-1 mt-1",
+                    title: "{delivery}",
+
+                    if message.edited {
+                        span {
+                            class: "text-[0.7rem] text-current/60 italic",
+                            "(edited)"
+                        }
+                    }
 
-                    span {
-                        class: "text-[0.7rem] text-white/60",
-                        "{time}"
+                    if timestamps != TimestampVisibility::Never {
+                        span {
+                            class: "{time_class}",
+                            title: "{full_time}",
+                            "{time}"
+                        }
                     }
 
-                    if let Some(icon) = status_icon {
+                    if let Some((icon, label)) = status_icon {
                         {
                             let status_class = match &message.status {
                                 MessageStatus::Error(_) => "text-[0.7rem] text-error",
                                 MessageStatus::Delivered => "text-[0.7rem] text-success",
-                                _ => "text-[0.7rem] text-white/60",
+                                MessageStatus::Read => "text-[0.7rem] text-current font-bold",
+                                _ => "text-[0.7rem] text-current/60",
                             };
                             rsx! {
                                 span {
                                     class: "{status_class}",
+                                    title: "{label}",
                                     "{icon}"
                                 }
                             }
@@ -98,14 +312,168 @@ pub fn MessageBubble(message: Message) -> Element {
                     }
                 }
 
-                // Error message if present
+                // Error message if present, with a way to send again
                 if let MessageStatus::Error(ref err) = message.status {
                     div {
-                        class: "text-xs text-error mt-1",
-                        "{err}"
+                        class: "flex items-center gap-2 text-xs text-error mt-1",
+                        span { class: "flex-1", "{err}" }
+                        if actions.contains(&MessageAction::Retry) {
+                            button {
+                                class: "shrink-0 px-2 py-0.5 rounded-md border border-current bg-transparent text-error text-xs cursor-pointer hover:bg-black/10",
+                                onclick: {
+                                    let mut handle_action = handle_action.clone();
+                                    move |e: MouseEvent| {
+                                        e.stop_propagation();
+                                        handle_action(MessageAction::Retry);
+                                    }
+                                },
+                                "Retry"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if show_avatar && is_user {
+                SenderAvatar { sender: message.sender.clone(), label: avatar_label.clone() }
+            }
+
+            if menu_open() {
+                MessageContextMenu {
+                    heading: full_time.clone(),
+                    actions,
+                    on_action: handle_action,
+                    on_react: react,
+                    on_close: move |_| menu_open.set(false),
+                }
+            }
+
+            if confirm_delete() {
+                DeleteConfirmSheet {
+                    on_confirm: {
+                        let message = message.clone();
+                        move |_| {
+                            confirm_delete.set(false);
+                            on_action.call((MessageAction::Delete, message.clone()));
+                        }
+                    },
+                    on_close: move |_| confirm_delete.set(false),
+                }
+            }
+        }
+    }
+}
+
+/// Full date and time of a message, e.g. "Monday, March 3, 2025 at 14:05"
+fn format_full_timestamp<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    time.format("%A, %B %-d, %Y at %H:%M").to_string()
+}
+
+/// Message text, rendered from markdown for assistant replies with the `markdown` feature
+///
+/// Bodies with a search match are shown as plain text with the matches marked.
+#[cfg_attr(not(feature = "markdown"), allow(unused_variables))]
+fn message_body(body: &str, sender: &MessageSender, highlight: Option<&str>) -> Element {
+    if let Some(query) = highlight {
+        return rsx! {
+            p {
+                class: "m-0 whitespace-pre-wrap break-words",
+                for (run, matched) in split_matches(body, query) {
+                    if matched {
+                        mark { class: "rounded-sm bg-warning/60 text-inherit", "{run}" }
+                    } else {
+                        {linked_text(run)}
                     }
                 }
             }
+        };
+    }
+    #[cfg(feature = "markdown")]
+    if *sender == MessageSender::Assistant {
+        return rsx! {
+            super::markdown::MarkdownBody { body: body.to_string() }
+        };
+    }
+    rsx! {
+        p {
+            class: "m-0 whitespace-pre-wrap break-words",
+            {linked_text(body)}
         }
     }
 }
+
+/// Text with bare http(s) URLs turned into links
+pub(super) fn linked_text(text: &str) -> Element {
+    rsx! {
+        for span in linkify(text) {
+            match span {
+                TextSpan::Text(text) => rsx! { "{text}" },
+                TextSpan::Link(url) => rsx! {
+                    a {
+                        href: "{url}",
+                        target: "_blank",
+                        rel: "noopener",
+                        onclick: move |e: MouseEvent| e.stop_propagation(),
+                        class: "underline text-inherit break-all",
+                        "{url}"
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Sender and opening line of a quoted message
+#[component]
+pub fn QuotedMessage(message: Message) -> Element {
+    let sender = match message.sender {
+        MessageSender::User => "You",
+        MessageSender::Assistant => "Assistant",
+        MessageSender::System => "System",
+    };
+    let first_line = message.body.lines().next().unwrap_or_default();
+    let mut snippet: String = first_line.chars().take(QUOTE_PREVIEW_CHARS).collect();
+    if snippet.is_empty() && message.image.is_some() {
+        snippet = "Image".to_string();
+    } else if snippet.len() < message.body.trim_end().len() {
+        snippet.push('…');
+    }
+
+    rsx! {
+        div {
+            class: "pl-2 border-l-2 border-current/40 text-xs min-w-0",
+            div { class: "font-semibold text-current/80", "{sender}" }
+            div { class: "text-current/60 truncate", "{snippet}" }
+        }
+    }
+}
+
+/// Characters of the original shown in a quote
+const QUOTE_PREVIEW_CHARS: usize = 100;
+
+/// Shown in place of an image that failed validation or loading
+#[component]
+fn ImageErrorPlaceholder() -> Element {
+    rsx! {
+        div {
+            class: "mb-2 flex items-center justify-center gap-2 px-4 py-6 rounded-lg border border-dashed border-border-light text-text-muted text-sm",
+            span { "!" }
+            span { "Image could not be displayed" }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_format_full_timestamp() {
+        let time = Utc.with_ymd_and_hms(2025, 3, 3, 14, 5, 9).unwrap();
+        assert_eq!(format_full_timestamp(&time), "Monday, March 3, 2025 at 14:05");
+    }
+}
@@ -0,0 +1,133 @@
+//! Conversation-scoped message filter and the image gallery it can switch to
+
+use dioxus::prelude::*;
+use prsnl_core::{Message, MessageSender};
+use crate::features::media::check_image;
+
+/// Which messages of the open conversation to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFilter {
+    #[default]
+    All,
+    Images,
+    Mine,
+    Assistant,
+}
+
+impl MessageFilter {
+    /// Every filter, in control order
+    pub const ALL: [MessageFilter; 4] = [
+        MessageFilter::All,
+        MessageFilter::Images,
+        MessageFilter::Mine,
+        MessageFilter::Assistant,
+    ];
+
+    /// Check if a message passes the filter
+    pub fn matches(&self, message: &Message) -> bool {
+        match self {
+            MessageFilter::All => true,
+            MessageFilter::Images => message.image.is_some(),
+            MessageFilter::Mine => message.sender == MessageSender::User,
+            MessageFilter::Assistant => message.sender == MessageSender::Assistant,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageFilter::All => "All",
+            MessageFilter::Images => "Images",
+            MessageFilter::Mine => "Mine",
+            MessageFilter::Assistant => "Assistant",
+        }
+    }
+}
+
+/// Messages that pass `filter`, in their original order
+pub fn filter_messages(messages: &[Message], filter: MessageFilter) -> Vec<Message> {
+    messages.iter().filter(|m| filter.matches(m)).cloned().collect()
+}
+
+/// Row of filter chips shown under the chat header
+#[component]
+pub fn MessageFilterBar(
+    selected: MessageFilter,
+    on_change: EventHandler<MessageFilter>,
+) -> Element {
+    rsx! {
+        div {
+            class: "shrink-0 flex gap-2 px-4 py-2 border-b border-border bg-bg-secondary overflow-x-auto",
+            for filter in MessageFilter::ALL {
+                button {
+                    key: "{filter.label()}",
+                    class: if filter == selected {
+                        "px-3 py-1 rounded-full border-none text-xs cursor-pointer bg-accent text-text-white"
+                    } else {
+                        "px-3 py-1 rounded-full border-none text-xs cursor-pointer bg-bg-tertiary text-text-muted hover:text-text-white"
+                    },
+                    onclick: move |_| on_change.call(filter),
+                    "{filter.label()}"
+                }
+            }
+        }
+    }
+}
+
+/// Compact thumbnail grid for the "Images" filter
+///
+/// Tapping a thumbnail reports the message id so the screen can jump to it
+/// in the full conversation.
+#[component]
+pub fn ImageGallery(
+    messages: Vec<Message>,
+    on_select: EventHandler<String>,
+) -> Element {
+    rsx! {
+        div {
+            class: "grid grid-cols-3 gap-2",
+            for message in messages {
+                if let Some(image) = message.image.as_ref().filter(|image| check_image(image).is_ok()) {
+                    button {
+                        key: "{message.id}",
+                        class: "aspect-square p-0 border-none rounded-lg overflow-hidden cursor-pointer bg-bg-tertiary",
+                        title: "{message.body}",
+                        onclick: {
+                            let id = message.id.clone();
+                            move |_| on_select.call(id.clone())
+                        },
+                        img {
+                            src: "data:{image.mimetype};base64,{image.data}",
+                            class: "w-full h-full object-cover",
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prsnl_core::ImageData;
+
+    #[test]
+    fn test_filters_by_sender_and_media() {
+        let image = ImageData { data: "aGk=".to_string(), mimetype: "image/png".to_string() };
+        let messages = vec![
+            Message::new_user("mine".to_string()),
+            Message::new_assistant("a1".to_string(), "reply".to_string(), None),
+            Message::new_user_with_image("photo".to_string(), image.clone()),
+            Message::new_assistant("a2".to_string(), "chart".to_string(), Some(image)),
+            Message::new_system("joined".to_string()),
+        ];
+        let bodies = |filter| -> Vec<String> {
+            filter_messages(&messages, filter).into_iter().map(|m| m.body).collect()
+        };
+
+        assert_eq!(bodies(MessageFilter::All).len(), 5);
+        assert_eq!(bodies(MessageFilter::Images), ["photo", "chart"]);
+        assert_eq!(bodies(MessageFilter::Mine), ["mine", "photo"]);
+        assert_eq!(bodies(MessageFilter::Assistant), ["reply", "chart"]);
+    }
+}
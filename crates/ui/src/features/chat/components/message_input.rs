@@ -1,54 +1,193 @@
 //! Message input component
 
 use dioxus::prelude::*;
+use crate::features::chat::HistoryCursor;
+use crate::features::media::{read_pasted_image, SelectedMedia};
+use crate::features::settings::SendButtonStyle;
+use crate::features::speech::{
+    append_transcript, is_speech_input_supported, listen, stop_listening, SpeechInputError,
+};
 
 /// Message input with send and media buttons
 #[component]
 pub fn MessageInput(
-    value: String,
+    /// Current input text; a signal so voice input can append to what's
+    /// typed while it listens
+    value: ReadSignal<String>,
     on_change: EventHandler<String>,
     on_send: EventHandler<()>,
     on_media_select: EventHandler<()>,
+    /// An image was pasted into the input
+    on_media_paste: EventHandler<SelectedMedia>,
+    #[props(default = SendButtonStyle::Icon)] send_style: SendButtonStyle,
+    /// Previously sent texts for Up/Down recall, oldest first
+    #[props(default)] history: Vec<String>,
 ) -> Element {
+    let mut cursor = use_signal(HistoryCursor::default);
+
+    // Up recalls older entries when the input is empty, already showing history,
+    // or the caret sits at the start; Down walks back toward the draft.
+    let on_keydown = move |e: KeyboardEvent| match e.key() {
+        Key::ArrowUp => {
+            let history = history.clone();
+            let current = value();
+            if current.is_empty() || cursor.read().is_browsing() {
+                e.prevent_default();
+                if let Some(text) = cursor.write().older(&history, &current) {
+                    on_change.call(text);
+                }
+                return;
+            }
+            spawn(async move {
+                let caret = document::eval(
+                    "return document.getElementById('message-input')?.selectionStart ?? -1",
+                )
+                .join::<i64>()
+                .await;
+                if matches!(caret, Ok(0)) {
+                    if let Some(text) = cursor.write().older(&history, &current) {
+                        on_change.call(text);
+                    }
+                }
+            });
+        }
+        Key::ArrowDown if cursor.read().is_browsing() => {
+            e.prevent_default();
+            if let Some(text) = cursor.write().newer(&history) {
+                on_change.call(text);
+            }
+        }
+        _ => {}
+    };
+
+    // Pasting a screenshot attaches it; text pastes go ahead as usual
+    let on_paste = move |e: ClipboardEvent| {
+        let Some(image) = read_pasted_image(&e) else {
+            return;
+        };
+        e.prevent_default();
+        spawn(async move {
+            if let Some(selected) = image.await {
+                on_media_paste.call(selected);
+            }
+        });
+    };
+
+    // Voice input state
+    let mut listening = use_signal(|| false);
+    let mut mic_error = use_signal(|| Option::<String>::None);
+
+    let on_mic = move |_| {
+        if listening() {
+            stop_listening();
+            return;
+        }
+        listening.set(true);
+        mic_error.set(None);
+        spawn(async move {
+            match listen().await {
+                Ok(transcript) if !transcript.trim().is_empty() => {
+                    // Append to the text as it is now, including anything typed while listening
+                    on_change.call(append_transcript(&value.peek(), &transcript));
+                }
+                Ok(_) | Err(SpeechInputError::NoSpeech) => {}
+                Err(e) => mic_error.set(Some(e.to_string())),
+            }
+            listening.set(false);
+        });
+    };
+
+    let mic_class = if listening() {
+        "w-control min-w-control h-control rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-error text-text-white animate-pulse-status"
+    } else {
+        "w-control min-w-control h-control rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-bg-tertiary text-text-white"
+    };
+
+    // Icon-only is a round button; a label needs horizontal padding instead of a fixed width
+    let send_class = if send_style.shows_text() {
+        "min-w-control h-control px-4 gap-2 rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-accent text-text-white text-sm font-medium disabled:opacity-50 disabled:cursor-not-allowed"
+    } else {
+        "w-control min-w-control h-control rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-accent text-text-white disabled:opacity-50 disabled:cursor-not-allowed"
+    };
+
     rsx! {
+        // Voice input error (e.g. microphone permission denied)
+        if let Some(err) = mic_error() {
+            div {
+                class: "shrink-0 px-4 py-1 bg-bg-secondary text-error text-xs",
+                "{err}"
+            }
+        }
+
         div {
-            class: "shrink-0 py-3 px-4 bg-bg-secondary border-t border-border flex gap-2 items-center",
+            class: "shrink-0 py-bar px-4 bg-bg-secondary border-t border-border flex gap-2 items-center",
 
             // Media upload button
             button {
                 onclick: move |_| on_media_select.call(()),
-                class: "w-11 min-w-11 h-11 rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-bg-tertiary text-text-white text-xl",
+                class: "w-control min-w-control h-control rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-bg-tertiary text-text-white text-xl",
                 "+"
             }
 
             // Text input - use min-w-0 to allow flex shrinking properly
             input {
+                id: "message-input",
                 r#type: "text",
                 value: "{value}",
                 placeholder: "Type a message...",
-                oninput: move |e| on_change.call(e.value()),
+                oninput: move |e| {
+                    cursor.write().reset();
+                    on_change.call(e.value());
+                },
+                onkeydown: on_keydown,
+                onpaste: on_paste,
                 onkeypress: move |e| {
                     if e.key() == Key::Enter {
+                        cursor.write().reset();
                         on_send.call(());
                     }
                 },
-                class: "flex-1 min-w-0 h-11 px-4 border-none rounded-full bg-bg-tertiary text-text-white text-base outline-none box-border",
+                class: "flex-1 min-w-0 h-control px-4 border-none rounded-full bg-bg-tertiary text-text-white text-base outline-none box-border",
+            }
+
+            // Voice input button (tap again to stop)
+            if is_speech_input_supported() {
+                button {
+                    onclick: on_mic,
+                    class: "{mic_class}",
+                    title: if listening() { "Stop listening" } else { "Voice input" },
+                    svg {
+                        width: "20",
+                        height: "20",
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M12 14c1.66 0 3-1.34 3-3V5c0-1.66-1.34-3-3-3S9 3.34 9 5v6c0 1.66 1.34 3 3 3zm5.3-3c0 3-2.54 5.1-5.3 5.1S6.7 14 6.7 11H5c0 3.41 2.72 6.23 6 6.72V21h2v-3.28c3.28-.48 6-3.3 6-6.72h-1.7z"
+                        }
+                    }
+                }
             }
 
             // Send button
             button {
                 onclick: move |_| on_send.call(()),
-                disabled: value.trim().is_empty(),
-                class: "w-11 min-w-11 h-11 rounded-full border-none cursor-pointer flex items-center justify-center shrink-0 bg-accent text-text-white disabled:opacity-50",
-                svg {
-                    width: "24",
-                    height: "24",
-                    view_box: "0 0 24 24",
-                    fill: "currentColor",
-                    path {
-                        d: "M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"
+                disabled: value.read().trim().is_empty(),
+                class: "{send_class}",
+                title: "Send",
+                if send_style.shows_icon() {
+                    svg {
+                        width: if send_style.shows_text() { "18" } else { "24" },
+                        height: if send_style.shows_text() { "18" } else { "24" },
+                        view_box: "0 0 24 24",
+                        fill: "currentColor",
+                        path {
+                            d: "M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"
+                        }
                     }
                 }
+                if send_style.shows_text() {
+                    span { "Send" }
+                }
             }
         }
     }
@@ -1,20 +1,184 @@
 //! Message list component
 
+use chrono::{Datelike, Duration, Local, NaiveDate};
 use dioxus::prelude::*;
-use prsnl_core::Message;
+use prsnl_core::{Message, MessageSender};
+use crate::features::chat::{contains_match, CollapseThreshold};
+use crate::features::settings::TimestampVisibility;
+use super::message_actions::MessageAction;
 use super::message_bubble::MessageBubble;
 
 /// List of messages in a chat
 #[component]
-pub fn MessageList(messages: Vec<Message>) -> Element {
+pub fn MessageList(
+    messages: Vec<Message>,
+    /// Per-message actions to offer
+    #[props(default = MessageAction::ALL.to_vec())]
+    actions: Vec<MessageAction>,
+    #[props(default)] on_action: EventHandler<(MessageAction, Message)>,
+    /// Toggles the user's reaction, with the message id and emoji (reactions are off when None)
+    #[props(default)]
+    on_react: Option<EventHandler<(String, String)>>,
+    /// Limits beyond which bodies are collapsed (the newest message starts expanded)
+    #[props(default)]
+    collapse: CollapseThreshold,
+    /// When message times are shown
+    #[props(default = TimestampVisibility::Always)]
+    timestamps: TimestampVisibility,
+    /// Requests the page before the oldest message; the trigger is hidden when None
+    #[props(default)]
+    on_load_older: Option<EventHandler<()>>,
+    /// Show sender avatars beside the bubbles
+    #[props(default = true)]
+    show_avatars: bool,
+    /// Newest message seen before the conversation was opened
+    #[props(default)]
+    last_read: Option<String>,
+    /// Unread count the conversation was opened with; no divider when 0
+    #[props(default)]
+    unread: u32,
+    /// Find-in-conversation query to highlight in the bodies
+    #[props(default)]
+    highlight: Option<String>,
+) -> Element {
+    let latest_id = messages.last().map(|m| m.id.clone());
+    let dividers = day_dividers(&messages, Local::now().date_naive());
+    let first_unread = first_unread(&messages, last_read.as_deref(), unread);
+
     rsx! {
         div {
-            for message in messages {
-                MessageBubble {
+            if let Some(on_load_older) = on_load_older {
+                div {
+                    class: "flex justify-center mb-3",
+                    button {
+                        class: "px-3 py-1 rounded-full border border-border bg-bg-secondary text-text-secondary text-xs cursor-pointer hover:bg-bg-hover",
+                        onclick: move |_| on_load_older.call(()),
+                        "Load older messages"
+                    }
+                }
+            }
+            for (message, divider) in messages.iter().cloned().zip(dividers) {
+                div {
                     key: "{message.id}",
-                    message,
+                    if let Some(label) = divider {
+                        div {
+                            class: "flex items-center gap-3 my-3 text-text-muted text-xs",
+                            div { class: "flex-1 h-px bg-border" }
+                            span { "{label}" }
+                            div { class: "flex-1 h-px bg-border" }
+                        }
+                    }
+                    if first_unread.as_ref() == Some(&message.id) {
+                        div {
+                            class: "flex items-center gap-3 my-3 text-accent text-xs font-medium",
+                            div { class: "flex-1 h-px bg-accent" }
+                            span { "Unread messages" }
+                            div { class: "flex-1 h-px bg-accent" }
+                        }
+                    }
+                    MessageBubble {
+                        highlight: highlight.clone().filter(|query| contains_match(&message.body, query)),
+                        latest: latest_id.as_ref() == Some(&message.id),
+                        quoted: message
+                            .reply_to
+                            .as_ref()
+                            .and_then(|id| messages.iter().find(|m| &m.id == id).cloned()),
+                        message,
+                        actions: actions.clone(),
+                        on_action,
+                        on_react,
+                        collapse,
+                        timestamps,
+                        show_avatar: show_avatars,
+                    }
                 }
             }
         }
     }
 }
+
+/// Label for the divider above each message that starts a new local day
+///
+/// None for messages on the same day as the one before them.
+fn day_dividers(messages: &[Message], today: NaiveDate) -> Vec<Option<String>> {
+    let mut previous = None;
+    messages
+        .iter()
+        .map(|message| {
+            let day = message.timestamp.with_timezone(&Local).date_naive();
+            (previous.replace(day) != Some(day)).then(|| format_day_label(day, today))
+        })
+        .collect()
+}
+
+/// Id of the message the "Unread messages" divider goes above
+///
+/// That is the first reply after `last_read`, or, when the marker is not
+/// loaded or nothing follows it, the reply `unread` from the end, so the
+/// divider always covers the replies the badge counted.
+fn first_unread(messages: &[Message], last_read: Option<&str>, unread: u32) -> Option<String> {
+    if unread == 0 {
+        return None;
+    }
+    let is_reply = |m: &&Message| m.sender != MessageSender::User;
+    let after_marker = last_read
+        .and_then(|id| messages.iter().position(|m| m.id == id))
+        .and_then(|index| messages[index + 1..].iter().find(is_reply));
+    after_marker
+        .or_else(|| messages.iter().rev().filter(is_reply).take(unread as usize).last())
+        .map(|m| m.id.clone())
+}
+
+/// Name a day relative to today ("Today", "Yesterday", "March 3")
+///
+/// The year is added for days outside the current one.
+fn format_day_label(day: NaiveDate, today: NaiveDate) -> String {
+    if day == today {
+        "Today".to_string()
+    } else if Some(day) == today.checked_sub_signed(Duration::days(1)) {
+        "Yesterday".to_string()
+    } else if day.year() == today.year() {
+        day.format("%B %-d").to_string()
+    } else {
+        day.format("%B %-d, %Y").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_day_label() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(format_day_label(today, today), "Today");
+        assert_eq!(format_day_label(day(2025, 2, 28), today), "Yesterday");
+        assert_eq!(format_day_label(day(2025, 2, 3), today), "February 3");
+        assert_eq!(format_day_label(day(2024, 12, 31), today), "December 31, 2024");
+    }
+
+    #[test]
+    fn test_first_unread_follows_marker_then_badge() {
+        let message = |id: &str, sender| {
+            let mut message = Message::new_user(id.to_string());
+            message.id = id.to_string();
+            message.sender = sender;
+            message
+        };
+        let messages = [
+            message("a", MessageSender::Assistant),
+            message("b", MessageSender::User),
+            message("c", MessageSender::Assistant),
+            message("d", MessageSender::Assistant),
+        ];
+
+        assert_eq!(first_unread(&messages, Some("a"), 2), Some("c".to_string()));
+        // Marker not loaded (e.g. after a restart): count back from the end
+        assert_eq!(first_unread(&messages, None, 1), Some("d".to_string()));
+        // Marked unread at the newest message: the badge's one reply
+        assert_eq!(first_unread(&messages, Some("d"), 1), Some("d".to_string()));
+        assert_eq!(first_unread(&messages, Some("a"), 0), None);
+    }
+}
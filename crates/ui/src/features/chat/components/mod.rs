@@ -1,15 +1,29 @@
 //! Chat UI components
 
 mod screen;
+mod avatar;
 mod message_bubble;
+mod message_actions;
 mod message_input;
+mod message_filter;
 mod message_list;
+mod reactions;
+#[cfg(feature = "markdown")]
+mod markdown;
 mod typing_indicator;
 mod chat_header;
+mod search_bar;
 
 pub use screen::ChatScreen;
-pub use message_bubble::MessageBubble;
+pub use avatar::SenderAvatar;
+pub use message_bubble::{MessageBubble, QuotedMessage};
+pub use message_actions::{DeleteConfirmSheet, MessageAction, MessageActionBar, MessageContextMenu};
+pub use message_filter::{filter_messages, ImageGallery, MessageFilter, MessageFilterBar};
 pub use message_input::MessageInput;
 pub use message_list::MessageList;
-pub use typing_indicator::TypingIndicator;
-pub use chat_header::ChatHeader;
+pub use reactions::{QuickReactPicker, ReactionBar, QUICK_REACTIONS};
+#[cfg(feature = "markdown")]
+pub use markdown::MarkdownBody;
+pub use typing_indicator::{TypingIndicator, TypingSummary};
+pub use chat_header::{ChatHeader, ConversationDebugInfo};
+pub use search_bar::ConversationSearchBar;
@@ -0,0 +1,71 @@
+//! Emoji reactions: the counts under a bubble and the quick-react picker
+
+use std::collections::HashSet;
+use dioxus::prelude::*;
+
+/// Emoji offered by the quick-react picker, in order
+pub const QUICK_REACTIONS: [&str; 6] = ["👍", "❤️", "😂", "😮", "🙏", "👎"];
+
+/// Row of reaction chips below a message body
+///
+/// Chips the user gave are highlighted; tapping a chip toggles the user's
+/// reaction when `on_toggle` is set.
+#[component]
+pub fn ReactionBar(
+    /// Emoji and counts, in display order
+    reactions: Vec<(String, u32)>,
+    /// Emoji the user reacted with
+    #[props(default)]
+    own: HashSet<String>,
+    #[props(default)] on_toggle: Option<EventHandler<String>>,
+) -> Element {
+    rsx! {
+        div {
+            class: "flex flex-wrap gap-1 mt-1",
+            for (emoji, count) in reactions {
+                button {
+                    key: "{emoji}",
+                    onclick: {
+                        let emoji = emoji.clone();
+                        move |e: MouseEvent| {
+                            e.stop_propagation();
+                            if let Some(on_toggle) = on_toggle {
+                                on_toggle.call(emoji.clone());
+                            }
+                        }
+                    },
+                    disabled: on_toggle.is_none(),
+                    class: if own.contains(&emoji) {
+                        "flex items-center gap-1 px-1.5 py-0.5 rounded-full border border-accent bg-accent/20 text-current text-xs cursor-pointer"
+                    } else {
+                        "flex items-center gap-1 px-1.5 py-0.5 rounded-full border border-transparent bg-black/15 text-current text-xs cursor-pointer hover:bg-black/25 disabled:cursor-default"
+                    },
+                    span { class: "leading-none", "{emoji}" }
+                    span { "{count}" }
+                }
+            }
+        }
+    }
+}
+
+/// Floating row of quick reactions shown while hovering a bubble (pointer devices only)
+#[component]
+pub fn QuickReactPicker(on_pick: EventHandler<String>) -> Element {
+    rsx! {
+        div {
+            class: "absolute -top-4 left-2 z-10 hidden group-hover:flex gap-0.5 p-1 rounded-lg bg-bg-secondary border border-border shadow-lg",
+            for emoji in QUICK_REACTIONS {
+                button {
+                    key: "{emoji}",
+                    onclick: move |e: MouseEvent| {
+                        e.stop_propagation();
+                        on_pick.call(emoji.to_string());
+                    },
+                    class: "w-7 h-7 flex items-center justify-center rounded-md bg-transparent border-none text-base leading-none hover:bg-bg-hover cursor-pointer",
+                    title: "React with {emoji}",
+                    "{emoji}"
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,29 @@
 //! Chat screen container component
 
+use chrono::Utc;
+use dioxus::html::HasFileData;
 use dioxus::prelude::*;
-use prsnl_core::ConnectionStatus;
-use crate::features::media::{SelectedMedia, MediaPreview, pick_image};
-use super::{ChatHeader, MessageList, MessageInput, TypingIndicator};
-use crate::features::chat::hooks::{use_messages_for, use_typing_indicator, use_send_message};
+use prsnl_core::{ConnectionStatus, Message, MessageSender, MAX_ATTACHMENT_BYTES};
+use crate::features::media::{
+    fit_image, format_file_size, pick_file, read_dropped_image, FileFilter, MediaPreview,
+    SelectedMedia,
+};
+use crate::features::notifications::NotificationsService;
+use super::{
+    filter_messages, ChatHeader, ConversationDebugInfo, ConversationSearchBar, ImageGallery, MessageAction, MessageFilter, MessageFilterBar, MessageList,
+    MessageInput, QuotedMessage, TypingIndicator,
+};
+use crate::features::chat::{
+    ChatService, ChatState, CollapseThreshold, TypingReporter, LOAD_OLDER_THRESHOLD_PX, TYPING_IDLE_SECS,
+};
+use crate::features::conversations::{ConversationsService, ConversationsState, ExportFormat};
+use crate::features::conversations::components::LabelEditor;
+use crate::features::settings::{IndicatorPlacement, SettingsState};
+use crate::shared::{timer, focus, scroll_into_view, scroll_to_bottom, scroll_to_offset_from_bottom, EmptyState, LoadingSpinner};
+use crate::features::chat::hooks::{use_local_search, use_messages_for, use_typing_indicator, use_send_message};
+
+/// Distance from the bottom (in px) within which the newest messages count as seen
+const AT_BOTTOM_THRESHOLD_PX: f64 = 40.0;
 
 /// Chat screen container
 #[component]
@@ -14,50 +33,366 @@ pub fn ChatScreen(
     status: ConnectionStatus,
     on_back: EventHandler<()>,
     on_status_tap: EventHandler<()>,
+    /// Distance from the top (in px) at which older messages are requested
+    #[props(default = LOAD_OLDER_THRESHOLD_PX)]
+    load_older_threshold: f64,
+    /// Line/character limits beyond which older messages start collapsed
+    #[props(default)]
+    collapse_threshold: CollapseThreshold,
 ) -> Element {
-    // Local state for input and media
-    let mut input_text = use_signal(|| String::new());
+    // Local state for input and media; the input starts from the saved draft
+    let mut chat_state = use_context::<ChatState>();
+    let mut input_text = use_signal(|| chat_state.get_draft(&conv_id));
     let mut pending_media = use_signal(|| Option::<SelectedMedia>::None);
+    // Id of the sent message whose body the input is currently editing
+    let mut editing_message = use_signal(|| Option::<String>::None);
+    // Earlier message the next send replies to
+    let mut replying_to = use_signal(|| Option::<Message>::None);
 
     // Get messages and typing state from hooks (reactive memos)
     let messages_memo = use_messages_for(&conv_id);
     let is_typing_memo = use_typing_indicator();
     let send_message = use_send_message();
+    let chat_service = use_context::<ChatService>();
+    let conv_service = use_context::<ConversationsService>();
+    let conv_state = use_context::<ConversationsState>();
+    let mut editing_labels = use_signal(|| false);
+    let mut filter = use_signal(MessageFilter::default);
+    let settings_state = use_context::<SettingsState>();
+
+    // Save the input as the conversation's draft, except while it holds a message being edited.
+    // Sending empties the input, which clears the draft.
+    let draft_conv_id = conv_id.clone();
+    use_effect(move || {
+        let text = input_text();
+        if editing_message().is_none() {
+            chat_state.set_draft(&draft_conv_id, &text);
+        }
+    });
+
+    // Scroll to a focused message (e.g. opened from the activity feed) once it is rendered
+    use_effect(move || {
+        let Some(msg_id) = chat_state.focused_message() else {
+            return;
+        };
+        if messages_memo.read().iter().any(|m| m.id == msg_id) {
+            scroll_into_view(&format!("msg-{msg_id}"));
+            chat_state.set_focused_message(None);
+        }
+    });
+
+    // Keep the viewport on the same message when older history is prepended:
+    // remember the distance from the bottom and restore it once the page lands
+    let mut scroll_anchor = use_signal(|| Option::<f64>::None);
+    let anchor_conv_id = conv_id.clone();
+    use_effect(move || {
+        let _ = messages_memo.read();
+        if chat_state.is_loading_older(&anchor_conv_id) {
+            return;
+        }
+        if let Some(from_bottom) = scroll_anchor.take() {
+            scroll_to_offset_from_bottom("chat-container", from_bottom);
+        }
+    });
+    // Follow new messages to the bottom, unless a specific message is being focused
+    let mut last_seen = use_signal(|| Option::<String>::None);
+    use_effect(move || {
+        let last = messages_memo.read().last().map(|m| m.id.clone());
+        if last.is_some() && last != *last_seen.peek() && chat_state.focused_message().is_none() {
+            scroll_to_bottom("chat-container");
+        }
+        last_seen.set(last);
+    });
+    // Find in conversation: bring each match in view as the user steps through
+    let mut searching = use_signal(|| false);
+    let mut search = use_local_search();
+    use_effect(move || {
+        if let Some(msg_id) = search.current_id() {
+            scroll_into_view(&format!("msg-{msg_id}"));
+        }
+    });
+    let mut close_search = move || {
+        searching.set(false);
+        search.set_query(String::new());
+    };
+
+    // Where the user left off, taken before scrolling moves the marker;
+    // the badge count arrives with the opening and doesn't change after
+    let last_read = use_hook(|| chat_state.last_read(&conv_id));
+    let unread = chat_state.unread_on_open(&conv_id);
+    // Report the conversation as read while its newest messages are on screen
+    let mut at_bottom = use_signal(|| true);
+    let read_conv_id = conv_id.clone();
+    let read_service = chat_service.clone();
+    use_effect(move || {
+        let _ = messages_memo.read();
+        if at_bottom() {
+            read_service.mark_read(&read_conv_id);
+        }
+    });
+    let on_scroll = {
+        let chat_service = chat_service.clone();
+        let conv_id = conv_id.clone();
+        move |e: ScrollEvent| {
+            let scroll_top = e.data().scroll_top();
+            let from_bottom = e.data().scroll_height() as f64 - scroll_top - e.data().client_height() as f64;
+            if at_bottom() != (from_bottom <= AT_BOTTOM_THRESHOLD_PX) {
+                at_bottom.set(from_bottom <= AT_BOTTOM_THRESHOLD_PX);
+            }
+            if scroll_top <= load_older_threshold && chat_service.load_older_history(&conv_id) {
+                scroll_anchor.set(Some(e.data().scroll_height() as f64 - scroll_top));
+            }
+        }
+    };
+    let loading_older = chat_state.is_loading_older(&conv_id);
+    // Fallback for when the list is too short to scroll
+    let on_load_older = (!loading_older && !chat_state.history_exhausted(&conv_id)).then(|| {
+        let chat_service = chat_service.clone();
+        let conv_id = conv_id.clone();
+        EventHandler::new(move |_| {
+            chat_service.load_older_history(&conv_id);
+        })
+    });
 
     // Read reactive values
     let messages = messages_memo.read();
     let is_typing = *is_typing_memo.read();
+    let visible = filter_messages(&messages, filter());
+
+    // "Start fresh" is offered once there is a prompt to re-send
+    let has_user_message = messages.iter().any(|m| m.sender == MessageSender::User);
+    let on_start_fresh = has_user_message.then(|| {
+        let chat_service = chat_service.clone();
+        let conv_service = conv_service.clone();
+        let conv_id = conv_id.clone();
+        EventHandler::new(move |_| {
+            let local_id = conv_service.create_conversation(None);
+            chat_service.start_fresh(&conv_id, &local_id);
+        })
+    });
+
+    let labels = conv_state
+        .get_conversation(&conv_id)
+        .map(|c| c.labels())
+        .unwrap_or_default();
+    let pinned = conv_state
+        .get_conversation(&conv_id)
+        .is_some_and(|c| c.pinned);
+    // Read-only threads keep history, copy and speak but take no input
+    let read_only = conv_state
+        .get_conversation(&conv_id)
+        .is_some_and(|c| c.read_only);
+    let actions = if read_only {
+        vec![MessageAction::Copy, MessageAction::Speak]
+    } else {
+        MessageAction::ALL.to_vec()
+    };
+    let on_toggle_pin = {
+        let conv_service = conv_service.clone();
+        let conv_id = conv_id.clone();
+        move |_| conv_service.toggle_pin(&conv_id)
+    };
+    // Conversation id and connection details, kept out of release builds
+    let debug_info = cfg!(any(debug_assertions, feature = "debug-ui")).then(|| ConversationDebugInfo {
+        conversation_id: conv_id.clone(),
+        server_url: settings_state.server_url(),
+        message_count: messages.len(),
+    });
+    let on_export = {
+        let conv_service = conv_service.clone();
+        let conv_id = conv_id.clone();
+        move |format: ExportFormat| conv_service.export_conversation(&conv_id, format)
+    };
+    let on_rename = {
+        let conv_service = conv_service.clone();
+        let conv_id = conv_id.clone();
+        move |title: String| conv_service.rename_conversation(&conv_id, &title)
+    };
+    let on_save_labels = {
+        let conv_id = conv_id.clone();
+        move |labels| {
+            conv_service.set_labels(&conv_id, labels);
+            editing_labels.set(false);
+        }
+    };
+
+    // Tell the server when the user starts and stops typing, not on every keystroke
+    let mut typing_reporter = use_signal(TypingReporter::default);
+    let on_input_change = {
+        let chat_service = chat_service.clone();
+        let conv_id = conv_id.clone();
+        move |new_value: String| {
+            let typing = !new_value.is_empty();
+            input_text.set(new_value);
+            if !typing {
+                if typing_reporter.write().stop() {
+                    chat_service.send_typing(&conv_id, false);
+                }
+                return;
+            }
+            if typing_reporter.write().input(Utc::now()) {
+                chat_service.send_typing(&conv_id, true);
+            }
+            let chat_service = chat_service.clone();
+            let conv_id = conv_id.clone();
+            spawn(async move {
+                timer::sleep(std::time::Duration::from_secs(TYPING_IDLE_SECS as u64)).await;
+                if typing_reporter.write().idle(Utc::now()) {
+                    chat_service.send_typing(&conv_id, false);
+                }
+            });
+        }
+    };
+    use_drop({
+        let chat_service = chat_service.clone();
+        let conv_id = conv_id.clone();
+        move || {
+            if typing_reporter.try_write().is_ok_and(|mut r| r.stop()) {
+                chat_service.send_typing(&conv_id, false);
+            }
+        }
+    });
 
     // Handlers
     let on_send = {
         let send_message = send_message.clone();
+        let chat_service = chat_service.clone();
+        let conv_id = conv_id.clone();
         move |_| {
+            if typing_reporter.write().stop() {
+                chat_service.send_typing(&conv_id, false);
+            }
             let text = input_text.read().clone();
             let media = pending_media.read().clone();
 
-            if !text.trim().is_empty() || media.is_some() {
-                send_message(text, media);
-                input_text.set(String::new());
-                pending_media.set(None);
+            if let Some(msg_id) = editing_message() {
+                if !text.trim().is_empty() {
+                    chat_service.edit_message(&msg_id, text);
+                    input_text.set(String::new());
+                    editing_message.set(None);
+                }
+            } else if !text.trim().is_empty() || media.is_some() {
+                let reply_to = replying_to.peek().as_ref().map(|m| m.id.clone());
+                // A throttled send keeps the input so nothing is lost
+                if send_message(text, media, reply_to) {
+                    replying_to.set(None);
+                    input_text.set(String::new());
+                    pending_media.set(None);
+                }
             }
         }
     };
 
-    let on_media_select = move |_| {
-        spawn(async move {
-            if let Some(selected) = pick_image().await {
-                pending_media.set(Some(selected));
-            }
-        });
+    let notifications = use_context::<NotificationsService>();
+    let on_media_select = {
+        let notifications = notifications.clone();
+        move |_| {
+            let notifications = notifications.clone();
+            spawn(async move {
+                let Some(selected) = pick_file(FileFilter::All).await else {
+                    return;
+                };
+                attach_media(selected, settings_state, pending_media, &notifications).await;
+            });
+        }
     };
 
     let on_media_remove = move |_| {
         pending_media.set(None);
     };
 
+    // Dropping an image anywhere on the chat attaches it like the "+" button
+    let mut dragging = use_signal(|| false);
+    let on_drop = {
+        let notifications = notifications.clone();
+        move |e: DragEvent| {
+            e.prevent_default();
+            dragging.set(false);
+            if read_only {
+                return;
+            }
+            let Some(file) = e.files().into_iter().next() else {
+                return;
+            };
+            let notifications = notifications.clone();
+            spawn(async move {
+                match read_dropped_image(file).await {
+                    Ok(selected) => attach_media(selected, settings_state, pending_media, &notifications).await,
+                    Err(e) => notifications.show_notice("Can't attach file", &e),
+                }
+            });
+        }
+    };
+
+    // So does pasting a screenshot into the input
+    let on_media_paste = {
+        let notifications = notifications.clone();
+        move |selected: SelectedMedia| {
+            let notifications = notifications.clone();
+            spawn(async move {
+                attach_media(selected, settings_state, pending_media, &notifications).await;
+            });
+        }
+    };
+
+    let on_react = (!read_only).then(|| {
+        let chat_service = chat_service.clone();
+        EventHandler::new(move |(msg_id, emoji): (String, String)| chat_service.toggle_reaction(&msg_id, &emoji))
+    });
+
+    // Copy and speak are handled by the bubble itself
+    let action_conv_id = conv_id.clone();
+    let on_message_action = move |(action, message): (MessageAction, Message)| match action {
+        MessageAction::Reply => {
+            if editing_message.take().is_some() {
+                input_text.set(String::new());
+            }
+            replying_to.set(Some(message));
+            focus("message-input");
+        }
+        MessageAction::Edit => {
+            replying_to.set(None);
+            editing_message.set(Some(message.id));
+            input_text.set(message.body);
+            focus("message-input");
+        }
+        MessageAction::Delete => chat_service.delete_message(&message.id),
+        MessageAction::Regenerate => chat_service.regenerate(&message.id),
+        MessageAction::Retry => chat_service.retry_message(&action_conv_id, &message.id),
+        MessageAction::Copy | MessageAction::Speak => {}
+    };
+
     rsx! {
         div {
-            class: "flex flex-col h-screen h-dvh min-h-full font-sans bg-bg-primary",
+            // Fill the shell rather than the viewport, leaving room for a status bar
+            class: "relative flex flex-col flex-1 min-h-0 font-sans bg-bg-primary",
+            ondragover: move |e: DragEvent| {
+                if !read_only {
+                    // Needed for the browser to allow dropping here
+                    e.prevent_default();
+                    if !dragging() {
+                        dragging.set(true);
+                    }
+                }
+            },
+            ondragleave: move |_| dragging.set(false),
+            ondrop: on_drop,
+            // Ctrl/Cmd+F opens find-in-conversation instead of the page's find
+            onkeydown: move |e: KeyboardEvent| {
+                let modifiers = e.modifiers();
+                if (modifiers.ctrl() || modifiers.meta()) && e.key() == Key::Character("f".to_string()) {
+                    e.prevent_default();
+                    searching.set(true);
+                    focus("conversation-search-input");
+                }
+            },
+
+            if dragging() {
+                div {
+                    class: "absolute inset-2 z-10 flex items-center justify-center rounded-xl border-2 border-dashed border-accent bg-accent-light text-text-white font-semibold pointer-events-none",
+                    "Drop image to attach"
+                }
+            }
 
             // Header
             ChatHeader {
@@ -65,46 +400,174 @@ pub fn ChatScreen(
                 status,
                 on_back,
                 on_status_tap,
+                on_start_fresh,
+                on_edit_labels: move |_| editing_labels.toggle(),
+                pinned,
+                on_toggle_pin,
+                show_status: settings_state.indicator_placement() == IndicatorPlacement::Header,
+                on_rename: on_rename,
+                on_export,
+                on_search: move |_| {
+                    searching.set(true);
+                    focus("conversation-search-input");
+                },
+                debug_info,
+            }
+
+            if searching() {
+                ConversationSearchBar {
+                    query: search.query.read().clone(),
+                    position: search.position(),
+                    on_input: move |query| search.set_query(query),
+                    on_step: move |newer| search.step(newer),
+                    on_close: move |_| close_search(),
+                }
+            }
+
+            // Tag/color editor
+            if editing_labels() {
+                LabelEditor {
+                    labels,
+                    on_save: on_save_labels,
+                    on_close: move |_| editing_labels.set(false),
+                }
+            }
+
+            if !messages.is_empty() {
+                MessageFilterBar {
+                    selected: filter(),
+                    on_change: move |f| filter.set(f),
+                }
             }
 
             // Messages area
             div {
                 class: "flex-1 overflow-y-auto p-4 bg-bg-primary min-h-0",
                 id: "chat-container",
+                onscroll: on_scroll,
+
+                if loading_older {
+                    LoadingSpinner {}
+                }
 
                 if messages.is_empty() {
-                    div {
-                        class: "flex flex-col items-center justify-center h-full text-text-muted",
-                        p { "Start a conversation" }
-                        p {
-                            class: "text-sm",
-                            "Type a message below"
-                        }
+                    EmptyState {
+                        title: "Start a conversation",
+                        subtitle: "Type a message below",
+                    }
+                } else if visible.is_empty() {
+                    EmptyState {
+                        title: "No matching messages",
+                        subtitle: "Try a different filter",
+                    }
+                } else if filter() == MessageFilter::Images {
+                    // Jump back to the full conversation at the tapped image
+                    ImageGallery {
+                        messages: visible,
+                        on_select: move |msg_id| {
+                            filter.set(MessageFilter::All);
+                            chat_state.set_focused_message(Some(msg_id));
+                        },
                     }
                 } else {
-                    MessageList { messages: messages.clone() }
+                    MessageList {
+                        messages: visible,
+                        actions,
+                        on_action: on_message_action,
+                        on_react,
+                        collapse: collapse_threshold,
+                        timestamps: settings_state.timestamp_visibility(),
+                        on_load_older,
+                        last_read,
+                        unread,
+                        highlight: searching().then(|| search.query.read().clone()),
+                    }
                 }
 
-                if is_typing {
-                    TypingIndicator {}
+                if is_typing && filter() != MessageFilter::Images {
+                    TypingIndicator { style: settings_state.typing_style() }
                 }
             }
 
-            // Media preview (if pending)
-            if let Some(media) = pending_media.read().clone() {
-                MediaPreview {
-                    media,
-                    on_remove: on_media_remove,
+            if read_only {
+                p {
+                    class: "shrink-0 m-0 p-4 border-t border-border bg-bg-secondary text-text-muted text-sm text-center",
+                    "This conversation is read-only"
+                }
+            } else {
+                // Media preview (if pending)
+                if let Some(media) = pending_media.read().clone() {
+                    MediaPreview {
+                        media,
+                        on_remove: on_media_remove,
+                    }
+                }
+
+                if let Some(original) = replying_to() {
+                    div {
+                        class: "shrink-0 flex items-center gap-3 px-4 py-2 border-t border-border bg-bg-secondary text-text-muted",
+                        div {
+                            class: "flex-1 min-w-0",
+                            QuotedMessage { message: original }
+                        }
+                        button {
+                            class: "px-3 py-1 rounded-md border-none bg-bg-tertiary text-text-white text-xs cursor-pointer",
+                            onclick: move |_| replying_to.set(None),
+                            "Cancel"
+                        }
+                    }
                 }
-            }
 
-            // Input area
-            MessageInput {
-                value: input_text.read().clone(),
-                on_change: move |new_value: String| input_text.set(new_value),
-                on_send,
-                on_media_select,
+                if editing_message().is_some() {
+                    div {
+                        class: "shrink-0 flex items-center gap-3 px-4 py-2 border-t border-border bg-bg-secondary text-sm text-text-muted",
+                        span { class: "flex-1", "Editing message" }
+                        button {
+                            class: "px-3 py-1 rounded-md border-none bg-bg-tertiary text-text-white text-xs cursor-pointer",
+                            onclick: move |_| {
+                                editing_message.set(None);
+                                input_text.set(String::new());
+                            },
+                            "Cancel"
+                        }
+                    }
+                }
+
+                // Input area
+                MessageInput {
+                    value: input_text.read().clone(),
+                    on_change: on_input_change,
+                    on_send,
+                    on_media_select,
+                    on_media_paste,
+                    send_style: settings_state.send_button_style(),
+                    history: chat_state.sent_history(&conv_id),
+                }
             }
         }
     }
 }
+
+/// Scale a picked image down to the size chosen in settings
+async fn fit_to_setting(selected: SelectedMedia, settings: SettingsState) -> SelectedMedia {
+    match settings.image_size_limit().limits() {
+        Some(limits) => fit_image(selected, limits).await,
+        None => selected,
+    }
+}
+
+/// Fit media to the settings and hold it for the next send, unless it's still over the size limit
+async fn attach_media(
+    selected: SelectedMedia,
+    settings: SettingsState,
+    mut pending_media: Signal<Option<SelectedMedia>>,
+    notifications: &NotificationsService,
+) {
+    let selected = fit_to_setting(selected, settings).await;
+    if selected.size() > MAX_ATTACHMENT_BYTES {
+        let limit = format_file_size(MAX_ATTACHMENT_BYTES);
+        notifications.show_notice("File too large", &format!("Attachments can be up to {}", limit));
+    } else {
+        pending_media.set(Some(selected));
+    }
+}
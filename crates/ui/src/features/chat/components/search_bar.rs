@@ -0,0 +1,68 @@
+//! Find-in-conversation bar
+
+use dioxus::prelude::*;
+
+/// Query field with the match count and older/newer buttons
+///
+/// Enter goes to the next older match and Shift+Enter to the next newer
+/// one; Escape closes the bar.
+#[component]
+pub fn ConversationSearchBar(
+    query: String,
+    /// 1-based position of the match in view and the number of matches
+    position: Option<(usize, usize)>,
+    on_input: EventHandler<String>,
+    /// Move through the matches: true for newer, false for older
+    on_step: EventHandler<bool>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let count = match position {
+        Some((current, total)) => format!("{} of {}", current, total),
+        None if query.trim().is_empty() => String::new(),
+        None => "No matches".to_string(),
+    };
+
+    rsx! {
+        div {
+            class: "shrink-0 flex items-center gap-2 px-4 py-2 bg-bg-secondary border-b border-border",
+            input {
+                id: "conversation-search-input",
+                r#type: "search",
+                value: "{query}",
+                placeholder: "Find in conversation",
+                autofocus: true,
+                oninput: move |e| on_input.call(e.value()),
+                onkeydown: move |e: KeyboardEvent| match e.key() {
+                    Key::Enter => {
+                        e.prevent_default();
+                        on_step.call(e.modifiers().shift());
+                    }
+                    Key::Escape => on_close.call(()),
+                    _ => {}
+                },
+                class: "flex-1 min-w-0 py-1.5 px-3 border border-border rounded-lg bg-bg-primary text-text-white text-sm outline-none focus:border-accent",
+            }
+            span { class: "text-text-muted text-xs whitespace-nowrap", "{count}" }
+            button {
+                onclick: move |_| on_step.call(false),
+                disabled: position.is_none(),
+                class: "bg-transparent border-none text-text-secondary hover:text-text-white disabled:opacity-40 cursor-pointer disabled:cursor-default p-1",
+                title: "Older match",
+                "▲"
+            }
+            button {
+                onclick: move |_| on_step.call(true),
+                disabled: position.is_none(),
+                class: "bg-transparent border-none text-text-secondary hover:text-text-white disabled:opacity-40 cursor-pointer disabled:cursor-default p-1",
+                title: "Newer match",
+                "▼"
+            }
+            button {
+                onclick: move |_| on_close.call(()),
+                class: "bg-transparent border-none text-text-secondary hover:text-text-white cursor-pointer p-1",
+                title: "Close search",
+                "✕"
+            }
+        }
+    }
+}
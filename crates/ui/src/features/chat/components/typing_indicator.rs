@@ -1,27 +1,81 @@
 //! Typing indicator component
 
 use dioxus::prelude::*;
+use crate::features::chat::hooks::use_any_typing;
+use crate::features::settings::{SettingsState, TypingStyle};
 
 /// Animated typing indicator
+///
+/// Renders without animation when the reduce-motion setting is on.
 #[component]
-pub fn TypingIndicator() -> Element {
+pub fn TypingIndicator(
+    #[props(default = TypingStyle::Dots)] style: TypingStyle,
+    /// Name shown by the text variant (defaults to "Assistant")
+    #[props(default, into)]
+    assistant_name: Option<String>,
+) -> Element {
+    let reduce_motion = try_use_context::<SettingsState>()
+        .map(|s| s.reduce_motion())
+        .unwrap_or(false);
+    let animate = |class: &'static str| if reduce_motion { "" } else { class };
+    let name = assistant_name.unwrap_or_else(|| "Assistant".to_string());
+    let dot_class = format!("w-2 h-2 bg-text-muted rounded-full {}", animate("animate-bounce-dot"));
+    let pulse_class = format!("bg-bg-tertiary w-12 h-8 rounded-2xl {}", animate("animate-pulse-status"));
+
     rsx! {
         div {
             class: "flex items-center py-2 px-4 mb-3",
+            role: "status",
+            aria_label: "{name} is typing",
 
-            div {
-                class: "bg-bg-tertiary py-3 px-4 rounded-2xl flex gap-1",
+            match style {
+                TypingStyle::Dots => rsx! {
+                    div {
+                        class: "bg-bg-tertiary py-3 px-4 rounded-2xl flex gap-1",
 
-                span {
-                    class: "w-2 h-2 bg-text-muted rounded-full animate-bounce-dot",
-                }
-                span {
-                    class: "w-2 h-2 bg-text-muted rounded-full animate-bounce-dot animation-delay-150",
-                }
-                span {
-                    class: "w-2 h-2 bg-text-muted rounded-full animate-bounce-dot animation-delay-300",
-                }
+                        span {
+                            class: "{dot_class}",
+                        }
+                        span {
+                            class: "{dot_class} animation-delay-150",
+                        }
+                        span {
+                            class: "{dot_class} animation-delay-300",
+                        }
+                    }
+                },
+                TypingStyle::Text => rsx! {
+                    span {
+                        class: "text-text-muted text-sm italic",
+                        "{name} is typing..."
+                    }
+                },
+                TypingStyle::Pulse => rsx! {
+                    div {
+                        class: "{pulse_class}",
+                    }
+                },
             }
         }
     }
 }
+
+/// Compact note listing how many chats the assistant is responding in
+///
+/// Renders nothing while the assistant is idle everywhere.
+#[component]
+pub fn TypingSummary() -> Element {
+    let typing = use_any_typing();
+    let label = match typing.len() {
+        0 => return rsx! {},
+        1 => "Assistant is responding in 1 chat".to_string(),
+        n => format!("Assistant is responding in {} chats", n),
+    };
+
+    rsx! {
+        span {
+            class: "text-text-muted text-xs truncate",
+            "{label}"
+        }
+    }
+}
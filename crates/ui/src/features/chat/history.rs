@@ -0,0 +1,80 @@
+//! Sent-message history recall for the message input (Up/Down like a terminal)
+
+/// Maximum number of sent messages remembered per conversation
+pub const INPUT_HISTORY_LIMIT: usize = 50;
+
+/// Position while stepping through the send history
+///
+/// The history slice is oldest first. Stepping past the newest entry
+/// restores whatever was in the input when browsing started.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryCursor {
+    index: Option<usize>,
+    draft: String,
+}
+
+impl HistoryCursor {
+    /// Check if an entry from the history is currently shown
+    pub fn is_browsing(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Step to the next older entry, returning the text to show
+    pub fn older(&mut self, history: &[String], current: &str) -> Option<String> {
+        let index = match self.index {
+            None if history.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                history.len() - 1
+            }
+            Some(0) => return None,
+            Some(i) => i - 1,
+        };
+        self.index = Some(index);
+        history.get(index).cloned()
+    }
+
+    /// Step to the next newer entry, or back to the saved draft
+    pub fn newer(&mut self, history: &[String]) -> Option<String> {
+        let index = self.index?;
+        if index + 1 < history.len() {
+            self.index = Some(index + 1);
+            history.get(index + 1).cloned()
+        } else {
+            self.index = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    /// Stop browsing (e.g. after the user edits the text)
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.draft.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_cycles_and_restores_draft() {
+        let history = vec!["first".to_string(), "second".to_string()];
+        let mut cursor = HistoryCursor::default();
+
+        assert_eq!(cursor.older(&history, "draft").as_deref(), Some("second"));
+        assert_eq!(cursor.older(&history, "second").as_deref(), Some("first"));
+        assert_eq!(cursor.older(&history, "first"), None);
+        assert_eq!(cursor.newer(&history).as_deref(), Some("second"));
+        assert_eq!(cursor.newer(&history).as_deref(), Some("draft"));
+        assert!(!cursor.is_browsing());
+        assert_eq!(cursor.newer(&history), None);
+    }
+
+    #[test]
+    fn test_cursor_with_empty_history() {
+        let mut cursor = HistoryCursor::default();
+        assert_eq!(cursor.older(&[], ""), None);
+        assert!(!cursor.is_browsing());
+    }
+}
@@ -3,7 +3,7 @@
 use dioxus::prelude::*;
 use prsnl_core::Message;
 use crate::features::media::SelectedMedia;
-use super::{ChatState, ChatService};
+use super::{find_matches, ChatState, ChatService, LocalSearch};
 
 /// Hook to get messages for the current conversation (reactive)
 ///
@@ -30,12 +30,22 @@ pub fn use_typing_indicator() -> Memo<bool> {
     use_memo(move || state.is_typing())
 }
 
-/// Hook to get a send message function
-pub fn use_send_message() -> impl Fn(String, Option<SelectedMedia>) + Clone {
+/// Hook to get the conversations where the assistant is typing (reactive)
+///
+/// Covers every conversation, not just the open one, for global indicators
+/// like "Assistant is responding in 2 chats".
+pub fn use_any_typing() -> Vec<String> {
+    let state = use_context::<ChatState>();
+    let typing = use_memo(move || state.typing_conversations());
+    typing()
+}
+
+/// Hook to get a send message function (text, media, id of the message replied to)
+pub fn use_send_message() -> impl Fn(String, Option<SelectedMedia>, Option<String>) -> bool + Clone {
     let service = use_context::<ChatService>();
 
-    move |text: String, media: Option<SelectedMedia>| {
-        service.send_message(text, media);
+    move |text: String, media: Option<SelectedMedia>, reply_to: Option<String>| {
+        service.send_message(text, media, reply_to)
     }
 }
 
@@ -46,3 +56,14 @@ pub fn use_current_conversation_id() -> Memo<Option<String>> {
     let state = use_context::<ChatState>();
     use_memo(move || state.current_conv_id())
 }
+
+/// Hook for finding text in the current conversation (reactive)
+///
+/// Matches the loaded messages only, without asking the server.
+pub fn use_local_search() -> LocalSearch {
+    let state = use_context::<ChatState>();
+    let query = use_signal(String::new);
+    let current = use_signal(|| usize::MAX);
+    let matches = use_memo(move || find_matches(&state.current_messages(), &query()));
+    LocalSearch { query, current, matches }
+}
@@ -0,0 +1,120 @@
+//! Finding bare http(s) URLs in message text
+
+/// A run of message text, either plain or a URL to link
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextSpan<'a> {
+    Text(&'a str),
+    Link(&'a str),
+}
+
+/// Punctuation that ends a sentence rather than the URL before it
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"'];
+
+/// Split text into alternating plain and URL spans
+///
+/// A URL starts with `http://` or `https://` at a word boundary and runs to
+/// the next whitespace. Trailing sentence punctuation and unbalanced closing
+/// brackets are left out of it.
+pub fn linkify(text: &str) -> Vec<TextSpan<'_>> {
+    let lower = text.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut pos = 0;
+
+    while let Some(found) = lower[pos..].find("http") {
+        let start = pos + found;
+        let at_boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        let scheme_len = ["https://", "http://"]
+            .iter()
+            .find(|scheme| lower[start..].starts_with(*scheme))
+            .map(|scheme| scheme.len());
+
+        let end = match scheme_len {
+            Some(scheme_len) if at_boundary => url_end(text, start, scheme_len),
+            _ => None,
+        };
+        let Some(end) = end else {
+            pos = start + "http".len();
+            continue;
+        };
+
+        if plain_start < start {
+            spans.push(TextSpan::Text(&text[plain_start..start]));
+        }
+        spans.push(TextSpan::Link(&text[start..end]));
+        plain_start = end;
+        pos = end;
+    }
+
+    if plain_start < text.len() {
+        spans.push(TextSpan::Text(&text[plain_start..]));
+    }
+    spans
+}
+
+/// End of the URL starting at `start`, or None when nothing follows the scheme
+fn url_end(text: &str, start: usize, scheme_len: usize) -> Option<usize> {
+    let rest = &text[start..];
+    let mut url = &rest[..rest
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>'))
+        .unwrap_or(rest.len())];
+
+    loop {
+        let trimmed = url.trim_end_matches(TRAILING_PUNCTUATION);
+        let trimmed = match trimmed.chars().next_back() {
+            Some(close @ (')' | ']' | '}')) if !is_balanced(trimmed, close) => &trimmed[..trimmed.len() - 1],
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            break;
+        }
+        url = trimmed;
+    }
+
+    (url.len() > scheme_len).then_some(start + url.len())
+}
+
+/// Check if the closing bracket has a matching opener (as in wiki URLs)
+fn is_balanced(url: &str, close: char) -> bool {
+    let open = match close {
+        ')' => '(',
+        ']' => '[',
+        _ => '{',
+    };
+    url.matches(open).count() >= url.matches(close).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TextSpan::{Link, Text};
+
+    #[test]
+    fn test_links_urls_without_trailing_punctuation() {
+        assert_eq!(
+            linkify("See https://example.com/a?b=1. Or (http://x.org/y), thanks!"),
+            [
+                Text("See "),
+                Link("https://example.com/a?b=1"),
+                Text(". Or ("),
+                Link("http://x.org/y"),
+                Text("), thanks!"),
+            ]
+        );
+        assert_eq!(
+            linkify("https://en.wikipedia.org/wiki/Rust_(programming_language)"),
+            [Link("https://en.wikipedia.org/wiki/Rust_(programming_language)")]
+        );
+    }
+
+    #[test]
+    fn test_ignores_non_urls() {
+        assert_eq!(linkify("no links here"), [Text("no links here")]);
+        assert_eq!(linkify("https:// alone"), [Text("https:// alone")]);
+        assert_eq!(linkify("xhttp://a.b"), [Text("xhttp://a.b")]);
+        assert_eq!(linkify(""), []);
+    }
+}
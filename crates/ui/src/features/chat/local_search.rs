@@ -0,0 +1,159 @@
+//! Finding text within the open conversation's loaded messages
+
+use std::ops::Range;
+use dioxus::prelude::*;
+use prsnl_core::Message;
+
+/// Find-in-conversation state (see `use_local_search`)
+#[derive(Clone, Copy, PartialEq)]
+pub struct LocalSearch {
+    /// Text being searched for; blank matches nothing
+    pub query: Signal<String>,
+    /// Index into `matches` of the match in view; past the end means the newest
+    pub current: Signal<usize>,
+    /// Ids of the loaded messages containing the query, oldest first
+    pub matches: Memo<Vec<String>>,
+}
+
+impl LocalSearch {
+    /// Id of the message holding the match in view
+    pub fn current_id(&self) -> Option<String> {
+        let (position, _) = self.position()?;
+        self.matches.read().get(position - 1).cloned()
+    }
+
+    /// 1-based position of the match in view and the number of matches
+    pub fn position(&self) -> Option<(usize, usize)> {
+        let len = self.matches.read().len();
+        (len > 0).then(|| ((self.current)().min(len - 1) + 1, len))
+    }
+
+    /// Move to the next match (`forward`) or the previous one, wrapping around
+    pub fn step(&mut self, forward: bool) {
+        let len = self.matches.read().len();
+        let next = step_match((self.current)(), len, forward);
+        self.current.set(next);
+    }
+
+    /// Search for new text, starting from the newest match
+    pub fn set_query(&mut self, query: String) {
+        self.query.set(query);
+        self.current.set(usize::MAX);
+    }
+}
+
+/// Ids of the messages whose body contains `query`, ignoring case
+pub fn find_matches(messages: &[Message], query: &str) -> Vec<String> {
+    if query.trim().is_empty() {
+        return Vec::new();
+    }
+    messages
+        .iter()
+        .filter(|m| contains_match(&m.body, query))
+        .map(|m| m.id.clone())
+        .collect()
+}
+
+/// Whether `text` contains `query`, ignoring case
+pub fn contains_match(text: &str, query: &str) -> bool {
+    !query.trim().is_empty() && text.char_indices().any(|(start, _)| match_len_at(text, query, start).is_some())
+}
+
+/// Byte ranges of the non-overlapping, case-insensitive matches of `query`
+pub fn match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    if query.trim().is_empty() {
+        return ranges;
+    }
+    let mut next_free = 0;
+    for (start, _) in text.char_indices() {
+        if start < next_free {
+            continue;
+        }
+        if let Some(len) = match_len_at(text, query, start) {
+            ranges.push(start..start + len);
+            next_free = start + len;
+        }
+    }
+    ranges
+}
+
+/// Split text into runs, flagging the ones that match `query`
+pub fn split_matches<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
+    let mut runs = Vec::new();
+    let mut plain_start = 0;
+    for range in match_ranges(text, query) {
+        if plain_start < range.start {
+            runs.push((&text[plain_start..range.start], false));
+        }
+        runs.push((&text[range.clone()], true));
+        plain_start = range.end;
+    }
+    if plain_start < text.len() {
+        runs.push((&text[plain_start..], false));
+    }
+    runs
+}
+
+/// Index after moving one match forward or back in `len` matches, wrapping around
+pub fn step_match(current: usize, len: usize, forward: bool) -> usize {
+    match len {
+        0 => 0,
+        _ if forward => (current.min(len - 1) + 1) % len,
+        _ => (current.min(len - 1) + len - 1) % len,
+    }
+}
+
+/// Length in bytes of `text` from `start` that matches `query` ignoring case
+fn match_len_at(text: &str, query: &str, start: usize) -> Option<usize> {
+    let mut wanted = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut len = 0;
+    for c in text[start..].chars() {
+        if wanted.peek().is_none() {
+            break;
+        }
+        for lower in c.to_lowercase() {
+            if wanted.next() != Some(lower) {
+                return None;
+            }
+        }
+        len += c.len_utf8();
+    }
+    (wanted.peek().is_none() && len > 0).then_some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_ignores_case_and_finds_every_occurrence() {
+        assert_eq!(match_ranges("Paris, then PARIS again", "paris"), [0..5, 12..17]);
+        assert_eq!(match_ranges("aaaa", "aa"), [0..2, 2..4]);
+        assert_eq!(match_ranges("Ünïcode üNÏ", "ünï"), [0..5, 10..15]);
+        assert!(match_ranges("anything", "  ").is_empty());
+        assert_eq!(
+            split_matches("Go to Paris.", "paris"),
+            [("Go to ", false), ("Paris", true), (".", false)]
+        );
+
+        let message = |id: &str, body: &str| {
+            let mut message = Message::new_user(body.to_string());
+            message.id = id.to_string();
+            message
+        };
+        let messages = [message("a", "Flights to Paris"), message("b", "Hotels"), message("c", "paris in May")];
+        assert_eq!(find_matches(&messages, "PARIS"), ["a", "c"]);
+        assert!(find_matches(&messages, "").is_empty());
+    }
+
+    #[test]
+    fn test_step_match_wraps_around() {
+        assert_eq!(step_match(2, 3, true), 0);
+        assert_eq!(step_match(0, 3, false), 2);
+        assert_eq!(step_match(1, 3, true), 2);
+        // A position past a shrunken list counts from the last match
+        assert_eq!(step_match(7, 3, false), 1);
+        assert_eq!(step_match(0, 0, true), 0);
+    }
+}
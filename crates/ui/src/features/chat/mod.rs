@@ -4,20 +4,38 @@
 
 mod state;
 mod service;
+mod history;
+mod collapse;
+mod linkify;
+mod typing;
+mod rate_limit;
+mod local_search;
 pub mod hooks;
 pub mod components;
 
-pub use state::ChatState;
+pub use state::{
+    ChatState, HistoryPaging, DEFAULT_MESSAGE_LIMIT, HISTORY_PAGE_SIZE, LOAD_OLDER_THRESHOLD_PX, TYPING_TIMEOUT_SECS,
+};
 pub use service::ChatService;
+pub use history::{HistoryCursor, INPUT_HISTORY_LIMIT};
+pub use collapse::CollapseThreshold;
+pub use linkify::{linkify, TextSpan};
+pub use typing::{TypingReporter, TYPING_IDLE_SECS};
+pub use rate_limit::{SendRateLimiter, DEFAULT_SENDS_PER_SECOND};
+pub use local_search::{contains_match, find_matches, match_ranges, split_matches, step_match, LocalSearch};
 
 use prsnl_core::{SharedEventBus, SharedTransport};
 
 /// Initialize the chat feature
+///
+/// `message_limit` caps the messages kept in memory per conversation
+/// (`DEFAULT_MESSAGE_LIMIT` unless the app needs otherwise).
 pub fn provide_chat_feature(
     event_bus: SharedEventBus,
     transport: SharedTransport,
+    message_limit: usize,
 ) -> (ChatState, ChatService) {
-    let state = ChatState::new();
+    let state = ChatState::with_message_limit(message_limit);
     let service = ChatService::new(state.clone(), event_bus, transport);
     (state, service)
 }
@@ -0,0 +1,100 @@
+//! Keeping a stuck Enter key or a runaway loop from flooding the server
+
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use prsnl_core::{Clock, SystemClock};
+
+/// Messages per second sent before further sends are dropped
+pub const DEFAULT_SENDS_PER_SECOND: f64 = 3.0;
+
+/// Token bucket for outgoing chat messages
+///
+/// The bucket holds one second's worth of sends, so a short burst goes
+/// through and a steady stream is held to the rate. A rate of 0 turns
+/// limiting off.
+pub struct SendRateLimiter {
+    per_second: f64,
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SendRateLimiter {
+    /// Create a limiter on the system clock
+    pub fn new(per_second: f64) -> Self {
+        Self::with_clock(per_second, Arc::new(SystemClock))
+    }
+
+    /// Create a limiter reading time from `clock` (tests pass a `FixedClock`)
+    pub fn with_clock(per_second: f64, clock: Arc<dyn Clock>) -> Self {
+        let per_second = per_second.max(0.0);
+        Self {
+            per_second,
+            tokens: per_second.max(1.0),
+            refilled_at: clock.now(),
+            clock,
+        }
+    }
+
+    /// Messages per second allowed
+    pub fn rate(&self) -> f64 {
+        self.per_second
+    }
+
+    /// Change the rate, starting again from a full bucket
+    pub fn set_rate(&mut self, per_second: f64) {
+        *self = Self::with_clock(per_second, self.clock.clone());
+    }
+
+    /// Take a token for one send; false means the send is over the rate
+    pub fn try_acquire(&mut self) -> bool {
+        if self.per_second == 0.0 {
+            return true;
+        }
+        let now = self.clock.now();
+        let elapsed = (now - self.refilled_at).num_milliseconds().max(0) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.per_second.max(1.0));
+        self.refilled_at = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+impl Default for SendRateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_SENDS_PER_SECOND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use prsnl_core::FixedClock;
+
+    #[test]
+    fn test_burst_then_refill_at_rate() {
+        let clock = Arc::new(FixedClock::new(Utc::now()));
+        let mut limiter = SendRateLimiter::with_clock(2.0, clock.clone());
+
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        clock.advance(Duration::milliseconds(500));
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        // Idle time refills no more than one second's worth
+        clock.advance(Duration::seconds(10));
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        limiter.set_rate(0.0);
+        assert!((0..100).all(|_| limiter.try_acquire()));
+    }
+}
@@ -1,15 +1,21 @@
 //! Chat feature service
 
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use dioxus::dioxus_core::spawn_forever;
 use dioxus::prelude::spawn;
 use futures::StreamExt;
-use tracing::info;
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use prsnl_core::{
-    AppEvent, SharedEventBus, SharedTransport, ImagePayload,
-    Message, ImageData,
+    AppEvent, SharedEventBus, SharedTransport, ImagePayload, Attachment, AttachmentKind,
+    AttachmentPayload, Conversation, Message, MessageSender, ImageData, TransportError,
 };
 use crate::features::media::SelectedMedia;
-use super::state::ChatState;
+use crate::shared::timer;
+use super::rate_limit::SendRateLimiter;
+use super::state::{ChatState, HISTORY_PAGE_SIZE, TYPING_TIMEOUT_SECS};
 
 /// Service for managing chat functionality
 #[derive(Clone)]
@@ -17,6 +23,9 @@ pub struct ChatService {
     state: ChatState,
     event_bus: SharedEventBus,
     transport: SharedTransport,
+    /// Shared by all clones so new sends and retries draw from one bucket;
+    /// messages held for a pending conversation took their token when sent
+    limiter: Arc<Mutex<SendRateLimiter>>,
 }
 
 impl ChatService {
@@ -26,46 +35,115 @@ impl ChatService {
         event_bus: SharedEventBus,
         transport: SharedTransport,
     ) -> Self {
-        Self { state, event_bus, transport }
+        Self {
+            state,
+            event_bus,
+            transport,
+            limiter: Arc::new(Mutex::new(SendRateLimiter::default())),
+        }
+    }
+
+    /// Use `limiter` for outgoing messages (tests pass one on a fixed clock)
+    pub fn with_rate_limiter(self, limiter: SendRateLimiter) -> Self {
+        Self { limiter: Arc::new(Mutex::new(limiter)), ..self }
+    }
+
+    /// Change how many messages per second may be sent (0 for no limit)
+    pub fn set_send_rate(&self, per_second: f64) {
+        info!("Setting send rate limit: {}/s", per_second);
+        self.limiter.lock().unwrap().set_rate(per_second);
     }
 
     /// Subscribe to relevant events from the event bus
     pub fn subscribe_to_events(&self) {
         let mut state = self.state;
+        let service = self.clone();
         let mut rx = self.event_bus.subscribe();
 
         spawn(async move {
             while let Some(event) = rx.next().await {
                 match event {
                     AppEvent::ConversationSelected(id) => {
-                        state.set_current_conversation(Some(id));
+                        service.set_current_conversation(Some(id));
                     }
-                    AppEvent::MessageReceived { conv_id, message } => {
-                        // Find the reply_to from the message context
-                        // For now, we'll use the last pending message
-                        let reply_to = state.current_messages()
-                            .iter()
-                            .rev()
-                            .find(|m| state.is_pending(&m.id))
-                            .map(|m| m.id.clone())
-                            .unwrap_or_default();
-
-                        state.add_received_message(&conv_id, &reply_to, message);
+                    AppEvent::ConversationOpened { id, unread } => {
+                        state.set_unread_on_open(&id, unread);
+                    }
+                    AppEvent::MessageReceived { conv_id, message, reply_to } => {
+                        state.add_reply(&conv_id, reply_to.as_deref(), message);
                     }
                     AppEvent::MessageError { conv_id, msg_id, error } => {
-                        state.mark_message_error(&conv_id, &msg_id, error);
+                        state.mark_message_error(&conv_id, &state.local_id(&msg_id), error);
+                    }
+                    AppEvent::QueuedMessageSent { conv_id, wire_id } => {
+                        state.mark_queued_sent(&conv_id, &wire_id);
+                    }
+                    AppEvent::MessageAcked { conv_id, wire_id, received_at } => {
+                        state.mark_acked(&conv_id, &wire_id, received_at);
+                    }
+                    AppEvent::MessageEdited { conv_id, msg_id, new_body } => {
+                        state.edit_message(&conv_id, &state.local_id(&msg_id), new_body);
+                    }
+                    AppEvent::MessageDeleted { conv_id, msg_id } => {
+                        state.remove_message(&conv_id, &state.local_id(&msg_id));
+                    }
+                    AppEvent::MessageReaction { conv_id, msg_id, emoji, count } => {
+                        state.apply_reaction(&conv_id, &state.local_id(&msg_id), &emoji, count);
+                    }
+                    AppEvent::ReadReceipt { conv_id, up_to_msg_id } => {
+                        state.apply_read_receipt(&conv_id, &state.local_id(&up_to_msg_id));
                     }
                     AppEvent::TypingChanged { conv_id, is_typing } => {
-                        state.set_typing(&conv_id, is_typing);
+                        state.set_typing(&conv_id, is_typing, Utc::now());
+                        if is_typing {
+                            // Clear the indicator if no stop or reply arrives in time
+                            let mut state = state;
+                            spawn(async move {
+                                timer::sleep(std::time::Duration::from_secs(TYPING_TIMEOUT_SECS as u64)).await;
+                                if state.expire_typing(Utc::now()) {
+                                    info!("Typing indicator timed out");
+                                }
+                            });
+                        }
+                    }
+                    AppEvent::HistoryLoaded { conv_id, messages, has_more } => {
+                        // Responses don't echo the cursor; an older page is the one in flight
+                        if state.is_loading_older(&conv_id) {
+                            state.prepend_history(&conv_id, messages, has_more);
+                        } else {
+                            state.apply_history_batch(&conv_id, messages, true, true, has_more);
+                        }
                     }
-                    AppEvent::HistoryLoaded { conv_id, messages } => {
-                        state.set_history(&conv_id, messages);
+                    AppEvent::HistoryBatch { conv_id, messages, first, last, has_more } => {
+                        state.apply_history_batch(&conv_id, messages, first, last, has_more);
                     }
                     AppEvent::ConversationDeleted(id) => {
                         state.clear_conversation(&id);
+                        service.transport.set_active_conversation(state.current_conv_id());
+                    }
+                    AppEvent::ConversationConfirmed { local_id, id } => {
+                        // Send what was written while the server was creating it
+                        let held = state.move_conversation(&local_id, &id);
+                        if state.current_conv_id().as_deref() == Some(id.as_str()) {
+                            service.set_current_conversation(Some(id.clone()));
+                        }
+                        for msg in held {
+                            service.transmit(id.clone(), msg);
+                        }
+                    }
+                    AppEvent::ConversationCreateFailed { local_id, .. } => {
+                        state.clear_conversation(&local_id);
+                        service.transport.set_active_conversation(state.current_conv_id());
                     }
                     AppEvent::NavigateToList => {
-                        state.set_current_conversation(None);
+                        service.set_current_conversation(None);
+                    }
+                    AppEvent::NavigateToMessage { conv_id, msg_id } => {
+                        service.set_current_conversation(Some(conv_id));
+                        state.set_focused_message(Some(msg_id));
+                    }
+                    AppEvent::SearchResultOpened(result) => {
+                        state.focus_matching(&result.conv_id, &result.message);
                     }
                     _ => {}
                 }
@@ -73,32 +151,199 @@ impl ChatService {
         });
     }
 
-    /// Send a message in the current conversation
-    pub fn send_message(&self, text: String, media: Option<SelectedMedia>) {
-        // Validate input
-        if text.trim().is_empty() && media.is_none() {
+    /// Change the current conversation and tell the transport which one is open
+    fn set_current_conversation(&self, conv_id: Option<String>) {
+        let mut state = self.state;
+        self.transport.set_active_conversation(conv_id.clone());
+        state.set_current_conversation(conv_id);
+    }
+
+    /// Ask for a new reply by re-sending the prompt behind an assistant message
+    pub fn regenerate(&self, msg_id: &str) {
+        let Some(conv_id) = self.state.current_conv_id() else {
+            return;
+        };
+        match self.state.prompt_for(&conv_id, msg_id) {
+            Some(prompt) => {
+                info!("Regenerating reply {} from prompt {}", msg_id, prompt.id);
+                self.send_message(prompt.body, None, None);
+            }
+            None => info!("No prompt found for {}, nothing to regenerate", msg_id),
+        }
+    }
+
+    /// Tell the server whether the user is typing in a conversation
+    ///
+    /// Not tied to the calling component, so a stop still goes out while the
+    /// chat view is being left.
+    pub fn send_typing(&self, conv_id: &str, is_typing: bool) {
+        if Conversation::is_pending_id(conv_id) {
+            return;
+        }
+        let transport = self.transport.clone();
+        let conv_id = conv_id.to_string();
+        spawn_forever(async move {
+            if let Err(e) = transport.send_typing(conv_id, is_typing).await {
+                info!("Failed to send typing state: {:?}", e);
+            }
+        });
+    }
+
+    /// Delete a message from the current conversation
+    ///
+    /// Removed locally right away; the server confirms with `MessageDeleted`.
+    /// Only messages the server has an id for can be deleted.
+    pub fn delete_message(&self, msg_id: &str) {
+        let Some(conv_id) = self.state.current_conv_id() else {
+            return;
+        };
+        let Some(server_id) = self.state.server_id(msg_id) else {
+            info!("Cannot delete {}: the server has no id for it", msg_id);
+            return;
+        };
+        let mut state = self.state;
+        if !state.remove_message(&conv_id, msg_id) {
+            info!("Cannot delete {}: message not loaded", msg_id);
             return;
         }
 
+        info!("Deleting message {}", msg_id);
+        self.event_bus.publish(AppEvent::MessageDeleted {
+            conv_id: conv_id.clone(),
+            msg_id: msg_id.to_string(),
+        });
+
+        let transport = self.transport.clone();
+        spawn(async move {
+            if let Err(e) = transport.send_delete_message(conv_id, server_id).await {
+                info!("Failed to send delete: {:?}", e);
+            }
+        });
+    }
+
+    /// Change the body of a message in the current conversation
+    ///
+    /// Applied locally right away; the server confirms with `MessageEdited`.
+    /// Only messages the server has an id for can be edited.
+    pub fn edit_message(&self, msg_id: &str, new_body: String) {
+        if new_body.trim().is_empty() {
+            return;
+        }
+        let Some(conv_id) = self.state.current_conv_id() else {
+            return;
+        };
+        let Some(server_id) = self.state.server_id(msg_id) else {
+            info!("Cannot edit {}: the server has no id for it", msg_id);
+            return;
+        };
+        let mut state = self.state;
+        if !state.edit_message(&conv_id, msg_id, new_body.clone()) {
+            info!("Cannot edit {}: message not loaded", msg_id);
+            return;
+        }
+
+        self.event_bus.publish(AppEvent::MessageEdited {
+            conv_id: conv_id.clone(),
+            msg_id: msg_id.to_string(),
+            new_body: new_body.clone(),
+        });
+
+        let transport = self.transport.clone();
+        spawn(async move {
+            if let Err(e) = transport.send_edit_message(conv_id, server_id, new_body).await {
+                info!("Failed to send edit: {:?}", e);
+            }
+        });
+    }
+
+    /// React to a message in the current conversation, or take the reaction back
+    ///
+    /// Counted locally right away; the server confirms with `MessageReaction`.
+    /// Only messages the server has an id for can be reacted to.
+    pub fn toggle_reaction(&self, msg_id: &str, emoji: &str) {
+        let Some(conv_id) = self.state.current_conv_id() else {
+            return;
+        };
+        let Some(server_id) = self.state.server_id(msg_id) else {
+            info!("Cannot react to {}: the server has no id for it", msg_id);
+            return;
+        };
+        let mut state = self.state;
+        let Some((added, count)) = state.toggle_reaction(&conv_id, msg_id, emoji) else {
+            info!("Cannot react to {}: message not loaded", msg_id);
+            return;
+        };
+
+        self.event_bus.publish(AppEvent::MessageReaction {
+            conv_id: conv_id.clone(),
+            msg_id: msg_id.to_string(),
+            emoji: emoji.to_string(),
+            count,
+        });
+
+        let transport = self.transport.clone();
+        let emoji = emoji.to_string();
+        spawn(async move {
+            if let Err(e) = transport.send_reaction(conv_id, server_id, emoji, !added).await {
+                info!("Failed to send reaction: {:?}", e);
+            }
+        });
+    }
+
+    /// Send a message in the current conversation
+    ///
+    /// `reply_to` is the local id of an earlier message this one answers; the
+    /// server is sent its server id, or no link if it has none. Images
+    /// are sent inline and other files as attachments. Returns false when
+    /// nothing was sent: empty input, no conversation, or over the send rate.
+    pub fn send_message(&self, text: String, media: Option<SelectedMedia>, reply_to: Option<String>) -> bool {
         let conv_id = match self.state.current_conv_id() {
             Some(id) => id,
             None => {
                 info!("Cannot send message: no conversation selected");
-                return;
+                return false;
             }
         };
+        self.send_to(conv_id, text, media, reply_to)
+    }
+
+    /// Send a message in `conv_id`, which need not be the open conversation
+    fn send_to(&self, conv_id: String, text: String, media: Option<SelectedMedia>, reply_to: Option<String>) -> bool {
+        // Validate input
+        if text.trim().is_empty() && media.is_none() {
+            return false;
+        }
+
+        // Throttled sends are dropped before the bubble is shown
+        let mut limiter = self.limiter.lock().unwrap();
+        if !limiter.try_acquire() {
+            warn!("Throttled: dropping message to {} (over {}/s)", conv_id, limiter.rate());
+            return false;
+        }
+        drop(limiter);
 
         // Create message
-        let msg = match media {
-            Some(ref m) => Message::new_user_with_image(
-                text.clone(),
+        let (image, file) = match media {
+            Some(m) if m.kind() == AttachmentKind::Image => (Some(m), None),
+            Some(m) => (None, Some(m)),
+            None => (None, None),
+        };
+        let files: Vec<Attachment> = file
+            .into_iter()
+            .map(|m| Attachment { filename: m.filename, mimetype: m.mimetype, data: m.data })
+            .collect();
+        let mut msg = match image {
+            Some(m) => Message::new_user_with_image(
+                text,
                 ImageData {
-                    data: m.data.clone(),
-                    mimetype: m.mimetype.clone(),
+                    data: m.data,
+                    mimetype: m.mimetype,
                 },
             ),
-            None => Message::new_user(text.clone()),
-        };
+            None => Message::new_user(text),
+        }
+        .with_files(files);
+        msg.reply_to = reply_to;
 
         // Optimistic update - add message to state immediately
         let mut state = self.state;
@@ -110,18 +355,150 @@ impl ChatService {
             message: msg.clone(),
         });
 
-        // Send to server
+        // Held until the server has created the conversation
+        if Conversation::is_pending_id(&conv_id) {
+            info!("Holding message {} until {} is created", msg.id, conv_id);
+            return true;
+        }
+        self.transmit(conv_id, msg);
+        true
+    }
+
+    /// Send a failed message again under the same id
+    ///
+    /// The bubble goes back to `Sending` and the original text, image and
+    /// files go out again. Only user messages that failed can be retried,
+    /// and a throttled retry leaves the message failed.
+    pub fn retry_message(&self, conv_id: &str, msg_id: &str) {
+        let mut limiter = self.limiter.lock().unwrap();
+        if !limiter.try_acquire() {
+            warn!("Throttled: not retrying {} (over {}/s)", msg_id, limiter.rate());
+            return;
+        }
+        drop(limiter);
+
+        let mut state = self.state;
+        let Some(msg) = state.reset_for_retry(conv_id, msg_id) else {
+            info!("Cannot retry {}: not a failed message", msg_id);
+            return;
+        };
+        info!("Retrying message {}", msg_id);
+        self.transmit(conv_id.to_string(), msg);
+    }
+
+    /// Send a message shown as `Sending` to the server, then record how it went
+    fn transmit(&self, conv_id: String, msg: Message) {
+        let mut state = self.state;
         let transport = self.transport.clone();
-        let image_payload = media.map(|m| ImagePayload {
-            data: m.data,
-            mimetype: m.mimetype,
+        let msg_id = msg.id;
+        let text = msg.body;
+        // The server knows the original by its server id; without one the reply goes out unlinked
+        let reply_to = msg.reply_to.and_then(|id| state.server_id(&id));
+        let image_payload = msg.image.map(|image| ImagePayload {
+            data: image.data,
+            mimetype: image.mimetype,
         });
-        let conv_id_owned = conv_id;
-        let text_owned = text;
+        let file_payloads: Vec<AttachmentPayload> = msg.files.into_iter().map(Into::into).collect();
+        // While offline the transport queues the message; it stays "Sending" until flushed
+        let mut queued = !transport.is_connected();
+        // Known before the frame goes out, so an ack or reply can't arrive ahead of it
+        let wire_id = Uuid::new_v4().to_string();
+        state.track_wire_id(&wire_id, &msg_id);
 
         spawn(async move {
-            if let Err(e) = transport.send_chat(conv_id_owned, text_owned, image_payload).await {
-                info!("Failed to send message: {:?}", e);
+            let mut result = transport
+                .send_chat(
+                    wire_id.clone(),
+                    conv_id.clone(),
+                    text.clone(),
+                    image_payload.clone(),
+                    file_payloads.clone(),
+                    reply_to.clone(),
+                )
+                .await;
+            // The connection dropped between the check and the send; sending
+            // again now queues the message for the reconnect
+            if matches!(result, Err(TransportError::NotConnected | TransportError::Closed))
+                && !transport.is_connected()
+            {
+                info!("Connection lost while sending {}, queueing it", msg_id);
+                queued = true;
+                result = transport
+                    .send_chat(wire_id.clone(), conv_id.clone(), text, image_payload, file_payloads, reply_to)
+                    .await;
+            }
+            match result {
+                Ok(()) if queued => {
+                    info!("Message {} queued as {}", msg_id, wire_id);
+                }
+                Ok(()) => {
+                    info!("Message {} sent as {}", msg_id, wire_id);
+                    state.mark_sent(&conv_id, &msg_id);
+                }
+                Err(e) => {
+                    info!("Failed to send message: {:?}", e);
+                    state.mark_message_error(&conv_id, &msg_id, e.to_string());
+                }
+            }
+        });
+    }
+
+    /// Re-send the last user message of `conv_id` into the new conversation `local_id`
+    ///
+    /// The copy is held like anything written while the server creates a
+    /// conversation: it goes out when `ConversationConfirmed` names
+    /// `local_id`, and is dropped with it if the creation fails. Returns
+    /// false if `conv_id` has nothing to re-send.
+    pub fn start_fresh(&self, conv_id: &str, local_id: &str) -> bool {
+        let Some(prompt) = self.state.last_user_message(conv_id) else {
+            info!("Cannot start fresh: no user message to re-send");
+            return false;
+        };
+        info!("Re-sending last prompt of {} into new conversation {}", conv_id, local_id);
+        let file = prompt.files.into_iter().next().map(|file| SelectedMedia {
+            data: file.data,
+            mimetype: file.mimetype,
+            filename: file.filename,
+        });
+        let media = prompt
+            .image
+            .map(|image| SelectedMedia {
+                data: image.data,
+                mimetype: image.mimetype,
+                filename: String::new(),
+            })
+            .or(file);
+        self.send_to(local_id.to_string(), prompt.body, media, None)
+    }
+
+    /// Tell the server the user has seen a conversation up to its newest reply
+    ///
+    /// Also moves the local last-read marker. Each reply is reported once,
+    /// so this can run on every scroll.
+    pub fn mark_read(&self, conv_id: &str) {
+        let mut state = self.state;
+        state.mark_seen(conv_id);
+        // History carries no ids, so only replies that arrived live can be
+        // named to the server
+        let Some(server_id) = state
+            .messages_for(conv_id)
+            .into_iter()
+            .rev()
+            .filter(|m| m.sender != MessageSender::User)
+            .find_map(|m| state.server_id(&m.id))
+        else {
+            return;
+        };
+        if !state.note_read_mark(conv_id, &server_id) {
+            return;
+        }
+
+        let transport = self.transport.clone();
+        let conv_id = conv_id.to_string();
+        spawn(async move {
+            if let Err(e) = transport.send_mark_read(conv_id.clone(), server_id).await {
+                info!("Failed to send read mark: {:?}", e);
+                state.clear_read_mark(&conv_id);
             }
         });
     }
@@ -131,9 +508,622 @@ impl ChatService {
         let transport = self.transport.clone();
         let id = conv_id.to_string();
         spawn(async move {
-            if let Err(e) = transport.send_get_history(id, Some(50)).await {
+            if let Err(e) = transport.send_get_history(id, Some(HISTORY_PAGE_SIZE), None).await {
                 info!("Failed to load history: {:?}", e);
             }
         });
     }
+
+    /// Request the page of messages before the oldest loaded one, returning whether a request was sent
+    ///
+    /// Skipped while a request is in flight or once the start of the
+    /// conversation has been reached.
+    pub fn load_older_history(&self, conv_id: &str) -> bool {
+        if Conversation::is_pending_id(conv_id) {
+            return false;
+        }
+        let mut state = self.state;
+        let Some(before) = state.begin_load_older(conv_id) else {
+            return false;
+        };
+        info!("Loading older history for {} (before {})", conv_id, before);
+        let transport = self.transport.clone();
+        let id = conv_id.to_string();
+        spawn(async move {
+            if let Err(e) = transport.send_get_history(id.clone(), Some(HISTORY_PAGE_SIZE), Some(before)).await {
+                info!("Failed to load older history: {:?}", e);
+                state.cancel_load_older(&id);
+            }
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use prsnl_core::{
+        AppEvent, ConnectionStatus, Conversation, EventBus, FixedClock, Message, MessageStatus, SearchResult,
+        WSClientMessage, WSServerMessage, QUEUE_FULL_ERROR,
+    };
+
+    use crate::features::chat::SendRateLimiter;
+    use crate::features::media::SelectedMedia;
+    use crate::test_support::{chat_fixture, sent_chat_ids};
+
+    fn response(id: &str, reply_to: &str, body: &str) -> WSServerMessage {
+        WSServerMessage::Response {
+            id: id.to_string(),
+            timestamp: 0,
+            reply_to: reply_to.to_string(),
+            conversation_id: Some("native-a".to_string()),
+            body: body.to_string(),
+            image: None,
+            files: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_then_receive_through_mock_transport() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+
+        let sent_id = f.in_scope(|| {
+            service.send_message("hello".to_string(), None, None);
+
+            // Optimistic message is shown and pending before the transport runs
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].status, MessageStatus::Sending);
+            assert!(state.is_pending(&messages[0].id));
+            messages[0].id.clone()
+        });
+        f.settle().await;
+
+        let wire_id = match f.transport.sent_messages().as_slice() {
+            [WSClientMessage::Chat { id, conversation_id, body, .. }] => {
+                assert_eq!(conversation_id, "native-a");
+                assert_eq!(body, "hello");
+                id.clone()
+            }
+            other => panic!("expected one chat frame, got {:?}", other),
+        };
+        f.in_scope(|| assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sent));
+
+        f.transport.inject(response("resp-1", &wire_id, "hi there"));
+        f.settle().await;
+
+        f.in_scope(|| {
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages.len(), 2);
+            assert_eq!(messages[0].id, sent_id);
+            assert_eq!(messages[0].status, MessageStatus::Delivered);
+            assert!(!state.is_pending(&sent_id));
+            assert_eq!(messages[1].id, "resp-1");
+            assert_eq!(messages[1].body, "hi there");
+        });
+    }
+
+    #[tokio::test]
+    async fn test_reply_handled_before_the_send_returns_finds_its_message() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        let (sent_id, wire_id) = f.in_scope(|| {
+            service.send_message("hello".to_string(), None, None);
+            let sent_id = state.messages_for("native-a")[0].id.clone();
+            // The send task hasn't run yet, but the server may already answer
+            let wire_id = state.server_id(&sent_id).expect("wire id known before sending");
+            let reply = Message::new_assistant("resp-1".to_string(), "hi".to_string(), None);
+            assert_eq!(state.add_reply("native-a", Some(&wire_id), reply), Some(sent_id.clone()));
+            (sent_id, wire_id)
+        });
+        f.settle().await;
+
+        assert_eq!(sent_chat_ids(&f.transport), [wire_id]);
+        f.in_scope(|| {
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages[0].id, sent_id);
+            assert_eq!(messages[0].status, MessageStatus::Delivered);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_responses_answer_the_message_they_reply_to() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+
+        f.in_scope(|| {
+            service.send_message("first".to_string(), None, None);
+            service.send_message("second".to_string(), None, None);
+        });
+        f.settle().await;
+
+        let wire_ids = sent_chat_ids(&f.transport);
+        assert_eq!(wire_ids.len(), 2);
+        let (first_id, second_id) = f.in_scope(|| {
+            let messages = state.messages_for("native-a");
+            (messages[0].id.clone(), messages[1].id.clone())
+        });
+
+        // The older message is answered first, while the newer one is still in flight
+        f.transport.inject(response("resp-1", &wire_ids[0], "ok"));
+        f.settle().await;
+        f.in_scope(|| {
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages[0].status, MessageStatus::Delivered);
+            assert_eq!(messages[1].status, MessageStatus::Sent);
+            assert!(!state.is_pending(&first_id));
+            assert!(state.is_pending(&second_id));
+        });
+
+        f.transport.inject(response("resp-2", &wire_ids[1], "ok"));
+        f.settle().await;
+        f.in_scope(|| {
+            assert_eq!(state.messages_for("native-a")[1].status, MessageStatus::Delivered);
+            assert!(!state.is_pending(&second_id));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_retry_resends_failed_message_under_same_id() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        let msg_id = f.in_scope(|| {
+            service.send_message("try again".to_string(), None, None);
+            state.messages_for("native-a")[0].id.clone()
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            state.mark_message_error("native-a", &msg_id, "server error".to_string());
+            assert!(!state.is_pending(&msg_id));
+
+            service.retry_message("native-a", &msg_id);
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].id, msg_id);
+            assert_eq!(messages[0].status, MessageStatus::Sending);
+            assert!(state.is_pending(&msg_id));
+        });
+        f.settle().await;
+
+        let bodies: Vec<String> = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .filter_map(|frame| match frame {
+                WSClientMessage::Chat { body, .. } => Some(body),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(bodies, ["try again", "try again"]);
+        f.in_scope(|| {
+            assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sent);
+
+            // Only failed messages are sent again
+            service.retry_message("native-a", &msg_id);
+            assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sent);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_edit_names_message_by_wire_id() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        let msg_id = f.in_scope(|| {
+            service.send_message("draft".to_string(), None, None);
+            state.messages_for("native-a")[0].id.clone()
+        });
+        f.settle().await;
+
+        let wire_id = sent_chat_ids(&f.transport).remove(0);
+        f.in_scope(|| service.edit_message(&msg_id, "final".to_string()));
+        f.settle().await;
+        assert!(matches!(
+            f.transport.sent_messages().last(),
+            Some(WSClientMessage::EditMessage { message_id, .. }) if *message_id == wire_id
+        ));
+
+        // The server's echo names the message by the same wire id
+        f.transport.inject(WSServerMessage::MessageEdited {
+            id: "edit-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            message_id: wire_id,
+            new_body: "final, really".to_string(),
+        });
+        f.settle().await;
+        f.in_scope(|| assert_eq!(state.messages_for("native-a")[0].body, "final, really"));
+
+        // Messages loaded from history have no server id and are not edited
+        let sent = f.transport.sent_messages().len();
+        f.in_scope(|| {
+            let mut old = Message::new_user("from history".to_string());
+            old.id = "hist-1".to_string();
+            state.prepend_history("native-a", vec![old], None);
+            service.edit_message("hist-1", "changed".to_string());
+            assert_eq!(state.messages_for("native-a")[0].body, "from history");
+        });
+        f.settle().await;
+        assert_eq!(f.transport.sent_messages().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_delete_names_message_by_wire_id() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        f.in_scope(|| {
+            service.send_message("one".to_string(), None, None);
+            service.send_message("two".to_string(), None, None);
+        });
+        f.settle().await;
+
+        let wire_ids = sent_chat_ids(&f.transport);
+        f.in_scope(|| {
+            let first_id = state.messages_for("native-a")[0].id.clone();
+            service.delete_message(&first_id);
+        });
+        f.settle().await;
+        assert!(matches!(
+            f.transport.sent_messages().last(),
+            Some(WSClientMessage::DeleteMessage { message_id, .. }) if *message_id == wire_ids[0]
+        ));
+
+        // A deletion made elsewhere names the message by its wire id too
+        f.transport.inject(WSServerMessage::MessageDeleted {
+            id: "del-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            message_id: wire_ids[1].clone(),
+        });
+        f.settle().await;
+        f.in_scope(|| assert!(state.messages_for("native-a").is_empty()));
+
+        // Messages loaded from history have no server id and stay
+        let sent = f.transport.sent_messages().len();
+        f.in_scope(|| {
+            let mut old = Message::new_user("from history".to_string());
+            old.id = "hist-1".to_string();
+            state.prepend_history("native-a", vec![old], None);
+            service.delete_message("hist-1");
+            assert_eq!(state.messages_for("native-a").len(), 1);
+        });
+        f.settle().await;
+        assert_eq!(f.transport.sent_messages().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_reactions_toggle_and_follow_server_counts() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        f.in_scope(|| {
+            let reply = Message::new_assistant("resp-1".to_string(), "hello".to_string(), None);
+            state.add_received_message("native-a", "", reply);
+            service.toggle_reaction("resp-1", "👍");
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            assert_eq!(state.messages_for("native-a")[0].reactions.get("👍"), Some(&1));
+            assert!(state.own_reactions("resp-1").contains("👍"));
+        });
+
+        // Others reacted too
+        f.transport.inject(WSServerMessage::MessageReaction {
+            id: "r-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            message_id: "resp-1".to_string(),
+            emoji: "👍".to_string(),
+            count: 3,
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            assert_eq!(state.messages_for("native-a")[0].reactions.get("👍"), Some(&3));
+            service.toggle_reaction("resp-1", "👍");
+            assert_eq!(state.messages_for("native-a")[0].reactions.get("👍"), Some(&2));
+            assert!(state.own_reactions("resp-1").is_empty());
+        });
+        f.settle().await;
+
+        let removes: Vec<bool> = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .filter_map(|frame| match frame {
+                WSClientMessage::ReactMessage { message_id, emoji, remove, .. } => {
+                    assert_eq!((message_id.as_str(), emoji.as_str()), ("resp-1", "👍"));
+                    Some(remove)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removes, [false, true]);
+
+        // Replies loaded from history have no server id to react to
+        f.in_scope(|| {
+            let old = Message::new_assistant("hist-1".to_string(), "earlier".to_string(), None);
+            state.prepend_history("native-a", vec![old], None);
+            service.toggle_reaction("hist-1", "👍");
+            assert!(state.messages_for("native-a")[0].reactions.is_empty());
+        });
+        f.settle().await;
+        assert_eq!(f.transport.sent_messages().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_documents_are_sent_as_files() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+
+        f.in_scope(|| {
+            let pdf = SelectedMedia {
+                data: "JVBERi0=".to_string(),
+                mimetype: "application/pdf".to_string(),
+                filename: "notes.pdf".to_string(),
+            };
+            service.send_message("see attached".to_string(), Some(pdf), None);
+        });
+        f.settle().await;
+
+        match f.transport.sent_messages().as_slice() {
+            [WSClientMessage::Chat { image: None, files, .. }] => assert_eq!(files[0].filename, "notes.pdf"),
+            other => panic!("expected one chat frame with a file, got {:?}", other),
+        }
+        f.in_scope(|| {
+            let message = &state.messages_for("native-a")[0];
+            assert!(message.image.is_none());
+            assert_eq!(message.files[0].size(), 5);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_reply_carries_server_id_of_original() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+        service.set_send_rate(0.0);
+        f.in_scope(|| service.send_message("first".to_string(), None, None));
+        f.settle().await;
+        let first_wire_id = sent_chat_ids(&f.transport)[0].clone();
+        f.transport.inject(response("resp-1", &first_wire_id, "which?"));
+        f.settle().await;
+
+        let (first_id, from_history) = f.in_scope(|| {
+            let history = Message::new_assistant("h1".to_string(), "old".to_string(), None);
+            let history_id = history.id.clone();
+            state.prepend_history("native-a", vec![history], None);
+            (state.messages_for("native-a")[1].id.clone(), history_id)
+        });
+        f.in_scope(|| {
+            service.send_message("this one".to_string(), None, Some("resp-1".to_string()));
+            service.send_message("mine".to_string(), None, Some(first_id.clone()));
+            service.send_message("that".to_string(), None, Some(from_history.clone()));
+        });
+        f.settle().await;
+
+        let replies: Vec<_> = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .skip(1)
+            .map(|frame| match frame {
+                WSClientMessage::Chat { reply_to, .. } => reply_to,
+                other => panic!("expected a chat frame, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(replies, [Some("resp-1".to_string()), Some(first_wire_id), None]);
+        // The bubbles still quote the original by its local id
+        f.in_scope(|| assert_eq!(state.messages_for("native-a")[4].reply_to.as_deref(), Some(first_id.as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_offline_send_stays_sending_until_flushed() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+        f.transport.set_status(ConnectionStatus::Disconnected);
+
+        f.in_scope(|| service.send_message("are you there?".to_string(), None, None));
+        f.settle().await;
+
+        assert!(f.transport.sent_messages().is_empty());
+        f.in_scope(|| assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sending));
+
+        f.transport.set_status(ConnectionStatus::Connected);
+        f.settle().await;
+
+        assert_eq!(f.transport.sent_messages().len(), 1);
+        f.in_scope(|| assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sent));
+    }
+
+    #[tokio::test]
+    async fn test_message_dropped_from_full_queue_shows_error() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+        f.transport.set_max_queued_messages(1);
+        f.transport.set_status(ConnectionStatus::Disconnected);
+
+        f.in_scope(|| service.send_message("first".to_string(), None, None));
+        f.settle().await;
+        f.in_scope(|| service.send_message("second".to_string(), None, None));
+        f.settle().await;
+
+        f.in_scope(|| {
+            let statuses: Vec<_> = state.messages_for("native-a").into_iter().map(|m| m.status).collect();
+            assert_eq!(
+                statuses,
+                [MessageStatus::Error(QUEUE_FULL_ERROR.to_string()), MessageStatus::Sending]
+            );
+        });
+    }
+
+    #[tokio::test]
+    async fn test_read_marks_and_receipts() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        f.in_scope(|| service.send_message("hello".to_string(), None, None));
+        f.settle().await;
+
+        f.transport.inject(response("resp-1", "unknown", "hi there"));
+        f.settle().await;
+
+        // The newest reply is reported once, however often the view asks
+        f.in_scope(|| {
+            service.mark_read("native-a");
+            service.mark_read("native-a");
+        });
+        f.settle().await;
+        let marks: Vec<_> = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                WSClientMessage::MarkRead { conversation_id, up_to_message_id, .. } => {
+                    Some((conversation_id, up_to_message_id))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(marks, [("native-a".to_string(), "resp-1".to_string())]);
+
+        // Replies loaded from history have no id to report
+        f.in_scope(|| {
+            let old = Message::new_assistant("hist-1".to_string(), "earlier".to_string(), None);
+            state.set_history("native-b", vec![old]);
+            service.mark_read("native-b");
+        });
+        f.settle().await;
+        let mark_count = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .filter(|msg| matches!(msg, WSClientMessage::MarkRead { .. }))
+            .count();
+        assert_eq!(mark_count, 1);
+
+        f.transport.inject(WSServerMessage::ReadReceipt {
+            id: "rr-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            up_to_message_id: "resp-1".to_string(),
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            let messages = state.messages_for("native-a");
+            assert_eq!(messages[0].status, MessageStatus::Read);
+            assert_eq!(messages[1].status, MessageStatus::Delivered);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_throttled_send_shows_no_bubble() {
+        let mut f = chat_fixture();
+        let clock = Arc::new(FixedClock::new(chrono::Utc::now()));
+        let state = f.state;
+        let service = f.service.clone().with_rate_limiter(SendRateLimiter::with_clock(1.0, clock.clone()));
+
+        f.in_scope(|| {
+            assert!(service.send_message("one".to_string(), None, None));
+            assert!(!service.send_message("two".to_string(), None, None));
+            assert_eq!(state.messages_for("native-a").len(), 1);
+        });
+        f.settle().await;
+        assert_eq!(f.transport.sent_messages().len(), 1);
+
+        clock.advance(chrono::Duration::seconds(1));
+        f.in_scope(|| {
+            assert!(service.send_message("three".to_string(), None, None));
+            let bodies: Vec<_> = state.messages_for("native-a").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies, ["one", "three"]);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_fresh_start_prompt_goes_only_to_its_own_conversation() {
+        let mut f = chat_fixture();
+        let (state, service) = (f.state, f.service.clone());
+        f.in_scope(|| service.send_message("hello".to_string(), None, None));
+        f.settle().await;
+        f.transport.clear_sent();
+
+        // The creation fails; a conversation made on another device gets nothing
+        let failed_id = Conversation::new_pending(None).id;
+        f.in_scope(|| assert!(service.start_fresh("native-a", &failed_id)));
+        f.bus.publish(AppEvent::ConversationCreateFailed { local_id: failed_id.clone(), error: "limit".to_string() });
+        f.bus.publish(AppEvent::ConversationCreated { id: "native-x".to_string(), title: None, reply_to: None });
+        f.settle().await;
+        assert!(f.transport.sent_messages().is_empty());
+        f.in_scope(|| assert!(state.messages_for(&failed_id).is_empty()));
+
+        let local_id = Conversation::new_pending(None).id;
+        f.in_scope(|| assert!(service.start_fresh("native-a", &local_id)));
+        f.bus.publish(AppEvent::ConversationConfirmed { local_id, id: "native-b".to_string() });
+        f.settle().await;
+        match f.transport.sent_messages().as_slice() {
+            [WSClientMessage::Chat { conversation_id, body, .. }] => {
+                assert_eq!(conversation_id, "native-b");
+                assert_eq!(body, "hello");
+            }
+            other => panic!("expected one chat frame, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opened_search_result_is_focused_once_loaded() {
+        let mut f = chat_fixture();
+        let mut state = f.state;
+        let found = Message::new_assistant("search-1".to_string(), "the answer".to_string(), None);
+
+        f.bus.publish(AppEvent::SearchResultOpened(SearchResult {
+            conv_id: "native-b".to_string(),
+            message: found.clone(),
+        }));
+        f.settle().await;
+
+        f.in_scope(|| {
+            assert_eq!(state.focused_message(), None);
+            state.set_history("native-b", vec![Message { id: "h-1".to_string(), ..found }]);
+            assert_eq!(state.focused_message().as_deref(), Some("h-1"));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_message_in_pending_conversation_waits_for_server_id() {
+        let mut f = chat_fixture();
+        let (mut state, service) = (f.state, f.service.clone());
+
+        let local_id = Conversation::new_pending(None).id;
+        f.in_scope(|| {
+            state.set_current_conversation(Some(local_id.clone()));
+            service.send_message("hello".to_string(), None, None);
+            state.set_draft(&local_id, "and also");
+        });
+        f.settle().await;
+        assert!(f.transport.sent_messages().is_empty());
+
+        f.bus.publish(AppEvent::ConversationConfirmed { local_id: local_id.clone(), id: "native-a".to_string() });
+        f.settle().await;
+
+        match f.transport.sent_messages().as_slice() {
+            [WSClientMessage::Chat { conversation_id, body, .. }] => {
+                assert_eq!(conversation_id, "native-a");
+                assert_eq!(body, "hello");
+            }
+            other => panic!("expected one chat frame, got {:?}", other),
+        }
+        f.in_scope(|| {
+            assert_eq!(state.current_conv_id().as_deref(), Some("native-a"));
+            assert_eq!(state.messages_for("native-a")[0].status, MessageStatus::Sent);
+            assert!(state.messages_for(&local_id).is_empty());
+            assert_eq!(state.get_draft("native-a"), "and also");
+        });
+    }
 }
@@ -1,8 +1,48 @@
 //! Chat feature state
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use chrono::{DateTime, Duration, Utc};
 use dioxus::prelude::*;
-use prsnl_core::{Message, MessageStatus};
+use prsnl_core::{Message, MessageSender, MessageStatus};
+use super::history::INPUT_HISTORY_LIMIT;
+
+/// Typing indicators not refreshed within this many seconds are cleared
+/// (covers a lost "stopped typing" frame or a dropped connection)
+pub const TYPING_TIMEOUT_SECS: i64 = 30;
+
+/// Number of messages requested per history page
+pub const HISTORY_PAGE_SIZE: u32 = 50;
+
+/// Default distance from the top of the chat (in px) that triggers loading older messages
+pub const LOAD_OLDER_THRESHOLD_PX: f64 = 200.0;
+
+/// Default number of messages kept in memory per conversation
+pub const DEFAULT_MESSAGE_LIMIT: usize = 500;
+
+/// How far apart (ms) a live message and its history copy may be stamped
+const SAME_MESSAGE_TOLERANCE_MS: i64 = 5_000;
+
+/// Paging bookkeeping for a conversation's history
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryPaging {
+    /// An older-history request is in flight
+    pub loading_older: bool,
+    /// Messages received so far for the history response being applied
+    pub received: u32,
+    /// There is nothing older than the loaded messages
+    pub end_reached: bool,
+}
+
+impl HistoryPaging {
+    /// Finish a history response
+    ///
+    /// Without a word from the server, a page shorter than
+    /// `HISTORY_PAGE_SIZE` means the start of the conversation is loaded.
+    fn finish(&mut self, has_more: Option<bool>) {
+        self.loading_older = false;
+        self.end_reached = has_more.map_or(self.received < HISTORY_PAGE_SIZE, |more| !more);
+    }
+}
 
 /// Internal state for the chat feature
 #[derive(Debug, Clone)]
@@ -11,10 +51,39 @@ pub struct ChatStateInner {
     pub messages: HashMap<String, Vec<Message>>,
     /// Currently selected conversation
     pub current_conv_id: Option<String>,
-    /// Whether assistant is typing
-    pub is_typing: bool,
+    /// Conversations where the assistant is typing, with when it was last reported
+    pub typing: HashMap<String, DateTime<Utc>>,
     /// Messages that are pending server acknowledgment
     pub pending_messages: HashSet<String>,
+    /// Message the chat view should scroll to once it is rendered
+    pub focused_message: Option<String>,
+    /// Timestamp and body of the focused message while a batched history reload is in progress
+    pub focus_match: Option<(DateTime<Utc>, String)>,
+    /// Recently sent texts per conversation, oldest first (for Up-arrow recall)
+    pub sent_history: HashMap<String, VecDeque<String>>,
+    /// History paging per conversation
+    pub history_paging: HashMap<String, HistoryPaging>,
+    /// Show more/less choices for long messages, kept for the session
+    pub expanded_messages: HashMap<String, bool>,
+    /// Emoji this client reacted with, by message id, kept for the session
+    pub own_reactions: HashMap<String, HashSet<String>>,
+    /// Local ids of messages by the id the server knows them by: the wire id
+    /// a message was sent under, or a live reply's own id. History carries
+    /// no ids, so messages loaded from it have no entry. Deleted messages
+    /// keep theirs so the server's echo of the delete still resolves.
+    pub wire_ids: HashMap<String, String>,
+    /// The reverse of `wire_ids`: server ids by local message id
+    pub server_ids: HashMap<String, String>,
+    /// Newest message the server was told each conversation is read up to
+    pub read_marks: HashMap<String, String>,
+    /// Newest message the user has scrolled down to in each conversation
+    pub last_read: HashMap<String, String>,
+    /// Unread count each conversation's badge showed when it was last opened
+    pub unread_on_open: HashMap<String, u32>,
+    /// Unsent input text per conversation, kept while navigating away
+    pub drafts: HashMap<String, String>,
+    /// Most messages kept per conversation; the oldest are dropped as new ones arrive
+    pub message_limit: usize,
 }
 
 /// State for the chat feature (wraps a Signal)
@@ -26,12 +95,32 @@ pub struct ChatState {
 impl ChatState {
     /// Create new chat state
     pub fn new() -> Self {
+        Self::with_message_limit(DEFAULT_MESSAGE_LIMIT)
+    }
+
+    /// Create chat state keeping at most `limit` messages per conversation
+    ///
+    /// Trimmed messages stay on the server and are paged back by "load older".
+    pub fn with_message_limit(limit: usize) -> Self {
         Self {
             inner: Signal::new(ChatStateInner {
                 messages: HashMap::new(),
                 current_conv_id: None,
-                is_typing: false,
+                typing: HashMap::new(),
                 pending_messages: HashSet::new(),
+                focused_message: None,
+                focus_match: None,
+                sent_history: HashMap::new(),
+                history_paging: HashMap::new(),
+                expanded_messages: HashMap::new(),
+                own_reactions: HashMap::new(),
+                wire_ids: HashMap::new(),
+                server_ids: HashMap::new(),
+                read_marks: HashMap::new(),
+                last_read: HashMap::new(),
+                unread_on_open: HashMap::new(),
+                drafts: HashMap::new(),
+                message_limit: limit.max(1),
             }),
         }
     }
@@ -55,9 +144,19 @@ impl ChatState {
         self.inner.read().messages.get(conv_id).cloned().unwrap_or_default()
     }
 
-    /// Check if assistant is typing
+    /// Check if assistant is typing in the current conversation
     pub fn is_typing(&self) -> bool {
-        self.inner.read().is_typing
+        let inner = self.inner.read();
+        inner.current_conv_id
+            .as_ref()
+            .is_some_and(|id| inner.typing.contains_key(id))
+    }
+
+    /// Get the conversations where the assistant is typing, sorted by id
+    pub fn typing_conversations(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.inner.read().typing.keys().cloned().collect();
+        ids.sort();
+        ids
     }
 
     /// Get current conversation ID
@@ -70,60 +169,305 @@ impl ChatState {
         self.inner.read().pending_messages.contains(msg_id)
     }
 
+    /// Id the server knows a message by, or None if it has none
+    /// (loaded from history, or never sent)
+    pub fn server_id(&self, msg_id: &str) -> Option<String> {
+        self.inner.read().server_ids.get(msg_id).cloned()
+    }
+
+    /// Local id of a message the server named, or the id itself if it isn't one of ours
+    pub fn local_id(&self, server_id: &str) -> String {
+        self.inner.read().wire_ids.get(server_id).cloned().unwrap_or_else(|| server_id.to_string())
+    }
+
+    /// Get the most recent loaded message for a conversation
+    pub fn latest_message(&self, conv_id: &str) -> Option<Message> {
+        self.inner.read().messages.get(conv_id).and_then(|m| m.last().cloned())
+    }
+
+    /// Get the message the chat view should scroll to, if any
+    pub fn focused_message(&self) -> Option<String> {
+        self.inner.read().focused_message.clone()
+    }
+
+    /// Get the most recent message the user sent in a conversation
+    pub fn last_user_message(&self, conv_id: &str) -> Option<Message> {
+        self.inner
+            .read()
+            .messages
+            .get(conv_id)
+            .and_then(|m| m.iter().rev().find(|m| m.sender == MessageSender::User).cloned())
+    }
+
+    /// Get the newest message the user has seen in a conversation
+    pub fn last_read(&self, conv_id: &str) -> Option<String> {
+        self.inner.read().last_read.get(conv_id).cloned()
+    }
+
+    /// Get how many unread messages a conversation had when it was last opened
+    pub fn unread_on_open(&self, conv_id: &str) -> u32 {
+        self.inner.read().unread_on_open.get(conv_id).copied().unwrap_or(0)
+    }
+
+    /// Get the unsent input text of a conversation (empty when there is none)
+    pub fn get_draft(&self, conv_id: &str) -> String {
+        self.inner.read().drafts.get(conv_id).cloned().unwrap_or_default()
+    }
+
+    /// Get the texts recently sent in a conversation, oldest first
+    pub fn sent_history(&self, conv_id: &str) -> Vec<String> {
+        self.inner
+            .read()
+            .sent_history
+            .get(conv_id)
+            .map(|h| h.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Check if older messages are being loaded for a conversation
+    pub fn is_loading_older(&self, conv_id: &str) -> bool {
+        self.inner
+            .read()
+            .history_paging
+            .get(conv_id)
+            .is_some_and(|p| p.loading_older)
+    }
+
+    /// Check if the server has no messages older than those loaded
+    pub fn history_exhausted(&self, conv_id: &str) -> bool {
+        self.inner
+            .read()
+            .history_paging
+            .get(conv_id)
+            .is_some_and(|p| p.end_reached)
+    }
+
+    /// Get whether the user expanded or collapsed a long message, if they chose
+    ///
+    /// Read without subscribing: bubbles only need it when they mount.
+    pub fn message_expanded(&self, msg_id: &str) -> Option<bool> {
+        self.inner.peek().expanded_messages.get(msg_id).copied()
+    }
+
+    /// Emoji the user reacted to a message with
+    pub fn own_reactions(&self, msg_id: &str) -> HashSet<String> {
+        self.inner.read().own_reactions.get(msg_id).cloned().unwrap_or_default()
+    }
+
+    /// Get the user message that prompted a reply (the closest one before it)
+    pub fn prompt_for(&self, conv_id: &str, msg_id: &str) -> Option<Message> {
+        let inner = self.inner.read();
+        let messages = inner.messages.get(conv_id)?;
+        let index = messages.iter().position(|m| m.id == msg_id)?;
+        messages[..index]
+            .iter()
+            .rev()
+            .find(|m| m.sender == MessageSender::User)
+            .cloned()
+    }
+
     // ============================================
     // Mutations (use mut self for Signal write access)
     // ============================================
 
     /// Set the current conversation
     pub fn set_current_conversation(&mut self, conv_id: Option<String>) {
-        let mut inner = self.inner.write();
-        inner.current_conv_id = conv_id;
-        inner.is_typing = false; // Reset typing when switching conversations
+        self.inner.write().current_conv_id = conv_id;
     }
 
-    /// Set typing indicator
-    pub fn set_typing(&mut self, conv_id: &str, is_typing: bool) {
+    /// Set typing indicator for a conversation
+    pub fn set_typing(&mut self, conv_id: &str, is_typing: bool, now: DateTime<Utc>) {
         let mut inner = self.inner.write();
-        // Only update if it's for the current conversation
-        if inner.current_conv_id.as_ref() == Some(&conv_id.to_string()) {
-            inner.is_typing = is_typing;
+        if is_typing {
+            inner.typing.insert(conv_id.to_string(), now);
+        } else {
+            inner.typing.remove(conv_id);
+        }
+    }
+
+    /// Clear typing indicators older than the timeout, returning whether any were removed
+    pub fn expire_typing(&mut self, now: DateTime<Utc>) -> bool {
+        let timeout = Duration::seconds(TYPING_TIMEOUT_SECS);
+        let stale = self
+            .inner
+            .read()
+            .typing
+            .values()
+            .any(|since| now - *since >= timeout);
+        if stale {
+            self.inner.write().typing.retain(|_, since| now - *since < timeout);
         }
+        stale
     }
 
     /// Add a user message (optimistic update)
     pub fn add_user_message(&mut self, conv_id: &str, message: Message) {
         let mut inner = self.inner.write();
+        let messages = inner.messages.entry(conv_id.to_string()).or_default();
+        if messages.iter().any(|m| m.id == message.id) {
+            return;
+        }
         inner.pending_messages.insert(message.id.clone());
+        if !message.body.trim().is_empty() {
+            let history = inner.sent_history.entry(conv_id.to_string()).or_default();
+            if history.back() != Some(&message.body) {
+                history.push_back(message.body.clone());
+            }
+            if history.len() > INPUT_HISTORY_LIMIT {
+                history.pop_front();
+            }
+        }
         inner.messages
             .entry(conv_id.to_string())
             .or_default()
             .push(message);
+        Self::trim_to_limit(&mut inner, conv_id);
+    }
+
+    /// Add a reply to the message it answers
+    ///
+    /// `wire_id` is the server's `replyTo`, matched against the wire ids
+    /// messages were sent under; one nobody was sent under answers nothing.
+    /// Without it the reply goes to the latest pending message of the
+    /// conversation. Finding the message and recording the reply happen under
+    /// one write, so a message sent in between can't be matched by mistake.
+    /// Returns the id of the message that was answered, if any.
+    pub fn add_reply(&mut self, conv_id: &str, wire_id: Option<&str>, message: Message) -> Option<String> {
+        let mut inner = self.inner.write();
+        let messages = inner.messages.get(conv_id);
+        if messages.is_some_and(|m| m.iter().any(|m| m.id == message.id)) {
+            return None;
+        }
+        let reply_to = match wire_id {
+            Some(wire_id) => inner.wire_ids.get(wire_id).cloned(),
+            None => messages.and_then(|messages| {
+                messages
+                    .iter()
+                    .rev()
+                    .find(|m| inner.pending_messages.contains(&m.id))
+                    .map(|m| m.id.clone())
+            }),
+        };
+        Self::apply_received(&mut inner, conv_id, reply_to.as_deref().unwrap_or_default(), message);
+        reply_to
     }
 
     /// Add a received message (from assistant)
     pub fn add_received_message(&mut self, conv_id: &str, reply_to: &str, message: Message) {
         let mut inner = self.inner.write();
+        Self::apply_received(&mut inner, conv_id, reply_to, message);
+    }
 
+    /// Record a received message within an existing write borrow
+    ///
+    /// A message whose id is already present (e.g. replayed after a
+    /// reconnect) is not added twice.
+    fn apply_received(inner: &mut ChatStateInner, conv_id: &str, reply_to: &str, message: Message) {
         // Remove from pending
         inner.pending_messages.remove(reply_to);
 
         // Mark original message as delivered
         if let Some(messages) = inner.messages.get_mut(conv_id) {
             if let Some(msg) = messages.iter_mut().find(|m| m.id == reply_to) {
-                msg.status = MessageStatus::Delivered;
+                msg.mark_delivered(message.timestamp);
             }
         }
 
         // Clear typing indicator
-        if inner.current_conv_id.as_ref() == Some(&conv_id.to_string()) {
-            inner.is_typing = false;
+        inner.typing.remove(conv_id);
+
+        // Add response message; a live reply arrives under the server's id
+        let messages = inner.messages.entry(conv_id.to_string()).or_default();
+        if !messages.iter().any(|m| m.id == message.id) {
+            let id = message.id.clone();
+            messages.push(message);
+            Self::link_server_id(inner, &id, &id);
+            Self::trim_to_limit(inner, conv_id);
         }
+    }
 
-        // Add response message
-        inner.messages
-            .entry(conv_id.to_string())
-            .or_default()
-            .push(message);
+    /// Pair a local message id with the id the server knows it by,
+    /// replacing any earlier one (e.g. from a failed send)
+    fn link_server_id(inner: &mut ChatStateInner, server_id: &str, msg_id: &str) {
+        if let Some(old) = inner.server_ids.insert(msg_id.to_string(), server_id.to_string()) {
+            inner.wire_ids.remove(&old);
+        }
+        inner.wire_ids.insert(server_id.to_string(), msg_id.to_string());
+    }
+
+    /// Drop the server ids of messages no longer loaded
+    fn forget_server_ids(inner: &mut ChatStateInner, dropped: &HashSet<String>) {
+        inner.server_ids.retain(|id, _| !dropped.contains(id));
+        inner.wire_ids.retain(|_, id| !dropped.contains(id));
+    }
+
+    /// Drop a conversation's oldest messages beyond the message limit
+    ///
+    /// The server still has them, so the start of the history counts as
+    /// not reached again and "load older" pages them back.
+    fn trim_to_limit(inner: &mut ChatStateInner, conv_id: &str) {
+        let Some(messages) = inner.messages.get_mut(conv_id) else {
+            return;
+        };
+        let excess = messages.len().saturating_sub(inner.message_limit);
+        if excess == 0 {
+            return;
+        }
+        let dropped: HashSet<String> = messages.drain(..excess).map(|m| m.id).collect();
+        inner.pending_messages.retain(|id| !dropped.contains(id));
+        inner.expanded_messages.retain(|id, _| !dropped.contains(id));
+        inner.own_reactions.retain(|id, _| !dropped.contains(id));
+        Self::forget_server_ids(inner, &dropped);
+        inner.history_paging.entry(conv_id.to_string()).or_default().end_reached = false;
+    }
+
+    /// Mark a message as sent (the frame left the client)
+    ///
+    /// Only upgrades from `Sending`, so a response that raced ahead and
+    /// already marked the message `Delivered` is not downgraded.
+    pub fn mark_sent(&mut self, conv_id: &str, msg_id: &str) {
+        let mut inner = self.inner.write();
+        if let Some(messages) = inner.messages.get_mut(conv_id) {
+            if let Some(msg) = messages.iter_mut().find(|m| m.id == msg_id) {
+                if msg.status == MessageStatus::Sending {
+                    msg.status = MessageStatus::Sent;
+                }
+            }
+        }
+    }
+
+    /// Remember the wire id a message is sent or queued under
+    ///
+    /// Flush, ack and reply events name messages by wire id, and edits,
+    /// deletes and reactions go out under it. Kept while the message is loaded.
+    pub fn track_wire_id(&mut self, wire_id: &str, msg_id: &str) {
+        Self::link_server_id(&mut self.inner.write(), wire_id, msg_id);
+    }
+
+    /// Mark a queued message sent once the transport flushed it
+    pub fn mark_queued_sent(&mut self, conv_id: &str, wire_id: &str) {
+        let msg_id = self.inner.read().wire_ids.get(wire_id).cloned();
+        if let Some(msg_id) = msg_id {
+            self.mark_sent(conv_id, &msg_id);
+        }
+    }
+
+    /// Mark a message sent once the server acknowledged receiving it
+    ///
+    /// Takes the server's receipt time when given, so the message matches
+    /// its copy in later history loads.
+    pub fn mark_acked(&mut self, conv_id: &str, wire_id: &str, received_at: Option<DateTime<Utc>>) {
+        let msg_id = self.inner.read().wire_ids.get(wire_id).cloned();
+        let Some(msg_id) = msg_id else {
+            return;
+        };
+        self.mark_sent(conv_id, &msg_id);
+        if let Some(at) = received_at {
+            let mut inner = self.inner.write();
+            if let Some(msg) = inner.messages.get_mut(conv_id).and_then(|m| m.iter_mut().find(|m| m.id == msg_id)) {
+                msg.timestamp = at;
+            }
+        }
     }
 
     /// Mark a message as having an error
@@ -138,23 +482,716 @@ impl ChatState {
         }
     }
 
+    /// Put a failed user message back to `Sending` for another attempt
+    ///
+    /// Returns the message to send again, or None if it isn't a failed user
+    /// message.
+    pub fn reset_for_retry(&mut self, conv_id: &str, msg_id: &str) -> Option<Message> {
+        let mut inner = self.inner.write();
+        let msg = inner
+            .messages
+            .get_mut(conv_id)?
+            .iter_mut()
+            .find(|m| m.id == msg_id && m.sender == MessageSender::User)?;
+        if !matches!(msg.status, MessageStatus::Error(_)) {
+            return None;
+        }
+        msg.status = MessageStatus::Sending;
+        let msg = msg.clone();
+        inner.pending_messages.insert(msg.id.clone());
+        Some(msg)
+    }
+
+    /// Replace the body of a message and flag it as edited
+    ///
+    /// Returns false if the message is not loaded.
+    pub fn edit_message(&mut self, conv_id: &str, msg_id: &str, new_body: String) -> bool {
+        let mut inner = self.inner.write();
+        let Some(msg) = inner.messages.get_mut(conv_id).and_then(|m| m.iter_mut().find(|m| m.id == msg_id)) else {
+            return false;
+        };
+        msg.edit(new_body);
+        true
+    }
+
+    /// Remove a message from a conversation
+    ///
+    /// Returns false if the message is not loaded (e.g. already removed).
+    pub fn remove_message(&mut self, conv_id: &str, msg_id: &str) -> bool {
+        let mut inner = self.inner.write();
+        inner.pending_messages.remove(msg_id);
+        inner.server_ids.remove(msg_id);
+        inner.own_reactions.remove(msg_id);
+        let Some(messages) = inner.messages.get_mut(conv_id) else {
+            return false;
+        };
+        let before = messages.len();
+        messages.retain(|m| m.id != msg_id);
+        messages.len() != before
+    }
+
+    /// Add the user's reaction to a message, or take it back if already given
+    ///
+    /// Returns whether the reaction is now the user's and the emoji's new
+    /// count, or None if the message is not loaded.
+    pub fn toggle_reaction(&mut self, conv_id: &str, msg_id: &str, emoji: &str) -> Option<(bool, u32)> {
+        let mut inner = self.inner.write();
+        let inner = &mut *inner;
+        let msg = inner.messages.get_mut(conv_id)?.iter_mut().find(|m| m.id == msg_id)?;
+        let own = inner.own_reactions.entry(msg_id.to_string()).or_default();
+        let added = own.insert(emoji.to_string());
+        if !added {
+            own.remove(emoji);
+        }
+        let count = msg.reactions.get(emoji).copied().unwrap_or(0);
+        let count = if added { count + 1 } else { count.saturating_sub(1) };
+        msg.set_reaction_count(emoji, count);
+        Some((added, count))
+    }
+
+    /// Set the count of one emoji on a message, as reported by the server
+    ///
+    /// Returns false if the message is not loaded.
+    pub fn apply_reaction(&mut self, conv_id: &str, msg_id: &str, emoji: &str, count: u32) -> bool {
+        let mut inner = self.inner.write();
+        if count == 0 {
+            if let Some(own) = inner.own_reactions.get_mut(msg_id) {
+                own.remove(emoji);
+            }
+        }
+        let Some(msg) = inner.messages.get_mut(conv_id).and_then(|m| m.iter_mut().find(|m| m.id == msg_id)) else {
+            return false;
+        };
+        msg.set_reaction_count(emoji, count);
+        true
+    }
+
+    /// Mark this client's messages read up to and including `up_to_msg_id`
+    ///
+    /// Returns false if that message is not loaded.
+    pub fn apply_read_receipt(&mut self, conv_id: &str, up_to_msg_id: &str) -> bool {
+        let mut inner = self.inner.write();
+        let Some(messages) = inner.messages.get_mut(conv_id) else {
+            return false;
+        };
+        let Some(pos) = messages.iter().position(|m| m.id == up_to_msg_id) else {
+            return false;
+        };
+        for msg in messages[..=pos].iter_mut() {
+            if msg.sender == MessageSender::User
+                && matches!(msg.status, MessageStatus::Sent | MessageStatus::Delivered)
+            {
+                msg.status = MessageStatus::Read;
+            }
+        }
+        true
+    }
+
+    /// Keep the unsent input text of a conversation; empty text clears it
+    pub fn set_draft(&mut self, conv_id: &str, text: &str) {
+        let mut inner = self.inner.write();
+        if text.is_empty() {
+            inner.drafts.remove(conv_id);
+        } else {
+            inner.drafts.insert(conv_id.to_string(), text.to_string());
+        }
+    }
+
+    /// Record that the server is being told a conversation is read up to a message
+    ///
+    /// Returns false if it was already told about that message.
+    pub fn note_read_mark(&mut self, conv_id: &str, msg_id: &str) -> bool {
+        let mut inner = self.inner.write();
+        if inner.read_marks.get(conv_id).is_some_and(|id| id == msg_id) {
+            return false;
+        }
+        inner.read_marks.insert(conv_id.to_string(), msg_id.to_string());
+        true
+    }
+
+    /// Move a conversation's last-read marker to its newest message
+    pub fn mark_seen(&mut self, conv_id: &str) {
+        let Some(latest) = self.latest_message(conv_id) else {
+            return;
+        };
+        // Only write on change: this runs from effects that read the messages
+        if self.last_read(conv_id).as_ref() != Some(&latest.id) {
+            self.inner.write().last_read.insert(conv_id.to_string(), latest.id);
+        }
+    }
+
+    /// Record the unread count a conversation is being opened with
+    pub fn set_unread_on_open(&mut self, conv_id: &str, count: u32) {
+        self.inner.write().unread_on_open.insert(conv_id.to_string(), count);
+    }
+
+    /// Forget a read mark that could not be sent, so it is tried again
+    pub fn clear_read_mark(&mut self, conv_id: &str) {
+        self.inner.write().read_marks.remove(conv_id);
+    }
+
+    /// Remember the user's show more/less choice for a message
+    pub fn set_message_expanded(&mut self, msg_id: &str, expanded: bool) {
+        self.inner.write().expanded_messages.insert(msg_id.to_string(), expanded);
+    }
+
+    /// Set the message the chat view should scroll to
+    pub fn set_focused_message(&mut self, msg_id: Option<String>) {
+        self.inner.write().focused_message = msg_id;
+    }
+
+    /// Scroll to a message identified by timestamp and body once it is loaded
+    ///
+    /// Used for messages found by search, whose ids don't match the ones
+    /// history hands out.
+    pub fn focus_matching(&mut self, conv_id: &str, message: &Message) {
+        let mut inner = self.inner.write();
+        let loaded = inner
+            .messages
+            .get(conv_id)
+            .and_then(|m| m.iter().find(|m| m.timestamp == message.timestamp && m.body == message.body))
+            .map(|m| m.id.clone());
+        inner.focus_match = match loaded {
+            Some(_) => None,
+            None => Some((message.timestamp, message.body.clone())),
+        };
+        inner.focused_message = loaded;
+    }
+
+    /// Start an older-history request, returning the cursor to page back from
+    ///
+    /// The cursor is the timestamp (ms) of the oldest loaded message.
+    /// Returns None while a request is already in flight, when nothing is
+    /// loaded yet, or once the server has run out of older messages.
+    pub fn begin_load_older(&mut self, conv_id: &str) -> Option<i64> {
+        let mut inner = self.inner.write();
+        let oldest = inner.messages.get(conv_id)?.first()?.timestamp.timestamp_millis();
+        let paging = inner.history_paging.entry(conv_id.to_string()).or_default();
+        if paging.loading_older || paging.end_reached {
+            return None;
+        }
+        paging.loading_older = true;
+        Some(oldest)
+    }
+
+    /// Give up on an older-history request (e.g. it could not be sent)
+    pub fn cancel_load_older(&mut self, conv_id: &str) {
+        if let Some(paging) = self.inner.write().history_paging.get_mut(conv_id) {
+            paging.loading_older = false;
+        }
+    }
+
     /// Set messages from history
     pub fn set_history(&mut self, conv_id: &str, messages: Vec<Message>) {
-        self.inner.write().messages.insert(conv_id.to_string(), messages);
+        self.apply_history_batch(conv_id, messages, true, true, None);
+    }
+
+    /// Insert a page of older messages in front of the loaded ones
+    ///
+    /// Loaded messages are kept as they are; any the page repeats are not
+    /// added twice.
+    pub fn prepend_history(&mut self, conv_id: &str, messages: Vec<Message>, has_more: Option<bool>) {
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        let received = messages.len() as u32;
+        let loaded = inner.messages.remove(conv_id).unwrap_or_default();
+        inner.messages.insert(conv_id.to_string(), merge_older(messages, loaded));
+
+        let paging = inner.history_paging.entry(conv_id.to_string()).or_default();
+        paging.received = received;
+        paging.finish(has_more);
+    }
+
+    /// Apply one batch of a history response (newest batch first)
+    ///
+    /// The first batch of a refresh replaces the loaded messages; later
+    /// batches, and every batch of an older page, are prepended. Loaded
+    /// messages newer than the first batch (received live while a reconnect
+    /// refresh was in flight) are kept after it, and a message already
+    /// present is never added twice. `has_more` is read once the last batch
+    /// is in.
+    pub fn apply_history_batch(
+        &mut self,
+        conv_id: &str,
+        mut messages: Vec<Message>,
+        first: bool,
+        last: bool,
+        has_more: Option<bool>,
+    ) {
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        let older_page = inner.history_paging.get(conv_id).is_some_and(|p| p.loading_older);
+        let replace = first && !older_page;
+        let received = messages.len() as u32;
+
+        // History messages get fresh ids on every load, so carry the focus
+        // target over to the matching message by timestamp and body
+        if replace {
+            let carried = inner.focused_message.as_ref().and_then(|focused| {
+                let old = inner.messages.get(conv_id)?.iter().find(|m| &m.id == focused)?;
+                Some((old.timestamp, old.body.clone()))
+            });
+            if carried.is_some() {
+                inner.focus_match = carried;
+            }
+        }
+        if let Some((timestamp, body)) = &inner.focus_match {
+            if let Some(m) = messages.iter().find(|m| &m.timestamp == timestamp && &m.body == body) {
+                inner.focused_message = Some(m.id.clone());
+                inner.focus_match = None;
+            }
+        }
+
+        // History carries no server ids, so overlap is matched by content
+        let loaded = inner.messages.remove(conv_id).unwrap_or_default();
+        if replace {
+            // A refreshed copy takes over the id of a loaded one the server knows
+            let mut carried = HashSet::new();
+            for m in messages.iter_mut() {
+                let known = loaded.iter().find(|l| {
+                    inner.server_ids.contains_key(&l.id) && !carried.contains(&l.id) && same_message(l, m)
+                });
+                if let Some(known) = known {
+                    m.id = known.id.clone();
+                    carried.insert(known.id.clone());
+                }
+            }
+            let newest = messages.last().map(|m| m.timestamp);
+            let (live, replaced): (Vec<Message>, Vec<Message>) = loaded
+                .into_iter()
+                .partition(|l| newest.is_some_and(|t| l.timestamp > t) && !messages.iter().any(|m| same_message(m, l)));
+            let dropped = replaced.into_iter().map(|m| m.id).filter(|id| !carried.contains(id)).collect();
+            Self::forget_server_ids(inner, &dropped);
+            messages.extend(live);
+        } else {
+            messages = merge_older(messages, loaded);
+        }
+        inner.messages.insert(conv_id.to_string(), messages);
+
+        let paging = inner.history_paging.entry(conv_id.to_string()).or_default();
+        if first {
+            paging.received = 0;
+        }
+        paging.received += received;
+        if last {
+            paging.finish(has_more);
+            inner.focus_match = None;
+        }
+    }
+
+    /// Move a conversation's messages, draft and history to a new id
+    ///
+    /// Used when a pending conversation gets its server id. Anything already
+    /// under `to` stays after the moved messages. Returns the moved messages
+    /// still waiting to be sent.
+    pub fn move_conversation(&mut self, from: &str, to: &str) -> Vec<Message> {
+        fn rekey<V>(map: &mut HashMap<String, V>, from: &str, to: &str) {
+            if let Some(value) = map.remove(from) {
+                map.insert(to.to_string(), value);
+            }
+        }
+
+        let mut inner = self.inner.write();
+        let moved = inner.messages.remove(from).unwrap_or_default();
+        let held = moved
+            .iter()
+            .filter(|m| m.status == MessageStatus::Sending)
+            .cloned()
+            .collect();
+        let existing = inner.messages.remove(to).unwrap_or_default();
+        inner.messages.insert(to.to_string(), moved.into_iter().chain(existing).collect());
+        rekey(&mut inner.drafts, from, to);
+        rekey(&mut inner.sent_history, from, to);
+        rekey(&mut inner.last_read, from, to);
+        rekey(&mut inner.unread_on_open, from, to);
+        if inner.current_conv_id.as_deref() == Some(from) {
+            inner.current_conv_id = Some(to.to_string());
+        }
+        held
     }
 
     /// Clear messages for a conversation (when deleted)
     pub fn clear_conversation(&mut self, conv_id: &str) {
-        let mut inner = self.inner.write();
-        inner.messages.remove(conv_id);
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        if let Some(messages) = inner.messages.remove(conv_id) {
+            Self::forget_server_ids(inner, &messages.into_iter().map(|m| m.id).collect());
+        }
+        inner.typing.remove(conv_id);
+        inner.history_paging.remove(conv_id);
+        inner.read_marks.remove(conv_id);
+        inner.last_read.remove(conv_id);
+        inner.unread_on_open.remove(conv_id);
+        inner.drafts.remove(conv_id);
         if inner.current_conv_id.as_ref() == Some(&conv_id.to_string()) {
             inner.current_conv_id = None;
         }
     }
 }
 
+/// Put older history in front of the loaded messages, skipping any already loaded
+fn merge_older(mut older: Vec<Message>, loaded: Vec<Message>) -> Vec<Message> {
+    older.retain(|m| !loaded.iter().any(|l| same_message(l, m)));
+    older.extend(loaded);
+    older
+}
+
+/// Check if two copies (e.g. live and from history) are the same message
+///
+/// Live messages take the server's time where it sends one, but a sent
+/// message that was never acked keeps the local send time, so times only
+/// have to be close.
+fn same_message(a: &Message, b: &Message) -> bool {
+    (a.timestamp - b.timestamp).num_milliseconds().abs() <= SAME_MESSAGE_TOLERANCE_MS
+        && a.sender == b.sender
+        && a.body == b.body
+}
+
 impl Default for ChatState {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+
+    #[test]
+    fn test_mark_sent_upgrades_sending_only() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let first = Message::new_user("first".to_string());
+            let second = Message::new_user("second".to_string());
+            let (first_id, second_id) = (first.id.clone(), second.id.clone());
+            state.add_user_message("conv", first);
+            state.add_user_message("conv", second);
+
+            state.mark_sent("conv", &first_id);
+            let reply = Message::new_assistant("r".to_string(), "ok".to_string(), None);
+            state.add_received_message("conv", &second_id, reply);
+            state.mark_sent("conv", &second_id);
+
+            let messages = state.messages_for("conv");
+            assert_eq!(messages[0].status, MessageStatus::Sent);
+            assert_eq!(messages[1].status, MessageStatus::Delivered);
+            assert!(state.is_pending(&first_id));
+        });
+    }
+
+    #[test]
+    fn test_ack_marks_message_sent_by_wire_id() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let message = Message::new_user("hello".to_string());
+            let msg_id = message.id.clone();
+            state.add_user_message("conv", message);
+            state.track_wire_id("wire-1", &msg_id);
+
+            state.mark_acked("conv", "wire-other", None);
+            assert_eq!(state.messages_for("conv")[0].status, MessageStatus::Sending);
+
+            // The server's receipt time replaces the local send time
+            let received_at = DateTime::from_timestamp_millis(1_700_000_000_000);
+            state.mark_acked("conv", "wire-1", received_at);
+            assert_eq!(state.messages_for("conv")[0].status, MessageStatus::Sent);
+            assert_eq!(Some(state.messages_for("conv")[0].timestamp), received_at);
+            assert!(state.is_pending(&msg_id));
+
+            // A re-send replaces the wire id, which outlives the answer
+            state.track_wire_id("wire-2", &msg_id);
+            let reply = Message::new_assistant("r".to_string(), "ok".to_string(), None);
+            state.add_received_message("conv", &msg_id, reply);
+            assert_eq!(state.server_id(&msg_id).as_deref(), Some("wire-2"));
+            assert_eq!(state.local_id("wire-2"), msg_id);
+            assert_eq!(state.local_id("wire-1"), "wire-1");
+            assert_eq!(state.server_id("r").as_deref(), Some("r"));
+
+            // A deleted message can't be named again, but the echo of its delete still resolves
+            state.remove_message("conv", &msg_id);
+            assert_eq!(state.server_id(&msg_id), None);
+            assert_eq!(state.local_id("wire-2"), msg_id);
+        });
+    }
+
+    #[test]
+    fn test_history_refresh_keeps_server_ids_of_live_messages() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let old = Message::new_assistant("x".to_string(), "from before".to_string(), None);
+            state.set_history("conv", vec![Message { id: "h-1".to_string(), ..old.clone() }]);
+            assert_eq!(state.server_id("h-1"), None);
+
+            let sent = Message::new_user("hello".to_string());
+            let sent_id = sent.id.clone();
+            state.add_user_message("conv", sent.clone());
+            state.track_wire_id("wire-1", &sent_id);
+
+            // History hands out fresh ids; the live copy's id and server id survive
+            let refreshed = vec![
+                Message { id: "h-2".to_string(), ..old },
+                Message { id: "h-3".to_string(), ..sent },
+            ];
+            state.set_history("conv", refreshed);
+            let ids: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.id).collect();
+            assert_eq!(ids, ["h-2".to_string(), sent_id.clone()]);
+            assert_eq!(state.server_id(&sent_id).as_deref(), Some("wire-1"));
+        });
+    }
+
+    #[test]
+    fn test_last_user_message_skips_replies() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            state.add_user_message("conv", Message::new_user("first".to_string()));
+            state.add_user_message("conv", Message::new_user("second".to_string()));
+            let reply = Message::new_assistant("r".to_string(), "ok".to_string(), None);
+            state.add_received_message("conv", "", reply);
+
+            let last = state.last_user_message("conv").unwrap();
+            assert_eq!(last.body, "second");
+            assert!(state.last_user_message("other").is_none());
+        });
+    }
+
+    #[test]
+    fn test_prompt_for_finds_preceding_user_message() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            state.add_user_message("conv", Message::new_user("first".to_string()));
+            let first_reply = Message::new_assistant("r1".to_string(), "one".to_string(), None);
+            state.add_received_message("conv", "", first_reply);
+            state.add_user_message("conv", Message::new_user("second".to_string()));
+            let second_reply = Message::new_assistant("r2".to_string(), "two".to_string(), None);
+            state.add_received_message("conv", "", second_reply);
+
+            assert_eq!(state.prompt_for("conv", "r1").unwrap().body, "first");
+            assert_eq!(state.prompt_for("conv", "r2").unwrap().body, "second");
+
+            state.remove_message("conv", "r1");
+            assert_eq!(state.messages_for("conv").len(), 3);
+            assert!(state.prompt_for("conv", "r1").is_none());
+        });
+    }
+
+    #[test]
+    fn test_typing_is_tracked_per_conversation_and_expires() {
+        use chrono::TimeZone;
+
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let start = Utc.timestamp_opt(1_000, 0).unwrap();
+            state.set_current_conversation(Some("a".to_string()));
+            state.set_typing("a", true, start);
+            state.set_typing("b", true, start + Duration::seconds(20));
+
+            assert!(state.is_typing());
+            assert_eq!(state.typing_conversations(), ["a", "b"]);
+
+            let reply = Message::new_assistant("r".to_string(), "ok".to_string(), None);
+            state.add_received_message("a", "", reply);
+            assert!(!state.is_typing());
+            assert_eq!(state.typing_conversations(), ["b"]);
+
+            assert!(!state.expire_typing(start + Duration::seconds(TYPING_TIMEOUT_SECS)));
+            assert!(state.expire_typing(start + Duration::seconds(20 + TYPING_TIMEOUT_SECS)));
+            assert!(state.typing_conversations().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_load_older_pages_back_from_oldest_and_stops_at_end() {
+        use chrono::TimeZone;
+
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let page = |secs: std::ops::Range<i64>| {
+                secs.map(|s| Message {
+                    timestamp: Utc.timestamp_opt(s, 0).unwrap(),
+                    ..Message::new_user(s.to_string())
+                })
+                .collect::<Vec<_>>()
+            };
+            let size = HISTORY_PAGE_SIZE as i64;
+            assert_eq!(state.begin_load_older("conv"), None);
+
+            state.set_history("conv", page(100..100 + size));
+            assert!(!state.history_exhausted("conv"));
+            assert_eq!(state.begin_load_older("conv"), Some(100_000));
+            assert!(state.is_loading_older("conv"));
+            assert_eq!(state.begin_load_older("conv"), None);
+
+            // A reply lands while the page is in flight, and the page repeats the oldest loaded message
+            state.add_received_message("conv", "", Message::new_assistant("live".to_string(), "live".to_string(), None));
+            state.prepend_history("conv", page(101 - size..101), Some(true));
+            let bodies: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies.len(), 2 * size as usize);
+            assert_eq!(bodies.first().map(String::as_str), Some("51"));
+            assert_eq!(bodies.last().map(String::as_str), Some("live"));
+            assert!(!state.is_loading_older("conv"));
+
+            assert_eq!(state.begin_load_older("conv"), Some(51_000));
+            state.prepend_history("conv", page(0..7), None);
+            assert!(state.history_exhausted("conv"));
+            assert_eq!(state.begin_load_older("conv"), None);
+        });
+    }
+
+    #[test]
+    fn test_trimmed_messages_can_be_paged_back() {
+        use chrono::TimeZone;
+
+        with_runtime(|| {
+            let mut state = ChatState::with_message_limit(3);
+            let page = |secs: std::ops::Range<i64>| {
+                secs.map(|s| Message {
+                    timestamp: Utc.timestamp_opt(s, 0).unwrap(),
+                    ..Message::new_user(s.to_string())
+                })
+                .collect::<Vec<_>>()
+            };
+            state.set_history("conv", page(1..4));
+            assert!(state.history_exhausted("conv"));
+
+            let sent = Message::new_user("new".to_string());
+            state.add_user_message("conv", sent.clone());
+            state.add_received_message("conv", &sent.id, Message::new_assistant("r".to_string(), "reply".to_string(), None));
+            let bodies: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies, ["3", "new", "reply"]);
+            assert_eq!(state.messages_for("conv")[1].status, MessageStatus::Delivered);
+
+            // The dropped messages are still on the server
+            assert!(!state.history_exhausted("conv"));
+            assert_eq!(state.begin_load_older("conv"), Some(3_000));
+            state.prepend_history("conv", page(1..3), Some(false));
+            assert_eq!(state.messages_for("conv").len(), 5);
+        });
+    }
+
+    #[test]
+    fn test_interleaved_sends_and_replies_are_not_lost_or_doubled() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let first = Message::new_user("first".to_string());
+            let second = Message::new_user("second".to_string());
+            let (first_id, second_id) = (first.id.clone(), second.id.clone());
+
+            // Second message is sent before the first reply arrives
+            state.add_user_message("conv", first);
+            state.add_user_message("conv", second.clone());
+            let reply = Message::new_assistant("r1".to_string(), "one".to_string(), None);
+            assert_eq!(state.add_reply("conv", None, reply.clone()).as_deref(), Some(second_id.as_str()));
+
+            // A replayed reply and a re-added send are ignored
+            assert_eq!(state.add_reply("conv", None, reply), None);
+            state.add_user_message("conv", second);
+            assert_eq!(state.messages_for("conv").len(), 3);
+
+            let other = Message::new_assistant("r2".to_string(), "elsewhere".to_string(), None);
+            assert_eq!(state.add_reply("other", None, other), None);
+            assert_eq!(state.messages_for("other").len(), 1);
+            assert!(state.is_pending(&first_id));
+            assert!(!state.is_pending(&second_id));
+        });
+    }
+
+    #[test]
+    fn test_history_batches_prepend_older_messages() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let batch = |range: std::ops::Range<u32>| range.map(|i| Message::new_user(i.to_string())).collect::<Vec<_>>();
+            state.set_history("conv", batch(0..3));
+
+            state.apply_history_batch("conv", batch(4..6), true, false, None);
+            assert_eq!(state.messages_for("conv").len(), 2);
+
+            state.apply_history_batch("conv", batch(0..4), false, true, None);
+            let bodies: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies, ["0", "1", "2", "3", "4", "5"]);
+            assert!(state.history_exhausted("conv"));
+        });
+    }
+
+    #[test]
+    fn test_reconnect_history_refresh_does_not_duplicate_live_messages() {
+        use chrono::TimeZone;
+
+        with_runtime(|| {
+            let at = |ms| Utc.timestamp_millis_opt(ms).unwrap();
+            let message = |ms, body: &str, sender| Message {
+                timestamp: at(ms),
+                sender,
+                ..Message::new_user(body.to_string())
+            };
+            // History hands out fresh ids on every load, stamped with the server's times
+            let reload = |messages: &[Message]| {
+                messages.iter().map(|m| Message { id: Message::new_user(String::new()).id, ..m.clone() }).collect::<Vec<_>>()
+            };
+            // The live copy, stamped by this client some time after (or, with a skewed clock, before) the server
+            let live = |m: &Message, offset_ms| Message {
+                id: Message::new_user(String::new()).id,
+                timestamp: m.timestamp + Duration::milliseconds(offset_ms),
+                ..m.clone()
+            };
+
+            let mut state = ChatState::new();
+            let hello = message(10_000, "hello", MessageSender::User);
+            let hi = message(11_000, "hi", MessageSender::Assistant);
+            state.set_history("conv", vec![hello.clone(), hi.clone()]);
+
+            // Live while the connection was flapping: a reply stamped on receipt,
+            // a send never acked that keeps the local time, and a later reply
+            let during = message(20_000, "you there?", MessageSender::Assistant);
+            let sent = message(21_000, "yes", MessageSender::User);
+            let after = message(30_000, "still here", MessageSender::Assistant);
+            state.add_received_message("conv", "", live(&during, 700));
+            state.add_user_message("conv", live(&sent, 1_800));
+            state.add_received_message("conv", "", live(&after, 400));
+
+            // The refresh snapshot overlaps the first two live messages but not the third
+            state.set_history("conv", reload(&[hello.clone(), hi.clone(), during.clone(), sent.clone()]));
+            let bodies: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies, ["hello", "hi", "you there?", "yes", "still here"]);
+
+            // Batched refresh whose older batch overlaps the newer one
+            let newer = reload(&[hi.clone(), during.clone(), sent.clone(), after.clone()]);
+            state.apply_history_batch("conv", newer, true, false, None);
+            state.apply_history_batch("conv", reload(&[hello, hi]), false, true, None);
+            let bodies: Vec<String> = state.messages_for("conv").into_iter().map(|m| m.body).collect();
+            assert_eq!(bodies, ["hello", "hi", "you there?", "yes", "still here"]);
+        });
+    }
+
+    #[test]
+    fn test_focus_matching_resolves_when_history_arrives() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            let found = Message::new_user("needle".to_string());
+            state.focus_matching("conv", &found);
+            assert_eq!(state.focused_message(), None);
+
+            // History hands out a fresh id for the same message
+            let loaded = Message { id: "history-id".to_string(), ..found.clone() };
+            state.set_history("conv", vec![Message::new_user("hay".to_string()), loaded]);
+            assert_eq!(state.focused_message().as_deref(), Some("history-id"));
+        });
+    }
+
+    #[test]
+    fn test_drafts_are_kept_per_conversation() {
+        with_runtime(|| {
+            let mut state = ChatState::new();
+            state.set_draft("a", "half a thought");
+            state.set_draft("b", "another");
+            assert_eq!(state.get_draft("a"), "half a thought");
+            assert_eq!(state.get_draft("b"), "another");
+
+            // Sending empties the input, which clears the draft
+            state.set_draft("a", "");
+            assert_eq!(state.get_draft("a"), "");
+            state.clear_conversation("b");
+            assert_eq!(state.get_draft("b"), "");
+        });
+    }
+}
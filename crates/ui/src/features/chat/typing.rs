@@ -0,0 +1,62 @@
+//! Reporting the user's own typing to the server without a frame per keystroke
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Seconds without a keystroke after which the user no longer counts as typing
+pub const TYPING_IDLE_SECS: i64 = 3;
+
+/// Decides when the user starts and stops typing
+///
+/// Only the transitions are reported: the first keystroke after a pause
+/// starts typing, and `TYPING_IDLE_SECS` of quiet or a send stops it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypingReporter {
+    last_input: Option<DateTime<Utc>>,
+}
+
+impl TypingReporter {
+    /// Note a keystroke; returns true if the server should hear typing started
+    pub fn input(&mut self, now: DateTime<Utc>) -> bool {
+        self.last_input.replace(now).is_none()
+    }
+
+    /// Check for a pause; returns true if the server should hear typing stopped
+    pub fn idle(&mut self, now: DateTime<Utc>) -> bool {
+        match self.last_input {
+            Some(last) if now - last >= Duration::seconds(TYPING_IDLE_SECS) => {
+                self.last_input = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stop typing (on send or leaving); returns true if typing was reported
+    pub fn stop(&mut self) -> bool {
+        self.last_input.take().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_only_start_and_stop() {
+        let start = Utc::now();
+        let at = |secs| start + Duration::seconds(secs);
+        let mut reporter = TypingReporter::default();
+
+        assert!(reporter.input(at(0)));
+        assert!(!reporter.input(at(1)));
+        assert!(!reporter.input(at(2)));
+        // Quiet is measured from the latest keystroke
+        assert!(!reporter.idle(at(3)));
+        assert!(reporter.idle(at(5)));
+        assert!(!reporter.idle(at(9)));
+
+        assert!(reporter.input(at(10)));
+        assert!(reporter.stop());
+        assert!(!reporter.stop());
+    }
+}
@@ -0,0 +1,102 @@
+//! Cross-conversation activity feed
+
+use chrono::{DateTime, Utc};
+use prsnl_core::{Conversation, Message, MessageSender};
+
+/// Maximum number of entries shown in the activity feed
+pub const ACTIVITY_FEED_LIMIT: usize = 50;
+
+/// Latest activity in a single conversation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub conv_id: String,
+    pub title: String,
+    /// Id of the loaded message this entry points at (None for server-side previews)
+    pub message_id: Option<String>,
+    pub sender: Option<MessageSender>,
+    pub preview: String,
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Build the activity feed: the latest message of each conversation, newest first
+///
+/// Loaded messages take precedence over the list preview since they include
+/// live traffic the server-provided summary hasn't caught up with.
+pub fn recent_activity(
+    conversations: &[Conversation],
+    latest_message: impl Fn(&str) -> Option<Message>,
+    limit: usize,
+) -> Vec<ActivityEntry> {
+    let mut entries: Vec<ActivityEntry> = conversations
+        .iter()
+        .filter_map(|conv| match latest_message(&conv.id) {
+            Some(msg) => Some(ActivityEntry {
+                conv_id: conv.id.clone(),
+                title: conv.display_title(),
+                message_id: Some(msg.id),
+                sender: Some(msg.sender),
+                preview: msg.body,
+                time: Some(msg.timestamp),
+            }),
+            None => conv.last_message_preview.clone().map(|preview| ActivityEntry {
+                conv_id: conv.id.clone(),
+                title: conv.display_title(),
+                message_id: None,
+                sender: None,
+                preview,
+                time: conv.last_message_time,
+            }),
+        })
+        .collect();
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.time));
+    entries.truncate(limit);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn conv(id: &str, preview: Option<&str>, time: Option<i64>) -> Conversation {
+        Conversation::from_server(id.to_string(), preview.map(str::to_string), time, 1)
+    }
+
+    #[test]
+    fn test_recent_activity_prefers_loaded_messages_and_sorts() {
+        let convs = vec![
+            conv("native-aaa", Some("old preview"), Some(1_000)),
+            conv("native-bbb", Some("server preview"), Some(2_000)),
+            conv("native-ccc", None, None),
+        ];
+        let mut live = Message::new_user("live message".to_string());
+        live.timestamp = Utc.timestamp_millis_opt(3_000).unwrap();
+        let live_id = live.id.clone();
+
+        let entries = recent_activity(
+            &convs,
+            |id| (id == "native-aaa").then(|| live.clone()),
+            10,
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].conv_id, "native-aaa");
+        assert_eq!(entries[0].message_id, Some(live_id));
+        assert_eq!(entries[0].preview, "live message");
+        assert_eq!(entries[1].conv_id, "native-bbb");
+        assert_eq!(entries[1].message_id, None);
+    }
+
+    #[test]
+    fn test_recent_activity_respects_limit() {
+        let convs: Vec<_> = (0..5)
+            .map(|i| conv(&format!("native-{i}"), Some("hi"), Some(i)))
+            .collect();
+
+        let entries = recent_activity(&convs, |_| None, 3);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].conv_id, "native-4");
+    }
+}
@@ -0,0 +1,63 @@
+//! Activity feed component
+
+use dioxus::prelude::*;
+use prsnl_core::MessageSender;
+use crate::features::conversations::ActivityEntry;
+use crate::shared::EmptyState;
+use super::item::format_time_ago;
+
+/// Feed of the latest message from each conversation
+#[component]
+pub fn ActivityFeed(
+    entries: Vec<ActivityEntry>,
+    on_open: EventHandler<ActivityEntry>,
+) -> Element {
+    if entries.is_empty() {
+        return rsx! {
+            EmptyState { title: "No recent activity" }
+        };
+    }
+
+    rsx! {
+        for entry in entries {
+            {
+                let time_ago = entry.time.map(format_time_ago).unwrap_or_default();
+                let prefix = match entry.sender {
+                    Some(MessageSender::User) => "You: ",
+                    Some(MessageSender::Assistant) => "Assistant: ",
+                    _ => "",
+                };
+                let preview: String = entry.preview.chars().take(80).collect();
+                let on_click = {
+                    let entry = entry.clone();
+                    move |_| on_open.call(entry.clone())
+                };
+
+                rsx! {
+                    button {
+                        key: "{entry.conv_id}",
+                        onclick: on_click,
+                        class: "w-full p-4 bg-transparent border-none border-b border-border text-left cursor-pointer flex flex-col gap-1 hover:bg-bg-hover transition-colors",
+
+                        div {
+                            class: "flex justify-between items-center",
+                            span {
+                                class: "text-text-white font-medium",
+                                "{entry.title}"
+                            }
+                            span {
+                                class: "text-text-muted text-xs",
+                                "{time_ago}"
+                            }
+                        }
+
+                        span {
+                            class: "text-text-secondary text-sm overflow-hidden text-ellipsis whitespace-nowrap",
+                            "{prefix}{preview}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Conversation context menus: a bottom sheet for long-press, a popup at
+//! the pointer for right-click on desktop
+
+use dioxus::prelude::*;
+
+/// Width of the right-click menu in px
+const POPUP_WIDTH: f64 = 200.0;
+
+/// Height of one row of the right-click menu in px
+const POPUP_ROW_HEIGHT: f64 = 36.0;
+
+/// Vertical padding around the rows, and the gap kept to the viewport edge
+const POPUP_MARGIN: f64 = 8.0;
+
+/// Inline position of a menu opened at the pointer, kept inside the viewport
+///
+/// Clamping is left to CSS `min()`/`max()`, so no viewport size is needed.
+fn popup_style(x: f64, y: f64, rows: usize) -> String {
+    let height = rows as f64 * POPUP_ROW_HEIGHT + 2.0 * POPUP_MARGIN;
+    format!(
+        "left: max({m}px, min({x}px, calc(100vw - {w}px - {m}px))); \
+         top: max({m}px, min({y}px, calc(100vh - {h}px - {m}px))); width: {w}px;",
+        m = POPUP_MARGIN,
+        w = POPUP_WIDTH,
+        h = height,
+    )
+}
+
+/// Bottom sheet with actions for a single conversation
+#[component]
+pub fn ConversationContextMenu(
+    title: String,
+    pinned: bool,
+    #[props(default)] on_mark_unread: Option<EventHandler<()>>,
+    #[props(default)] on_toggle_pin: Option<EventHandler<()>>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let item_class = "w-full px-6 py-3 bg-transparent border-none text-text-white text-base text-left cursor-pointer hover:bg-bg-hover";
+
+    rsx! {
+        div {
+            onclick: move |_| on_close.call(()),
+            class: "fixed inset-0 bg-black/50 flex items-end justify-center z-[1000]",
+            div {
+                onclick: move |e| e.stop_propagation(),
+                class: "w-full max-w-[480px] bg-bg-secondary rounded-t-2xl py-2 shadow-2xl animate-slide-in",
+                div {
+                    class: "px-6 py-2 text-text-muted text-sm truncate",
+                    "{title}"
+                }
+                if let Some(on_mark_unread) = on_mark_unread {
+                    button {
+                        onclick: move |_| {
+                            on_close.call(());
+                            on_mark_unread.call(());
+                        },
+                        class: item_class,
+                        "Mark as unread"
+                    }
+                }
+                if let Some(on_toggle_pin) = on_toggle_pin {
+                    button {
+                        onclick: move |_| {
+                            on_close.call(());
+                            on_toggle_pin.call(());
+                        },
+                        class: item_class,
+                        if pinned { "Unpin" } else { "Pin to top" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Menu opened by right-clicking a conversation, at the pointer
+///
+/// Closes on a click outside or Escape. Rename turns the menu into a title
+/// field, and Delete asks for confirmation first.
+#[component]
+pub fn ConversationPopupMenu(
+    title: String,
+    pinned: bool,
+    archived: bool,
+    /// Pointer position the menu opens at (client coordinates)
+    x: f64,
+    y: f64,
+    #[props(default)] on_mark_unread: Option<EventHandler<()>>,
+    #[props(default)] on_rename: Option<EventHandler<String>>,
+    #[props(default)] on_toggle_pin: Option<EventHandler<()>>,
+    /// Archive (true) or bring back (false)
+    #[props(default)] on_archive: Option<EventHandler<bool>>,
+    #[props(default)] on_delete: Option<EventHandler<()>>,
+    on_close: EventHandler<()>,
+) -> Element {
+    // Title being edited once Rename is chosen
+    let mut draft = use_signal(|| Option::<String>::None);
+    let mut confirm_delete = use_signal(|| false);
+    let item_class = "w-full px-3 py-2 bg-transparent border-none text-text-white text-sm text-left cursor-pointer hover:bg-bg-hover";
+
+    if confirm_delete() {
+        return rsx! {
+            DeleteConversationDialog {
+                title,
+                on_confirm: move |_| {
+                    on_close.call(());
+                    if let Some(on_delete) = on_delete {
+                        on_delete.call(());
+                    }
+                },
+                on_close: move |_| on_close.call(()),
+            }
+        };
+    }
+
+    let rows = [
+        on_mark_unread.is_some(),
+        on_rename.is_some(),
+        on_toggle_pin.is_some(),
+        on_archive.is_some(),
+        on_delete.is_some(),
+    ]
+    .into_iter()
+    .filter(|&shown| shown)
+    .count();
+    let mut commit = move || {
+        if let (Some(text), Some(on_rename)) = (draft.take(), on_rename) {
+            on_close.call(());
+            on_rename.call(text);
+        }
+    };
+
+    rsx! {
+        // Transparent backdrop: any click outside closes the menu
+        div {
+            onclick: move |_| on_close.call(()),
+            oncontextmenu: move |e: MouseEvent| {
+                e.prevent_default();
+                on_close.call(());
+            },
+            onkeydown: move |e: KeyboardEvent| {
+                if e.key() == Key::Escape {
+                    on_close.call(());
+                }
+            },
+            class: "fixed inset-0 z-[1000]",
+            div {
+                onclick: move |e| e.stop_propagation(),
+                onmounted: move |e: MountedEvent| async move {
+                    // Focused so Escape reaches the menu
+                    let _ = e.set_focus(true).await;
+                },
+                tabindex: "-1",
+                style: popup_style(x, y, rows),
+                class: "fixed py-2 rounded-lg bg-bg-secondary border border-border shadow-lg outline-none",
+
+                if let Some(text) = draft() {
+                    div {
+                        class: "px-2",
+                        input {
+                            r#type: "text",
+                            value: "{text}",
+                            placeholder: "Conversation title",
+                            autofocus: true,
+                            oninput: move |e| draft.set(Some(e.value())),
+                            onkeydown: move |e: KeyboardEvent| match e.key() {
+                                Key::Enter => commit(),
+                                Key::Escape => {
+                                    // Back to the menu rather than closing it
+                                    e.stop_propagation();
+                                    draft.set(None);
+                                }
+                                _ => {}
+                            },
+                            class: "w-full p-1.5 border border-border rounded-md bg-bg-primary text-text-white text-sm box-border outline-none focus:border-accent",
+                        }
+                    }
+                } else {
+                    if let Some(on_mark_unread) = on_mark_unread {
+                        button {
+                            onclick: move |_| {
+                                on_close.call(());
+                                on_mark_unread.call(());
+                            },
+                            class: item_class,
+                            "Mark as unread"
+                        }
+                    }
+                    if on_rename.is_some() {
+                        button {
+                            onclick: {
+                                let title = title.clone();
+                                move |_| draft.set(Some(title.clone()))
+                            },
+                            class: item_class,
+                            "Rename"
+                        }
+                    }
+                    if let Some(on_toggle_pin) = on_toggle_pin {
+                        button {
+                            onclick: move |_| {
+                                on_close.call(());
+                                on_toggle_pin.call(());
+                            },
+                            class: item_class,
+                            if pinned { "Unpin" } else { "Pin to top" }
+                        }
+                    }
+                    if let Some(on_archive) = on_archive {
+                        button {
+                            onclick: move |_| {
+                                on_close.call(());
+                                on_archive.call(!archived);
+                            },
+                            class: item_class,
+                            if archived { "Unarchive" } else { "Archive" }
+                        }
+                    }
+                    if on_delete.is_some() {
+                        button {
+                            onclick: move |_| confirm_delete.set(true),
+                            class: "w-full px-3 py-2 bg-transparent border-none text-error text-sm text-left cursor-pointer hover:bg-bg-hover",
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Dialog asking before a conversation is deleted
+#[component]
+pub fn DeleteConversationDialog(title: String, on_confirm: EventHandler<()>, on_close: EventHandler<()>) -> Element {
+    rsx! {
+        div {
+            onclick: move |_| on_close.call(()),
+            onkeydown: move |e: KeyboardEvent| {
+                if e.key() == Key::Escape {
+                    on_close.call(());
+                }
+            },
+            class: "fixed inset-0 bg-black/70 flex items-center justify-center z-[1000]",
+            div {
+                onclick: move |e| e.stop_propagation(),
+                class: "bg-bg-secondary rounded-2xl p-6 w-[90%] max-w-[400px] shadow-2xl flex flex-col gap-4",
+                h2 {
+                    class: "text-text-white m-0 text-lg",
+                    "Delete conversation?"
+                }
+                p {
+                    class: "m-0 text-text-secondary text-sm",
+                    "“{title}” and all its messages will be deleted. This can't be undone."
+                }
+                div {
+                    class: "flex justify-end gap-2",
+                    button {
+                        onclick: move |_| on_close.call(()),
+                        autofocus: true,
+                        class: "px-4 py-2 rounded-lg border-none bg-bg-tertiary text-text-white text-sm cursor-pointer",
+                        "Cancel"
+                    }
+                    button {
+                        onclick: move |_| on_confirm.call(()),
+                        class: "px-4 py-2 rounded-lg border-none bg-error text-white text-sm cursor-pointer",
+                        "Delete"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_style_clamps_to_viewport() {
+        assert_eq!(
+            popup_style(120.0, 300.0, 4),
+            "left: max(8px, min(120px, calc(100vw - 200px - 8px))); \
+             top: max(8px, min(300px, calc(100vh - 160px - 8px))); width: 200px;"
+        );
+    }
+}
@@ -1,31 +1,55 @@
 //! Single conversation item component
 
+use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
 use prsnl_core::Conversation;
+use super::context_menu::{ConversationContextMenu, ConversationPopupMenu};
+use super::labels::TagChips;
+
+/// Format a timestamp as a short relative label ("5m ago", "Just now")
+pub(crate) fn format_time_ago(time: DateTime<Utc>) -> String {
+    let diff = Utc::now().signed_duration_since(time);
+
+    if diff.num_days() > 0 {
+        format!("{}d ago", diff.num_days())
+    } else if diff.num_hours() > 0 {
+        format!("{}h ago", diff.num_hours())
+    } else if diff.num_minutes() > 0 {
+        format!("{}m ago", diff.num_minutes())
+    } else {
+        "Just now".to_string()
+    }
+}
 
 /// A single conversation in the list
 #[component]
 pub fn ConversationItem(
     conversation: Conversation,
     on_select: EventHandler<String>,
+    /// Context menu actions (the menu is disabled when all are None)
+    #[props(default)] on_mark_unread: Option<EventHandler<String>>,
+    #[props(default)] on_toggle_pin: Option<EventHandler<String>>,
+    /// Right-click menu actions, offered with `popup_menu`
+    #[props(default)] on_rename: Option<EventHandler<(String, String)>>,
+    #[props(default)] on_archive: Option<EventHandler<(String, bool)>>,
+    #[props(default)] on_delete: Option<EventHandler<String>>,
+    /// Right-click opens a menu at the pointer (desktop) instead of the bottom sheet
+    #[props(default)] popup_menu: bool,
+    /// Marked by keyboard navigation of the list
+    #[props(default)] highlighted: bool,
 ) -> Element {
     let conv_id = conversation.id.clone();
+    let mut menu_open = use_signal(|| false);
+    // Pointer position of an open right-click menu
+    let mut popup_at = use_signal(|| Option::<(f64, f64)>::None);
+    let has_menu = on_mark_unread.is_some()
+        || on_toggle_pin.is_some()
+        || (popup_menu && (on_rename.is_some() || on_archive.is_some() || on_delete.is_some()));
+    let unread = conversation.unread_count;
+    let title = conversation.display_title();
 
     // Format time ago
-    let time_ago = conversation.last_message_time.map(|time| {
-        let now = chrono::Utc::now();
-        let diff = now.signed_duration_since(time);
-
-        if diff.num_days() > 0 {
-            format!("{}d ago", diff.num_days())
-        } else if diff.num_hours() > 0 {
-            format!("{}h ago", diff.num_hours())
-        } else if diff.num_minutes() > 0 {
-            format!("{}m ago", diff.num_minutes())
-        } else {
-            "Just now".to_string()
-        }
-    }).unwrap_or_else(|| "".to_string());
+    let time_ago = conversation.last_message_time.map(format_time_ago).unwrap_or_default();
 
     // Truncate preview safely for UTF-8
     let preview = conversation.last_message_preview.unwrap_or_default();
@@ -35,21 +59,69 @@ pub fn ConversationItem(
         preview
     };
 
+    // Color stripe along the left edge
+    let stripe_style = conversation
+        .color
+        .as_ref()
+        .map(|c| format!("box-shadow: inset 4px 0 0 {}", c))
+        .unwrap_or_default();
+
     rsx! {
         button {
-            onclick: move |_| on_select.call(conv_id.clone()),
-            class: "w-full p-4 bg-transparent border-none border-b border-border text-left cursor-pointer flex flex-col gap-1 hover:bg-bg-hover transition-colors",
+            onclick: {
+                let conv_id = conv_id.clone();
+                move |_| on_select.call(conv_id.clone())
+            },
+            oncontextmenu: move |e: MouseEvent| {
+                // Long-press on touch fires contextmenu too
+                if has_menu {
+                    e.prevent_default();
+                    if popup_menu {
+                        let point = e.client_coordinates();
+                        popup_at.set(Some((point.x, point.y)));
+                    } else {
+                        menu_open.set(true);
+                    }
+                }
+            },
+            id: "conv-item-{conv_id}",
+            class: if highlighted {
+                "w-full p-item bg-bg-hover border-none border-b border-border text-left cursor-pointer flex flex-col gap-1 outline outline-2 -outline-offset-2 outline-accent transition-colors"
+            } else {
+                "w-full p-item bg-transparent border-none border-b border-border text-left cursor-pointer flex flex-col gap-1 hover:bg-bg-hover transition-colors"
+            },
+            style: "{stripe_style}",
 
             // Title and time row
             div {
-                class: "flex justify-between items-center",
-                span {
-                    class: "text-text-white font-medium",
-                    "{conversation.title}"
+                class: "flex justify-between items-center gap-2",
+                if conversation.pinned {
+                    span {
+                        class: "text-xs leading-none shrink-0",
+                        title: "Pinned",
+                        "📌"
+                    }
                 }
                 span {
-                    class: "text-text-muted text-xs",
-                    "{time_ago}"
+                    class: if unread > 0 { "text-text-white font-bold flex-1 truncate" } else { "text-text-white font-medium flex-1 truncate" },
+                    "{title}"
+                }
+                if conversation.pending {
+                    span {
+                        class: "text-text-muted text-xs italic",
+                        "Creating…"
+                    }
+                } else {
+                    span {
+                        class: "text-text-muted text-xs",
+                        "{time_ago}"
+                    }
+                }
+                if unread > 0 {
+                    span {
+                        class: "min-w-5 h-5 px-1.5 rounded-full bg-accent text-text-white text-xs font-medium flex items-center justify-center",
+                        "{unread}"
+                    }
                 }
             }
 
@@ -67,6 +139,57 @@ pub fn ConversationItem(
                     }
                 }
             }
+
+            if !conversation.tags.is_empty() {
+                TagChips { tags: conversation.tags.clone() }
+            }
+        }
+
+        if menu_open() {
+            ConversationContextMenu {
+                title: title.clone(),
+                pinned: conversation.pinned,
+                on_mark_unread: on_mark_unread.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |_| handler.call(conv_id.clone()))
+                }),
+                on_toggle_pin: on_toggle_pin.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |_| handler.call(conv_id.clone()))
+                }),
+                on_close: move |_| menu_open.set(false),
+            }
+        }
+
+        if let Some((x, y)) = popup_at() {
+            ConversationPopupMenu {
+                title: title.clone(),
+                pinned: conversation.pinned,
+                archived: conversation.archived,
+                x,
+                y,
+                on_mark_unread: on_mark_unread.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |_| handler.call(conv_id.clone()))
+                }),
+                on_rename: on_rename.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |title: String| handler.call((conv_id.clone(), title)))
+                }),
+                on_toggle_pin: on_toggle_pin.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |_| handler.call(conv_id.clone()))
+                }),
+                on_archive: on_archive.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |archived: bool| handler.call((conv_id.clone(), archived)))
+                }),
+                on_delete: on_delete.map(|handler| {
+                    let conv_id = conv_id.clone();
+                    EventHandler::new(move |_| handler.call(conv_id.clone()))
+                }),
+                on_close: move |_| popup_at.set(None),
+            }
         }
     }
 }
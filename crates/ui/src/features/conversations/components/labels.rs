@@ -0,0 +1,119 @@
+//! Conversation tag chips and label editor
+
+use dioxus::prelude::*;
+use prsnl_core::ConversationLabels;
+
+/// Colors offered in the label editor
+pub const LABEL_COLORS: &[&str] = &[
+    "#ef4444", "#f97316", "#fbbf24", "#4ade80", "#1e88e5", "#a855f7", "#ec4899",
+];
+
+/// Parse comma-separated tags, trimming and dropping empty or duplicate entries
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Small chips showing a conversation's tags
+#[component]
+pub fn TagChips(tags: Vec<String>) -> Element {
+    rsx! {
+        div {
+            class: "flex flex-wrap gap-1",
+            for tag in tags {
+                span {
+                    key: "{tag}",
+                    class: "px-2 py-0.5 rounded-full bg-bg-tertiary text-text-secondary text-[0.7rem]",
+                    "{tag}"
+                }
+            }
+        }
+    }
+}
+
+/// Inline editor for a conversation's tags and color
+#[component]
+pub fn LabelEditor(
+    labels: ConversationLabels,
+    on_save: EventHandler<ConversationLabels>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut tags_input = use_signal(|| labels.tags.join(", "));
+    let mut color = use_signal(|| labels.color.clone());
+
+    let swatch_class = move |c: Option<&str>| {
+        if color.read().as_deref() == c {
+            "w-6 h-6 rounded-full cursor-pointer border-2 border-text-white"
+        } else {
+            "w-6 h-6 rounded-full cursor-pointer border-2 border-transparent"
+        }
+    };
+
+    rsx! {
+        div {
+            class: "shrink-0 p-4 bg-bg-secondary border-b border-border flex flex-col gap-3",
+
+            input {
+                r#type: "text",
+                value: "{tags_input}",
+                oninput: move |e| tags_input.set(e.value()),
+                placeholder: "Tags, separated by commas",
+                class: "w-full p-2 border border-border rounded-lg bg-bg-primary text-text-white text-sm outline-none focus:border-accent",
+            }
+
+            // Color swatches ("none" first)
+            div {
+                class: "flex items-center gap-2",
+                button {
+                    onclick: move |_| color.set(None),
+                    class: "{swatch_class(None)} bg-bg-tertiary text-text-muted text-xs",
+                    title: "No color",
+                    "×"
+                }
+                for c in LABEL_COLORS.iter().copied() {
+                    button {
+                        key: "{c}",
+                        onclick: move |_| color.set(Some(c.to_string())),
+                        class: swatch_class(Some(c)),
+                        style: "background: {c}",
+                    }
+                }
+            }
+
+            div {
+                class: "flex gap-2 justify-end",
+                button {
+                    onclick: move |_| on_close.call(()),
+                    class: "py-1 px-4 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover",
+                    "Cancel"
+                }
+                button {
+                    onclick: move |_| {
+                        on_save.call(ConversationLabels {
+                            tags: parse_tags(&tags_input.read()),
+                            color: color.read().clone(),
+                        });
+                    },
+                    class: "py-1 px-4 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm hover:bg-accent-hover",
+                    "Save"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags_trims_and_dedupes() {
+        assert_eq!(parse_tags(" work, ,home,work "), vec!["work", "home"]);
+        assert!(parse_tags("").is_empty());
+    }
+}
@@ -1,9 +1,62 @@
 //! Conversation list component
 
+use chrono::{DateTime, Duration, Utc};
 use dioxus::prelude::*;
 use prsnl_core::Conversation;
+use crate::features::conversations::ActivityEntry;
+use crate::shared::{scroll_into_view, EmptyState, LoadingSpinner};
+use super::activity::ActivityFeed;
 use super::item::ConversationItem;
 
+/// Tabs shown at the top of the list
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListTab {
+    Chats,
+    Activity,
+}
+
+/// How long a touch must rest on a drag handle before it starts reordering
+const LONG_PRESS_MS: i64 = 400;
+
+/// Row height used until a pinned row has been measured
+const DEFAULT_ROW_HEIGHT: f64 = 72.0;
+
+/// An in-progress reorder of a pinned conversation
+#[derive(Debug, Clone, PartialEq)]
+struct PinDrag {
+    conv_id: String,
+    from: usize,
+    target: usize,
+    start_y: f64,
+    started_at: DateTime<Utc>,
+    /// False until the gesture qualifies as a drag (moved with a mouse, or long-pressed on touch)
+    active: bool,
+}
+
+/// Index a dragged row lands on after moving `dy` pixels
+fn drag_target_index(from: usize, dy: f64, row_height: f64, len: usize) -> usize {
+    if len == 0 || row_height <= 0.0 {
+        return from;
+    }
+    let offset = (dy / row_height).round() as isize;
+    (from as isize + offset).clamp(0, len as isize - 1) as usize
+}
+
+/// Index the highlight moves to after Up (`down` false) or Down in a list of `len` rows
+///
+/// The first press highlights the first or last row; moves stop at the ends.
+fn step_highlight(current: Option<usize>, len: usize, down: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    Some(match (current.filter(|&i| i < len), down) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(i), true) => (i + 1).min(len - 1),
+        (Some(i), false) => i.saturating_sub(1),
+    })
+}
+
 /// List of conversations with new chat button
 #[component]
 pub fn ConversationList(
@@ -11,31 +64,293 @@ pub fn ConversationList(
     loading: bool,
     on_select: EventHandler<String>,
     on_new: EventHandler<()>,
+    #[props(default)] activity: Vec<ActivityEntry>,
+    #[props(default)] on_open_activity: EventHandler<ActivityEntry>,
+    /// Move a pinned conversation to a new index within the pinned group
+    #[props(default)] on_reorder_pinned: EventHandler<(String, usize)>,
+    /// Context menu actions passed through to each item
+    #[props(default)] on_mark_unread: Option<EventHandler<String>>,
+    #[props(default)] on_toggle_pin: Option<EventHandler<String>>,
+    #[props(default)] on_rename: Option<EventHandler<(String, String)>>,
+    #[props(default)] on_archive: Option<EventHandler<(String, bool)>>,
+    #[props(default)] on_delete: Option<EventHandler<String>>,
+    /// Right-click opens a menu at the pointer instead of the bottom sheet
+    #[props(default)] popup_menu: bool,
+    /// Up/Down highlight a conversation and Enter opens it (for desktop; touch has no use for it)
+    #[props(default)] keyboard_nav: bool,
 ) -> Element {
+    let mut tab = use_signal(|| ListTab::Chats);
+    let mut highlighted = use_signal(|| Option::<usize>::None);
+    let mut tag_filter = use_signal(|| Option::<String>::None);
+    let mut drag = use_signal(|| Option::<PinDrag>::None);
+    let mut row_height = use_signal(|| DEFAULT_ROW_HEIGHT);
+    let mut show_archived = use_signal(|| false);
+
+    // Tags offered as filters, and the conversations matching the active one
+    let mut all_tags: Vec<String> = conversations.iter().flat_map(|c| c.tags.clone()).collect();
+    all_tags.sort();
+    all_tags.dedup();
+    let conversations: Vec<Conversation> = match tag_filter.read().as_deref() {
+        Some(tag) if all_tags.iter().any(|t| t == tag) => conversations.into_iter().filter(|c| c.has_tag(tag)).collect(),
+        _ => conversations,
+    };
+
+    // Pinned conversations arrive first in their manual order; the rest stay time-sorted.
+    // Reordering is only offered on the unfiltered list so indices match the full pinned group.
+    let reorderable = tag_filter.read().is_none();
+    // Archived conversations sit in their own collapsed section at the end.
+    let (archived, conversations): (Vec<Conversation>, Vec<Conversation>) =
+        conversations.into_iter().partition(|c| c.archived);
+    let (mut pinned, unpinned): (Vec<Conversation>, Vec<Conversation>) =
+        conversations.into_iter().partition(|c| c.pinned);
+    let pinned_len = pinned.len();
+    if let Some(d) = drag.read().as_ref().filter(|d| d.active && d.from < pinned_len) {
+        let conv = pinned.remove(d.from);
+        pinned.insert(d.target, conv);
+    }
+    let dragging_id = drag.read().as_ref().filter(|d| d.active).map(|d| d.conv_id.clone());
+
+    // Rows in display order, for keyboard navigation
+    let visible_archived: &[Conversation] = if show_archived() { &archived } else { &[] };
+    let row_ids: Vec<String> = pinned
+        .iter()
+        .chain(unpinned.iter())
+        .chain(visible_archived)
+        .map(|c| c.id.clone())
+        .collect();
+    let highlighted_id = highlighted().and_then(|i| row_ids.get(i).cloned());
+    let on_keydown = move |e: KeyboardEvent| {
+        if !keyboard_nav || *tab.read() != ListTab::Chats {
+            return;
+        }
+        match e.key() {
+            Key::ArrowUp | Key::ArrowDown => {
+                e.prevent_default();
+                let next = step_highlight(highlighted(), row_ids.len(), e.key() == Key::ArrowDown);
+                highlighted.set(next);
+                if let Some(id) = next.and_then(|i| row_ids.get(i)) {
+                    scroll_into_view(&format!("conv-item-{id}"));
+                }
+            }
+            Key::Enter => {
+                if let Some(id) = highlighted().and_then(|i| row_ids.get(i)) {
+                    e.prevent_default();
+                    on_select.call(id.clone());
+                }
+            }
+            _ => {}
+        }
+    };
+
+    let on_drag_move = move |e: PointerEvent| {
+        let mut current = drag.write();
+        let Some(d) = current.as_mut() else {
+            return;
+        };
+        let dy = e.client_coordinates().y - d.start_y;
+        if !d.active {
+            if e.pointer_type() == "mouse" {
+                if dy.abs() < 4.0 {
+                    return;
+                }
+            } else if Utc::now() - d.started_at < Duration::milliseconds(LONG_PRESS_MS) {
+                // Moving before the long press completes cancels it
+                if dy.abs() > 10.0 {
+                    *current = None;
+                }
+                return;
+            }
+            d.active = true;
+        }
+        d.target = drag_target_index(d.from, dy, row_height(), pinned_len);
+    };
+
+    let on_drag_end = move |_| {
+        if let Some(d) = drag.take() {
+            if d.active && d.target != d.from {
+                on_reorder_pinned.call((d.conv_id, d.target));
+            }
+        }
+    };
+
+    let chip_class = move |t: Option<&str>| {
+        if tag_filter.read().as_deref() == t {
+            "px-3 py-1 rounded-full border-none bg-accent text-text-white text-xs cursor-pointer"
+        } else {
+            "px-3 py-1 rounded-full border-none bg-bg-tertiary text-text-secondary text-xs cursor-pointer"
+        }
+    };
+
+    let tab_class = move |t: ListTab| {
+        if *tab.read() == t {
+            "flex-1 py-2 bg-transparent border-none border-b-2 border-accent text-text-white text-sm cursor-pointer"
+        } else {
+            "flex-1 py-2 bg-transparent border-none border-b-2 border-transparent text-text-muted text-sm cursor-pointer"
+        }
+    };
+
     rsx! {
         div {
-            class: "flex-1 overflow-y-auto",
+            class: "shrink-0 flex border-b border-border",
+            button {
+                onclick: move |_| tab.set(ListTab::Chats),
+                class: tab_class(ListTab::Chats),
+                "Chats"
+            }
+            button {
+                onclick: move |_| tab.set(ListTab::Activity),
+                class: tab_class(ListTab::Activity),
+                "Activity"
+            }
+        }
 
-            if loading {
-                div {
-                    class: "flex justify-center p-8 text-text-muted",
-                    "Loading conversations..."
-                }
-            } else if conversations.is_empty() {
-                div {
-                    class: "flex flex-col items-center justify-center p-8 text-text-muted",
-                    p { "No conversations yet" }
-                    p {
-                        class: "text-sm",
-                        "Tap the button below to start"
+        // Tag filter (only when some conversation is tagged)
+        if *tab.read() == ListTab::Chats && !all_tags.is_empty() {
+            div {
+                class: "shrink-0 flex gap-2 px-4 py-2 overflow-x-auto border-b border-border",
+                button {
+                    onclick: move |_| tag_filter.set(None),
+                    class: chip_class(None),
+                    "All"
+                }
+                for tag in all_tags {
+                    button {
+                        key: "{tag}",
+                        class: chip_class(Some(&tag)),
+                        onclick: {
+                            let tag = tag.clone();
+                            move |_| tag_filter.set(Some(tag.clone()))
+                        },
+                        "{tag}"
                     }
                 }
+            }
+        }
+
+        div {
+            class: "flex-1 overflow-y-auto outline-none",
+            tabindex: if keyboard_nav { "0" },
+            onkeydown: on_keydown,
+
+            if loading {
+                LoadingSpinner { label: "Loading conversations..." }
+            } else if *tab.read() == ListTab::Activity {
+                ActivityFeed {
+                    entries: activity,
+                    on_open: on_open_activity,
+                }
+            } else if pinned.is_empty() && unpinned.is_empty() && archived.is_empty() {
+                EmptyState {
+                    title: "No conversations yet",
+                    subtitle: "Tap the button below to start",
+                }
             } else {
-                for conv in conversations {
+                if !pinned.is_empty() {
+                    div {
+                        class: "px-4 pt-3 pb-1 text-text-muted text-xs uppercase tracking-wide",
+                        "Pinned"
+                    }
+                    div {
+                        onpointermove: on_drag_move,
+                        onpointerup: on_drag_end,
+                        onpointercancel: move |_| drag.set(None),
+                        onpointerleave: move |_| drag.set(None),
+                        for (index, conv) in pinned.into_iter().enumerate() {
+                            div {
+                                key: "{conv.id}",
+                                onmounted: move |e: MountedEvent| async move {
+                                    if let Ok(rect) = e.get_client_rect().await {
+                                        row_height.set(rect.size.height);
+                                    }
+                                },
+                                class: if dragging_id.as_deref() == Some(conv.id.as_str()) {
+                                    "flex items-stretch bg-bg-hover opacity-70"
+                                } else {
+                                    "flex items-stretch"
+                                },
+
+                                // Drag handle (press and drag with a mouse, long-press then drag on touch)
+                                if reorderable {
+                                    div {
+                                        onpointerdown: {
+                                            let conv_id = conv.id.clone();
+                                            move |e: PointerEvent| {
+                                                e.prevent_default();
+                                                drag.set(Some(PinDrag {
+                                                    conv_id: conv_id.clone(),
+                                                    from: index,
+                                                    target: index,
+                                                    start_y: e.client_coordinates().y,
+                                                    started_at: Utc::now(),
+                                                    active: false,
+                                                }));
+                                            }
+                                        },
+                                        class: "shrink-0 w-8 flex items-center justify-center text-text-muted cursor-grab touch-none select-none border-b border-border",
+                                        title: "Drag to reorder",
+                                        "⋮⋮"
+                                    }
+                                }
+                                div {
+                                    class: "flex-1 min-w-0",
+                                    ConversationItem {
+                                        highlighted: highlighted_id.as_ref() == Some(&conv.id),
+                                        conversation: conv.clone(),
+                                        on_select,
+                                        on_mark_unread,
+                                        on_toggle_pin,
+                                        on_rename,
+                                        on_archive,
+                                        on_delete,
+                                        popup_menu,
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if !unpinned.is_empty() {
+                        div {
+                            class: "px-4 pt-3 pb-1 text-text-muted text-xs uppercase tracking-wide",
+                            "All chats"
+                        }
+                    }
+                }
+                for conv in unpinned {
                     ConversationItem {
                         key: "{conv.id}",
+                        highlighted: highlighted_id.as_ref() == Some(&conv.id),
                         conversation: conv.clone(),
                         on_select,
+                        on_mark_unread,
+                        on_toggle_pin,
+                        on_rename,
+                        on_archive,
+                        on_delete,
+                        popup_menu,
+                    }
+                }
+                if !archived.is_empty() {
+                    button {
+                        onclick: move |_| show_archived.toggle(),
+                        class: "w-full px-4 pt-3 pb-1 bg-transparent border-none text-left text-text-muted text-xs uppercase tracking-wide cursor-pointer hover:text-text-secondary",
+                        if show_archived() { "▾ " } else { "▸ " }
+                        "Archived ({archived.len()})"
+                    }
+                    if show_archived() {
+                        for conv in archived {
+                            ConversationItem {
+                                key: "{conv.id}",
+                                highlighted: highlighted_id.as_ref() == Some(&conv.id),
+                                conversation: conv.clone(),
+                                on_select,
+                                on_mark_unread,
+                                on_toggle_pin,
+                                on_rename,
+                                on_archive,
+                                on_delete,
+                                popup_menu,
+                            }
+                        }
                     }
                 }
             }
@@ -49,3 +364,29 @@ pub fn ConversationList(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_target_index_rounds_and_clamps() {
+        assert_eq!(drag_target_index(1, 30.0, 72.0, 4), 1);
+        assert_eq!(drag_target_index(1, 40.0, 72.0, 4), 2);
+        assert_eq!(drag_target_index(1, -80.0, 72.0, 4), 0);
+        assert_eq!(drag_target_index(1, 1000.0, 72.0, 4), 3);
+        assert_eq!(drag_target_index(2, 50.0, 0.0, 4), 2);
+    }
+
+    #[test]
+    fn test_step_highlight_stops_at_the_ends() {
+        assert_eq!(step_highlight(None, 3, true), Some(0));
+        assert_eq!(step_highlight(None, 3, false), Some(2));
+        assert_eq!(step_highlight(Some(1), 3, true), Some(2));
+        assert_eq!(step_highlight(Some(2), 3, true), Some(2));
+        assert_eq!(step_highlight(Some(0), 3, false), Some(0));
+        // A highlight past a shrunken list starts over
+        assert_eq!(step_highlight(Some(5), 3, true), Some(0));
+        assert_eq!(step_highlight(Some(1), 0, true), None);
+    }
+}
@@ -2,6 +2,13 @@
 
 mod list;
 mod item;
+mod activity;
+mod labels;
+mod context_menu;
 
 pub use list::ConversationList;
 pub use item::ConversationItem;
+pub use activity::ActivityFeed;
+pub use labels::{LabelEditor, TagChips};
+pub use context_menu::{ConversationContextMenu, ConversationPopupMenu, DeleteConversationDialog};
+pub(crate) use item::format_time_ago;
@@ -0,0 +1,215 @@
+//! Saving a conversation as plain text or JSON
+//!
+//! Exports cover the messages loaded in `ChatState`; older history that was
+//! never paged in is not fetched first. Text exports reference images and
+//! files by name, JSON exports carry them as base64.
+
+use prsnl_core::{base64_decoded_len, Message, MessageSender};
+use serde::Serialize;
+use crate::features::media::format_file_size;
+
+/// File format for an exported conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One `[time] Sender: body` block per message
+    Text,
+    /// The conversation's messages as serialized on the wire
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension (without the dot)
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Json => "json",
+        }
+    }
+
+    /// Mimetype of the exported file
+    pub fn mimetype(self) -> &'static str {
+        match self {
+            Self::Text => "text/plain",
+            Self::Json => "application/json",
+        }
+    }
+
+    /// Name of the format in menus and the save dialog
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Text => "Text",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+/// JSON export document
+#[derive(Serialize)]
+struct ConversationExport<'a> {
+    conversation_id: &'a str,
+    title: &'a str,
+    messages: &'a [Message],
+}
+
+/// Render a conversation in the given format
+pub fn render_export(format: ExportFormat, conv_id: &str, title: &str, messages: &[Message]) -> String {
+    match format {
+        ExportFormat::Text => export_text(title, messages),
+        ExportFormat::Json => export_json(conv_id, title, messages),
+    }
+}
+
+/// Plain-text transcript with sender prefixes and UTC timestamps
+fn export_text(title: &str, messages: &[Message]) -> String {
+    let mut out = format!("{}\n", title);
+    for message in messages {
+        let sender = match message.sender {
+            MessageSender::User => "You",
+            MessageSender::Assistant => "Assistant",
+            MessageSender::System => "System",
+        };
+        out.push_str(&format!(
+            "\n[{}] {}: {}\n",
+            message.timestamp.format("%Y-%m-%d %H:%M UTC"),
+            sender,
+            message.body
+        ));
+        if let Some(image) = &message.image {
+            out.push_str(&format!(
+                "[Image: {}, {}]\n",
+                image.mimetype,
+                format_file_size(base64_decoded_len(&image.data))
+            ));
+        }
+        for file in &message.files {
+            out.push_str(&format!("[File: {}, {}]\n", file.filename, format_file_size(file.size())));
+        }
+    }
+    out
+}
+
+/// Pretty-printed JSON with images and files as base64
+fn export_json(conv_id: &str, title: &str, messages: &[Message]) -> String {
+    let export = ConversationExport { conversation_id: conv_id, title, messages };
+    serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+/// Suggested file name: the title with characters file systems reject replaced
+pub fn export_filename(title: &str, format: ExportFormat) -> String {
+    let stem: String = title
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect();
+    let stem = if stem.is_empty() { "conversation" } else { stem.as_str() };
+    format!("{}.{}", stem, format.extension())
+}
+
+/// Ask where to save the export and write it (desktop)
+///
+/// Returns false if the user cancels or the file can't be written.
+#[cfg(all(not(target_arch = "wasm32"), not(target_os = "android"), feature = "desktop"))]
+pub async fn save_export(filename: String, format: ExportFormat, contents: String) -> bool {
+    use tracing::warn;
+
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .add_filter(format.label(), &[format.extension()])
+        .set_title("Export conversation")
+        .set_file_name(filename)
+        .save_file()
+        .await
+    else {
+        return false;
+    };
+    match file.write(contents.as_bytes()).await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Could not write export to {}: {}", file.file_name(), e);
+            false
+        }
+    }
+}
+
+/// Save an export on Android/Mobile (placeholder - not implemented)
+#[cfg(any(
+    target_os = "android",
+    all(feature = "mobile", not(feature = "desktop"), not(target_arch = "wasm32"))
+))]
+pub async fn save_export(filename: String, _format: ExportFormat, _contents: String) -> bool {
+    use tracing::warn;
+    warn!("Saving {} is not yet implemented for mobile platforms", filename);
+    false
+}
+
+/// Download the export through a Blob and a temporary link (Web)
+#[cfg(target_arch = "wasm32")]
+pub async fn save_export(filename: String, format: ExportFormat, contents: String) -> bool {
+    use crate::shared::{quote, run_script};
+
+    run_script(
+        "save_export",
+        format!(
+            r#"
+            const url = URL.createObjectURL(new Blob([{contents}], {{ type: {mimetype} }}));
+            const link = document.createElement("a");
+            link.href = url;
+            link.download = {filename};
+            document.body.appendChild(link);
+            link.click();
+            link.remove();
+            setTimeout(() => URL.revokeObjectURL(url), 0);
+            "#,
+            contents = quote(&contents),
+            mimetype = quote(format.mimetype()),
+            filename = quote(&filename),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use prsnl_core::{Attachment, ImageData};
+
+    fn message(sender: MessageSender, body: &str) -> Message {
+        let mut message = Message::new_user(body.to_string());
+        message.sender = sender;
+        message.timestamp = Utc.with_ymd_and_hms(2026, 3, 14, 9, 26, 0).unwrap();
+        message
+    }
+
+    #[test]
+    fn test_export_text_and_json_include_attachments() {
+        let mut question = message(MessageSender::User, "What is in this picture?");
+        question.image = Some(ImageData { data: "iVBORw0KGgo=".to_string(), mimetype: "image/png".to_string() });
+        let mut answer = message(MessageSender::Assistant, "A cat.\nSee the notes.");
+        answer.files.push(Attachment {
+            filename: "notes.txt".to_string(),
+            mimetype: "text/plain".to_string(),
+            data: "aGVsbG8=".to_string(),
+        });
+        let messages = [question, answer];
+
+        assert_eq!(
+            render_export(ExportFormat::Text, "c1", "Cats", &messages),
+            "Cats\n\
+             \n[2026-03-14 09:26 UTC] You: What is in this picture?\n\
+             [Image: image/png, 8 B]\n\
+             \n[2026-03-14 09:26 UTC] Assistant: A cat.\nSee the notes.\n\
+             [File: notes.txt, 5 B]\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_export(ExportFormat::Json, "c1", "Cats", &messages)).unwrap();
+        assert_eq!(json["conversation_id"], "c1");
+        assert_eq!(json["messages"][0]["image"]["data"], "iVBORw0KGgo=");
+        assert_eq!(json["messages"][1]["files"][0]["data"], "aGVsbG8=");
+    }
+
+    #[test]
+    fn test_export_filename_replaces_unsafe_characters() {
+        assert_eq!(export_filename("Q3: plans/ideas?", ExportFormat::Text), "Q3_ plans_ideas_.txt");
+        assert_eq!(export_filename("  ", ExportFormat::Json), "conversation.json");
+    }
+}
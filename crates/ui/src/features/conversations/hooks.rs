@@ -0,0 +1,30 @@
+//! Custom hooks for the conversations feature
+
+use dioxus::prelude::*;
+use futures::StreamExt;
+use prsnl_core::{AppEvent, SharedEventBus};
+
+/// Hook to know when startup is done (reactive)
+///
+/// Turns true once `AppEvent::AppReady` is seen: the first conversation list
+/// is applied and every service is subscribed. Useful for holding a splash
+/// screen instead of flashing empty states. The event is replayed, so
+/// components mounted later see it too.
+pub fn use_app_ready() -> ReadSignal<bool> {
+    let event_bus = use_context::<SharedEventBus>();
+    let mut ready = use_signal(|| false);
+
+    use_hook(move || {
+        let mut rx = event_bus.subscribe();
+        spawn(async move {
+            while let Some(event) = rx.next().await {
+                if matches!(event, AppEvent::AppReady) {
+                    ready.set(true);
+                    break;
+                }
+            }
+        });
+    });
+
+    ready.into()
+}
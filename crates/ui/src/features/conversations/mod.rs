@@ -4,20 +4,53 @@
 
 mod state;
 mod service;
+mod activity;
+mod export;
+pub mod hooks;
 pub mod components;
 
-pub use state::{ConversationsState, ViewState};
+pub use state::{ConversationsState, ReadState, ViewState};
 pub use service::ConversationsService;
+pub use activity::{recent_activity, ActivityEntry, ACTIVITY_FEED_LIMIT};
+pub use export::ExportFormat;
 
 use std::sync::Arc;
 use prsnl_core::{EventBus, Transport};
+use crate::features::chat::ChatState;
+use crate::shared::storage;
 
 /// Initialize the conversations feature
 pub fn provide_conversations_feature(
     event_bus: Arc<dyn EventBus>,
     transport: Arc<dyn Transport>,
+    chat: ChatState,
 ) -> (ConversationsState, ConversationsService) {
-    let state = ConversationsState::new();
-    let service = ConversationsService::new(state, event_bus, transport);
+    let mut state = ConversationsState::new();
+    match storage::load_json(service::LABELS_STORAGE_KEY) {
+        Ok(Some(labels)) => state.load_local_labels(labels),
+        Ok(None) => {}
+        Err(e) => storage::warn_in_memory(service::LABELS_STORAGE_KEY, &e),
+    }
+    match storage::load_json(service::TITLES_STORAGE_KEY) {
+        Ok(Some(titles)) => state.load_local_titles(titles),
+        Ok(None) => {}
+        Err(e) => storage::warn_in_memory(service::TITLES_STORAGE_KEY, &e),
+    }
+    match storage::load_json(service::PINNED_STORAGE_KEY) {
+        Ok(Some(pinned)) => state.load_pinned(pinned),
+        Ok(None) => {}
+        Err(e) => storage::warn_in_memory(service::PINNED_STORAGE_KEY, &e),
+    }
+    match storage::load_json(service::ARCHIVED_STORAGE_KEY) {
+        Ok(Some(archived)) => state.load_archived(archived),
+        Ok(None) => {}
+        Err(e) => storage::warn_in_memory(service::ARCHIVED_STORAGE_KEY, &e),
+    }
+    match storage::load_json(service::READ_STATE_STORAGE_KEY) {
+        Ok(Some(read_state)) => state.load_read_state(read_state),
+        Ok(None) => {}
+        Err(e) => storage::warn_in_memory(service::READ_STATE_STORAGE_KEY, &e),
+    }
+    let service = ConversationsService::new(state, chat, event_bus, transport);
     (state, service)
 }
@@ -1,17 +1,40 @@
 //! Conversations feature service
 
 use std::sync::Arc;
+use chrono::Utc;
 use dioxus::prelude::spawn;
 use futures::StreamExt;
 use tracing::info;
 
-use prsnl_core::{AppEvent, EventBus, Transport};
+use prsnl_core::{AppEvent, Conversation, ConversationLabels, EventBus, MessageSender, Transport};
+use crate::features::chat::{ChatState, HISTORY_PAGE_SIZE};
+use crate::shared::{storage, timer};
+use super::export::{export_filename, render_export, save_export, ExportFormat};
 use super::state::ConversationsState;
 
+/// Storage key for locally persisted conversation labels
+pub(crate) const LABELS_STORAGE_KEY: &str = "conversation_labels";
+
+/// Storage key for locally persisted conversation titles
+pub(crate) const TITLES_STORAGE_KEY: &str = "conversation_titles";
+
+/// Storage key for the ordered list of pinned conversation ids
+pub(crate) const PINNED_STORAGE_KEY: &str = "pinned_conversations";
+
+/// Storage key for the archived conversation ids
+pub(crate) const ARCHIVED_STORAGE_KEY: &str = "archived_conversations";
+
+/// Storage key for unread counts and read times
+pub(crate) const READ_STATE_STORAGE_KEY: &str = "conversation_read_state";
+
+/// Seconds to wait for `ConversationCreated` before giving up on a creation
+pub(crate) const CREATE_TIMEOUT_SECS: u64 = 15;
+
 /// Service for managing conversations
 #[derive(Clone)]
 pub struct ConversationsService {
     state: ConversationsState,
+    chat: ChatState,
     event_bus: Arc<dyn EventBus>,
     transport: Arc<dyn Transport>,
 }
@@ -20,28 +43,76 @@ impl ConversationsService {
     /// Create a new conversations service
     pub fn new(
         state: ConversationsState,
+        chat: ChatState,
         event_bus: Arc<dyn EventBus>,
         transport: Arc<dyn Transport>,
     ) -> Self {
-        Self { state, event_bus, transport }
+        Self { state, chat, event_bus, transport }
     }
 
     /// Subscribe to relevant events from the event bus
     pub fn subscribe_to_events(&self) {
         let mut state = self.state;
+        let chat = self.chat;
+        let event_bus = self.event_bus.clone();
         let mut rx = self.event_bus.subscribe();
 
         spawn(async move {
             while let Some(event) = rx.next().await {
                 match event {
                     AppEvent::ConversationsLoaded(conversations) => {
+                        // The first load ends startup. Every service subscribes in the
+                        // same effect, before this task first runs, so all are listening.
+                        let first_load = state.is_loading();
                         state.set_conversations(conversations);
+                        if first_load {
+                            info!("Initial conversations applied, app ready");
+                            event_bus.publish(AppEvent::AppReady);
+                        }
                     }
-                    AppEvent::ConversationCreated { id, title } => {
-                        state.create_conversation(id, title);
+                    AppEvent::ConversationCreated { id, title, reply_to } => {
+                        if let Some(local_id) = state.confirm_conversation(reply_to.as_deref(), id.clone(), title) {
+                            event_bus.publish(AppEvent::ConversationConfirmed { local_id, id });
+                        }
+                    }
+                    AppEvent::RequestFailed { request_id, error } => {
+                        if let Some(local_id) = state.take_creation(&request_id) {
+                            info!("Server refused to create conversation: {}", error);
+                            state.discard_pending_conversation(&local_id);
+                            event_bus.publish(AppEvent::ConversationCreateFailed { local_id, error });
+                        }
                     }
                     AppEvent::ConversationDeleted(id) => {
                         state.delete_conversation(&id);
+                        save_local_labels(&state);
+                        save_local_titles(&state);
+                        save_pinned(&state);
+                        save_archived(&state);
+                        save_read_state(&state);
+                    }
+                    AppEvent::MessageReceived { conv_id, message, .. } => {
+                        let incoming = message.sender != MessageSender::User;
+                        if incoming && state.record_incoming(&conv_id) {
+                            save_read_state(&state);
+                        }
+                    }
+                    AppEvent::MessageDeleted { conv_id, msg_id } => {
+                        // Our own delete is published by local id and echoed by wire id
+                        let msg_id = chat.local_id(&msg_id);
+                        state.remove_message(&conv_id, &msg_id, chat.messages_for(&conv_id));
+                    }
+                    AppEvent::ConversationLabelsChanged { conv_id, labels } => {
+                        state.set_labels(&conv_id, labels);
+                        save_local_labels(&state);
+                    }
+                    AppEvent::ConversationRenamed { conv_id, title } => {
+                        state.rename_conversation(&conv_id, &title);
+                        save_local_titles(&state);
+                    }
+                    AppEvent::ConversationPinned { conv_id, pinned } => {
+                        state.set_pinned(&conv_id, pinned);
+                        save_pinned(&state);
+                        save_archived(&state);
                     }
                     AppEvent::NavigateToList => {
                         state.go_to_list();
@@ -59,28 +130,78 @@ impl ConversationsService {
     pub fn select_conversation(&self, id: &str) {
         info!("Opening conversation: {}", id);
         let mut state = self.state;
+        // Pass on the badge count so the chat can place its unread divider
+        let unread = state.get_conversation(id).map_or(0, |c| c.unread_count);
         state.open_conversation(id);
+        state.mark_read(id, Utc::now());
+        save_read_state(&state);
+        self.event_bus.publish(AppEvent::ConversationOpened { id: id.to_string(), unread });
         self.event_bus.publish(AppEvent::ConversationSelected(id.to_string()));
 
+        // A pending conversation has no history on the server yet
+        if Conversation::is_pending_id(id) {
+            return;
+        }
+
         // Request history for this conversation
         let transport = self.transport.clone();
         let conv_id = id.to_string();
         spawn(async move {
-            if let Err(e) = transport.send_get_history(conv_id, Some(50)).await {
+            if let Err(e) = transport.send_get_history(conv_id, Some(HISTORY_PAGE_SIZE), None).await {
                 info!("Failed to get history: {:?}", e);
             }
         });
     }
 
+    /// Open a conversation and scroll to a specific message
+    pub fn open_message(&self, conv_id: &str, msg_id: &str) {
+        self.select_conversation(conv_id);
+        self.event_bus.publish(AppEvent::NavigateToMessage {
+            conv_id: conv_id.to_string(),
+            msg_id: msg_id.to_string(),
+        });
+    }
+
     /// Create a new conversation
-    pub fn create_conversation(&self, title: Option<String>) {
+    ///
+    /// A pending conversation is shown and opened right away; it takes the
+    /// server's id when `ConversationCreated` answers the request, or is
+    /// removed again if the request can't be sent, the server refuses it,
+    /// or no answer comes within `CREATE_TIMEOUT_SECS`. Returns the pending
+    /// conversation's local id.
+    pub fn create_conversation(&self, title: Option<String>) -> String {
         info!("Creating new conversation");
+        let mut state = self.state;
+        let conv = Conversation::new_pending(title.clone());
+        let local_id = conv.id.clone();
+        state.add_pending_conversation(conv);
+        self.event_bus.publish(AppEvent::ConversationSelected(local_id.clone()));
+
         let transport = self.transport.clone();
+        let event_bus = self.event_bus.clone();
+        let created_id = local_id.clone();
         spawn(async move {
-            if let Err(e) = transport.send_create_conversation(title).await {
-                info!("Failed to create conversation: {:?}", e);
+            match transport.send_create_conversation(title).await {
+                Ok(request_id) => state.track_creation(&request_id, &local_id),
+                Err(e) => {
+                    info!("Failed to create conversation: {:?}", e);
+                    state.discard_pending_conversation(&local_id);
+                    event_bus.publish(AppEvent::ConversationCreateFailed { local_id, error: e.to_string() });
+                    return;
+                }
+            }
+
+            timer::sleep(std::time::Duration::from_secs(CREATE_TIMEOUT_SECS)).await;
+            if state.is_pending(&local_id) {
+                info!("No answer to creating conversation {}", local_id);
+                state.discard_pending_conversation(&local_id);
+                event_bus.publish(AppEvent::ConversationCreateFailed {
+                    local_id,
+                    error: "The server did not answer".to_string(),
+                });
             }
         });
+        created_id
     }
 
     /// Delete a conversation
@@ -95,6 +216,121 @@ impl ConversationsService {
         });
     }
 
+    /// Set the tags and color of a conversation
+    ///
+    /// Labels are saved locally first so they survive restarts even when
+    /// the server doesn't support them yet.
+    pub fn set_labels(&self, conv_id: &str, labels: ConversationLabels) {
+        info!("Setting labels for {}: {:?}", conv_id, labels);
+        let mut state = self.state;
+        state.set_labels(conv_id, labels.clone());
+        save_local_labels(&state);
+
+        let transport = self.transport.clone();
+        let conv_id = conv_id.to_string();
+        spawn(async move {
+            if let Err(e) = transport
+                .send_set_conversation_labels(conv_id, labels.tags, labels.color)
+                .await
+            {
+                info!("Failed to sync conversation labels: {:?}", e);
+            }
+        });
+    }
+
+    /// Give a conversation a new title (blank goes back to the automatic one)
+    ///
+    /// Applied and saved locally first, then sent to the server, which
+    /// echoes it back as `ConversationRenamed`.
+    pub fn rename_conversation(&self, conv_id: &str, title: &str) {
+        let title = title.trim().to_string();
+        info!("Renaming {} to {:?}", conv_id, title);
+        let mut state = self.state;
+        state.rename_conversation(conv_id, &title);
+        save_local_titles(&state);
+        self.event_bus.publish(AppEvent::ConversationRenamed {
+            conv_id: conv_id.to_string(),
+            title: title.clone(),
+        });
+
+        let transport = self.transport.clone();
+        let conv_id = conv_id.to_string();
+        spawn(async move {
+            if let Err(e) = transport.send_rename_conversation(conv_id, title).await {
+                info!("Failed to sync conversation title: {:?}", e);
+            }
+        });
+    }
+
+    /// Mark a conversation unread so it stands out in the list again
+    ///
+    /// This is a local flag only: nothing is sent to the server, so no read
+    /// receipt is affected.
+    pub fn mark_unread(&self, conv_id: &str) {
+        info!("Marking {} unread", conv_id);
+        let mut state = self.state;
+        state.mark_unread(conv_id);
+        save_read_state(&state);
+    }
+
+    /// Pin or unpin a conversation
+    ///
+    /// Saved locally first so the pin holds across reloads, then sent to
+    /// the server, which confirms with `ConversationPinned`.
+    pub fn toggle_pin(&self, conv_id: &str) {
+        info!("Toggling pin for {}", conv_id);
+        let mut state = self.state;
+        let pinned = state.toggle_pin(conv_id);
+        save_pinned(&state);
+        save_archived(&state);
+
+        let transport = self.transport.clone();
+        let conv_id = conv_id.to_string();
+        spawn(async move {
+            if let Err(e) = transport.send_pin_conversation(conv_id, pinned).await {
+                info!("Failed to sync conversation pin: {:?}", e);
+            }
+        });
+    }
+
+    /// Archive a conversation, or bring it back to the list
+    ///
+    /// Local only, like unread marks: the server keeps the conversation as is.
+    pub fn set_archived(&self, conv_id: &str, archived: bool) {
+        info!("{} {}", if archived { "Archiving" } else { "Unarchiving" }, conv_id);
+        let mut state = self.state;
+        state.set_archived(conv_id, archived);
+        save_archived(&state);
+        save_pinned(&state);
+    }
+
+    /// Save a conversation's loaded messages to a file
+    ///
+    /// Asks where to save on desktop and downloads the file on web.
+    pub fn export_conversation(&self, conv_id: &str, format: ExportFormat) {
+        let title = self
+            .state
+            .get_conversation(conv_id)
+            .map(|c| c.title)
+            .unwrap_or_default();
+        let messages = self.chat.messages_for(conv_id);
+        info!("Exporting {} messages of {} as {}", messages.len(), conv_id, format.label());
+        let contents = render_export(format, conv_id, &title, &messages);
+        let filename = export_filename(&title, format);
+        spawn(async move {
+            if save_export(filename.clone(), format, contents).await {
+                info!("Exported conversation to {}", filename);
+            }
+        });
+    }
+
+    /// Move a pinned conversation to a new position within the pinned group
+    pub fn move_pinned(&self, conv_id: &str, to_index: usize) {
+        let mut state = self.state;
+        state.move_pinned(conv_id, to_index);
+        save_pinned(&state);
+    }
+
     /// Go back to conversation list
     pub fn go_back(&self) {
         let mut state = self.state;
@@ -102,3 +338,216 @@ impl ConversationsService {
         self.event_bus.publish(AppEvent::NavigateToList);
     }
 }
+
+/// Persist local labels; on failure they stay in memory for this session
+fn save_local_labels(state: &ConversationsState) {
+    if let Err(e) = storage::save_json(LABELS_STORAGE_KEY, &state.local_labels()) {
+        storage::warn_in_memory(LABELS_STORAGE_KEY, &e);
+    }
+}
+
+/// Persist renamed titles; on failure they stay in memory for this session
+fn save_local_titles(state: &ConversationsState) {
+    if let Err(e) = storage::save_json(TITLES_STORAGE_KEY, &state.local_titles()) {
+        storage::warn_in_memory(TITLES_STORAGE_KEY, &e);
+    }
+}
+
+/// Persist the pinned order; on failure it stays in memory for this session
+fn save_pinned(state: &ConversationsState) {
+    if let Err(e) = storage::save_json(PINNED_STORAGE_KEY, &state.pinned_ids()) {
+        storage::warn_in_memory(PINNED_STORAGE_KEY, &e);
+    }
+}
+
+/// Persist the archive; on failure it stays in memory for this session
+fn save_archived(state: &ConversationsState) {
+    if let Err(e) = storage::save_json(ARCHIVED_STORAGE_KEY, &state.archived_ids()) {
+        storage::warn_in_memory(ARCHIVED_STORAGE_KEY, &e);
+    }
+}
+
+/// Persist unread counts; on failure they stay in memory for this session
+fn save_read_state(state: &ConversationsState) {
+    if let Err(e) = storage::save_json(READ_STATE_STORAGE_KEY, &state.read_state()) {
+        storage::warn_in_memory(READ_STATE_STORAGE_KEY, &e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dioxus::dioxus_core::{ScopeId, VirtualDom};
+    use dioxus::prelude::*;
+    use futures::{FutureExt, StreamExt};
+    use prsnl_core::{AppEvent, Conversation, EventBus, WSClientMessage, WSServerMessage};
+
+    use super::ConversationsService;
+    use crate::features::chat::ChatState;
+    use crate::features::conversations::ConversationsState;
+    use prsnl_core::testing::MockTransport;
+    use crate::test_support::{chat_fixture, sent_chat_ids, settle, ChannelEventBus};
+
+    fn created(conversation_id: &str, reply_to: Option<&str>) -> WSServerMessage {
+        WSServerMessage::ConversationCreated {
+            id: format!("created-{}", conversation_id),
+            timestamp: 0,
+            reply_to: reply_to.map(str::to_string),
+            conversation_id: conversation_id.to_string(),
+            title: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_creation_follows_the_answer_to_its_request() {
+        let bus = Arc::new(ChannelEventBus::new());
+        let transport = Arc::new(MockTransport::new(bus.clone()));
+        let mut events = bus.subscribe();
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+
+        let (state, service, first_id) = dom.in_scope(ScopeId::ROOT, || {
+            let state = ConversationsState::new();
+            let service = ConversationsService::new(state, ChatState::new(), bus.clone(), transport.clone());
+            service.subscribe_to_events();
+            service.create_conversation(Some("Ideas".to_string()));
+            (state, service, state.current_conversation_id().unwrap())
+        });
+        settle(&mut dom).await;
+        let request_id = |transport: &MockTransport| match transport.sent_messages().last() {
+            Some(WSClientMessage::CreateConversation { id, .. }) => id.clone(),
+            other => panic!("expected a create frame, got {:?}", other),
+        };
+        let first_request = request_id(&transport);
+
+        // A conversation created on another client leaves this one pending and open
+        transport.inject(created("native-x", Some("req-elsewhere")));
+        settle(&mut dom).await;
+        dom.in_scope(ScopeId::ROOT, || {
+            assert!(state.get_conversation("native-x").is_some());
+            assert!(state.is_pending(&first_id));
+            assert_eq!(state.current_conversation_id(), Some(first_id.clone()));
+        });
+
+        transport.inject(created("native-a", Some(&first_request)));
+        settle(&mut dom).await;
+        dom.in_scope(ScopeId::ROOT, || {
+            assert_eq!(state.get_conversation("native-a").unwrap().title, "Ideas");
+            assert!(state.get_conversation(&first_id).is_none());
+        });
+
+        // A refused creation is taken back
+        let second_id = dom.in_scope(ScopeId::ROOT, || {
+            service.create_conversation(None);
+            state.current_conversation_id().unwrap()
+        });
+        settle(&mut dom).await;
+        transport.inject(WSServerMessage::Error {
+            id: "err-1".to_string(),
+            timestamp: 0,
+            reply_to: Some(request_id(&transport)),
+            conversation_id: None,
+            code: "limit".to_string(),
+            message: "Too many conversations".to_string(),
+        });
+        settle(&mut dom).await;
+        dom.in_scope(ScopeId::ROOT, || assert!(state.get_conversation(&second_id).is_none()));
+
+        let mut failures = Vec::new();
+        while let Some(Some(event)) = events.next().now_or_never() {
+            if let AppEvent::ConversationCreateFailed { local_id, error } = event {
+                failures.push((local_id, error));
+            }
+        }
+        assert_eq!(failures, [(second_id, "Too many conversations".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_answer_without_request_id_confirms_the_pending_conversation() {
+        let mut f = chat_fixture();
+        let (chat, chat_service) = (f.state, f.service.clone());
+        let (state, local_id) = f.in_scope(|| {
+            let state = ConversationsState::new();
+            let service = ConversationsService::new(state, chat, f.bus.clone(), f.transport.clone());
+            service.subscribe_to_events();
+            (state, service.create_conversation(None))
+        });
+        f.settle().await;
+        f.in_scope(|| chat_service.send_message("held".to_string(), None, None));
+        f.settle().await;
+
+        // This server doesn't echo the request id
+        f.transport.inject(created("native-b", None));
+        f.settle().await;
+
+        f.in_scope(|| {
+            assert!(state.get_conversation(&local_id).is_none());
+            assert_eq!(state.current_conversation_id().as_deref(), Some("native-b"));
+        });
+        let sent: Vec<_> = f
+            .transport
+            .sent_messages()
+            .into_iter()
+            .filter_map(|frame| match frame {
+                WSClientMessage::Chat { conversation_id, body, .. } => Some((conversation_id, body)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sent, [("native-b".to_string(), "held".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_opening_hands_the_badge_count_to_the_chat() {
+        let mut f = chat_fixture();
+        let chat = f.state;
+        f.in_scope(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![Conversation::from_server("native-b".to_string(), None, None, 3)]);
+            state.record_incoming("native-b");
+            state.record_incoming("native-b");
+            let service = ConversationsService::new(state, chat, f.bus.clone(), f.transport.clone());
+            service.select_conversation("native-b");
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            assert_eq!(chat.current_conv_id().as_deref(), Some("native-b"));
+            assert_eq!(chat.unread_on_open("native-b"), 2);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_deleted_message_counts_once_when_echoed() {
+        let mut f = chat_fixture();
+        let (chat, chat_service) = (f.state, f.service.clone());
+        let state = f.in_scope(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![Conversation::from_server("native-a".to_string(), None, None, 2)]);
+            ConversationsService::new(state, chat, f.bus.clone(), f.transport.clone()).subscribe_to_events();
+            chat_service.send_message("one".to_string(), None, None);
+            chat_service.send_message("two".to_string(), None, None);
+            state
+        });
+        f.settle().await;
+        f.in_scope(|| {
+            let last_id = chat.messages_for("native-a")[1].id.clone();
+            chat_service.delete_message(&last_id);
+        });
+        f.settle().await;
+
+        f.transport.inject(WSServerMessage::MessageDeleted {
+            id: "del-1".to_string(),
+            timestamp: 0,
+            conversation_id: "native-a".to_string(),
+            message_id: sent_chat_ids(&f.transport)[1].clone(),
+        });
+        f.settle().await;
+
+        f.in_scope(|| {
+            let conv = state.get_conversation("native-a").unwrap();
+            assert_eq!(conv.message_count, 1);
+            assert_eq!(conv.last_message_preview.as_deref(), Some("one"));
+        });
+    }
+}
@@ -1,8 +1,12 @@
 //! Conversations feature state
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
-use prsnl_core::Conversation;
+use prsnl_core::{Conversation, ConversationLabels, Message};
+use serde::{Deserialize, Serialize};
+use crate::features::chat::ChatState;
+use super::activity::{recent_activity, ActivityEntry};
 
 /// View state for navigation
 #[derive(Debug, Clone, PartialEq)]
@@ -11,12 +15,60 @@ pub enum ViewState {
     Chat(String),
 }
 
+/// Unread bookkeeping persisted locally so badges survive a restart
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadState {
+    pub unread_count: u32,
+    pub last_read_at: Option<DateTime<Utc>>,
+}
+
+impl ReadState {
+    fn apply_to(&self, conv: &mut Conversation) {
+        conv.unread_count = self.unread_count;
+        conv.last_read_at = self.last_read_at;
+    }
+}
+
 /// Internal state for the conversations feature
 #[derive(Debug, Clone)]
 pub struct ConversationsStateInner {
     pub conversations: HashMap<String, Conversation>,
     pub view: ViewState,
     pub loading: bool,
+    /// Locally persisted tags/colors, applied over whatever the server sends
+    pub local_labels: HashMap<String, ConversationLabels>,
+    /// Locally persisted titles from renames, applied over whatever the server sends
+    pub local_titles: HashMap<String, String>,
+    /// Pinned conversation ids in display order (persisted locally)
+    pub pinned: Vec<String>,
+    /// Archived conversation ids (persisted locally)
+    pub archived: HashSet<String>,
+    /// Unread counts and read times by conversation (persisted locally)
+    pub read_state: HashMap<String, ReadState>,
+    /// Messages already dropped from the counts (the server echoes this client's deletes)
+    pub deleted_messages: HashSet<String>,
+    /// Conversations awaiting `ConversationCreated` as (request id,
+    /// temporary id), oldest first
+    pub pending_creations: Vec<(String, String)>,
+}
+
+impl ConversationsStateInner {
+    /// Sync each conversation's `pinned`/`pin_order` and `archived` with the local lists
+    fn apply_local_flags(&mut self) {
+        for conv in self.conversations.values_mut() {
+            conv.archived = self.archived.contains(&conv.id);
+            match self.pinned.iter().position(|id| *id == conv.id) {
+                Some(index) => {
+                    conv.pinned = true;
+                    conv.pin_order = index as u32;
+                }
+                None => {
+                    conv.pinned = false;
+                    conv.pin_order = 0;
+                }
+            }
+        }
+    }
 }
 
 /// State for the conversations feature (wraps a Signal)
@@ -33,6 +85,13 @@ impl ConversationsState {
                 conversations: HashMap::new(),
                 view: ViewState::ConversationList,
                 loading: true,
+                local_labels: HashMap::new(),
+                local_titles: HashMap::new(),
+                pinned: Vec::new(),
+                archived: HashSet::new(),
+                read_state: HashMap::new(),
+                deleted_messages: HashSet::new(),
+                pending_creations: Vec::new(),
             }),
         }
     }
@@ -51,14 +110,71 @@ impl ConversationsState {
         self.inner.read().loading
     }
 
-    /// Get all conversations sorted by most recent first
+    /// Get all conversations: pinned first in their manual order, then the
+    /// rest in server order (`sort_index`) where given, otherwise (and as a
+    /// tiebreak) most recent first; archived ones come last
     pub fn sorted_conversations(&self) -> Vec<Conversation> {
         let inner = self.inner.read();
         let mut convs: Vec<_> = inner.conversations.values().cloned().collect();
-        convs.sort_by(|a, b| b.last_message_time.cmp(&a.last_message_time));
+        convs.sort_by(|a, b| {
+            a.archived
+                .cmp(&b.archived)
+                .then_with(|| b.pinned.cmp(&a.pinned))
+                .then_with(|| match a.pinned {
+                    true => a.pin_order.cmp(&b.pin_order),
+                    false => compare_server_order(a, b),
+                })
+                .then_with(|| b.last_message_time.cmp(&a.last_message_time))
+        });
         convs
     }
 
+    /// Get the unread bookkeeping for all conversations
+    pub fn read_state(&self) -> HashMap<String, ReadState> {
+        self.inner.read().read_state.clone()
+    }
+
+    /// Get pinned conversation ids in display order
+    pub fn pinned_ids(&self) -> Vec<String> {
+        self.inner.read().pinned.clone()
+    }
+
+    /// Get archived conversation ids, sorted
+    pub fn archived_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.inner.read().archived.iter().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Get the latest activity across all conversations, newest first
+    pub fn recent_activity(&self, chat: &ChatState, limit: usize) -> Vec<ActivityEntry> {
+        let conversations: Vec<_> = self.inner.read().conversations.values().cloned().collect();
+        recent_activity(&conversations, |id| chat.latest_message(id), limit)
+    }
+
+    /// Get every tag in use, sorted alphabetically
+    pub fn all_tags(&self) -> Vec<String> {
+        let inner = self.inner.read();
+        let mut tags: Vec<String> = inner
+            .conversations
+            .values()
+            .flat_map(|c| c.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Get the locally persisted labels for all conversations
+    pub fn local_labels(&self) -> HashMap<String, ConversationLabels> {
+        self.inner.read().local_labels.clone()
+    }
+
+    /// Get the locally persisted titles for all renamed conversations
+    pub fn local_titles(&self) -> HashMap<String, String> {
+        self.inner.read().local_titles.clone()
+    }
+
     /// Get a specific conversation
     pub fn get_conversation(&self, id: &str) -> Option<Conversation> {
         self.inner.read().conversations.get(id).cloned()
@@ -93,30 +209,307 @@ impl ConversationsState {
 
     /// Add or update a conversation
     pub fn upsert_conversation(&mut self, conversation: Conversation) {
-        self.inner.write().conversations.insert(conversation.id.clone(), conversation);
+        let mut inner = self.inner.write();
+        inner.conversations.insert(conversation.id.clone(), conversation);
+        inner.apply_local_flags();
     }
 
     /// Set all conversations (from server load)
     pub fn set_conversations(&mut self, conversations: Vec<Conversation>) {
         let mut inner = self.inner.write();
         inner.loading = false;
-        for conv in conversations {
+        for mut conv in conversations {
+            if let Some(labels) = inner.local_labels.get(&conv.id) {
+                conv.set_labels(labels.clone());
+            }
+            if let Some(title) = inner.local_titles.get(&conv.id) {
+                conv.title = title.clone();
+            }
+            if let Some(read) = inner.read_state.get(&conv.id) {
+                read.apply_to(&mut conv);
+            }
             inner.conversations.insert(conv.id.clone(), conv);
         }
+        inner.apply_local_flags();
+    }
+
+    /// Seed the locally persisted unread counts (on startup)
+    pub fn load_read_state(&mut self, read_state: HashMap<String, ReadState>) {
+        let mut inner = self.inner.write();
+        for (id, read) in &read_state {
+            if let Some(conv) = inner.conversations.get_mut(id) {
+                read.apply_to(conv);
+            }
+        }
+        inner.read_state = read_state;
+    }
+
+    /// Count a new message unless its conversation is open
+    ///
+    /// Returns true when the unread count changed.
+    pub fn record_incoming(&mut self, conv_id: &str) -> bool {
+        let mut inner = self.inner.write();
+        if matches!(&inner.view, ViewState::Chat(id) if id == conv_id) {
+            return false;
+        }
+        let read = inner.read_state.entry(conv_id.to_string()).or_default();
+        read.unread_count += 1;
+        let read = read.clone();
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            read.apply_to(conv);
+        }
+        true
+    }
+
+    /// Clear the unread count of a conversation
+    pub fn mark_read(&mut self, conv_id: &str, now: DateTime<Utc>) {
+        let read = ReadState {
+            unread_count: 0,
+            last_read_at: Some(now),
+        };
+        let mut inner = self.inner.write();
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            read.apply_to(conv);
+        }
+        inner.read_state.insert(conv_id.to_string(), read);
     }
 
-    /// Create a new conversation and navigate to it
-    pub fn create_conversation(&mut self, id: String, title: Option<String>) {
+    /// Mark a conversation unread (at least one unread message)
+    pub fn mark_unread(&mut self, conv_id: &str) {
+        let mut inner = self.inner.write();
+        let mut read = inner.read_state.get(conv_id).cloned().unwrap_or_default();
+        read.unread_count = read.unread_count.max(1);
+        read.last_read_at = None;
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            read.apply_to(conv);
+        }
+        inner.read_state.insert(conv_id.to_string(), read);
+    }
+
+    /// Seed the locally persisted pin order (on startup)
+    pub fn load_pinned(&mut self, pinned: Vec<String>) {
+        let mut inner = self.inner.write();
+        inner.pinned = pinned;
+        inner.apply_local_flags();
+    }
+
+    /// Pin a conversation at the end of the pinned group, or unpin it
+    ///
+    /// Returns whether the conversation is pinned now.
+    pub fn toggle_pin(&mut self, conv_id: &str) -> bool {
+        let pinned = !self.inner.read().pinned.iter().any(|id| id == conv_id);
+        self.set_pinned(conv_id, pinned);
+        pinned
+    }
+
+    /// Pin or unpin a conversation; already pinned ones keep their place
+    ///
+    /// Pinning an archived conversation takes it out of the archive.
+    pub fn set_pinned(&mut self, conv_id: &str, pinned: bool) {
+        let mut inner = self.inner.write();
+        let position = inner.pinned.iter().position(|id| id == conv_id);
+        match (position, pinned) {
+            (Some(index), false) => {
+                inner.pinned.remove(index);
+            }
+            (None, true) => {
+                inner.pinned.push(conv_id.to_string());
+                inner.archived.remove(conv_id);
+            }
+            _ => return,
+        }
+        inner.apply_local_flags();
+    }
+
+    /// Seed the locally persisted archive (on startup)
+    pub fn load_archived(&mut self, archived: Vec<String>) {
+        let mut inner = self.inner.write();
+        inner.archived = archived.into_iter().collect();
+        inner.apply_local_flags();
+    }
+
+    /// Archive a conversation (which also unpins it), or bring it back
+    pub fn set_archived(&mut self, conv_id: &str, archived: bool) {
+        let mut inner = self.inner.write();
+        if archived {
+            inner.archived.insert(conv_id.to_string());
+            inner.pinned.retain(|id| id != conv_id);
+        } else {
+            inner.archived.remove(conv_id);
+        }
+        inner.apply_local_flags();
+    }
+
+    /// Move a pinned conversation to a new position within the pinned group
+    pub fn move_pinned(&mut self, conv_id: &str, to_index: usize) {
+        let mut inner = self.inner.write();
+        let Some(from) = inner.pinned.iter().position(|id| id == conv_id) else {
+            return;
+        };
+        let id = inner.pinned.remove(from);
+        let to_index = to_index.min(inner.pinned.len());
+        inner.pinned.insert(to_index, id);
+        inner.apply_local_flags();
+    }
+
+    /// Seed the locally persisted labels (on startup)
+    pub fn load_local_labels(&mut self, labels: HashMap<String, ConversationLabels>) {
+        let mut inner = self.inner.write();
+        for (id, conv_labels) in &labels {
+            if let Some(conv) = inner.conversations.get_mut(id) {
+                conv.set_labels(conv_labels.clone());
+            }
+        }
+        inner.local_labels = labels;
+    }
+
+    /// Set the tags and color of a conversation
+    pub fn set_labels(&mut self, conv_id: &str, labels: ConversationLabels) {
+        let mut inner = self.inner.write();
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            conv.set_labels(labels.clone());
+        }
+        inner.local_labels.insert(conv_id.to_string(), labels);
+    }
+
+    /// Seed the locally persisted titles (on startup)
+    pub fn load_local_titles(&mut self, titles: HashMap<String, String>) {
+        let mut inner = self.inner.write();
+        for (id, title) in &titles {
+            if let Some(conv) = inner.conversations.get_mut(id) {
+                conv.title = title.clone();
+            }
+        }
+        inner.local_titles = titles;
+    }
+
+    /// Give a conversation a new title
+    ///
+    /// A blank title goes back to the automatic one.
+    pub fn rename_conversation(&mut self, conv_id: &str, title: &str) {
+        let title = title.trim().to_string();
+        let mut inner = self.inner.write();
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            conv.title = title.clone();
+        }
+        inner.local_titles.insert(conv_id.to_string(), title);
+    }
+
+    /// Add a conversation created on another client, leaving the view alone
+    pub fn add_conversation(&mut self, id: String, title: Option<String>) {
         let conv = Conversation::new(id.clone(), title);
         let mut inner = self.inner.write();
+        inner.conversations.insert(id, conv);
+        inner.apply_local_flags();
+    }
+
+    /// Show a conversation created on this client and open it
+    pub fn add_pending_conversation(&mut self, conv: Conversation) {
+        let mut inner = self.inner.write();
+        inner.view = ViewState::Chat(conv.id.clone());
+        inner.conversations.insert(conv.id.clone(), conv);
+        inner.apply_local_flags();
+    }
+
+    /// Remember which request asked for a pending conversation
+    pub fn track_creation(&mut self, request_id: &str, local_id: &str) {
+        self.inner
+            .write()
+            .pending_creations
+            .push((request_id.to_string(), local_id.to_string()));
+    }
+
+    /// Check if a conversation created on this client still awaits its server id
+    pub fn is_pending(&self, local_id: &str) -> bool {
+        self.inner.read().conversations.get(local_id).is_some_and(|c| c.pending)
+    }
+
+    /// Take the pending conversation a failed request asked for, if any
+    pub fn take_creation(&mut self, request_id: &str) -> Option<String> {
+        let mut inner = self.inner.write();
+        let index = inner.pending_creations.iter().position(|(id, _)| id == request_id)?;
+        Some(inner.pending_creations.remove(index).1)
+    }
+
+    /// Apply a server-created conversation
+    ///
+    /// When `reply_to` names a creation request from this client, the
+    /// conversation replaces the pending one it asked for, keeping its title
+    /// unless the server sent one, and its temporary id is returned. Without
+    /// `reply_to` it answers the oldest pending creation, if any. Otherwise
+    /// it was created elsewhere and is added without opening it.
+    pub fn confirm_conversation(
+        &mut self,
+        reply_to: Option<&str>,
+        id: String,
+        title: Option<String>,
+    ) -> Option<String> {
+        let mut inner = self.inner.write();
+        let index = match reply_to {
+            Some(request_id) => inner.pending_creations.iter().position(|(pending, _)| pending == request_id),
+            None => (!inner.pending_creations.is_empty()).then_some(0),
+        };
+        let Some(index) = index else {
+            drop(inner);
+            self.add_conversation(id, title);
+            return None;
+        };
+        let (_, local_id) = inner.pending_creations.remove(index);
+        let mut conv = inner
+            .conversations
+            .remove(&local_id)
+            .unwrap_or_else(|| Conversation::new(id.clone(), None));
+        conv.id = id.clone();
+        conv.pending = false;
+        if let Some(title) = title {
+            conv.title = title;
+        }
         inner.conversations.insert(id.clone(), conv);
-        inner.view = ViewState::Chat(id);
+        for pinned_id in inner.pinned.iter_mut().filter(|pinned_id| **pinned_id == local_id) {
+            *pinned_id = id.clone();
+        }
+        if inner.archived.remove(&local_id) {
+            inner.archived.insert(id.clone());
+        }
+        inner.apply_local_flags();
+        if matches!(&inner.view, ViewState::Chat(view_id) if *view_id == local_id) {
+            inner.view = ViewState::Chat(id);
+        }
+        Some(local_id)
+    }
+
+    /// Remove a conversation the server could not be asked to create
+    pub fn discard_pending_conversation(&mut self, local_id: &str) {
+        self.inner.write().pending_creations.retain(|(_, id)| id != local_id);
+        self.delete_conversation(local_id);
+    }
+
+    /// Drop a deleted message from its conversation's count and preview
+    ///
+    /// `loaded` are the conversation's loaded messages, with or without the
+    /// deleted one, which give the new preview. Repeated deletions of the
+    /// same message are ignored.
+    pub fn remove_message(&mut self, conv_id: &str, msg_id: &str, loaded: Vec<Message>) {
+        let mut inner = self.inner.write();
+        if !inner.deleted_messages.insert(msg_id.to_string()) {
+            return;
+        }
+        if let Some(conv) = inner.conversations.get_mut(conv_id) {
+            conv.messages = loaded;
+            conv.remove_message(msg_id);
+        }
     }
 
     /// Delete a conversation
     pub fn delete_conversation(&mut self, id: &str) {
         let mut inner = self.inner.write();
         inner.conversations.remove(id);
+        inner.local_labels.remove(id);
+        inner.local_titles.remove(id);
+        inner.pinned.retain(|pinned_id| pinned_id != id);
+        inner.archived.remove(id);
+        inner.read_state.remove(id);
+        inner.apply_local_flags();
 
         // If viewing the deleted conversation, go back to list
         if matches!(&inner.view, ViewState::Chat(view_id) if view_id == id) {
@@ -125,8 +518,293 @@ impl ConversationsState {
     }
 }
 
+/// Compare by server-assigned position; conversations without one sort after
+fn compare_server_order(a: &Conversation, b: &Conversation) -> std::cmp::Ordering {
+    match (a.sort_index, b.sort_index) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
 impl Default for ConversationsState {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+
+    #[test]
+    fn test_local_labels_override_server() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let local = ConversationLabels {
+                tags: vec!["work".to_string()],
+                color: Some("#ef4444".to_string()),
+            };
+            state.load_local_labels(HashMap::from([("native-a".to_string(), local.clone())]));
+
+            let mut from_server = Conversation::from_server("native-a".to_string(), None, None, 0);
+            from_server.tags = vec!["server".to_string()];
+            let other = Conversation::from_server("native-b".to_string(), None, None, 0);
+            state.set_conversations(vec![from_server, other]);
+
+            assert_eq!(state.get_conversation("native-a").unwrap().labels(), local);
+            assert_eq!(state.all_tags(), vec!["work".to_string()]);
+
+            state.set_labels("native-b", ConversationLabels {
+                tags: vec!["home".to_string()],
+                color: None,
+            });
+            assert!(state.get_conversation("native-b").unwrap().has_tag("home"));
+            assert_eq!(state.local_labels().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_rename_survives_reload_and_blank_title_falls_back() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![Conversation::from_server("native-a12345678".to_string(), None, None, 0)]);
+
+            state.rename_conversation("native-a12345678", "  Trip planning ");
+            assert_eq!(state.get_conversation("native-a12345678").unwrap().display_title(), "Trip planning");
+
+            // A later list from the server still carries the automatic title
+            state.set_conversations(vec![Conversation::from_server("native-a12345678".to_string(), None, None, 0)]);
+            assert_eq!(state.get_conversation("native-a12345678").unwrap().display_title(), "Trip planning");
+
+            state.rename_conversation("native-a12345678", "   ");
+            let fallback = Conversation::from_server("native-a12345678".to_string(), None, None, 0).display_title();
+            assert_eq!(state.get_conversation("native-a12345678").unwrap().display_title(), fallback);
+
+            // A blank title shows the latest message the list carries
+            let listed = Conversation::from_server("native-a12345678".to_string(), Some("See you at 8".to_string()), None, 3);
+            state.set_conversations(vec![listed]);
+            assert_eq!(state.get_conversation("native-a12345678").unwrap().display_title(), "See you at 8");
+        });
+    }
+
+    #[test]
+    fn test_removed_message_counts_once_and_moves_preview() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![Conversation::from_server(
+                "native-a".to_string(),
+                Some("oops".to_string()),
+                None,
+                4,
+            )]);
+            let remaining = vec![Message::new_user("hello".to_string())];
+
+            // Deleted locally, then echoed by the server
+            state.remove_message("native-a", "m4", remaining.clone());
+            state.remove_message("native-a", "m4", remaining);
+
+            let conv = state.get_conversation("native-a").unwrap();
+            assert_eq!(conv.message_count, 3);
+            assert_eq!(conv.last_message_preview.as_deref(), Some("hello"));
+        });
+    }
+
+    #[test]
+    fn test_pinned_conversations_sort_by_pin_order() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![
+                Conversation::from_server("native-a".to_string(), None, Some(1_000), 0),
+                Conversation::from_server("native-b".to_string(), None, Some(2_000), 0),
+                Conversation::from_server("native-c".to_string(), None, Some(3_000), 0),
+                Conversation::from_server("native-d".to_string(), None, Some(4_000), 0),
+            ]);
+            state.toggle_pin("native-a");
+            state.toggle_pin("native-b");
+            state.move_pinned("native-b", 0);
+
+            let ids = |state: &ConversationsState| -> Vec<String> {
+                state.sorted_conversations().into_iter().map(|c| c.id).collect()
+            };
+            assert_eq!(ids(&state), ["native-b", "native-a", "native-d", "native-c"]);
+            assert_eq!(state.get_conversation("native-a").unwrap().pin_order, 1);
+
+            state.toggle_pin("native-b");
+            assert_eq!(ids(&state), ["native-a", "native-d", "native-c", "native-b"]);
+            assert_eq!(state.pinned_ids(), ["native-a"]);
+        });
+    }
+
+    #[test]
+    fn test_server_pin_confirmation_survives_reload() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let convs = || vec![
+                Conversation::from_server("native-a".to_string(), None, Some(1_000), 0),
+                Conversation::from_server("native-b".to_string(), None, Some(2_000), 0),
+                Conversation::from_server("native-c".to_string(), None, Some(3_000), 0),
+            ];
+            state.set_conversations(convs());
+            assert!(state.toggle_pin("native-a"));
+            assert!(state.toggle_pin("native-b"));
+
+            // The echo of an existing pin doesn't move it to the end
+            state.set_pinned("native-a", true);
+            assert_eq!(state.pinned_ids(), ["native-a", "native-b"]);
+
+            state.set_conversations(convs());
+            let ids: Vec<String> = state.sorted_conversations().into_iter().map(|c| c.id).collect();
+            assert_eq!(ids, ["native-a", "native-b", "native-c"]);
+            assert!(state.get_conversation("native-b").unwrap().pinned);
+
+            state.set_pinned("native-a", false);
+            assert!(!state.get_conversation("native-a").unwrap().pinned);
+            assert_eq!(state.pinned_ids(), ["native-b"]);
+        });
+    }
+
+    #[test]
+    fn test_archived_conversations_sort_last_and_unpin() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            state.set_conversations(vec![
+                Conversation::from_server("native-a".to_string(), None, Some(1_000), 0),
+                Conversation::from_server("native-b".to_string(), None, Some(2_000), 0),
+                Conversation::from_server("native-c".to_string(), None, Some(3_000), 0),
+            ]);
+            state.toggle_pin("native-a");
+            state.set_archived("native-a", true);
+            state.set_archived("native-c", true);
+
+            let ids = |state: &ConversationsState| -> Vec<String> {
+                state.sorted_conversations().into_iter().map(|c| c.id).collect()
+            };
+            assert_eq!(ids(&state), ["native-b", "native-c", "native-a"]);
+            assert!(state.pinned_ids().is_empty());
+            assert_eq!(state.archived_ids(), ["native-a", "native-c"]);
+
+            // Pinning brings a conversation back out
+            state.toggle_pin("native-c");
+            assert!(!state.get_conversation("native-c").unwrap().archived);
+            assert_eq!(ids(&state), ["native-c", "native-b", "native-a"]);
+        });
+    }
+
+    #[test]
+    fn test_server_sort_index_takes_precedence_over_recency() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let conv = |id: &str, time: i64, sort_index: Option<i64>| {
+                let mut conv = Conversation::from_server(id.to_string(), None, Some(time), 0);
+                conv.sort_index = sort_index;
+                conv
+            };
+            state.set_conversations(vec![
+                conv("native-a", 1_000, Some(2)),
+                conv("native-b", 2_000, Some(1)),
+                conv("native-c", 3_000, Some(1)),
+                conv("native-d", 4_000, None),
+                conv("native-e", 5_000, None),
+            ]);
+            state.toggle_pin("native-d");
+
+            let ids: Vec<String> = state.sorted_conversations().into_iter().map(|c| c.id).collect();
+            assert_eq!(ids, ["native-d", "native-c", "native-b", "native-a", "native-e"]);
+        });
+    }
+
+    #[test]
+    fn test_pending_conversations_take_server_ids_by_request() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let first = Conversation::new_pending(Some("Ideas".to_string()));
+            let second = Conversation::new_pending(None);
+            let (first_id, second_id) = (first.id.clone(), second.id.clone());
+            state.add_pending_conversation(first);
+            state.add_pending_conversation(second);
+            state.track_creation("req-1", &first_id);
+            state.track_creation("req-2", &second_id);
+            assert_eq!(state.view(), ViewState::Chat(second_id.clone()));
+            assert!(state.is_pending(&first_id));
+
+            // Answers may come in any order
+            assert_eq!(
+                state.confirm_conversation(Some("req-2"), "native-b".to_string(), None),
+                Some(second_id)
+            );
+            assert_eq!(state.view(), ViewState::Chat("native-b".to_string()));
+
+            // Created elsewhere: added without taking a pending conversation or the view
+            assert_eq!(state.confirm_conversation(Some("req-other"), "native-c".to_string(), None), None);
+            assert!(state.get_conversation("native-c").is_some());
+            assert!(state.is_pending(&first_id));
+            assert_eq!(state.view(), ViewState::Chat("native-b".to_string()));
+
+            assert_eq!(
+                state.confirm_conversation(Some("req-1"), "native-a".to_string(), None),
+                Some(first_id.clone())
+            );
+            let confirmed = state.get_conversation("native-a").unwrap();
+            assert_eq!(confirmed.title, "Ideas");
+            assert!(!confirmed.pending);
+            assert!(state.get_conversation(&first_id).is_none());
+            assert_eq!(state.take_creation("req-1"), None);
+        });
+    }
+
+    #[test]
+    fn test_answer_without_request_id_takes_the_oldest_pending_conversation() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let first = Conversation::new_pending(Some("Ideas".to_string()));
+            let second = Conversation::new_pending(None);
+            let (first_id, second_id) = (first.id.clone(), second.id.clone());
+            state.add_pending_conversation(first);
+            state.add_pending_conversation(second);
+            state.track_creation("req-1", &first_id);
+            state.track_creation("req-2", &second_id);
+
+            assert_eq!(state.confirm_conversation(None, "native-a".to_string(), None), Some(first_id));
+            assert_eq!(state.get_conversation("native-a").unwrap().title, "Ideas");
+            assert!(state.is_pending(&second_id));
+            assert_eq!(state.view(), ViewState::Chat(second_id.clone()));
+
+            assert_eq!(state.confirm_conversation(None, "native-b".to_string(), None), Some(second_id));
+            assert_eq!(state.view(), ViewState::Chat("native-b".to_string()));
+            // Nothing left pending: a further creation came from elsewhere
+            assert_eq!(state.confirm_conversation(None, "native-c".to_string(), None), None);
+            assert_eq!(state.view(), ViewState::Chat("native-b".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_unread_counts_survive_server_reload() {
+        with_runtime(|| {
+            let mut state = ConversationsState::new();
+            let conv = || Conversation::from_server("native-a".to_string(), None, None, 0);
+            state.set_conversations(vec![conv()]);
+
+            state.open_conversation("native-a");
+            assert!(!state.record_incoming("native-a"));
+            state.go_to_list();
+            assert!(state.record_incoming("native-a"));
+            assert!(state.record_incoming("native-a"));
+            assert_eq!(state.get_conversation("native-a").unwrap().unread_count, 2);
+
+            state.mark_read("native-a", Utc::now());
+            state.mark_unread("native-a");
+            let read = state.read_state()["native-a"].clone();
+            assert_eq!(read.unread_count, 1);
+            assert_eq!(read.last_read_at, None);
+
+            // A restart reloads the persisted state before the server list arrives
+            let mut restarted = ConversationsState::new();
+            restarted.load_read_state(state.read_state());
+            restarted.set_conversations(vec![conv()]);
+            assert_eq!(restarted.get_conversation("native-a").unwrap().unread_count, 1);
+        });
+    }
+}
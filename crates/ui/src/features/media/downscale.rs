@@ -0,0 +1,188 @@
+//! Shrinking large photos before they are sent
+//!
+//! A full-resolution phone photo is several megabytes of base64 on the
+//! socket and in history. Images over the chosen limits are re-encoded to
+//! fit, keeping their aspect ratio: with the `image` crate on native
+//! (`image-resize` feature) and a canvas on web. Animated GIFs are left as
+//! they are.
+
+use prsnl_core::AttachmentKind;
+use tracing::info;
+use super::types::SelectedMedia;
+
+/// Largest image the client sends without scaling it down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    /// Longest side in pixels
+    pub max_dimension: u32,
+    /// Encoded size in bytes
+    pub max_bytes: usize,
+}
+
+/// JPEG quality for re-encoded photos
+#[cfg_attr(not(any(target_arch = "wasm32", feature = "image-resize")), allow(dead_code))]
+const JPEG_QUALITY: u8 = 85;
+
+/// Scale a picked image down to the limits
+///
+/// Files that are not images, GIFs, and images already within the limits
+/// come back unchanged, as does anything that fails to re-encode.
+pub async fn fit_image(media: SelectedMedia, limits: ImageLimits) -> SelectedMedia {
+    if media.kind() != AttachmentKind::Image || media.mimetype == "image/gif" {
+        return media;
+    }
+    let before = media.size();
+    match reencode(&media, limits).await {
+        Some(smaller) => {
+            info!(
+                "Downscaled {} from {} to {} bytes",
+                media.filename,
+                before,
+                smaller.size()
+            );
+            smaller
+        }
+        None => media,
+    }
+}
+
+/// Size of an image scaled to fit `max_dimension` on its longest side
+#[cfg_attr(not(feature = "image-resize"), allow(dead_code))]
+fn fit_within(width: u32, height: u32, max_dimension: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max_dimension || longest == 0 {
+        return (width, height);
+    }
+    let scale = |side: u32| ((u64::from(side) * u64::from(max_dimension) / u64::from(longest)) as u32).max(1);
+    (scale(width), scale(height))
+}
+
+/// Re-encode with the `image` crate; None when the image already fits
+#[cfg(all(feature = "image-resize", not(target_arch = "wasm32")))]
+async fn reencode(media: &SelectedMedia, limits: ImageLimits) -> Option<SelectedMedia> {
+    use std::io::Cursor;
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+    use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, ImageFormat, ImageReader};
+    use tracing::warn;
+
+    let bytes = BASE64.decode(media.data.trim()).ok()?;
+    let (width, height) = ImageReader::new(Cursor::new(&bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    if bytes.len() <= limits.max_bytes && width.max(height) <= limits.max_dimension {
+        return None;
+    }
+
+    let image = match image::load_from_memory(&bytes) {
+        Ok(image) => image,
+        Err(e) => {
+            warn!("Could not decode {} for downscaling: {}", media.filename, e);
+            return None;
+        }
+    };
+    // Keep transparency in PNGs; everything else becomes a JPEG
+    let keep_png = media.mimetype == "image/png" && image.color().has_alpha();
+
+    let mut max_dimension = limits.max_dimension;
+    loop {
+        let (w, h) = fit_within(width, height, max_dimension);
+        let scaled = if (w, h) == (width, height) {
+            image.clone()
+        } else {
+            image.resize(w, h, FilterType::Triangle)
+        };
+        let mut out = Vec::new();
+        let encoded = if keep_png {
+            scaled.write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+        } else {
+            scaled.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))
+        };
+        if let Err(e) = encoded {
+            warn!("Could not re-encode {}: {}", media.filename, e);
+            return None;
+        }
+        // Still too heavy: try again at three quarters the size
+        if out.len() > limits.max_bytes && w.max(h) > 256 {
+            max_dimension = w.max(h) * 3 / 4;
+            continue;
+        }
+        info!("Resized {} from {}x{} to {}x{}", media.filename, width, height, w, h);
+        return (out.len() < bytes.len()).then(|| SelectedMedia {
+            data: BASE64.encode(&out),
+            mimetype: if keep_png { "image/png" } else { "image/jpeg" }.to_string(),
+            filename: media.filename.clone(),
+        });
+    }
+}
+
+/// Re-encode on a canvas; None when the image already fits
+#[cfg(target_arch = "wasm32")]
+async fn reencode(media: &SelectedMedia, limits: ImageLimits) -> Option<SelectedMedia> {
+    use dioxus::prelude::document;
+    use crate::shared::{has_document, quote};
+
+    if !has_document() {
+        return None;
+    }
+
+    // Resolves to null when the image fits, else the smaller data URL
+    let script = format!(
+        r#"
+        const img = new Image();
+        img.onload = () => {{
+            const maxBytes = {max_bytes}, src = img.src;
+            const fits = (url) => (url.length - url.indexOf(",") - 1) * 3 / 4 <= maxBytes;
+            let max = {max_dimension};
+            if (fits(src) && Math.max(img.naturalWidth, img.naturalHeight) <= max) {{
+                dioxus.send(null);
+                return;
+            }}
+            const png = {keep_png};
+            let url;
+            for (;;) {{
+                const scale = Math.min(1, max / Math.max(img.naturalWidth, img.naturalHeight));
+                const canvas = document.createElement("canvas");
+                canvas.width = Math.max(1, Math.round(img.naturalWidth * scale));
+                canvas.height = Math.max(1, Math.round(img.naturalHeight * scale));
+                canvas.getContext("2d").drawImage(img, 0, 0, canvas.width, canvas.height);
+                url = png ? canvas.toDataURL("image/png") : canvas.toDataURL("image/jpeg", {quality});
+                if (fits(url) || Math.max(canvas.width, canvas.height) <= 256) break;
+                max = Math.floor(Math.max(canvas.width, canvas.height) * 3 / 4);
+            }}
+            dioxus.send(url.length < src.length ? url : null);
+        }};
+        img.onerror = () => dioxus.send(null);
+        img.src = {src};
+        "#,
+        max_bytes = limits.max_bytes,
+        max_dimension = limits.max_dimension,
+        keep_png = media.mimetype == "image/png",
+        quality = f64::from(JPEG_QUALITY) / 100.0,
+        src = quote(&format!("data:{};base64,{}", media.mimetype, media.data)),
+    );
+    let url: Option<String> = document::eval(&script).recv().await.ok()?;
+    let (header, data) = url?.split_once(',').map(|(h, d)| (h.to_string(), d.to_string()))?;
+    let mimetype = header.strip_prefix("data:")?.strip_suffix(";base64")?.to_string();
+    Some(SelectedMedia { data, mimetype, filename: media.filename.clone() })
+}
+
+/// Builds without an encoder send images as picked
+#[cfg(not(any(target_arch = "wasm32", feature = "image-resize")))]
+async fn reencode(_media: &SelectedMedia, _limits: ImageLimits) -> Option<SelectedMedia> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_within_keeps_aspect_ratio() {
+        assert_eq!(fit_within(4032, 3024, 2048), (2048, 1536));
+        assert_eq!(fit_within(3024, 4032, 1280), (960, 1280));
+        assert_eq!(fit_within(800, 600, 2048), (800, 600));
+        assert_eq!(fit_within(10_000, 1, 100), (100, 1));
+    }
+}
@@ -0,0 +1,54 @@
+//! Images dropped onto the chat from the desktop or browser
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use dioxus::html::FileData;
+use super::picker::get_mimetype_from_filename;
+use super::types::SelectedMedia;
+
+/// Read a dropped file as an image attachment
+///
+/// Dioxus hands over dropped files the same way on web (from the drop's
+/// `DataTransfer`) and desktop (from the window's file-drop event). Files
+/// that are not images are rejected with a message for the user.
+pub async fn read_dropped_image(file: FileData) -> Result<SelectedMedia, String> {
+    let filename = file.name();
+    let mimetype = image_mimetype(file.content_type(), &filename)?;
+    let data = file
+        .read_bytes()
+        .await
+        .map_err(|e| format!("Could not read {}: {}", filename, e))?;
+
+    Ok(SelectedMedia {
+        data: BASE64.encode(&data),
+        mimetype,
+        filename,
+    })
+}
+
+/// MIME type of a dropped file, or an error if it is not an image
+///
+/// Desktop drops carry no content type, so the extension decides there.
+fn image_mimetype(content_type: Option<String>, filename: &str) -> Result<String, String> {
+    let mimetype = content_type
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| get_mimetype_from_filename(filename));
+    if mimetype.starts_with("image/") {
+        Ok(mimetype)
+    } else {
+        Err(format!("{} is not an image", filename))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_images_are_accepted() {
+        assert_eq!(image_mimetype(Some("image/png".to_string()), "a.bin"), Ok("image/png".to_string()));
+        assert_eq!(image_mimetype(None, "photo.JPG"), Ok("image/jpeg".to_string()));
+        assert_eq!(image_mimetype(Some(String::new()), "cat.webp"), Ok("image/webp".to_string()));
+        assert!(image_mimetype(Some("application/pdf".to_string()), "doc.pdf").is_err());
+        assert!(image_mimetype(None, "notes.txt").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Compact card for an attached file
+
+use dioxus::prelude::*;
+use prsnl_core::AttachmentKind;
+
+/// Icon, name and size of an attached file
+///
+/// With `data` the chip links to the file so it can be opened or saved;
+/// without it (e.g. while composing) it is only a label.
+#[component]
+pub fn FileChip(
+    filename: String,
+    mimetype: String,
+    size: usize,
+    /// Base64 contents of the file, for the open/download link
+    #[props(default)]
+    data: Option<String>,
+) -> Element {
+    let icon = match AttachmentKind::from_mimetype(&mimetype) {
+        AttachmentKind::Document => "📄",
+        _ => "📎",
+    };
+    let size = format_file_size(size);
+    let label = rsx! {
+        span { class: "text-lg leading-none shrink-0", "{icon}" }
+        span {
+            class: "flex-1 min-w-0 flex flex-col",
+            span {
+                class: "text-sm overflow-hidden text-ellipsis whitespace-nowrap",
+                "{filename}"
+            }
+            span { class: "text-xs opacity-70", "{size}" }
+        }
+    };
+
+    match data {
+        Some(data) => rsx! {
+            a {
+                href: "data:{mimetype};base64,{data}",
+                download: "{filename}",
+                target: "_blank",
+                title: "Open {filename}",
+                onclick: move |e: MouseEvent| e.stop_propagation(),
+                class: "flex items-center gap-2 max-w-[260px] p-2 rounded-lg bg-black/15 text-inherit no-underline hover:bg-black/25 transition-colors",
+                {label}
+            }
+        },
+        None => rsx! {
+            div {
+                class: "flex items-center gap-2 max-w-[260px] p-2 rounded-lg bg-bg-tertiary text-text-white",
+                {label}
+            }
+        },
+    }
+}
+
+/// Human-readable file size ("512 B", "1.5 KB", "3.2 MB")
+pub fn format_file_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KB {
+        format!("{} B", bytes)
+    } else if bytes_f < KB * KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{:.1} MB", bytes_f / (KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(3 * 1024 * 1024 + 200 * 1024), "3.2 MB");
+    }
+}
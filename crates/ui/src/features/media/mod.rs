@@ -1,11 +1,22 @@
 //! Media feature module
 //!
-//! This feature handles media selection, preview, and processing.
+//! This feature handles media selection, preview, and processing. Images
+//! are sent inline; other files (documents) travel as attachments.
 
 mod types;
 mod picker;
 mod preview;
+mod validate;
+mod downscale;
+mod file_chip;
+mod drop;
+mod paste;
 
 pub use types::SelectedMedia;
-pub use picker::pick_image;
+pub use picker::{pick_file, FileFilter};
 pub use preview::MediaPreview;
+pub use file_chip::{format_file_size, FileChip};
+pub use validate::check_image;
+pub use downscale::{fit_image, ImageLimits};
+pub use drop::read_dropped_image;
+pub use paste::read_pasted_image;
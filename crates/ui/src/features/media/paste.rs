@@ -0,0 +1,71 @@
+//! Images pasted into the message input
+
+use std::future::Future;
+use dioxus::prelude::*;
+use super::types::SelectedMedia;
+
+// Base64 encoding only needed for desktop and web implementations
+#[cfg(any(feature = "desktop", target_arch = "wasm32"))]
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+
+/// Start reading the clipboard image of a paste, if it has one (desktop, via arboard)
+#[cfg(all(not(target_arch = "wasm32"), not(target_os = "android"), feature = "desktop"))]
+pub fn read_pasted_image(_event: &ClipboardEvent) -> Option<impl Future<Output = Option<SelectedMedia>>> {
+    use tracing::warn;
+
+    let pasted = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_image()).ok()?;
+    Some(async move {
+        // arboard hands over raw RGBA; send it as a PNG
+        let rgba = image::RgbaImage::from_raw(pasted.width as u32, pasted.height as u32, pasted.bytes.into_owned())?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        if let Err(e) = rgba.write_to(&mut png, image::ImageFormat::Png) {
+            warn!("Could not encode pasted image: {}", e);
+            return None;
+        }
+        Some(SelectedMedia {
+            data: BASE64.encode(png.into_inner()),
+            mimetype: "image/png".to_string(),
+            filename: "pasted-image.png".to_string(),
+        })
+    })
+}
+
+/// Pasting images on Android/Mobile (placeholder - not implemented)
+#[cfg(any(
+    target_os = "android",
+    all(feature = "mobile", not(feature = "desktop"), not(target_arch = "wasm32"))
+))]
+pub fn read_pasted_image(_event: &ClipboardEvent) -> Option<impl Future<Output = Option<SelectedMedia>>> {
+    None::<std::future::Ready<Option<SelectedMedia>>>
+}
+
+/// Start reading the image a paste carries, if any (web, from its `DataTransferItem`s)
+#[cfg(target_arch = "wasm32")]
+pub fn read_pasted_image(event: &ClipboardEvent) -> Option<impl Future<Output = Option<SelectedMedia>>> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    // The clipboard contents are only readable while the event is dispatched
+    let event = event.data().downcast::<web_sys::Event>()?.clone();
+    let items = event.dyn_ref::<web_sys::ClipboardEvent>()?.clipboard_data()?.items();
+    let file = (0..items.length())
+        .filter_map(|i| items.get(i))
+        .find(|item| item.kind() == "file" && item.type_().starts_with("image/"))?
+        .get_as_file()
+        .ok()??;
+    let bytes = JsFuture::from(file.array_buffer());
+
+    Some(async move {
+        let buffer = bytes.await.ok()?;
+        let data = js_sys::Uint8Array::new(&buffer).to_vec();
+        let filename = match file.name() {
+            name if name.is_empty() => "pasted-image".to_string(),
+            name => name,
+        };
+        Some(SelectedMedia {
+            data: BASE64.encode(&data),
+            mimetype: file.type_(),
+            filename,
+        })
+    })
+}
@@ -1,12 +1,54 @@
-//! Media picker for cross-platform image selection
+//! Media picker for cross-platform file selection
 
 use super::types::SelectedMedia;
 
+/// Kinds of file offered by the picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFilter {
+    Images,
+    /// PDFs, text and office documents
+    Documents,
+    /// Images and documents
+    All,
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "txt", "md", "csv", "json", "rtf", "doc", "docx"];
+
+impl FileFilter {
+    /// File extensions to offer (without the dot)
+    pub fn extensions(self) -> Vec<&'static str> {
+        match self {
+            Self::Images => IMAGE_EXTENSIONS.to_vec(),
+            Self::Documents => DOCUMENT_EXTENSIONS.to_vec(),
+            Self::All => [IMAGE_EXTENSIONS, DOCUMENT_EXTENSIONS].concat(),
+        }
+    }
+
+    /// Name of the filter in the file dialog
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Images => "Images",
+            Self::Documents => "Documents",
+            Self::All => "Images and documents",
+        }
+    }
+
+    /// Value for a file input's `accept` attribute
+    pub fn accept(self) -> String {
+        self.extensions()
+            .iter()
+            .map(|ext| format!(".{}", ext))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
 // Base64 encoding only needed for desktop and web implementations
 #[cfg(any(feature = "desktop", target_arch = "wasm32"))]
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
-/// Pick an image file using the native file picker (desktop only)
+/// Pick a file using the native file picker (desktop only)
 /// Returns None if the user cancels or an error occurs
 ///
 /// This only compiles when:
@@ -14,13 +56,13 @@ use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 /// - Not targeting Android (Android has its own implementation)
 /// - The `desktop` feature is enabled (which enables `rfd`)
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android"), feature = "desktop"))]
-pub async fn pick_image() -> Option<SelectedMedia> {
+pub async fn pick_file(filter: FileFilter) -> Option<SelectedMedia> {
     use tracing::info;
 
     // Use rfd for native file picking
     let file = rfd::AsyncFileDialog::new()
-        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp"])
-        .set_title("Select an image")
+        .add_filter(filter.label(), &filter.extensions())
+        .set_title("Select a file")
         .pick_file()
         .await?;
 
@@ -41,7 +83,7 @@ pub async fn pick_image() -> Option<SelectedMedia> {
     })
 }
 
-/// Pick an image file on Android/Mobile (placeholder - not implemented)
+/// Pick a file on Android/Mobile (placeholder - not implemented)
 /// Android requires JNI calls or Activity intents for file picking
 /// This compiles for:
 /// - Android target
@@ -50,16 +92,16 @@ pub async fn pick_image() -> Option<SelectedMedia> {
     target_os = "android",
     all(feature = "mobile", not(feature = "desktop"), not(target_arch = "wasm32"))
 ))]
-pub async fn pick_image() -> Option<SelectedMedia> {
+pub async fn pick_file(_filter: FileFilter) -> Option<SelectedMedia> {
     use tracing::warn;
-    warn!("Image picker not yet implemented for mobile platforms");
+    warn!("File picker not yet implemented for mobile platforms");
     // TODO: Implement Android file picker using JNI/Activity intents
     None
 }
 
-/// Pick an image file using web file input
+/// Pick a file using web file input
 #[cfg(target_arch = "wasm32")]
-pub async fn pick_image() -> Option<SelectedMedia> {
+pub async fn pick_file(filter: FileFilter) -> Option<SelectedMedia> {
     use wasm_bindgen::JsCast;
     use web_sys::{window, HtmlInputElement, File, FileReader};
     use tracing::info;
@@ -75,7 +117,7 @@ pub async fn pick_image() -> Option<SelectedMedia> {
         .ok()?;
 
     input.set_type("file");
-    input.set_accept("image/*");
+    input.set_accept(&filter.accept());
 
     // Trigger the file picker
     input.click();
@@ -143,9 +185,7 @@ pub async fn pick_image() -> Option<SelectedMedia> {
 }
 
 /// Get MIME type from filename extension
-/// Only used by desktop and web implementations
-#[cfg(any(feature = "desktop", target_arch = "wasm32"))]
-fn get_mimetype_from_filename(filename: &str) -> String {
+pub(super) fn get_mimetype_from_filename(filename: &str) -> String {
     let ext = filename
         .rsplit('.')
         .next()
@@ -159,6 +199,14 @@ fn get_mimetype_from_filename(filename: &str) -> String {
         "webp" => "image/webp",
         "bmp" => "image/bmp",
         "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "rtf" => "application/rtf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
         _ => "application/octet-stream",
     }.to_string()
 }
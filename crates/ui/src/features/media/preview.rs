@@ -1,14 +1,35 @@
 //! Media preview component
 
 use dioxus::prelude::*;
+use prsnl_core::AttachmentKind;
+use super::file_chip::FileChip;
 use super::types::SelectedMedia;
 
 /// Preview of selected media with remove button
+///
+/// Images get a thumbnail; other files a chip with their name and size.
 #[component]
 pub fn MediaPreview(
     media: SelectedMedia,
     on_remove: EventHandler<()>,
 ) -> Element {
+    if media.kind() != AttachmentKind::Image {
+        return rsx! {
+            div {
+                class: "shrink-0 py-2 px-4 bg-bg-secondary border-t border-border flex items-center gap-3",
+                div {
+                    class: "flex-1 min-w-0",
+                    FileChip {
+                        filename: media.filename.clone(),
+                        mimetype: media.mimetype.clone(),
+                        size: media.size(),
+                    }
+                }
+                RemoveButton { on_remove }
+            }
+        };
+    }
+
     rsx! {
         div {
             class: "shrink-0 py-2 px-4 bg-bg-secondary border-t border-border flex items-center gap-3",
@@ -40,12 +61,19 @@ pub fn MediaPreview(
                 }
             }
 
-            // Remove button
-            button {
-                onclick: move |_| on_remove.call(()),
-                class: "bg-error border-none rounded-full w-8 h-8 text-text-white cursor-pointer flex items-center justify-center shrink-0 hover:opacity-80 transition-opacity",
-                "x"
-            }
+            RemoveButton { on_remove }
+        }
+    }
+}
+
+/// Round button that discards the selected media
+#[component]
+fn RemoveButton(on_remove: EventHandler<()>) -> Element {
+    rsx! {
+        button {
+            onclick: move |_| on_remove.call(()),
+            class: "bg-error border-none rounded-full w-8 h-8 text-text-white cursor-pointer flex items-center justify-center shrink-0 hover:opacity-80 transition-opacity",
+            "x"
         }
     }
 }
@@ -1,5 +1,7 @@
 //! Media types
 
+use prsnl_core::{base64_decoded_len, AttachmentKind};
+
 /// Selected media from the file picker
 #[derive(Debug, Clone, PartialEq)]
 pub struct SelectedMedia {
@@ -7,3 +9,15 @@ pub struct SelectedMedia {
     pub mimetype: String,
     pub filename: String,
 }
+
+impl SelectedMedia {
+    /// Images are sent inline; anything else goes as an attached file
+    pub fn kind(&self) -> AttachmentKind {
+        AttachmentKind::from_mimetype(&self.mimetype)
+    }
+
+    /// Size of the file in bytes
+    pub fn size(&self) -> usize {
+        base64_decoded_len(&self.data)
+    }
+}
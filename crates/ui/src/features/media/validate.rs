@@ -0,0 +1,24 @@
+//! Validation of incoming image payloads before rendering
+
+use prsnl_core::ImageData;
+
+/// Check that an incoming image is safe to render as a `data:` URI
+///
+/// Always validates the base64 payload and file signature. With the
+/// `image-verify` feature on native, the image is also fully decoded.
+pub fn check_image(image: &ImageData) -> Result<(), String> {
+    let bytes = image.decode().map_err(|e| e.to_string())?;
+    verify_decodes(&bytes)
+}
+
+#[cfg(all(feature = "image-verify", not(target_arch = "wasm32")))]
+fn verify_decodes(bytes: &[u8]) -> Result<(), String> {
+    image::load_from_memory(bytes)
+        .map(|_| ())
+        .map_err(|e| format!("image failed to decode: {}", e))
+}
+
+#[cfg(not(all(feature = "image-verify", not(target_arch = "wasm32"))))]
+fn verify_decodes(_bytes: &[u8]) -> Result<(), String> {
+    Ok(())
+}
@@ -6,12 +6,21 @@ pub mod chat;
 pub mod conversations;
 pub mod settings;
 pub mod media;
+pub mod speech;
+pub mod notifications;
+pub mod search;
 
 // Re-export commonly used types
-pub use chat::{ChatService, ChatState, provide_chat_feature};
-pub use chat::components::{ChatScreen, ChatHeader, MessageList, MessageBubble, MessageInput, TypingIndicator};
+pub use chat::{ChatService, ChatState, DEFAULT_MESSAGE_LIMIT, provide_chat_feature};
+pub use chat::components::{ChatScreen, ChatHeader, MessageList, MessageBubble, MessageInput, TypingIndicator, TypingSummary, MessageAction, MessageFilter};
 pub use conversations::{ConversationsService, ConversationsState, ViewState, provide_conversations_feature};
 pub use conversations::components::{ConversationList, ConversationItem};
-pub use settings::{SettingsService, SettingsState, provide_settings_feature};
-pub use settings::components::ServerUrlModal;
-pub use media::{MediaPreview, SelectedMedia, pick_image};
+pub use conversations::hooks::use_app_ready;
+pub use settings::{Density, ImageSizeLimit, IndicatorPlacement, SendButtonStyle, ThemeMode, TimestampVisibility, TypingStyle, SettingsService, SettingsState, provide_settings_feature};
+pub use settings::components::{ConnectLinkPrompt, EditableConnectionIndicator, ServerUrlModal, ServerUrlPopover, SettingsModal};
+pub use media::{FileChip, FileFilter, MediaPreview, SelectedMedia, pick_file};
+pub use speech::{SpeechService, provide_speech_feature};
+pub use notifications::{NotificationsService, NotificationsState, provide_notifications_feature};
+pub use notifications::components::{NotificationToast, ToastStack};
+pub use search::{SearchService, SearchState, provide_search_feature};
+pub use search::components::{SearchBar, SearchResults};
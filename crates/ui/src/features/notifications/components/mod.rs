@@ -0,0 +1,5 @@
+//! Notification UI components
+
+mod toast;
+
+pub use toast::{NotificationToast, ToastStack};
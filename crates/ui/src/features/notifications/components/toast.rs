@@ -0,0 +1,109 @@
+//! Toast stack component
+
+use dioxus::prelude::*;
+use crate::features::conversations::ConversationsService;
+use crate::features::notifications::{Notice, NotificationsService, NotificationsState};
+
+/// Stack of server notifications and unread-message toasts
+///
+/// Tapping a message toast opens its conversation; the close buttons only dismiss.
+#[component]
+pub fn ToastStack() -> Element {
+    let state: NotificationsState = use_context();
+    let service: NotificationsService = use_context();
+    let conv_service: ConversationsService = use_context();
+
+    let toasts = state.toasts();
+    let notices = state.notices();
+    if toasts.is_empty() && notices.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "fixed top-4 right-4 left-4 sm:left-auto sm:w-80 z-[900] flex flex-col gap-2 pointer-events-none",
+            for notice in notices {
+                NotificationToast {
+                    key: "notice-{notice.id}",
+                    notice: notice.clone(),
+                    on_dismiss: {
+                        let service = service.clone();
+                        let id = notice.id;
+                        move |_| service.dismiss_notice(id)
+                    },
+                }
+            }
+            for toast in toasts {
+                div {
+                    key: "{toast.id}",
+                    onclick: {
+                        let conv_service = conv_service.clone();
+                        let conv_id = toast.conv_id.clone();
+                        move |_| conv_service.select_conversation(&conv_id)
+                    },
+                    class: "pointer-events-auto flex items-start gap-3 p-3 rounded-xl bg-bg-tertiary border border-border-light shadow-2xl cursor-pointer animate-slide-in hover:bg-bg-hover",
+                    div {
+                        class: "flex-1 min-w-0",
+                        div {
+                            class: "text-text-white text-sm font-medium truncate",
+                            "{toast.conv_title}"
+                        }
+                        div {
+                            class: "text-text-secondary text-sm truncate",
+                            "{toast.summary()}"
+                        }
+                    }
+                    button {
+                        onclick: {
+                            let service = service.clone();
+                            let id = toast.id;
+                            move |e: MouseEvent| {
+                                e.stop_propagation();
+                                service.dismiss(id);
+                            }
+                        },
+                        class: "bg-transparent border-none text-text-muted cursor-pointer text-lg leading-none p-0",
+                        title: "Dismiss",
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Toast for a server notification
+#[component]
+pub fn NotificationToast(notice: Notice, on_dismiss: EventHandler<()>) -> Element {
+    rsx! {
+        div {
+            class: "pointer-events-auto flex items-start gap-3 p-3 rounded-xl bg-bg-tertiary border border-border-light shadow-2xl animate-slide-in",
+            div {
+                class: "flex-1 min-w-0",
+                div {
+                    class: "flex items-baseline gap-2",
+                    span {
+                        class: "text-text-white text-sm font-medium truncate",
+                        "{notice.title}"
+                    }
+                    if !notice.category.is_empty() {
+                        span {
+                            class: "shrink-0 text-text-muted text-xs uppercase tracking-wide",
+                            "{notice.category}"
+                        }
+                    }
+                }
+                div {
+                    class: "text-text-secondary text-sm line-clamp-2",
+                    "{notice.body}"
+                }
+            }
+            button {
+                onclick: move |_| on_dismiss.call(()),
+                class: "bg-transparent border-none text-text-muted cursor-pointer text-lg leading-none p-0",
+                title: "Dismiss",
+                "×"
+            }
+        }
+    }
+}
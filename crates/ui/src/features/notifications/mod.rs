@@ -0,0 +1,27 @@
+//! Notifications feature module
+//!
+//! This feature shows in-app toasts for messages arriving in conversations
+//! other than the open one, one toast per conversation, and for
+//! notifications pushed by the server, which dismiss themselves.
+
+mod state;
+mod service;
+pub mod components;
+
+pub use state::{Notice, NotificationsState, Toast, MAX_NOTICES, NOTICE_DURATION_SECS};
+pub use service::NotificationsService;
+
+use prsnl_core::SharedEventBus;
+use crate::features::chat::ChatState;
+use crate::features::conversations::ConversationsState;
+
+/// Initialize the notifications feature
+pub fn provide_notifications_feature(
+    event_bus: SharedEventBus,
+    chat: ChatState,
+    conversations: ConversationsState,
+) -> (NotificationsState, NotificationsService) {
+    let state = NotificationsState::new();
+    let service = NotificationsService::new(state, chat, conversations, event_bus);
+    (state, service)
+}
@@ -0,0 +1,107 @@
+//! Notifications feature service
+
+use chrono::{Duration, Utc};
+use dioxus::prelude::spawn;
+use futures::StreamExt;
+use tracing::info;
+
+use prsnl_core::{AppEvent, MessageSender, SharedEventBus};
+use crate::features::chat::ChatState;
+use crate::features::conversations::ConversationsState;
+use crate::shared::timer;
+use super::state::{NotificationsState, NOTICE_DURATION_SECS};
+
+/// Service that turns messages in background conversations and server
+/// notifications into toasts
+#[derive(Clone)]
+pub struct NotificationsService {
+    state: NotificationsState,
+    chat: ChatState,
+    conversations: ConversationsState,
+    event_bus: SharedEventBus,
+}
+
+impl NotificationsService {
+    /// Create a new notifications service
+    pub fn new(
+        state: NotificationsState,
+        chat: ChatState,
+        conversations: ConversationsState,
+        event_bus: SharedEventBus,
+    ) -> Self {
+        Self { state, chat, conversations, event_bus }
+    }
+
+    /// Subscribe to relevant events from the event bus
+    pub fn subscribe_to_events(&self) {
+        let mut state = self.state;
+        let chat = self.chat;
+        let conversations = self.conversations;
+        let mut rx = self.event_bus.subscribe();
+
+        spawn(async move {
+            while let Some(event) = rx.next().await {
+                match event {
+                    AppEvent::MessageReceived { conv_id, message, .. } => {
+                        // The open conversation shows the message already
+                        if message.sender == MessageSender::User
+                            || chat.current_conv_id().as_deref() == Some(conv_id.as_str())
+                        {
+                            continue;
+                        }
+                        let title = conversations
+                            .get_conversation(&conv_id)
+                            .map(|c| c.display_title())
+                            .unwrap_or_else(|| "New Chat".to_string());
+                        state.notify(&conv_id, &title, &message.body, Utc::now());
+                    }
+                    AppEvent::NotificationReceived { title, body, category } => {
+                        show_notice(state, &title, &body, &category);
+                    }
+                    AppEvent::ConversationCreateFailed { error, .. } => {
+                        show_notice(state, "Couldn't create conversation", &error, "");
+                    }
+                    AppEvent::ConversationSelected(id) | AppEvent::ConversationDeleted(id) => {
+                        state.dismiss_conversation(&id);
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Dismiss a toast without opening its conversation
+    pub fn dismiss(&self, id: u64) {
+        let mut state = self.state;
+        state.dismiss(id);
+    }
+
+    /// Show a notice raised by the app itself, such as a rejected attachment
+    ///
+    /// These have no category, so the toast shows no category badge.
+    pub fn show_notice(&self, title: &str, body: &str) {
+        show_notice(self.state, title, body, "");
+    }
+
+    /// Dismiss a server notification before it times out
+    pub fn dismiss_notice(&self, id: u64) {
+        let mut state = self.state;
+        state.dismiss_notice(id);
+    }
+
+    /// Change how long a conversation's toast keeps absorbing new messages
+    pub fn set_coalesce_window(&self, window: Duration) {
+        info!("Setting notification coalescing window: {}s", window.num_seconds());
+        let mut state = self.state;
+        state.set_coalesce_window(window);
+    }
+}
+
+/// Queue a notice and dismiss it after `NOTICE_DURATION_SECS`
+fn show_notice(mut state: NotificationsState, title: &str, body: &str, category: &str) {
+    let id = state.push_notice(title, body, category);
+    spawn(async move {
+        timer::sleep(std::time::Duration::from_secs(NOTICE_DURATION_SECS)).await;
+        state.dismiss_notice(id);
+    });
+}
@@ -0,0 +1,244 @@
+//! Notifications feature state
+
+use chrono::{DateTime, Duration, Utc};
+use dioxus::prelude::*;
+
+/// Default window in which new messages from one conversation share a toast
+const DEFAULT_COALESCE_WINDOW_SECS: i64 = 60;
+
+/// Most server notifications shown at once; the oldest is dropped beyond this
+pub const MAX_NOTICES: usize = 4;
+
+/// How long a server notification stays on screen
+pub const NOTICE_DURATION_SECS: u64 = 6;
+
+/// A toast for unread messages in one conversation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub id: u64,
+    pub conv_id: String,
+    pub conv_title: String,
+    /// Number of messages collapsed into this toast
+    pub count: usize,
+    /// Body of the latest message
+    pub preview: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Toast {
+    /// Text shown in the toast body
+    pub fn summary(&self) -> String {
+        if self.count == 1 {
+            self.preview.clone()
+        } else {
+            format!("{} new messages in {}", self.count, self.conv_title)
+        }
+    }
+}
+
+/// A notification pushed by the server (reminders and the like)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub category: String,
+}
+
+/// Internal state for notifications
+#[derive(Debug, Clone)]
+pub struct NotificationsStateInner {
+    /// Visible toasts, oldest first
+    pub toasts: Vec<Toast>,
+    /// Visible server notifications, oldest first
+    pub notices: Vec<Notice>,
+    /// Messages arriving within this window of a conversation's toast update it in place
+    pub coalesce_window: Duration,
+    next_id: u64,
+}
+
+/// State for the notifications feature (wraps a Signal)
+#[derive(Clone, Copy)]
+pub struct NotificationsState {
+    inner: Signal<NotificationsStateInner>,
+}
+
+impl NotificationsState {
+    /// Create new notifications state
+    pub fn new() -> Self {
+        Self {
+            inner: Signal::new(NotificationsStateInner {
+                toasts: Vec::new(),
+                notices: Vec::new(),
+                coalesce_window: Duration::seconds(DEFAULT_COALESCE_WINDOW_SECS),
+                next_id: 0,
+            }),
+        }
+    }
+
+    // ============================================
+    // Read accessors
+    // ============================================
+
+    /// Get visible toasts, oldest first
+    pub fn toasts(&self) -> Vec<Toast> {
+        self.inner.read().toasts.clone()
+    }
+
+    /// Get visible server notifications, oldest first
+    pub fn notices(&self) -> Vec<Notice> {
+        self.inner.read().notices.clone()
+    }
+
+    /// Get the coalescing window
+    pub fn coalesce_window(&self) -> Duration {
+        self.inner.read().coalesce_window
+    }
+
+    // ============================================
+    // Mutations (use mut self for Signal write access)
+    // ============================================
+
+    /// Record a new message, merging it into the conversation's toast when
+    /// that toast was updated within the coalescing window
+    pub fn notify(&mut self, conv_id: &str, conv_title: &str, preview: &str, now: DateTime<Utc>) {
+        let mut inner = self.inner.write();
+        let window = inner.coalesce_window;
+
+        if let Some(pos) = inner.toasts.iter().position(|t| t.conv_id == conv_id) {
+            if now - inner.toasts[pos].updated_at <= window {
+                let toast = &mut inner.toasts[pos];
+                toast.count += 1;
+                toast.conv_title = conv_title.to_string();
+                toast.preview = preview.to_string();
+                toast.updated_at = now;
+                return;
+            }
+            // Stale toast: replace it rather than stacking a second one
+            inner.toasts.remove(pos);
+        }
+
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.toasts.push(Toast {
+            id,
+            conv_id: conv_id.to_string(),
+            conv_title: conv_title.to_string(),
+            count: 1,
+            preview: preview.to_string(),
+            updated_at: now,
+        });
+    }
+
+    /// Queue a server notification, dropping the oldest beyond `MAX_NOTICES`
+    ///
+    /// Returns the notice id, for dismissing it later.
+    pub fn push_notice(&mut self, title: &str, body: &str, category: &str) -> u64 {
+        let mut inner = self.inner.write();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.notices.push(Notice {
+            id,
+            title: title.to_string(),
+            body: body.to_string(),
+            category: category.to_string(),
+        });
+        if inner.notices.len() > MAX_NOTICES {
+            let excess = inner.notices.len() - MAX_NOTICES;
+            inner.notices.drain(..excess);
+        }
+        id
+    }
+
+    /// Remove a single server notification
+    pub fn dismiss_notice(&mut self, id: u64) {
+        self.inner.write().notices.retain(|n| n.id != id);
+    }
+
+    /// Remove a single toast
+    pub fn dismiss(&mut self, id: u64) {
+        self.inner.write().toasts.retain(|t| t.id != id);
+    }
+
+    /// Remove toasts for a conversation (e.g. once it is opened)
+    pub fn dismiss_conversation(&mut self, conv_id: &str) {
+        self.inner.write().toasts.retain(|t| t.conv_id != conv_id);
+    }
+
+    /// Set the coalescing window
+    pub fn set_coalesce_window(&mut self, window: Duration) {
+        self.inner.write().coalesce_window = window;
+    }
+}
+
+impl Default for NotificationsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_messages_within_window_share_a_toast() {
+        with_runtime(|| {
+            let mut state = NotificationsState::new();
+            state.set_coalesce_window(Duration::seconds(30));
+
+            state.notify("native-a", "Chat A", "one", at(0));
+            state.notify("native-b", "Chat B", "hello", at(5));
+            state.notify("native-a", "Chat A", "two", at(10));
+            state.notify("native-a", "Chat A", "three", at(35));
+
+            let toasts = state.toasts();
+            assert_eq!(toasts.len(), 2);
+            assert_eq!(toasts[0].conv_id, "native-a");
+            assert_eq!(toasts[0].summary(), "3 new messages in Chat A");
+            assert_eq!(toasts[1].summary(), "hello");
+        });
+    }
+
+    #[test]
+    fn test_stale_toast_is_replaced() {
+        with_runtime(|| {
+            let mut state = NotificationsState::new();
+            state.set_coalesce_window(Duration::seconds(30));
+
+            state.notify("native-a", "Chat A", "one", at(0));
+            let first_id = state.toasts()[0].id;
+            state.notify("native-a", "Chat A", "later", at(100));
+
+            let toasts = state.toasts();
+            assert_eq!(toasts.len(), 1);
+            assert_ne!(toasts[0].id, first_id);
+            assert_eq!(toasts[0].summary(), "later");
+        });
+    }
+
+    #[test]
+    fn test_notices_queue_and_drop_the_oldest() {
+        with_runtime(|| {
+            let mut state = NotificationsState::new();
+
+            let ids: Vec<u64> = (0..MAX_NOTICES + 1)
+                .map(|i| state.push_notice(&format!("Reminder {}", i), "Stand up", "reminders"))
+                .collect();
+
+            let titles: Vec<String> = state.notices().into_iter().map(|n| n.title).collect();
+            assert_eq!(titles.len(), MAX_NOTICES);
+            assert_eq!(titles[0], "Reminder 1");
+
+            state.dismiss_notice(ids[MAX_NOTICES]);
+            assert!(state.notices().iter().all(|n| n.id != ids[MAX_NOTICES]));
+            assert_eq!(state.notices().len(), MAX_NOTICES - 1);
+        });
+    }
+}
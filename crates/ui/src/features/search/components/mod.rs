@@ -0,0 +1,5 @@
+//! Search UI components
+
+mod search;
+
+pub use search::{SearchBar, SearchResults};
@@ -0,0 +1,129 @@
+//! Message search input and results
+
+use dioxus::prelude::*;
+use prsnl_core::{MessageSender, SearchResult};
+use crate::features::conversations::components::format_time_ago;
+use crate::features::conversations::ConversationsState;
+use crate::features::search::{SearchService, SearchState};
+use crate::shared::{EmptyState, LoadingSpinner};
+
+/// Search input; Enter searches all conversations, Escape leaves search
+#[component]
+pub fn SearchBar() -> Element {
+    let state: SearchState = use_context();
+    let service: SearchService = use_context();
+    let mut query = use_signal(|| state.query());
+
+    let on_clear = {
+        let service = service.clone();
+        move |_| {
+            query.set(String::new());
+            service.clear();
+        }
+    };
+
+    rsx! {
+        div {
+            class: "shrink-0 p-2 border-b border-border flex items-center gap-2",
+            input {
+                r#type: "search",
+                value: "{query}",
+                placeholder: "Search messages",
+                oninput: move |e| query.set(e.value()),
+                onkeydown: {
+                    let service = service.clone();
+                    move |e: KeyboardEvent| match e.key() {
+                        Key::Enter => service.search(&query.read()),
+                        Key::Escape => {
+                            query.set(String::new());
+                            service.clear();
+                        }
+                        _ => {}
+                    }
+                },
+                class: "flex-1 min-w-0 p-2 border border-border rounded-lg bg-bg-primary text-text-white text-sm outline-none focus:border-accent",
+            }
+            if state.is_active() {
+                button {
+                    onclick: on_clear,
+                    class: "bg-transparent border-none text-text-muted cursor-pointer text-lg leading-none p-1",
+                    title: "Clear search",
+                    "×"
+                }
+            }
+        }
+    }
+}
+
+/// Matches for the active search, grouped by conversation
+#[component]
+pub fn SearchResults(on_open: EventHandler<SearchResult>) -> Element {
+    let state: SearchState = use_context();
+    let conv_state: ConversationsState = use_context();
+
+    if state.is_loading() {
+        return rsx! {
+            LoadingSpinner { label: "Searching..." }
+        };
+    }
+
+    let groups = state.grouped_results();
+    if groups.is_empty() {
+        return rsx! {
+            EmptyState {
+                title: "No messages found",
+                subtitle: "Try different words",
+            }
+        };
+    }
+
+    rsx! {
+        div {
+            class: "flex-1 overflow-y-auto",
+            for (conv_id, matches) in groups {
+                section {
+                    key: "{conv_id}",
+                    div {
+                        class: "px-4 pt-3 pb-1 text-text-muted text-xs font-semibold uppercase tracking-wide",
+                        {
+                            conv_state
+                                .get_conversation(&conv_id)
+                                .map(|c| c.display_title())
+                                .unwrap_or_else(|| "New Chat".to_string())
+                        }
+                    }
+                    for result in matches {
+                        {
+                            let prefix = match result.message.sender {
+                                MessageSender::User => "You: ",
+                                MessageSender::Assistant => "Assistant: ",
+                                MessageSender::System => "",
+                            };
+                            let preview: String = result.message.body.chars().take(120).collect();
+                            let time_ago = format_time_ago(result.message.timestamp);
+
+                            rsx! {
+                                button {
+                                    key: "{result.message.id}",
+                                    onclick: {
+                                        let result = result.clone();
+                                        move |_| on_open.call(result.clone())
+                                    },
+                                    class: "w-full px-4 py-2 bg-transparent border-none border-b border-border text-left cursor-pointer flex flex-col gap-1 hover:bg-bg-hover transition-colors",
+                                    span {
+                                        class: "text-text-secondary text-sm line-clamp-2",
+                                        "{prefix}{preview}"
+                                    }
+                                    span {
+                                        class: "text-text-muted text-xs",
+                                        "{time_ago}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Search feature module
+//!
+//! This feature searches message bodies across all conversations on the
+//! server and lists the matches grouped by conversation.
+
+mod state;
+mod service;
+pub mod components;
+
+pub use state::{SearchState, SEARCH_RESULT_LIMIT};
+pub use service::SearchService;
+
+use prsnl_core::{SharedEventBus, SharedTransport};
+
+/// Initialize the search feature
+pub fn provide_search_feature(
+    event_bus: SharedEventBus,
+    transport: SharedTransport,
+) -> (SearchState, SearchService) {
+    let state = SearchState::new();
+    let service = SearchService::new(state, event_bus, transport);
+    (state, service)
+}
@@ -0,0 +1,80 @@
+//! Search feature service
+
+use dioxus::prelude::spawn;
+use futures::StreamExt;
+use tracing::info;
+
+use prsnl_core::{AppEvent, SearchResult, SharedEventBus, SharedTransport};
+use super::state::{SearchState, SEARCH_RESULT_LIMIT};
+
+/// Service that runs message searches on the server
+#[derive(Clone)]
+pub struct SearchService {
+    state: SearchState,
+    event_bus: SharedEventBus,
+    transport: SharedTransport,
+}
+
+impl SearchService {
+    /// Create a new search service
+    pub fn new(
+        state: SearchState,
+        event_bus: SharedEventBus,
+        transport: SharedTransport,
+    ) -> Self {
+        Self { state, event_bus, transport }
+    }
+
+    /// Subscribe to relevant events from the event bus
+    pub fn subscribe_to_events(&self) {
+        let mut state = self.state;
+        let mut rx = self.event_bus.subscribe();
+
+        spawn(async move {
+            while let Some(event) = rx.next().await {
+                match event {
+                    AppEvent::SearchResultsLoaded { query, results } => {
+                        state.apply_results(&query, results);
+                    }
+                    AppEvent::ConversationDeleted(id) => {
+                        state.remove_conversation(&id);
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Search all conversations; an empty query leaves search
+    pub fn search(&self, query: &str) {
+        let query = query.trim().to_string();
+        let mut state = self.state;
+        if query.is_empty() {
+            state.clear();
+            return;
+        }
+
+        info!("Searching messages for {:?}", query);
+        state.begin(&query);
+        let transport = self.transport.clone();
+        spawn(async move {
+            if let Err(e) = transport.send_search(query, None, Some(SEARCH_RESULT_LIMIT)).await {
+                info!("Failed to search: {:?}", e);
+                state.cancel();
+            }
+        });
+    }
+
+    /// Leave search
+    pub fn clear(&self) {
+        let mut state = self.state;
+        state.clear();
+    }
+
+    /// Have the chat view scroll to a result once its conversation is open
+    ///
+    /// Call before selecting the conversation so the history load picks it up.
+    pub fn focus_result(&self, result: &SearchResult) {
+        self.event_bus.publish(AppEvent::SearchResultOpened(result.clone()));
+    }
+}
@@ -0,0 +1,159 @@
+//! Search feature state
+
+use dioxus::prelude::*;
+use prsnl_core::SearchResult;
+
+/// Most matches to ask the server for per search
+pub const SEARCH_RESULT_LIMIT: u32 = 50;
+
+/// Internal state for search
+#[derive(Debug, Clone, Default)]
+pub struct SearchStateInner {
+    /// Query of the latest search (empty when no search is active)
+    pub query: String,
+    /// Matches for `query`, in the order the server ranked them
+    pub results: Vec<SearchResult>,
+    /// A search was sent and its results haven't arrived yet
+    pub loading: bool,
+}
+
+/// State for the search feature (wraps a Signal)
+#[derive(Clone, Copy)]
+pub struct SearchState {
+    inner: Signal<SearchStateInner>,
+}
+
+impl SearchState {
+    /// Create new search state
+    pub fn new() -> Self {
+        Self {
+            inner: Signal::new(SearchStateInner::default()),
+        }
+    }
+
+    // ============================================
+    // Read accessors
+    // ============================================
+
+    /// Get the query of the latest search
+    pub fn query(&self) -> String {
+        self.inner.read().query.clone()
+    }
+
+    /// Check if a search is active (results are shown instead of the list)
+    pub fn is_active(&self) -> bool {
+        !self.inner.read().query.is_empty()
+    }
+
+    /// Check if results are still on their way
+    pub fn is_loading(&self) -> bool {
+        self.inner.read().loading
+    }
+
+    /// Get matches grouped by conversation
+    ///
+    /// Conversations are ordered by their best-ranked match, and matches
+    /// keep the server's order within each group.
+    pub fn grouped_results(&self) -> Vec<(String, Vec<SearchResult>)> {
+        let mut groups: Vec<(String, Vec<SearchResult>)> = Vec::new();
+        for result in self.inner.read().results.iter() {
+            match groups.iter_mut().find(|(conv_id, _)| *conv_id == result.conv_id) {
+                Some((_, matches)) => matches.push(result.clone()),
+                None => groups.push((result.conv_id.clone(), vec![result.clone()])),
+            }
+        }
+        groups
+    }
+
+    // ============================================
+    // Mutations (use mut self for Signal write access)
+    // ============================================
+
+    /// Start a search, dropping the previous results
+    pub fn begin(&mut self, query: &str) {
+        let mut inner = self.inner.write();
+        inner.query = query.to_string();
+        inner.results.clear();
+        inner.loading = true;
+    }
+
+    /// Apply results from the server, ignoring those of an older query
+    pub fn apply_results(&mut self, query: &str, results: Vec<SearchResult>) {
+        let mut inner = self.inner.write();
+        if inner.query != query {
+            return;
+        }
+        inner.results = results;
+        inner.loading = false;
+    }
+
+    /// Stop waiting for results (e.g. the request could not be sent)
+    pub fn cancel(&mut self) {
+        self.inner.write().loading = false;
+    }
+
+    /// Drop matches from a deleted conversation
+    pub fn remove_conversation(&mut self, conv_id: &str) {
+        self.inner.write().results.retain(|r| r.conv_id != conv_id);
+    }
+
+    /// Leave search
+    pub fn clear(&mut self) {
+        *self.inner.write() = SearchStateInner::default();
+    }
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+    use prsnl_core::Message;
+
+    fn hit(conv_id: &str, body: &str) -> SearchResult {
+        SearchResult {
+            conv_id: conv_id.to_string(),
+            message: Message::new_user(body.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_results_group_by_conversation_and_ignore_stale_queries() {
+        with_runtime(|| {
+            let mut state = SearchState::new();
+            state.begin("tea");
+            state.begin("green tea");
+            assert!(state.is_loading());
+
+            state.apply_results("tea", vec![hit("a", "tea")]);
+            assert!(state.is_loading());
+            assert!(state.grouped_results().is_empty());
+
+            state.apply_results(
+                "green tea",
+                vec![hit("b", "green tea?"), hit("a", "more green tea"), hit("b", "green tea!")],
+            );
+            assert!(!state.is_loading());
+            let groups: Vec<(String, Vec<String>)> = state
+                .grouped_results()
+                .into_iter()
+                .map(|(conv_id, matches)| (conv_id, matches.into_iter().map(|r| r.message.body).collect()))
+                .collect();
+            assert_eq!(
+                groups,
+                [
+                    ("b".to_string(), vec!["green tea?".to_string(), "green tea!".to_string()]),
+                    ("a".to_string(), vec!["more green tea".to_string()]),
+                ]
+            );
+
+            state.clear();
+            assert!(!state.is_active());
+        });
+    }
+}
@@ -0,0 +1,66 @@
+//! Confirmation before switching to the server from a setup link
+
+use dioxus::prelude::*;
+use crate::features::settings::{SettingsService, SettingsState};
+
+/// Asks whether to connect to the server from a pending setup link
+///
+/// Renders nothing until a link is pending. Connecting to another server
+/// leaves the saved auth token behind, which the prompt points out.
+#[component]
+pub fn ConnectLinkPrompt() -> Element {
+    let settings_state: SettingsState = use_context();
+    let settings_service: SettingsService = use_context();
+
+    let Some(url) = settings_state.link_url() else {
+        return rsx! {};
+    };
+    let drops_token = settings_state.auth_token().is_some() && settings_state.token_for(&url).is_none();
+    let dismiss_service = settings_service.clone();
+
+    rsx! {
+        // Backdrop
+        div {
+            class: "fixed inset-0 bg-black/70 flex items-center justify-center z-[1000]",
+
+            div {
+                class: "bg-bg-secondary rounded-2xl p-6 w-[90%] max-w-[400px] shadow-2xl",
+
+                h2 {
+                    class: "text-text-white m-0 mb-4 text-xl",
+                    "Connect to this server?"
+                }
+                p {
+                    class: "text-text-muted text-sm mb-2",
+                    "A setup link wants to switch the app to:"
+                }
+                p {
+                    class: "text-text-white text-sm mb-4 break-all font-mono",
+                    "{url}"
+                }
+                if drops_token {
+                    p {
+                        class: "text-text-muted text-sm mb-4",
+                        "This is a different server, so your auth token won't be sent to it."
+                    }
+                }
+
+                div {
+                    class: "flex gap-3 justify-end",
+
+                    button {
+                        onclick: move |_| dismiss_service.dismiss_link(),
+                        class: "py-3 px-6 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-base hover:bg-bg-hover transition-colors",
+                        "Ignore"
+                    }
+
+                    button {
+                        onclick: move |_| settings_service.accept_link(),
+                        class: "py-3 px-6 border-none rounded-lg bg-accent text-text-white cursor-pointer text-base hover:bg-accent-hover transition-colors",
+                        "Connect"
+                    }
+                }
+            }
+        }
+    }
+}
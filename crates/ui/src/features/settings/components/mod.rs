@@ -1,5 +1,9 @@
 //! Settings UI components
 
+mod link_prompt;
 mod modal;
+mod server_popover;
 
-pub use modal::ServerUrlModal;
+pub use link_prompt::ConnectLinkPrompt;
+pub use modal::{ServerUrlModal, SettingsModal};
+pub use server_popover::{EditableConnectionIndicator, ServerUrlPopover};
@@ -1,18 +1,52 @@
 //! Server URL settings modal
 
 use dioxus::prelude::*;
+use crate::features::settings::{
+    connect_link, Density, ImageSizeLimit, SettingsService, SettingsState, IndicatorPlacement, SendButtonStyle, ThemeMode,
+    TimestampVisibility, TypingStyle,
+};
+use crate::features::speech::is_speech_output_supported;
+use crate::shared::copy_to_clipboard;
 
 /// Modal for editing server URL
 #[component]
 pub fn ServerUrlModal(
     current_url: String,
-    on_save: EventHandler<String>,
+    /// Auth token sent on connect, if one is set
+    #[props(default)]
+    current_token: Option<String>,
+    /// Called with the URL and token (blank when cleared) on Save
+    on_save: EventHandler<(String, String)>,
     on_close: EventHandler<()>,
+    #[props(default = ThemeMode::Dark)] theme: ThemeMode,
+    #[props(default)] on_theme_change: EventHandler<ThemeMode>,
+    #[props(default)] reduce_motion: bool,
+    #[props(default)] on_reduce_motion_change: EventHandler<bool>,
+    #[props(default)] auto_speak: bool,
+    #[props(default)] on_auto_speak_change: EventHandler<bool>,
+    #[props(default = Density::Comfortable)] density: Density,
+    #[props(default)] on_density_change: EventHandler<Density>,
+    #[props(default = SendButtonStyle::Icon)] send_button_style: SendButtonStyle,
+    #[props(default)] on_send_button_style_change: EventHandler<SendButtonStyle>,
+    #[props(default = TypingStyle::Dots)] typing_style: TypingStyle,
+    #[props(default)] on_typing_style_change: EventHandler<TypingStyle>,
+    #[props(default = IndicatorPlacement::Header)] indicator_placement: IndicatorPlacement,
+    #[props(default)] on_indicator_placement_change: EventHandler<IndicatorPlacement>,
+    #[props(default = TimestampVisibility::Always)] timestamp_visibility: TimestampVisibility,
+    #[props(default)] on_timestamp_visibility_change: EventHandler<TimestampVisibility>,
+    #[props(default = ImageSizeLimit::Large)] image_size_limit: ImageSizeLimit,
+    #[props(default)] on_image_size_limit_change: EventHandler<ImageSizeLimit>,
+    #[props(default = true)] persistence_available: bool,
 ) -> Element {
     let mut url_input = use_signal(|| current_url.clone());
+    let mut token_input = use_signal(|| current_token.clone().unwrap_or_default());
+    // Which share button was last used, for a "Copied" confirmation
+    let mut copied = use_signal(|| Option::<&'static str>::None);
+    let share_url = current_url.clone();
+    let share_link = connect_link(&current_url);
 
     let handle_save = move |_| {
-        on_save.call(url_input.read().clone());
+        on_save.call((url_input.read().clone(), token_input.read().clone()));
     };
 
     rsx! {
@@ -48,12 +82,214 @@ pub fn ServerUrlModal(
                     }
                 }
 
+                // Auth token input
+                div {
+                    class: "mb-4",
+                    label {
+                        class: "block text-text-muted text-sm mb-2",
+                        "Auth token"
+                    }
+                    input {
+                        r#type: "password",
+                        value: "{token_input}",
+                        oninput: move |e| token_input.set(e.value()),
+                        placeholder: "Only if your server requires one",
+                        autocomplete: "off",
+                        class: "w-full p-3 border border-border rounded-lg bg-bg-primary text-text-white text-base box-border outline-none focus:border-accent",
+                    }
+                }
+
                 // Help text
                 p {
-                    class: "text-text-muted text-xs mb-6",
+                    class: "text-text-muted text-xs mb-4",
                     "Enter the WebSocket server address. Changes will trigger a reconnection."
                 }
 
+                // Share the saved server with another device
+                div {
+                    class: "flex items-center gap-3 mb-6 text-text-white text-sm",
+                    span { class: "flex-1", "Share" }
+                    for (kind, label, text) in [("url", "Copy URL", share_url), ("link", "Copy setup link", share_link)] {
+                        button {
+                            key: "{kind}",
+                            onclick: move |_| {
+                                copy_to_clipboard(&text);
+                                copied.set(Some(kind));
+                            },
+                            class: "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover",
+                            if copied() == Some(kind) { "Copied" } else { "{label}" }
+                        }
+                    }
+                }
+
+                if !persistence_available {
+                    p {
+                        class: "text-warning text-xs mb-4",
+                        "Storage is unavailable on this device, so these preferences won't be saved after you close the app."
+                    }
+                }
+
+                // Appearance
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Theme" }
+                    for (option, label) in [(ThemeMode::Dark, "Dark"), (ThemeMode::Light, "Light")] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_theme_change.call(option),
+                            class: if theme == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Density" }
+                    for (option, label) in [(Density::Compact, "Compact"), (Density::Comfortable, "Comfortable")] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_density_change.call(option),
+                            class: if density == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Send button" }
+                    for (option, label) in [
+                        (SendButtonStyle::Icon, "Icon"),
+                        (SendButtonStyle::Text, "Text"),
+                        (SendButtonStyle::IconAndText, "Both"),
+                    ] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_send_button_style_change.call(option),
+                            class: if send_button_style == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Typing indicator" }
+                    for (option, label) in [
+                        (TypingStyle::Dots, "Dots"),
+                        (TypingStyle::Text, "Text"),
+                        (TypingStyle::Pulse, "Pulse"),
+                    ] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_typing_style_change.call(option),
+                            class: if typing_style == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Connection status" }
+                    for (option, label) in [(IndicatorPlacement::Header, "Header"), (IndicatorPlacement::StatusBar, "Status bar")] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_indicator_placement_change.call(option),
+                            class: if indicator_placement == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Timestamps" }
+                    for (option, label) in [
+                        (TimestampVisibility::Always, "Always"),
+                        (TimestampVisibility::OnHover, "On hover"),
+                        (TimestampVisibility::Never, "Never"),
+                    ] {
+                        button {
+                            key: "{label}",
+                            onclick: move |_| on_timestamp_visibility_change.call(option),
+                            class: if timestamp_visibility == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                // Attachments
+                div {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm",
+                    span { class: "flex-1", "Max image size" }
+                    for (option, label, hint) in [
+                        (ImageSizeLimit::Medium, "Medium", "1280 px, 1 MB"),
+                        (ImageSizeLimit::Large, "Large", "2048 px, 2 MB"),
+                        (ImageSizeLimit::Original, "Original", "Send images as picked"),
+                    ] {
+                        button {
+                            key: "{label}",
+                            title: "{hint}",
+                            onclick: move |_| on_image_size_limit_change.call(option),
+                            class: if image_size_limit == option {
+                                "py-1 px-3 border-none rounded-lg bg-accent text-text-white cursor-pointer text-sm"
+                            } else {
+                                "py-1 px-3 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-sm hover:bg-bg-hover"
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+
+                label {
+                    class: "flex items-center gap-3 mb-3 text-text-white text-sm cursor-pointer",
+                    input {
+                        r#type: "checkbox",
+                        checked: reduce_motion,
+                        onchange: move |e| on_reduce_motion_change.call(e.checked()),
+                    }
+                    "Reduce motion"
+                }
+
+                // Speech, where the build can speak
+                if is_speech_output_supported() {
+                    label {
+                        class: "flex items-center gap-3 mb-6 text-text-white text-sm cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            checked: auto_speak,
+                            onchange: move |e| on_auto_speak_change.call(e.checked()),
+                        }
+                        "Read new replies aloud"
+                    }
+                }
+
                 // Buttons
                 div {
                     class: "flex gap-3 justify-end",
@@ -74,3 +310,72 @@ pub fn ServerUrlModal(
         }
     }
 }
+
+/// `ServerUrlModal` wired to the settings from context
+///
+/// Saving updates the server URL and auth token (publishing `ServerUrlChanged`
+/// or `AuthTokenChanged` when they changed) and closes the modal; every other
+/// control applies at once.
+#[component]
+pub fn SettingsModal(on_close: EventHandler<()>) -> Element {
+    let settings_state: SettingsState = use_context();
+    let settings_service: SettingsService = use_context();
+
+    rsx! {
+        ServerUrlModal {
+            current_url: settings_state.server_url(),
+            current_token: settings_state.auth_token(),
+            on_save: {
+                let settings_service = settings_service.clone();
+                move |(url, token): (String, String)| {
+                    settings_service.update_connection(url, Some(token));
+                    on_close.call(());
+                }
+            },
+            on_close: on_close,
+            theme: settings_state.theme(),
+            on_theme_change: {
+                let settings_service = settings_service.clone();
+                move |theme| settings_service.set_theme(theme)
+            },
+            reduce_motion: settings_state.reduce_motion(),
+            on_reduce_motion_change: {
+                let settings_service = settings_service.clone();
+                move |reduce_motion| settings_service.set_reduce_motion(reduce_motion)
+            },
+            auto_speak: settings_state.auto_speak(),
+            on_auto_speak_change: {
+                let settings_service = settings_service.clone();
+                move |auto_speak| settings_service.set_auto_speak(auto_speak)
+            },
+            density: settings_state.density(),
+            on_density_change: {
+                let settings_service = settings_service.clone();
+                move |density| settings_service.set_density(density)
+            },
+            send_button_style: settings_state.send_button_style(),
+            on_send_button_style_change: {
+                let settings_service = settings_service.clone();
+                move |style| settings_service.set_send_button_style(style)
+            },
+            typing_style: settings_state.typing_style(),
+            on_typing_style_change: {
+                let settings_service = settings_service.clone();
+                move |style| settings_service.set_typing_style(style)
+            },
+            indicator_placement: settings_state.indicator_placement(),
+            on_indicator_placement_change: {
+                let settings_service = settings_service.clone();
+                move |placement| settings_service.set_indicator_placement(placement)
+            },
+            timestamp_visibility: settings_state.timestamp_visibility(),
+            on_timestamp_visibility_change: {
+                let settings_service = settings_service.clone();
+                move |visibility| settings_service.set_timestamp_visibility(visibility)
+            },
+            image_size_limit: settings_state.image_size_limit(),
+            on_image_size_limit_change: move |limit| settings_service.set_image_size_limit(limit),
+            persistence_available: settings_state.persistence_available(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Inline server URL editor anchored to the connection indicator
+
+use dioxus::prelude::*;
+use prsnl_core::ConnectionStatus;
+use crate::features::settings::{validate_server_url, SettingsService, SettingsState};
+use crate::shared::ConnectionIndicator;
+
+/// Connection indicator that opens a compact server URL editor when tapped
+#[component]
+pub fn EditableConnectionIndicator(
+    status: ConnectionStatus,
+    /// Also called on every tap, before the editor toggles
+    #[props(default)]
+    on_tap: EventHandler<()>,
+    /// Open the editor above the indicator (for the bottom status bar)
+    #[props(default)]
+    open_upward: bool,
+) -> Element {
+    let mut editing = use_signal(|| false);
+
+    rsx! {
+        div {
+            class: "relative",
+            ConnectionIndicator {
+                status,
+                on_tap: move |_| {
+                    on_tap.call(());
+                    editing.toggle();
+                },
+            }
+            if editing() {
+                ServerUrlPopover {
+                    open_upward,
+                    on_close: move |_| editing.set(false),
+                }
+            }
+        }
+    }
+}
+
+/// Compact server URL input; Enter saves and reconnects, Escape cancels
+#[component]
+pub fn ServerUrlPopover(
+    on_close: EventHandler<()>,
+    #[props(default)] open_upward: bool,
+) -> Element {
+    let settings_state: SettingsState = use_context();
+    let settings_service: SettingsService = use_context();
+    let mut url_input = use_signal(|| settings_state.server_url());
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let position = if open_upward { "bottom-full mb-2" } else { "top-full mt-2" };
+
+    let mut save = move || match validate_server_url(&url_input.read()) {
+        Ok(url) => {
+            settings_service.update_server_url(url);
+            on_close.call(());
+        }
+        Err(e) => error.set(Some(e.to_string())),
+    };
+
+    rsx! {
+        div {
+            class: "absolute right-0 {position} z-50 w-72 p-3 rounded-lg bg-bg-secondary border border-border shadow-lg",
+            onclick: move |e| e.stop_propagation(),
+            input {
+                r#type: "text",
+                value: "{url_input}",
+                placeholder: "ws://hostname:port/ws",
+                autofocus: true,
+                oninput: move |e| {
+                    error.set(None);
+                    url_input.set(e.value());
+                },
+                onkeydown: move |e| match e.key() {
+                    Key::Enter => save(),
+                    Key::Escape => on_close.call(()),
+                    _ => {}
+                },
+                class: "w-full p-2 border border-border rounded-md bg-bg-primary text-text-white text-sm box-border outline-none focus:border-accent",
+            }
+            if let Some(message) = error() {
+                p {
+                    class: "text-error text-xs mt-1.5 mb-0",
+                    "{message}"
+                }
+            } else {
+                p {
+                    class: "text-text-muted text-xs mt-1.5 mb-0",
+                    "Enter to save and reconnect, Esc to cancel"
+                }
+            }
+        }
+    }
+}
@@ -4,16 +4,24 @@
 
 mod state;
 mod service;
+mod share;
 pub mod components;
 
-pub use state::SettingsState;
+pub use state::{
+    Density, ImageSizeLimit, IndicatorPlacement, SendButtonStyle, SettingsState, ThemeMode, TimestampVisibility,
+    TypingStyle,
+};
 pub use service::SettingsService;
+pub use share::{
+    connect_link, launch_server_url, parse_connect_link, parse_connect_query, server_origin, validate_server_url,
+    ConnectLinkError, CONNECT_LINK_PREFIX,
+};
 
 use prsnl_core::SharedEventBus;
 
 /// Initialize the settings feature
 pub fn provide_settings_feature(event_bus: SharedEventBus) -> (SettingsState, SettingsService) {
-    let state = SettingsState::new();
+    let state = SettingsState::load();
     let service = SettingsService::new(state.clone(), event_bus);
     (state, service)
 }
@@ -2,10 +2,14 @@
 
 use dioxus::prelude::spawn;
 use futures::StreamExt;
-use tracing::info;
+use tracing::{info, warn};
 
 use prsnl_core::{AppEvent, SharedEventBus};
-use super::state::SettingsState;
+use super::share::{launch_server_url, parse_connect_link, ConnectLinkError};
+use super::state::{
+    Density, ImageSizeLimit, IndicatorPlacement, SendButtonStyle, SettingsState, ThemeMode, TimestampVisibility,
+    TypingStyle,
+};
 
 /// Service for managing settings
 #[derive(Clone)]
@@ -23,19 +27,17 @@ impl SettingsService {
     /// Subscribe to relevant events
     pub fn subscribe_to_events(&self) {
         let mut state = self.state;
-        let mut rx = self.event_bus.subscribe();
+        // Chat and message traffic is of no interest here
+        let mut rx = self
+            .event_bus
+            .subscribe_filtered(Box::new(|event| matches!(event, AppEvent::SettingsModalToggled(_))));
 
         spawn(async move {
-            while let Some(event) = rx.next().await {
-                match event {
-                    AppEvent::SettingsModalToggled(open) => {
-                        if open {
-                            state.open_modal();
-                        } else {
-                            state.close_modal();
-                        }
-                    }
-                    _ => {}
+            while let Some(AppEvent::SettingsModalToggled(open)) = rx.next().await {
+                if open {
+                    state.open_modal();
+                } else {
+                    state.close_modal();
                 }
             }
         });
@@ -53,12 +55,299 @@ impl SettingsService {
         state.close_modal();
     }
 
+    /// Enable or disable animations app-wide
+    pub fn set_reduce_motion(&self, reduce_motion: bool) {
+        info!("Setting reduce motion: {}", reduce_motion);
+        let mut state = self.state;
+        state.set_reduce_motion(reduce_motion);
+        state.save();
+    }
+
+    /// Switch between the dark and light themes
+    pub fn set_theme(&self, theme: ThemeMode) {
+        info!("Setting theme: {:?}", theme);
+        let mut state = self.state;
+        state.set_theme(theme);
+        state.save();
+    }
+
+    /// Enable or disable reading new assistant replies aloud
+    pub fn set_auto_speak(&self, auto_speak: bool) {
+        info!("Setting auto-speak: {}", auto_speak);
+        let mut state = self.state;
+        state.set_auto_speak(auto_speak);
+        state.save();
+    }
+
+    /// Change the spacing density
+    pub fn set_density(&self, density: Density) {
+        info!("Setting density: {:?}", density);
+        let mut state = self.state;
+        state.set_density(density);
+        state.save();
+    }
+
+    /// Change what the send button shows
+    pub fn set_send_button_style(&self, style: SendButtonStyle) {
+        info!("Setting send button style: {:?}", style);
+        let mut state = self.state;
+        state.set_send_button_style(style);
+        state.save();
+    }
+
+    /// Change how the typing indicator looks
+    pub fn set_typing_style(&self, style: TypingStyle) {
+        info!("Setting typing style: {:?}", style);
+        let mut state = self.state;
+        state.set_typing_style(style);
+        state.save();
+    }
+
+    /// Move the connection indicator between the headers and a status bar
+    pub fn set_indicator_placement(&self, placement: IndicatorPlacement) {
+        info!("Setting indicator placement: {:?}", placement);
+        let mut state = self.state;
+        state.set_indicator_placement(placement);
+        state.save();
+    }
+
+    /// Change when message timestamps are shown
+    pub fn set_timestamp_visibility(&self, visibility: TimestampVisibility) {
+        info!("Setting timestamp visibility: {:?}", visibility);
+        let mut state = self.state;
+        state.set_timestamp_visibility(visibility);
+        state.save();
+    }
+
+    /// Change the size outgoing images are scaled down to
+    pub fn set_image_size_limit(&self, limit: ImageSizeLimit) {
+        info!("Setting image size limit: {:?}", limit);
+        let mut state = self.state;
+        state.set_image_size_limit(limit);
+        state.save();
+    }
+
     /// Update server URL and trigger reconnection
+    ///
+    /// Saving an unchanged URL only closes the modal, so a healthy
+    /// connection isn't dropped and re-established for nothing. The auth
+    /// token is kept only while the URL stays on the same server.
     pub fn update_server_url(&self, url: String) {
-        info!("Updating server URL to: {}", url);
+        let auth_token = self.state.token_for(&url);
+        self.update_connection(url, auth_token);
+    }
+
+    /// Update the server URL and auth token together, reconnecting once if either changed
+    ///
+    /// A blank token clears it. A new URL publishes `ServerUrlChanged`; a new
+    /// token alone publishes `AuthTokenChanged`.
+    pub fn update_connection(&self, url: String, auth_token: Option<String>) {
         let mut state = self.state;
-        state.set_server_url(url.clone());
         state.close_modal();
-        self.event_bus.publish(AppEvent::ServerUrlChanged(url));
+
+        let auth_token = auth_token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+        let url_changed = url != state.server_url();
+        let token_changed = auth_token != state.auth_token();
+        if !url_changed && !token_changed {
+            info!("Server URL unchanged, skipping reconnection");
+            return;
+        }
+
+        state.set_auth_token(auth_token);
+        if url_changed {
+            info!("Updating server URL to: {}", url);
+            state.set_server_url(url.clone());
+        } else {
+            info!("Updating auth token");
+        }
+        state.save();
+        self.event_bus.publish(if url_changed {
+            AppEvent::ServerUrlChanged(url)
+        } else {
+            AppEvent::AuthTokenChanged
+        });
+    }
+
+    /// Offer the server URL from a `prsnl://connect?url=...` setup link
+    pub fn apply_connect_link(&self, link: &str) -> Result<(), ConnectLinkError> {
+        let url = parse_connect_link(link)?;
+        info!("Offering server URL from setup link");
+        self.offer_link(url);
+        Ok(())
+    }
+
+    /// Offer the server URL from a setup link passed at launch, if any
+    pub fn apply_launch_config(&self) {
+        match launch_server_url() {
+            Some(Ok(url)) => {
+                info!("Offering server URL from launch link");
+                self.offer_link(url);
+            }
+            Some(Err(e)) => warn!("Ignoring launch link: {}", e),
+            None => {}
+        }
+    }
+
+    /// Switch to the server from the pending setup link
+    ///
+    /// The auth token stays behind unless the link is for the same server.
+    pub fn accept_link(&self) {
+        let mut state = self.state;
+        let Some(url) = state.link_url() else {
+            return;
+        };
+        state.set_link_url(None);
+        self.update_server_url(url);
+    }
+
+    /// Drop the pending setup link, keeping the current server
+    pub fn dismiss_link(&self) {
+        let mut state = self.state;
+        state.set_link_url(None);
+    }
+
+    /// Hold a setup link's URL until the user accepts it
+    ///
+    /// Anyone can craft a link, so nothing is saved or reconnected before then.
+    fn offer_link(&self, url: String) {
+        let mut state = self.state;
+        if url == state.server_url() {
+            info!("Setup link is for the current server, nothing to do");
+            return;
+        }
+        state.set_link_url(Some(url));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::test_support::{with_runtime, RecordingEventBus};
+
+    #[test]
+    fn test_unchanged_url_does_not_publish() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+
+            service.update_server_url(state.server_url());
+
+            assert!(bus.published().is_empty());
+            assert!(!state.is_modal_open());
+        });
+    }
+
+    #[test]
+    fn test_changed_url_publishes_once() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+
+            service.update_server_url("ws://example.com/ws".to_string());
+            service.update_server_url("ws://example.com/ws".to_string());
+
+            let events = bus.published();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(&events[0], AppEvent::ServerUrlChanged(url) if url == "ws://example.com/ws"));
+            assert_eq!(state.server_url(), "ws://example.com/ws");
+        });
+    }
+
+    #[test]
+    fn test_new_auth_token_publishes_once() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+
+            service.update_connection(state.server_url(), Some(" secret ".to_string()));
+            assert_eq!(state.auth_token().as_deref(), Some("secret"));
+            service.update_server_url(state.server_url());
+            service.update_connection(state.server_url(), Some(String::new()));
+
+            let events = bus.published();
+            assert_eq!(events.len(), 2);
+            assert!(events.iter().all(|e| matches!(e, AppEvent::AuthTokenChanged)));
+            assert_eq!(state.auth_token(), None);
+        });
+    }
+
+    #[test]
+    fn test_link_to_another_server_waits_and_leaves_the_token_behind() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+            service.update_connection("wss://home.example.com/ws".to_string(), Some("secret".to_string()));
+
+            service
+                .apply_connect_link("prsnl://connect?url=wss%3A%2F%2Fevil.example.net%2Fws")
+                .unwrap();
+            assert_eq!(state.server_url(), "wss://home.example.com/ws");
+            assert_eq!(state.link_url().as_deref(), Some("wss://evil.example.net/ws"));
+            assert_eq!(bus.published().len(), 1);
+
+            service.accept_link();
+            assert_eq!(state.server_url(), "wss://evil.example.net/ws");
+            assert_eq!(state.auth_token(), None);
+            assert_eq!(state.link_url(), None);
+            assert!(matches!(bus.published().last(), Some(AppEvent::ServerUrlChanged(url)) if url == "wss://evil.example.net/ws"));
+        });
+    }
+
+    #[test]
+    fn test_link_to_the_same_server_keeps_the_token() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+            service.update_connection("wss://home.example.com/ws".to_string(), Some("secret".to_string()));
+
+            service
+                .apply_connect_link("prsnl://connect?url=wss%3A%2F%2Fhome.example.com%2Fv2")
+                .unwrap();
+            service.accept_link();
+
+            assert_eq!(state.server_url(), "wss://home.example.com/v2");
+            assert_eq!(state.auth_token().as_deref(), Some("secret"));
+        });
+    }
+
+    #[test]
+    fn test_dismissed_link_changes_nothing() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+            let original = state.server_url();
+
+            service
+                .apply_connect_link("prsnl://connect?url=wss%3A%2F%2Fevil.example.net%2Fws")
+                .unwrap();
+            service.dismiss_link();
+            service.accept_link();
+
+            assert!(bus.published().is_empty());
+            assert_eq!(state.server_url(), original);
+        });
+    }
+
+    #[test]
+    fn test_invalid_connect_link_is_not_applied() {
+        with_runtime(|| {
+            let bus = Arc::new(RecordingEventBus::new());
+            let state = SettingsState::new();
+            let service = SettingsService::new(state, bus.clone());
+            let original = state.server_url();
+
+            let result = service.apply_connect_link("prsnl://connect?url=http%3A%2F%2Fexample.com");
+
+            assert!(matches!(result, Err(ConnectLinkError::InvalidUrl(_))));
+            assert!(bus.published().is_empty());
+            assert_eq!(state.server_url(), original);
+        });
     }
 }
@@ -1,14 +1,175 @@
 //! Settings feature state
 
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::features::media::ImageLimits;
+use crate::shared::{storage, Theme};
+use super::share::server_origin;
 
 const DEFAULT_SERVER_URL: &str = "ws://10.8.0.8:8765/ws";
 
+/// Storage key for the saved server URL and preferences
+pub(crate) const SETTINGS_STORAGE_KEY: &str = "settings";
+
+/// Color theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeMode {
+    Dark,
+    Light,
+}
+
+impl ThemeMode {
+    /// Color tokens for this theme
+    pub fn palette(&self) -> Theme {
+        match self {
+            ThemeMode::Dark => Theme::dark(),
+            ThemeMode::Light => Theme::light(),
+        }
+    }
+}
+
+/// Spacing density for messages, list items, and the input bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    /// Tighter spacing to fit more on screen
+    Compact,
+    /// Roomier spacing with larger tap targets
+    Comfortable,
+}
+
+impl Density {
+    /// CSS class applied on the shell root (scales theme spacing tokens)
+    pub fn class(&self) -> &'static str {
+        match self {
+            Density::Compact => "density-compact",
+            Density::Comfortable => "",
+        }
+    }
+}
+
+/// What the send button in the message input shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SendButtonStyle {
+    /// Paper-plane icon only (saves space on narrow screens)
+    Icon,
+    /// "Send" label only
+    Text,
+    /// Icon followed by the label
+    IconAndText,
+}
+
+impl SendButtonStyle {
+    pub fn shows_icon(&self) -> bool {
+        matches!(self, SendButtonStyle::Icon | SendButtonStyle::IconAndText)
+    }
+
+    pub fn shows_text(&self) -> bool {
+        matches!(self, SendButtonStyle::Text | SendButtonStyle::IconAndText)
+    }
+}
+
+/// How the "assistant is typing" indicator looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypingStyle {
+    /// Three bouncing dots
+    Dots,
+    /// "Assistant is typing..." label
+    Text,
+    /// Softly pulsing bubble
+    Pulse,
+}
+
+/// Where the connection status indicator is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndicatorPlacement {
+    /// In the list and chat headers
+    Header,
+    /// In a thin status bar along the bottom of the app
+    StatusBar,
+}
+
+/// When the per-message time is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampVisibility {
+    /// Under every message
+    Always,
+    /// Only while the bubble is hovered, or after tapping it
+    OnHover,
+    /// Hidden
+    Never,
+}
+
+/// Largest image sent before it is scaled down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageSizeLimit {
+    /// Send images as picked
+    Original,
+    /// Up to 2048 px and 2 MB
+    Large,
+    /// Up to 1280 px and 1 MB
+    Medium,
+}
+
+impl ImageSizeLimit {
+    /// Limits to downscale to, or None to leave images alone
+    pub fn limits(&self) -> Option<ImageLimits> {
+        match self {
+            ImageSizeLimit::Original => None,
+            ImageSizeLimit::Large => Some(ImageLimits { max_dimension: 2048, max_bytes: 2 * 1024 * 1024 }),
+            ImageSizeLimit::Medium => Some(ImageLimits { max_dimension: 1280, max_bytes: 1024 * 1024 }),
+        }
+    }
+}
+
 /// Internal state for settings
 #[derive(Debug, Clone)]
 pub struct SettingsStateInner {
     pub server_url: String,
+    /// Token the server expects on connect, if it requires one
+    pub auth_token: Option<String>,
+    /// Server URL from a setup link, until the user accepts or dismisses it (not saved)
+    pub link_url: Option<String>,
     pub modal_open: bool,
+    /// Color theme
+    pub theme: ThemeMode,
+    /// Disable animations and transitions app-wide
+    pub reduce_motion: bool,
+    /// Read new assistant replies aloud as they arrive
+    pub auto_speak: bool,
+    /// Spacing density
+    pub density: Density,
+    /// Send button variant
+    pub send_button_style: SendButtonStyle,
+    /// Typing indicator variant
+    pub typing_style: TypingStyle,
+    /// Connection indicator placement
+    pub indicator_placement: IndicatorPlacement,
+    /// Message timestamp visibility
+    pub timestamp_visibility: TimestampVisibility,
+    /// Size outgoing images are scaled down to
+    pub image_size_limit: ImageSizeLimit,
+    /// Whether preferences can be saved across restarts
+    pub persistence_available: bool,
+}
+
+/// Settings as saved between launches
+///
+/// Every field is optional so files written by older versions (or edited
+/// by hand) still load, with anything missing left at its default.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StoredSettings {
+    server_url: Option<String>,
+    auth_token: Option<String>,
+    theme: Option<ThemeMode>,
+    reduce_motion: Option<bool>,
+    auto_speak: Option<bool>,
+    density: Option<Density>,
+    send_button_style: Option<SendButtonStyle>,
+    typing_style: Option<TypingStyle>,
+    indicator_placement: Option<IndicatorPlacement>,
+    timestamp_visibility: Option<TimestampVisibility>,
+    image_size_limit: Option<ImageSizeLimit>,
 }
 
 /// State for the settings feature (wraps a Signal)
@@ -23,11 +184,88 @@ impl SettingsState {
         Self {
             inner: Signal::new(SettingsStateInner {
                 server_url: DEFAULT_SERVER_URL.to_string(),
+                auth_token: None,
+                link_url: None,
                 modal_open: false,
+                theme: default_theme(),
+                reduce_motion: prefers_reduced_motion(),
+                auto_speak: false,
+                density: default_density(),
+                send_button_style: default_send_button_style(),
+                typing_style: TypingStyle::Dots,
+                indicator_placement: IndicatorPlacement::Header,
+                timestamp_visibility: TimestampVisibility::Always,
+                image_size_limit: ImageSizeLimit::Large,
+                persistence_available: storage::persistence_available(),
             }),
         }
     }
 
+    /// Create settings state from the saved settings
+    ///
+    /// A missing file starts from the defaults; an unreadable or corrupt
+    /// one is logged and ignored.
+    pub fn load() -> Self {
+        Self::load_from(SETTINGS_STORAGE_KEY)
+    }
+
+    fn load_from(key: &str) -> Self {
+        let mut state = Self::new();
+        match storage::load_json::<StoredSettings>(key) {
+            Ok(Some(stored)) => state.apply(stored),
+            Ok(None) => {}
+            Err(e) => storage::warn_in_memory(key, &e),
+        }
+        state
+    }
+
+    /// Apply saved values over the current ones
+    fn apply(&mut self, stored: StoredSettings) {
+        let mut inner = self.inner.write();
+        if let Some(url) = stored.server_url {
+            inner.server_url = url;
+        }
+        if stored.auth_token.is_some() {
+            inner.auth_token = stored.auth_token;
+        }
+        inner.theme = stored.theme.unwrap_or(inner.theme);
+        inner.reduce_motion = stored.reduce_motion.unwrap_or(inner.reduce_motion);
+        inner.auto_speak = stored.auto_speak.unwrap_or(inner.auto_speak);
+        inner.density = stored.density.unwrap_or(inner.density);
+        inner.send_button_style = stored.send_button_style.unwrap_or(inner.send_button_style);
+        inner.typing_style = stored.typing_style.unwrap_or(inner.typing_style);
+        inner.indicator_placement = stored.indicator_placement.unwrap_or(inner.indicator_placement);
+        inner.timestamp_visibility = stored.timestamp_visibility.unwrap_or(inner.timestamp_visibility);
+        inner.image_size_limit = stored.image_size_limit.unwrap_or(inner.image_size_limit);
+    }
+
+    /// Current values in their saved form
+    fn stored(&self) -> StoredSettings {
+        let inner = self.inner.read();
+        StoredSettings {
+            server_url: Some(inner.server_url.clone()),
+            auth_token: inner.auth_token.clone(),
+            theme: Some(inner.theme),
+            reduce_motion: Some(inner.reduce_motion),
+            auto_speak: Some(inner.auto_speak),
+            density: Some(inner.density),
+            send_button_style: Some(inner.send_button_style),
+            typing_style: Some(inner.typing_style),
+            indicator_placement: Some(inner.indicator_placement),
+            timestamp_visibility: Some(inner.timestamp_visibility),
+            image_size_limit: Some(inner.image_size_limit),
+        }
+    }
+
+    /// Save the server URL and preferences for the next launch
+    ///
+    /// Failures are logged and the settings stay in memory.
+    pub fn save(&self) {
+        if let Err(e) = storage::save_json(SETTINGS_STORAGE_KEY, &self.stored()) {
+            storage::warn_in_memory(SETTINGS_STORAGE_KEY, &e);
+        }
+    }
+
     // ============================================
     // Read accessors
     // ============================================
@@ -37,11 +275,90 @@ impl SettingsState {
         self.inner.read().server_url.clone()
     }
 
+    /// Get the auth token sent on connect, if one is set
+    pub fn auth_token(&self) -> Option<String> {
+        self.inner.read().auth_token.clone()
+    }
+
+    /// Get the auth token to send to `url`
+    ///
+    /// Only a URL on the current server gets the saved token, so switching
+    /// servers never hands it to another host.
+    pub fn token_for(&self, url: &str) -> Option<String> {
+        let inner = self.inner.read();
+        let same_server = server_origin(url).is_some_and(|origin| server_origin(&inner.server_url) == Some(origin));
+        inner.auth_token.clone().filter(|_| same_server)
+    }
+
+    /// Get the server URL from a setup link that awaits the user's answer
+    pub fn link_url(&self) -> Option<String> {
+        self.inner.read().link_url.clone()
+    }
+
+    /// Get the server URL and auth token to connect with
+    ///
+    /// Read without subscribing: the connection only restarts on the
+    /// change events, not on every settings write.
+    pub fn connection_target(&self) -> (String, Option<String>) {
+        let inner = self.inner.peek();
+        (inner.server_url.clone(), inner.auth_token.clone())
+    }
+
     /// Check if settings modal is open
     pub fn is_modal_open(&self) -> bool {
         self.inner.read().modal_open
     }
 
+    /// Get the color theme
+    pub fn theme(&self) -> ThemeMode {
+        self.inner.read().theme
+    }
+
+    /// Check if animations should be disabled
+    pub fn reduce_motion(&self) -> bool {
+        self.inner.read().reduce_motion
+    }
+
+    /// Check if new assistant replies should be read aloud
+    pub fn auto_speak(&self) -> bool {
+        self.inner.read().auto_speak
+    }
+
+    /// Get the spacing density
+    pub fn density(&self) -> Density {
+        self.inner.read().density
+    }
+
+    /// Get the send button variant
+    pub fn send_button_style(&self) -> SendButtonStyle {
+        self.inner.read().send_button_style
+    }
+
+    /// Get the typing indicator variant
+    pub fn typing_style(&self) -> TypingStyle {
+        self.inner.read().typing_style
+    }
+
+    /// Get where the connection indicator is shown
+    pub fn indicator_placement(&self) -> IndicatorPlacement {
+        self.inner.read().indicator_placement
+    }
+
+    /// Get when message timestamps are shown
+    pub fn timestamp_visibility(&self) -> TimestampVisibility {
+        self.inner.read().timestamp_visibility
+    }
+
+    /// Get the size outgoing images are scaled down to
+    pub fn image_size_limit(&self) -> ImageSizeLimit {
+        self.inner.read().image_size_limit
+    }
+
+    /// Check if preferences will be saved (false in private browsing etc.)
+    pub fn persistence_available(&self) -> bool {
+        self.inner.read().persistence_available
+    }
+
     // ============================================
     // Mutations (use mut self for Signal write access)
     // ============================================
@@ -51,6 +368,61 @@ impl SettingsState {
         self.inner.write().server_url = url;
     }
 
+    /// Set or clear the auth token
+    pub fn set_auth_token(&mut self, token: Option<String>) {
+        self.inner.write().auth_token = token;
+    }
+
+    /// Hold or drop the server URL from a setup link
+    pub fn set_link_url(&mut self, url: Option<String>) {
+        self.inner.write().link_url = url;
+    }
+
+    /// Set the color theme
+    pub fn set_theme(&mut self, theme: ThemeMode) {
+        self.inner.write().theme = theme;
+    }
+
+    /// Enable or disable reduced motion
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        self.inner.write().reduce_motion = reduce_motion;
+    }
+
+    /// Enable or disable auto-speak of new replies
+    pub fn set_auto_speak(&mut self, auto_speak: bool) {
+        self.inner.write().auto_speak = auto_speak;
+    }
+
+    /// Set the spacing density
+    pub fn set_density(&mut self, density: Density) {
+        self.inner.write().density = density;
+    }
+
+    /// Set the send button variant
+    pub fn set_send_button_style(&mut self, style: SendButtonStyle) {
+        self.inner.write().send_button_style = style;
+    }
+
+    /// Set the typing indicator variant
+    pub fn set_typing_style(&mut self, style: TypingStyle) {
+        self.inner.write().typing_style = style;
+    }
+
+    /// Set where the connection indicator is shown
+    pub fn set_indicator_placement(&mut self, placement: IndicatorPlacement) {
+        self.inner.write().indicator_placement = placement;
+    }
+
+    /// Set when message timestamps are shown
+    pub fn set_timestamp_visibility(&mut self, visibility: TimestampVisibility) {
+        self.inner.write().timestamp_visibility = visibility;
+    }
+
+    /// Set the size outgoing images are scaled down to
+    pub fn set_image_size_limit(&mut self, limit: ImageSizeLimit) {
+        self.inner.write().image_size_limit = limit;
+    }
+
     /// Open settings modal
     pub fn open_modal(&mut self) {
         self.inner.write().modal_open = true;
@@ -68,8 +440,127 @@ impl SettingsState {
     }
 }
 
+/// Follow the OS `prefers-color-scheme` preference (Web)
+#[cfg(target_arch = "wasm32")]
+fn default_theme() -> ThemeMode {
+    let prefers_light = web_sys::window()
+        .and_then(|w| w.match_media("(prefers-color-scheme: light)").ok().flatten())
+        .map(|mq| mq.matches())
+        .unwrap_or(false);
+    if prefers_light {
+        ThemeMode::Light
+    } else {
+        ThemeMode::Dark
+    }
+}
+
+/// Native platforms start dark
+#[cfg(not(target_arch = "wasm32"))]
+fn default_theme() -> ThemeMode {
+    ThemeMode::Dark
+}
+
+/// Read the OS `prefers-reduced-motion` preference (Web)
+#[cfg(target_arch = "wasm32")]
+fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|w| w.match_media("(prefers-reduced-motion: reduce)").ok().flatten())
+        .map(|mq| mq.matches())
+        .unwrap_or(false)
+}
+
+/// Native platforms don't expose the preference, so default to animations on
+#[cfg(not(target_arch = "wasm32"))]
+fn prefers_reduced_motion() -> bool {
+    false
+}
+
+/// Touch devices default to Comfortable, pointer devices to Compact (Web)
+#[cfg(target_arch = "wasm32")]
+fn default_density() -> Density {
+    let coarse_pointer = web_sys::window()
+        .and_then(|w| w.match_media("(pointer: coarse)").ok().flatten())
+        .map(|mq| mq.matches())
+        .unwrap_or(false);
+    if coarse_pointer {
+        Density::Comfortable
+    } else {
+        Density::Compact
+    }
+}
+
+/// Mobile platforms are touch-first, so default to Comfortable
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn default_density() -> Density {
+    Density::Comfortable
+}
+
+/// Desktop platforms default to Compact
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+fn default_density() -> Density {
+    Density::Compact
+}
+
+/// Narrow viewports get the icon, wider ones the text label (Web)
+#[cfg(target_arch = "wasm32")]
+fn default_send_button_style() -> SendButtonStyle {
+    let narrow = web_sys::window()
+        .and_then(|w| w.match_media("(max-width: 640px)").ok().flatten())
+        .map(|mq| mq.matches())
+        .unwrap_or(false);
+    if narrow {
+        SendButtonStyle::Icon
+    } else {
+        SendButtonStyle::Text
+    }
+}
+
+/// Phone screens are narrow, so default to the icon
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn default_send_button_style() -> SendButtonStyle {
+    SendButtonStyle::Icon
+}
+
+/// Desktop has room for the text label
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+fn default_send_button_style() -> SendButtonStyle {
+    SendButtonStyle::Text
+}
+
 impl Default for SettingsState {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+
+    #[test]
+    fn test_load_falls_back_on_missing_or_corrupt_settings() {
+        with_runtime(|| {
+            let missing = SettingsState::load_from("settings-test-missing");
+            assert_eq!(missing.server_url(), DEFAULT_SERVER_URL);
+            assert_eq!(missing.auth_token(), None);
+
+            storage::save_json("settings-test-corrupt", &"not an object").unwrap();
+            let corrupt = SettingsState::load_from("settings-test-corrupt");
+            assert_eq!(corrupt.server_url(), DEFAULT_SERVER_URL);
+
+            let mut state = SettingsState::new();
+            state.set_server_url("ws://example.com/ws".to_string());
+            state.set_auth_token(Some("secret".to_string()));
+            state.set_typing_style(TypingStyle::Pulse);
+            state.set_theme(ThemeMode::Light);
+            storage::save_json("settings-test-saved", &state.stored()).unwrap();
+            let loaded = SettingsState::load_from("settings-test-saved");
+            assert_eq!(loaded.server_url(), "ws://example.com/ws");
+            assert_eq!(loaded.auth_token().as_deref(), Some("secret"));
+            assert_eq!(loaded.typing_style(), TypingStyle::Pulse);
+            assert_eq!(loaded.theme(), ThemeMode::Light);
+            assert_eq!(loaded.indicator_placement(), IndicatorPlacement::Header);
+        });
+    }
+}
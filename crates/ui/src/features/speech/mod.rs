@@ -0,0 +1,19 @@
+//! Speech feature module
+//!
+//! This feature handles reading assistant replies aloud and voice input.
+
+mod tts;
+mod stt;
+mod service;
+
+pub use tts::{is_speaking, is_speech_output_supported, speak, stop_speaking};
+pub use stt::{append_transcript, is_speech_input_supported, listen, stop_listening, SpeechInputError};
+pub use service::SpeechService;
+
+use prsnl_core::SharedEventBus;
+use crate::features::settings::SettingsState;
+
+/// Initialize the speech feature
+pub fn provide_speech_feature(event_bus: SharedEventBus, settings: SettingsState) -> SpeechService {
+    SpeechService::new(settings, event_bus)
+}
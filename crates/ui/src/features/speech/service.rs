@@ -0,0 +1,43 @@
+//! Speech feature service
+
+use dioxus::prelude::spawn;
+use futures::StreamExt;
+
+use prsnl_core::{AppEvent, MessageSender, SharedEventBus};
+use crate::features::settings::SettingsState;
+use super::tts::{is_speech_output_supported, speak};
+
+/// Service that reads new assistant replies aloud when auto-speak is enabled
+#[derive(Clone)]
+pub struct SpeechService {
+    settings: SettingsState,
+    event_bus: SharedEventBus,
+}
+
+impl SpeechService {
+    /// Create a new speech service
+    pub fn new(settings: SettingsState, event_bus: SharedEventBus) -> Self {
+        Self { settings, event_bus }
+    }
+
+    /// Subscribe to relevant events from the event bus
+    ///
+    /// Does nothing in builds that can't speak.
+    pub fn subscribe_to_events(&self) {
+        if !is_speech_output_supported() {
+            return;
+        }
+        let settings = self.settings;
+        let mut rx = self.event_bus.subscribe();
+
+        spawn(async move {
+            while let Some(event) = rx.next().await {
+                if let AppEvent::MessageReceived { message, .. } = event {
+                    if message.sender == MessageSender::Assistant && settings.auto_speak() {
+                        speak(&message.body);
+                    }
+                }
+            }
+        });
+    }
+}
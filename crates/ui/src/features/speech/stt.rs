@@ -0,0 +1,204 @@
+//! Cross-platform speech-to-text for voice input
+
+use std::fmt;
+
+/// Reasons voice input produced no transcript
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpeechInputError {
+    /// The user or platform denied microphone access
+    PermissionDenied,
+    /// Recognition ended without hearing any speech
+    NoSpeech,
+    /// Voice input isn't available on this platform
+    Unsupported,
+    /// Any other recognizer failure
+    Failed(String),
+}
+
+impl fmt::Display for SpeechInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionDenied => write!(f, "Microphone access was denied"),
+            Self::NoSpeech => write!(f, "No speech detected"),
+            Self::Unsupported => write!(f, "Voice input is not supported here"),
+            Self::Failed(e) => write!(f, "Voice input failed: {}", e),
+        }
+    }
+}
+
+/// Append a transcript to the existing input text, separated by a space
+pub fn append_transcript(current: &str, transcript: &str) -> String {
+    let current = current.trim_end();
+    let transcript = transcript.trim();
+    if current.is_empty() {
+        transcript.to_string()
+    } else {
+        format!("{} {}", current, transcript)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static RECOGNITION: std::cell::RefCell<Option<web_sys::SpeechRecognition>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Look up the recognizer constructor, which Chromium still ships prefixed (Web)
+#[cfg(target_arch = "wasm32")]
+fn recognition_constructor() -> Option<js_sys::Function> {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let window = web_sys::window()?;
+    ["SpeechRecognition", "webkitSpeechRecognition"]
+        .iter()
+        .find_map(|name| {
+            js_sys::Reflect::get(&window, &JsValue::from_str(name))
+                .ok()
+                .filter(|ctor| ctor.is_function())
+                .map(|ctor| ctor.unchecked_into::<js_sys::Function>())
+        })
+}
+
+/// Check if voice input is available (Web)
+#[cfg(target_arch = "wasm32")]
+pub fn is_speech_input_supported() -> bool {
+    recognition_constructor().is_some()
+}
+
+/// Capture a single utterance and return its transcript (Web)
+///
+/// Uses the Web Speech API; the browser prompts for microphone access
+/// on first use.
+#[cfg(target_arch = "wasm32")]
+pub async fn listen() -> Result<String, SpeechInputError> {
+    use std::{cell::RefCell, rc::Rc};
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{SpeechRecognition, SpeechRecognitionError, SpeechRecognitionErrorCode, SpeechRecognitionEvent};
+
+    let ctor = recognition_constructor().ok_or(SpeechInputError::Unsupported)?;
+    let recognition: SpeechRecognition = js_sys::Reflect::construct(&ctor, &js_sys::Array::new())
+        .map_err(|e| SpeechInputError::Failed(format!("{:?}", e)))?
+        .unchecked_into();
+    recognition.set_interim_results(false);
+    recognition.set_max_alternatives(1);
+
+    let (tx, rx) = futures_channel::oneshot::channel::<Result<String, SpeechInputError>>();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+
+    let onresult = {
+        let tx = tx.clone();
+        Closure::wrap(Box::new(move |event: SpeechRecognitionEvent| {
+            let transcript = event
+                .results()
+                .map(|results| {
+                    (0..results.length())
+                        .filter_map(|i| results.get(i))
+                        .filter_map(|result| result.get(0))
+                        .map(|alt| alt.transcript())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default();
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(Ok(transcript));
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+
+    let onerror = {
+        let tx = tx.clone();
+        Closure::wrap(Box::new(move |event: SpeechRecognitionError| {
+            let error = match event.error() {
+                SpeechRecognitionErrorCode::NotAllowed
+                | SpeechRecognitionErrorCode::ServiceNotAllowed => SpeechInputError::PermissionDenied,
+                SpeechRecognitionErrorCode::NoSpeech
+                | SpeechRecognitionErrorCode::Aborted => SpeechInputError::NoSpeech,
+                other => SpeechInputError::Failed(format!("{:?}", other)),
+            };
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(Err(error));
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+
+    // Ending without a result or error means nothing was heard
+    let onend = {
+        let tx = tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(Err(SpeechInputError::NoSpeech));
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+
+    recognition.set_onresult(Some(onresult.as_ref().unchecked_ref()));
+    recognition.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    recognition.set_onend(Some(onend.as_ref().unchecked_ref()));
+
+    recognition
+        .start()
+        .map_err(|e| SpeechInputError::Failed(format!("{:?}", e)))?;
+    RECOGNITION.with(|r| *r.borrow_mut() = Some(recognition.clone()));
+
+    let result = rx.await.unwrap_or(Err(SpeechInputError::NoSpeech));
+
+    // Detach handlers before the closures are dropped
+    recognition.set_onresult(None);
+    recognition.set_onerror(None);
+    recognition.set_onend(None);
+    recognition.abort();
+    RECOGNITION.with(|r| r.borrow_mut().take());
+
+    result
+}
+
+/// Stop listening; the transcript captured so far is still delivered (Web)
+#[cfg(target_arch = "wasm32")]
+pub fn stop_listening() {
+    RECOGNITION.with(|r| {
+        if let Some(recognition) = r.borrow().as_ref() {
+            recognition.stop();
+        }
+    });
+}
+
+/// Check if voice input is available (Native/Android)
+///
+/// Android needs a `RecognitionListener` implemented on the Java side to
+/// receive results from `android.speech.SpeechRecognizer`, which the app
+/// doesn't ship yet. Voice input is therefore reported as unsupported and
+/// the message input leaves out the mic button.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn is_speech_input_supported() -> bool {
+    false
+}
+
+/// Capture a single utterance (Native)
+///
+/// Always `Unsupported`; callers check `is_speech_input_supported` first.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn listen() -> Result<String, SpeechInputError> {
+    Err(SpeechInputError::Unsupported)
+}
+
+/// Stop listening (no-op on native)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn stop_listening() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_transcript() {
+        assert_eq!(append_transcript("", " hello "), "hello");
+        assert_eq!(append_transcript("note: ", "buy milk"), "note: buy milk");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_native_hides_voice_input() {
+        assert!(!is_speech_input_supported());
+        assert_eq!(futures::executor::block_on(listen()), Err(SpeechInputError::Unsupported));
+    }
+}
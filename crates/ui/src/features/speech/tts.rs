@@ -0,0 +1,106 @@
+//! Cross-platform text-to-speech
+
+/// Check if this build can speak at all
+///
+/// Browsers have speech synthesis; native builds need the `tts` feature.
+/// The Speak action and the auto-speak setting are hidden otherwise.
+pub const fn is_speech_output_supported() -> bool {
+    cfg!(any(target_arch = "wasm32", feature = "tts"))
+}
+
+/// Speak text aloud, queued after anything already being spoken (Web)
+#[cfg(target_arch = "wasm32")]
+pub fn speak(text: &str) {
+    use tracing::warn;
+
+    let Some(synth) = web_sys::window().and_then(|w| w.speech_synthesis().ok()) else {
+        warn!("Speech synthesis not available in this browser");
+        return;
+    };
+    match web_sys::SpeechSynthesisUtterance::new_with_text(text) {
+        Ok(utterance) => synth.speak(&utterance),
+        Err(e) => warn!("Failed to create utterance: {:?}", e),
+    }
+}
+
+/// Stop speaking and drop any queued utterances (Web)
+#[cfg(target_arch = "wasm32")]
+pub fn stop_speaking() {
+    if let Some(synth) = web_sys::window().and_then(|w| w.speech_synthesis().ok()) {
+        synth.cancel();
+    }
+}
+
+/// Check if speech is currently playing (Web)
+#[cfg(target_arch = "wasm32")]
+pub fn is_speaking() -> bool {
+    web_sys::window()
+        .and_then(|w| w.speech_synthesis().ok())
+        .map(|synth| synth.speaking())
+        .unwrap_or(false)
+}
+
+// The `tts` crate wraps each platform's engine; on Android it drives
+// `android.speech.tts.TextToSpeech` over JNI.
+#[cfg(all(not(target_arch = "wasm32"), feature = "tts"))]
+thread_local! {
+    static TTS: std::cell::RefCell<Option<tts::Tts>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Run a closure with the lazily initialized engine (Native)
+#[cfg(all(not(target_arch = "wasm32"), feature = "tts"))]
+fn with_tts<R>(f: impl FnOnce(&mut tts::Tts) -> R) -> Option<R> {
+    use tracing::warn;
+
+    TTS.with(|cell| {
+        let mut engine = cell.borrow_mut();
+        if engine.is_none() {
+            match tts::Tts::default() {
+                Ok(tts) => *engine = Some(tts),
+                Err(e) => {
+                    warn!("Failed to initialize text-to-speech: {}", e);
+                    return None;
+                }
+            }
+        }
+        engine.as_mut().map(f)
+    })
+}
+
+/// Speak text aloud, queued after anything already being spoken (Native)
+#[cfg(all(not(target_arch = "wasm32"), feature = "tts"))]
+pub fn speak(text: &str) {
+    if let Some(Err(e)) = with_tts(|tts| tts.speak(text, false).map(|_| ())) {
+        tracing::warn!("Failed to speak: {}", e);
+    }
+}
+
+/// Stop speaking and drop any queued utterances (Native)
+#[cfg(all(not(target_arch = "wasm32"), feature = "tts"))]
+pub fn stop_speaking() {
+    if let Some(Err(e)) = with_tts(|tts| tts.stop().map(|_| ())) {
+        tracing::warn!("Failed to stop speaking: {}", e);
+    }
+}
+
+/// Check if speech is currently playing (Native)
+#[cfg(all(not(target_arch = "wasm32"), feature = "tts"))]
+pub fn is_speaking() -> bool {
+    with_tts(|tts| tts.is_speaking().unwrap_or(false)).unwrap_or(false)
+}
+
+/// Speak text aloud (placeholder - built without the `tts` feature)
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "tts")))]
+pub fn speak(_text: &str) {
+    tracing::warn!("Text-to-speech not available: built without the `tts` feature");
+}
+
+/// Stop speaking (no-op without the `tts` feature)
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "tts")))]
+pub fn stop_speaking() {}
+
+/// Check if speech is currently playing (always false without the `tts` feature)
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "tts")))]
+pub fn is_speaking() -> bool {
+    false
+}
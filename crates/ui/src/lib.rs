@@ -6,15 +6,21 @@ pub mod features;
 pub mod shared;
 pub mod shells;
 
-pub use shared::ConnectionIndicator;
+#[cfg(test)]
+mod test_support;
+
+pub use shared::{ConnectionIndicator, ConnectionStatusBar, EmptyState, ErrorState, LoadingSpinner, OfflineBanner};
 pub use shells::{DesktopShell, MobileShell, ResponsiveApp};
 
 // Re-export feature types
 pub use features::{
     ChatScreen, ChatHeader, MessageList, MessageBubble, MessageInput, TypingIndicator,
-    ChatService, ChatState, provide_chat_feature,
+    ChatService, ChatState, DEFAULT_MESSAGE_LIMIT, provide_chat_feature,
     ConversationItem, ConversationList, ConversationsService, ConversationsState,
-    ViewState, provide_conversations_feature,
-    MediaPreview, SelectedMedia, pick_image,
-    ServerUrlModal, SettingsService, SettingsState, provide_settings_feature,
+    ViewState, provide_conversations_feature, use_app_ready,
+    FileChip, FileFilter, MediaPreview, SelectedMedia, pick_file,
+    EditableConnectionIndicator, ServerUrlModal, SettingsModal, SettingsService, SettingsState, provide_settings_feature,
+    SpeechService, provide_speech_feature,
+    NotificationToast, NotificationsService, NotificationsState, ToastStack, provide_notifications_feature,
+    SearchBar, SearchResults, SearchService, SearchState, provide_search_feature,
 };
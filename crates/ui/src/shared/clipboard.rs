@@ -0,0 +1,9 @@
+//! Clipboard access via the webview
+
+use super::run_script;
+
+/// Copy text to the system clipboard
+pub fn copy_to_clipboard(text: &str) {
+    let quoted = serde_json::to_string(text).unwrap_or_default();
+    run_script("copy_to_clipboard", format!("navigator.clipboard.writeText({})", quoted));
+}
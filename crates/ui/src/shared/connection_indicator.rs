@@ -1,9 +1,23 @@
 //! Connection status indicator component
 
+use std::time::Duration;
 use dioxus::prelude::*;
 use prsnl_core::ConnectionStatus;
+use super::{timer, use_reconnect, FrameLogPanel};
+
+/// Taps this close together count toward the triple tap opening the frame log
+const MULTI_TAP_WINDOW: Duration = Duration::from_millis(400);
 
 /// Connection indicator that shows current WebSocket status
+///
+/// While disconnected a retry button sits next to it; it is hidden while
+/// an attempt is in flight so presses can't stack, and counts down the
+/// same cooldown as the offline banner's button. An auth error gets its
+/// own color and no retry button, since only a new token helps; hovering
+/// shows the server's reason.
+///
+/// With the `debug-log` feature a triple tap opens the recent frames
+/// instead; a single tap then waits out the tap window before `on_tap`.
 #[component]
 pub fn ConnectionIndicator(
     status: ConnectionStatus,
@@ -14,19 +28,84 @@ pub fn ConnectionIndicator(
         ConnectionStatus::Connecting => ("bg-warning animate-pulse-status", "Connecting...", ""),
         ConnectionStatus::Reconnecting => ("bg-warning animate-pulse-status", "Reconnecting...", ""),
         ConnectionStatus::Disconnected => ("bg-error", "Disconnected", ""),
+        ConnectionStatus::AuthError(_) => ("bg-auth-error", "Auth failed", ""),
+    };
+    let (reason, text_class) = match &status {
+        ConnectionStatus::AuthError(reason) => (reason.clone(), "text-auth-error"),
+        _ => (String::new(), "text-text-muted"),
+    };
+    let reconnect = use_reconnect();
+    let retry_in = reconnect.seconds_left();
+    let mut taps = use_signal(|| 0u32);
+    let mut show_frames = use_signal(|| false);
+
+    let handle_tap = move |_| {
+        if !cfg!(feature = "debug-log") {
+            on_tap.call(());
+            return;
+        }
+        taps += 1;
+        match taps() {
+            1 => {
+                spawn(async move {
+                    timer::sleep(MULTI_TAP_WINDOW).await;
+                    // Zero when the taps already opened the frame log
+                    if taps() > 0 {
+                        taps.set(0);
+                        on_tap.call(());
+                    }
+                });
+            }
+            3 => {
+                taps.set(0);
+                show_frames.set(true);
+            }
+            _ => {}
+        }
     };
 
     rsx! {
-        button {
-            onclick: move |_| on_tap.call(()),
-            class: "bg-transparent border-none cursor-pointer flex items-center gap-1.5 p-2 {btn_class}",
-            span {
-                class: "w-2 h-2 rounded-full {dot_class}",
+        div {
+            class: "flex items-center",
+            button {
+                onclick: handle_tap,
+                class: "bg-transparent border-none cursor-pointer flex items-center gap-1.5 p-2 {btn_class}",
+                title: "{reason}",
+                span {
+                    class: "w-2 h-2 rounded-full {dot_class}",
+                }
+                span {
+                    class: "{text_class} text-xs",
+                    "{text}"
+                }
             }
-            span {
-                class: "text-text-muted text-xs",
-                "{text}"
+            if status == ConnectionStatus::Disconnected {
+                button {
+                    onclick: move |_| reconnect.request(),
+                    class: "px-2 py-0.5 rounded-md border-none bg-bg-tertiary text-text-white text-xs cursor-pointer disabled:opacity-50 disabled:cursor-default",
+                    disabled: retry_in.is_some(),
+                    title: "Reconnect now",
+                    if let Some(left) = retry_in {
+                        "Retry ({left}s)"
+                    } else {
+                        "Retry"
+                    }
+                }
             }
+            if show_frames() {
+                FrameLogPanel { on_close: move |_| show_frames.set(false) }
+            }
+        }
+    }
+}
+
+/// Thin bar along the bottom of the app hosting the connection indicator
+#[component]
+pub fn ConnectionStatusBar(children: Element) -> Element {
+    rsx! {
+        footer {
+            class: "shrink-0 flex justify-center border-t border-border bg-bg-secondary",
+            {children}
         }
     }
 }
@@ -0,0 +1,93 @@
+//! Scroll and focus helpers that work on every renderer
+//!
+//! These go through `document::eval`, which desktop, mobile and web all
+//! support. Where there is no document to talk to (outside a component, in
+//! tests, or on a renderer without JS interop) they log a warning and do
+//! nothing instead of panicking.
+
+use std::rc::Rc;
+
+use dioxus::dioxus_core::Runtime;
+use dioxus::document::Document;
+use dioxus::prelude::*;
+use tracing::warn;
+
+/// Scroll an element to the end of its content
+pub fn scroll_to_bottom(element_id: &str) {
+    run_script(
+        "scroll_to_bottom",
+        format!("const el = document.getElementById({}); if (el) el.scrollTop = el.scrollHeight;", quote(element_id)),
+    );
+}
+
+/// Scroll an element so its content ends `from_bottom` px below the top of the viewport
+///
+/// Used to keep the same message in view after content is prepended.
+pub fn scroll_to_offset_from_bottom(element_id: &str, from_bottom: f64) {
+    run_script(
+        "scroll_to_offset_from_bottom",
+        format!(
+            "const el = document.getElementById({}); if (el) el.scrollTop = el.scrollHeight - {};",
+            quote(element_id),
+            from_bottom
+        ),
+    );
+}
+
+/// Scroll the page until an element is centered in view
+pub fn scroll_into_view(element_id: &str) {
+    run_script(
+        "scroll_into_view",
+        format!("document.getElementById({})?.scrollIntoView({{ block: 'center' }});", quote(element_id)),
+    );
+}
+
+/// Move keyboard focus to an element
+pub fn focus(element_id: &str) {
+    run_script("focus", format!("document.getElementById({})?.focus();", quote(element_id)));
+}
+
+/// Fire-and-forget a script, returning false if it could not be run
+///
+/// Failures reported by the renderer (e.g. eval unsupported) are logged
+/// once the script settles.
+pub(crate) fn run_script(action: &'static str, script: String) -> bool {
+    if !has_document() {
+        warn!("{}: no document available, skipping", action);
+        return false;
+    }
+    let eval = document::eval(&script);
+    spawn(async move {
+        if let Err(e) = eval.await {
+            warn!("{}: script failed: {}", action, e);
+        }
+    });
+    true
+}
+
+/// Check if scripts can run: inside a component, on a renderer with a document
+pub(crate) fn has_document() -> bool {
+    let in_scope = Runtime::try_current().is_some_and(|rt| rt.try_current_scope_id().is_some());
+    in_scope && try_consume_context::<Rc<dyn Document>>().is_some()
+}
+
+/// Element ids are interpolated as JS string literals
+pub(crate) fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_runtime;
+
+    #[test]
+    fn test_skips_without_a_document() {
+        assert!(!run_script("test", String::new()));
+        with_runtime(|| {
+            assert!(!run_script("test", String::new()));
+            scroll_to_bottom("chat-container");
+            focus("message-input");
+        });
+    }
+}
@@ -0,0 +1,77 @@
+//! Hidden panel listing the transport's recent raw frames
+
+use dioxus::prelude::*;
+use prsnl_core::{format_frames, SharedTransport};
+use super::copy_to_clipboard;
+
+/// Modal dumping the recent frames so they can be pasted into a bug report
+///
+/// The frames are read once when the panel opens. Builds without the
+/// `debug-log` feature keep no frames.
+#[component]
+pub fn FrameLogPanel(on_close: EventHandler<()>) -> Element {
+    let log = use_hook(|| {
+        try_use_context::<SharedTransport>()
+            .map(|transport| format_frames(&transport.recent_frames()))
+            .unwrap_or_default()
+    });
+    let mut copied = use_signal(|| false);
+
+    rsx! {
+        // Backdrop
+        div {
+            onclick: move |_| on_close.call(()),
+            class: "fixed inset-0 bg-black/70 flex items-center justify-center z-[1000]",
+
+            div {
+                onclick: move |e| e.stop_propagation(),
+                class: "bg-bg-secondary rounded-2xl p-6 w-[90%] max-w-[640px] shadow-2xl flex flex-col max-h-[80vh]",
+
+                h2 {
+                    class: "text-text-white m-0 mb-4 text-xl",
+                    "Recent Frames"
+                }
+
+                if log.is_empty() {
+                    p {
+                        class: "text-text-muted text-sm mb-4",
+                        if cfg!(feature = "debug-log") {
+                            "No frames yet."
+                        } else {
+                            "Frame logging is off in this build."
+                        }
+                    }
+                } else {
+                    pre {
+                        class: "flex-1 min-h-0 overflow-auto m-0 mb-4 p-3 rounded-lg bg-bg-primary text-text-secondary text-xs whitespace-pre-wrap break-all select-text",
+                        "{log}"
+                    }
+                }
+
+                div {
+                    class: "flex gap-3 justify-end",
+
+                    if !log.is_empty() {
+                        button {
+                            onclick: {
+                                let log = log.clone();
+                                move |_| {
+                                    copy_to_clipboard(&log);
+                                    copied.set(true);
+                                }
+                            },
+                            class: "py-3 px-6 border border-border rounded-lg bg-transparent text-text-white cursor-pointer text-base hover:bg-bg-hover transition-colors",
+                            if copied() { "Copied" } else { "Copy" }
+                        }
+                    }
+
+                    button {
+                        onclick: move |_| on_close.call(()),
+                        class: "py-3 px-6 border-none rounded-lg bg-accent text-text-white cursor-pointer text-base hover:bg-accent-hover transition-colors",
+                        "Close"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,24 @@
 //! Shared UI components used across features
 
+mod clipboard;
 mod connection_indicator;
+mod dom;
+mod frame_log_panel;
+mod offline_banner;
+mod reconnect;
+mod states;
+pub mod storage;
+mod theme;
+pub mod timer;
 
-pub use connection_indicator::ConnectionIndicator;
+pub use clipboard::copy_to_clipboard;
+pub use connection_indicator::{ConnectionIndicator, ConnectionStatusBar};
+pub use frame_log_panel::FrameLogPanel;
+pub use offline_banner::OfflineBanner;
+pub use reconnect::{use_reconnect, use_reconnect_provider, Reconnect, ReconnectCooldown, RECONNECT_COOLDOWN};
+pub use dom::{focus, scroll_into_view, scroll_to_bottom, scroll_to_offset_from_bottom};
+pub(crate) use dom::run_script;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use dom::{has_document, quote};
+pub use states::{EmptyState, ErrorState, LoadingSpinner};
+pub use theme::{use_theme, use_theme_provider, Theme};
@@ -0,0 +1,38 @@
+//! Offline banner with a rate-limited manual reconnect
+
+use dioxus::prelude::*;
+use prsnl_core::ConnectionStatus;
+
+use super::use_reconnect;
+
+/// Banner shown while disconnected, with a Reconnect button
+///
+/// The button shares its cooldown with the connection indicator's Retry.
+#[component]
+pub fn OfflineBanner() -> Element {
+    let status = use_context::<Signal<ConnectionStatus>>();
+    let reconnect = use_reconnect();
+
+    if status() != ConnectionStatus::Disconnected {
+        return rsx! {};
+    }
+
+    let remaining = reconnect.seconds_left();
+    let label = match remaining {
+        Some(left) => format!("Reconnect ({}s)", left),
+        None => "Reconnect".to_string(),
+    };
+
+    rsx! {
+        div {
+            class: "shrink-0 flex items-center justify-center gap-3 px-4 py-2 bg-error/15 border-b border-error/40 text-sm text-text-white",
+            span { "You're offline" }
+            button {
+                class: "px-3 py-1 rounded-md border-none bg-bg-tertiary text-text-white text-xs cursor-pointer disabled:opacity-50 disabled:cursor-default",
+                disabled: remaining.is_some(),
+                onclick: move |_| reconnect.request(),
+                "{label}"
+            }
+        }
+    }
+}
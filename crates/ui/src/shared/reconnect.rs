@@ -0,0 +1,173 @@
+//! Rate-limited manual reconnect shared by every reconnect button
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dioxus::dioxus_core::spawn_forever;
+use dioxus::prelude::*;
+use prsnl_core::{AppEvent, ConnectionStatus, SharedEventBus};
+
+use super::timer;
+
+/// Default time the reconnect buttons stay disabled after a manual attempt
+pub const RECONNECT_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// When the last manual reconnect was started, to rate-limit the buttons
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconnectCooldown {
+    last_attempt: Option<DateTime<Utc>>,
+}
+
+impl ReconnectCooldown {
+    /// Time left before another manual attempt is allowed, if any
+    pub fn remaining(&self, cooldown: Duration, now: DateTime<Utc>) -> Option<Duration> {
+        let elapsed = (now - self.last_attempt?).to_std().unwrap_or_default();
+        cooldown.checked_sub(elapsed).filter(|d| !d.is_zero())
+    }
+
+    /// Record a manual attempt, or return false while still cooling down
+    pub fn try_start(&mut self, cooldown: Duration, now: DateTime<Utc>) -> bool {
+        if self.remaining(cooldown, now).is_some() {
+            return false;
+        }
+        self.last_attempt = Some(now);
+        true
+    }
+
+    /// Allow the next attempt right away (after a successful connection)
+    pub fn reset(&mut self) {
+        self.last_attempt = None;
+    }
+}
+
+/// Manual reconnect with one cooldown for the offline banner and the
+/// connection indicator, so neither can get around the other's countdown
+#[derive(Clone)]
+pub struct Reconnect {
+    event_bus: SharedEventBus,
+    cooldown: Duration,
+    attempts: Signal<ReconnectCooldown>,
+    /// Ticks once a second while cooling down to refresh the countdown
+    now: Signal<DateTime<Utc>>,
+}
+
+impl Reconnect {
+    /// Create a reconnect that waits `cooldown` between manual attempts
+    pub fn new(event_bus: SharedEventBus, cooldown: Duration) -> Self {
+        Self {
+            event_bus,
+            cooldown,
+            attempts: Signal::new(ReconnectCooldown::default()),
+            now: Signal::new(Utc::now()),
+        }
+    }
+
+    /// Whole seconds left before the buttons work again, if cooling down
+    pub fn seconds_left(&self) -> Option<u64> {
+        let now = *self.now.read();
+        self.attempts
+            .read()
+            .remaining(self.cooldown, now)
+            .map(|left| left.as_millis().div_ceil(1000) as u64)
+    }
+
+    /// Ask the app to reconnect now; ignored while cooling down
+    pub fn request(&self) {
+        let started = Utc::now();
+        let mut attempts = self.attempts;
+        if !attempts.write().try_start(self.cooldown, started) {
+            return;
+        }
+        let mut now = self.now;
+        now.set(started);
+        self.event_bus.publish(AppEvent::ReconnectRequested);
+
+        // Not tied to the pressed button, which hides once reconnecting
+        let cooldown = self.cooldown;
+        spawn_forever(async move {
+            while attempts.peek().remaining(cooldown, Utc::now()).is_some() {
+                timer::sleep(Duration::from_secs(1)).await;
+                now.set(Utc::now());
+            }
+        });
+    }
+
+    /// Allow the next attempt right away
+    pub fn reset(&self) {
+        let mut attempts = self.attempts;
+        attempts.write().reset();
+    }
+}
+
+/// Provide the shared manual reconnect to descendants
+///
+/// Called once near the root; the cooldown ends early once a connection
+/// succeeds.
+pub fn use_reconnect_provider(cooldown: Duration) -> Reconnect {
+    let event_bus = use_context::<SharedEventBus>();
+    let status = use_context::<Signal<ConnectionStatus>>();
+    let reconnect = use_context_provider(|| Reconnect::new(event_bus, cooldown));
+
+    let connected = reconnect.clone();
+    use_effect(move || {
+        if status() == ConnectionStatus::Connected {
+            connected.reset();
+        }
+    });
+    reconnect
+}
+
+/// The manual reconnect provided by `use_reconnect_provider`
+pub fn use_reconnect() -> Reconnect {
+    use_context::<Reconnect>()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_support::{with_runtime, RecordingEventBus};
+
+    #[test]
+    fn test_cooldown_blocks_until_elapsed_or_reset() {
+        let cooldown = Duration::from_secs(10);
+        let t0 = Utc::now();
+        let mut attempts = ReconnectCooldown::default();
+
+        assert!(attempts.try_start(cooldown, t0));
+        assert!(!attempts.try_start(cooldown, t0 + chrono::Duration::seconds(4)));
+        assert_eq!(
+            attempts.remaining(cooldown, t0 + chrono::Duration::seconds(4)),
+            Some(Duration::from_secs(6))
+        );
+        assert!(attempts.try_start(cooldown, t0 + chrono::Duration::seconds(10)));
+
+        attempts.reset();
+        assert!(attempts.try_start(cooldown, t0 + chrono::Duration::seconds(11)));
+    }
+
+    #[test]
+    fn test_requests_share_one_cooldown() {
+        let bus = Arc::new(RecordingEventBus::new());
+        with_runtime(|| {
+            // Two copies, as the banner and the indicator each hold one
+            let banner = Reconnect::new(bus.clone(), RECONNECT_COOLDOWN);
+            let indicator = banner.clone();
+
+            banner.request();
+            indicator.request();
+            assert_eq!(indicator.seconds_left(), Some(10));
+
+            banner.reset();
+            assert_eq!(indicator.seconds_left(), None);
+            indicator.request();
+        });
+        let requests = bus
+            .published()
+            .into_iter()
+            .filter(|event| matches!(event, AppEvent::ReconnectRequested))
+            .count();
+        assert_eq!(requests, 2);
+    }
+}
@@ -0,0 +1,77 @@
+//! Loading, empty and error placeholders shared across features
+
+use dioxus::prelude::*;
+use crate::features::settings::SettingsState;
+
+/// Spinning ring with an optional label
+///
+/// Renders a static ring when the reduce-motion setting is on.
+#[component]
+pub fn LoadingSpinner(#[props(default, into)] label: Option<String>) -> Element {
+    let reduce_motion = try_use_context::<SettingsState>()
+        .map(|s| s.reduce_motion())
+        .unwrap_or(false);
+    let ring_class = if reduce_motion {
+        "w-6 h-6 rounded-full border-2 border-border border-t-accent"
+    } else {
+        "w-6 h-6 rounded-full border-2 border-border border-t-accent animate-spin"
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-col items-center justify-center gap-3 p-8 text-text-muted",
+            role: "status",
+            span { class: "{ring_class}" }
+            if let Some(label) = label {
+                span { class: "text-sm", "{label}" }
+            }
+        }
+    }
+}
+
+/// Placeholder for a view with nothing to show yet
+#[component]
+pub fn EmptyState(
+    #[props(default, into)] icon: Option<String>,
+    #[props(into)] title: String,
+    #[props(default, into)] subtitle: Option<String>,
+    #[props(default)] action: Option<Element>,
+) -> Element {
+    rsx! {
+        div {
+            class: "flex-1 h-full flex flex-col items-center justify-center p-8 text-center text-text-muted",
+            if let Some(icon) = icon {
+                div { class: "text-6xl mb-6 opacity-50", "{icon}" }
+            }
+            p { class: "m-0 mb-2 text-lg text-text-secondary", "{title}" }
+            if let Some(subtitle) = subtitle {
+                p { class: "m-0 text-sm", "{subtitle}" }
+            }
+            if let Some(action) = action {
+                div { class: "mt-4", {action} }
+            }
+        }
+    }
+}
+
+/// Placeholder for a view that failed to load, with an optional retry button
+#[component]
+pub fn ErrorState(
+    #[props(into)] message: String,
+    #[props(default)] on_retry: Option<EventHandler<()>>,
+) -> Element {
+    rsx! {
+        div {
+            class: "flex-1 flex flex-col items-center justify-center gap-4 p-8 text-center",
+            role: "alert",
+            p { class: "m-0 text-error text-sm", "{message}" }
+            if let Some(on_retry) = on_retry {
+                button {
+                    onclick: move |_| on_retry.call(()),
+                    class: "px-4 py-2 rounded-lg bg-bg-tertiary border border-border text-text-white text-sm cursor-pointer hover:bg-bg-hover",
+                    "Retry"
+                }
+            }
+        }
+    }
+}
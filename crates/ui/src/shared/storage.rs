@@ -0,0 +1,145 @@
+//! Local key-value persistence for UI state
+//!
+//! Values are stored as JSON: in `localStorage` on web and as files in the
+//! platform config directory on native. Storage can be missing entirely
+//! (private browsing, headless hosts without a config directory), so every
+//! operation returns a `Result` and callers keep working in memory.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+/// Errors from the persistence layer
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    /// No backing store on this platform/session
+    Unavailable,
+    /// The store exists but reading or writing failed
+    Io(String),
+    /// The stored value couldn't be (de)serialized
+    Format(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "local storage is unavailable"),
+            StorageError::Io(e) => write!(f, "storage I/O failed: {}", e),
+            StorageError::Format(e) => write!(f, "stored value is malformed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Set once the in-memory fallback warning has been logged
+static FALLBACK_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Load a previously saved value (`Ok(None)` when nothing is stored)
+pub fn load_json<T: DeserializeOwned>(key: &str) -> Result<Option<T>, StorageError> {
+    let Some(raw) = read_raw(key)? else {
+        return Ok(None);
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|e| StorageError::Format(e.to_string()))
+}
+
+/// Save a value, replacing anything stored under the same key
+pub fn save_json<T: Serialize>(key: &str, value: &T) -> Result<(), StorageError> {
+    let raw = serde_json::to_string(value).map_err(|e| StorageError::Format(e.to_string()))?;
+    write_raw(key, &raw)
+}
+
+/// Log that a value is only kept in memory
+///
+/// Unavailable storage is reported once per session rather than on every
+/// save; other failures are logged each time.
+pub fn warn_in_memory(key: &str, error: &StorageError) {
+    if *error == StorageError::Unavailable {
+        if !FALLBACK_WARNED.swap(true, Ordering::Relaxed) {
+            warn!("Local storage unavailable, settings and caches are kept in memory only");
+        }
+    } else {
+        warn!("Failed to persist {}, keeping it in memory: {}", key, error);
+    }
+}
+
+/// Check whether values saved now will survive a restart
+#[cfg(target_arch = "wasm32")]
+pub fn persistence_available() -> bool {
+    // Some private modes expose localStorage but throw on write
+    const PROBE_KEY: &str = "prsnl.__probe";
+    local_storage().is_some_and(|storage| {
+        let ok = storage.set_item(PROBE_KEY, "1").is_ok();
+        let _ = storage.remove_item(PROBE_KEY);
+        ok
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_raw(key: &str) -> Result<Option<String>, StorageError> {
+    let storage = local_storage().ok_or(StorageError::Unavailable)?;
+    storage
+        .get_item(&format!("prsnl.{}", key))
+        .map_err(|e| StorageError::Io(format!("{:?}", e)))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_raw(key: &str, raw: &str) -> Result<(), StorageError> {
+    let storage = local_storage().ok_or(StorageError::Unavailable)?;
+    storage
+        .set_item(&format!("prsnl.{}", key), raw)
+        .map_err(|e| StorageError::Io(format!("{:?}", e)))
+}
+
+/// Check whether values saved now will survive a restart
+#[cfg(not(target_arch = "wasm32"))]
+pub fn persistence_available() -> bool {
+    dirs::config_dir()
+        .and_then(|dir| std::fs::metadata(dir).ok())
+        .is_some_and(|meta| meta.is_dir() && !meta.permissions().readonly())
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn storage_dir() -> Option<std::path::PathBuf> {
+    Some(dirs::config_dir()?.join("prsnl-assistant"))
+}
+
+/// Unit tests never read or overwrite the user's real settings
+#[cfg(all(not(target_arch = "wasm32"), test))]
+fn storage_dir() -> Option<std::path::PathBuf> {
+    Some(std::env::temp_dir().join(format!("prsnl-assistant-test-{}", std::process::id())))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_path(key: &str) -> Result<std::path::PathBuf, StorageError> {
+    storage_dir()
+        .map(|dir| dir.join(format!("{}.json", key)))
+        .ok_or(StorageError::Unavailable)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_raw(key: &str) -> Result<Option<String>, StorageError> {
+    match std::fs::read_to_string(storage_path(key)?) {
+        Ok(raw) => Ok(Some(raw)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(StorageError::Io(e.to_string())),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_raw(key: &str, raw: &str) -> Result<(), StorageError> {
+    let path = storage_path(key)?;
+    path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, raw))
+        .map_err(|e| StorageError::Io(format!("{}: {}", path.display(), e)))
+}
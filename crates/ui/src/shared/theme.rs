@@ -0,0 +1,139 @@
+//! Color palettes for the app themes
+//!
+//! Components style themselves with the Tailwind color tokens from
+//! `assets/input.css` (`bg-bg-primary`, `text-text-muted`, ...). A `Theme`
+//! holds a value for each token; the shells set them as CSS custom
+//! properties on their root element, so every token class below it follows
+//! the active theme.
+
+use dioxus::prelude::*;
+use crate::features::settings::SettingsState;
+
+/// Values for the theme color tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub bg_primary: &'static str,
+    pub bg_secondary: &'static str,
+    pub bg_tertiary: &'static str,
+    pub bg_hover: &'static str,
+    pub text_primary: &'static str,
+    pub text_secondary: &'static str,
+    pub text_muted: &'static str,
+    /// Strongest text color (white on the dark theme)
+    pub text_white: &'static str,
+    pub accent: &'static str,
+    pub accent_hover: &'static str,
+    pub accent_light: &'static str,
+    pub success: &'static str,
+    pub warning: &'static str,
+    pub error: &'static str,
+    pub border: &'static str,
+    pub border_light: &'static str,
+}
+
+impl Theme {
+    /// The original palette (same values as `assets/input.css`)
+    pub const fn dark() -> Self {
+        Self {
+            bg_primary: "#0f0f23",
+            bg_secondary: "#1a1a2e",
+            bg_tertiary: "#2d2d44",
+            bg_hover: "#1f1f3a",
+            text_primary: "#e0e0e0",
+            text_secondary: "#8888a8",
+            text_muted: "#6b6b8a",
+            text_white: "#ffffff",
+            accent: "#1e88e5",
+            accent_hover: "#1976d2",
+            accent_light: "oklch(0.59 0.15 250 / 0.5)",
+            success: "#4ade80",
+            warning: "#fbbf24",
+            error: "#ef4444",
+            border: "#2d2d44",
+            border_light: "#3d3d5c",
+        }
+    }
+
+    /// Light backgrounds with dark text
+    pub const fn light() -> Self {
+        Self {
+            bg_primary: "#f5f5fa",
+            bg_secondary: "#ffffff",
+            bg_tertiary: "#e6e6ef",
+            bg_hover: "#ededf5",
+            text_primary: "#1f1f2e",
+            text_secondary: "#55556e",
+            text_muted: "#74748e",
+            text_white: "#0f0f23",
+            accent: "#1e88e5",
+            accent_hover: "#1976d2",
+            accent_light: "oklch(0.59 0.15 250 / 0.3)",
+            success: "#16a34a",
+            warning: "#d97706",
+            error: "#dc2626",
+            border: "#dcdce6",
+            border_light: "#c8c8d8",
+        }
+    }
+
+    /// Inline style declaring every token as a CSS custom property
+    pub fn css_vars(&self) -> String {
+        [
+            ("bg-primary", self.bg_primary),
+            ("bg-secondary", self.bg_secondary),
+            ("bg-tertiary", self.bg_tertiary),
+            ("bg-hover", self.bg_hover),
+            ("text-primary", self.text_primary),
+            ("text-secondary", self.text_secondary),
+            ("text-muted", self.text_muted),
+            ("text-white", self.text_white),
+            ("accent", self.accent),
+            ("accent-hover", self.accent_hover),
+            ("accent-light", self.accent_light),
+            ("success", self.success),
+            ("warning", self.warning),
+            ("error", self.error),
+            ("border", self.border),
+            ("border-light", self.border_light),
+        ]
+        .iter()
+        .map(|(token, value)| format!("--color-{}: {};", token, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Provide the palette of the chosen theme to descendants
+///
+/// Called once near the root; the value follows the settings.
+pub fn use_theme_provider() -> Memo<Theme> {
+    let settings: SettingsState = use_context();
+    let theme = use_memo(move || settings.theme().palette());
+    use_context_provider(|| theme)
+}
+
+/// Palette of the active theme (dark when none was provided)
+pub fn use_theme() -> Theme {
+    try_use_context::<Memo<Theme>>().map(|theme| theme()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_vars_declare_every_token() {
+        let vars = Theme::light().css_vars();
+
+        assert!(vars.starts_with("--color-bg-primary: #f5f5fa;"));
+        assert!(vars.contains("--color-text-white: #0f0f23;"));
+        assert_eq!(vars.matches("--color-").count(), 16);
+        assert_ne!(Theme::dark().css_vars(), vars);
+    }
+}
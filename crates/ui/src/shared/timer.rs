@@ -0,0 +1,15 @@
+//! Cross-platform async sleep for UI tasks
+
+use std::time::Duration;
+
+/// Wait for the given duration without blocking the UI
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Wait for the given duration without blocking the UI
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
@@ -16,17 +16,20 @@
 //! ```
 
 use dioxus::prelude::*;
-use prsnl_core::ConnectionStatus;
+use prsnl_core::{ConnectionStatus, SearchResult};
 
 use crate::features::{
     ConversationList, ConversationsService, ConversationsState,
-    ChatScreen,
+    ChatScreen, ChatState, ConnectLinkPrompt, EditableConnectionIndicator, IndicatorPlacement, SearchBar, SearchResults,
+    SearchService, SearchState, SettingsModal, SettingsState, ToastStack, TypingSummary,
 };
+use crate::features::conversations::{ActivityEntry, ACTIVITY_FEED_LIMIT};
+use crate::shared::{use_theme, ConnectionStatusBar, EmptyState, OfflineBanner};
 
 /// Desktop shell with sidebar and main content area
 ///
 /// This component renders a side-by-side layout with:
-/// - A fixed-width sidebar (300px) showing the conversation list
+/// - A fixed-width sidebar (300px, or 240px with `compact_sidebar`) showing the conversation list
 /// - A flexible main content area showing the active chat
 ///
 /// # Example
@@ -40,12 +43,27 @@ use crate::features::{
 /// }
 /// ```
 #[component]
-pub fn DesktopShell() -> Element {
+pub fn DesktopShell(
+    /// Narrower sidebar for tablet-sized viewports
+    #[props(default)]
+    compact_sidebar: bool,
+) -> Element {
     // Get state and services from context
     let conv_state: ConversationsState = use_context();
     let conv_service: ConversationsService = use_context();
+    let chat_state: ChatState = use_context();
+    let search_state: SearchState = use_context();
+    let search_service: SearchService = use_context();
+    let settings_state: SettingsState = use_context();
     let connection_status: Signal<ConnectionStatus> = use_context();
 
+    let motion_class = if settings_state.reduce_motion() { "reduce-motion" } else { "" };
+    let density_class = settings_state.density().class();
+    let theme_vars = use_theme().css_vars();
+
+    // Settings modal, opened from the sidebar gear or the chat's status indicator
+    let mut show_settings = use_signal(|| false);
+
     // Get current conversation ID (if any)
     let current_conv_id = conv_state.current_conversation_id();
 
@@ -64,65 +82,167 @@ pub fn DesktopShell() -> Element {
         }
     };
 
+    let on_open_activity = {
+        let conv_service = conv_service.clone();
+        move |entry: ActivityEntry| match entry.message_id {
+            Some(msg_id) => conv_service.open_message(&entry.conv_id, &msg_id),
+            None => conv_service.select_conversation(&entry.conv_id),
+        }
+    };
+
+    let on_open_search_result = {
+        let conv_service = conv_service.clone();
+        move |result: SearchResult| {
+            search_service.focus_result(&result);
+            conv_service.select_conversation(&result.conv_id);
+        }
+    };
+
+    let on_mark_unread = {
+        let conv_service = conv_service.clone();
+        move |conv_id: String| conv_service.mark_unread(&conv_id)
+    };
+
+    let on_toggle_pin = {
+        let conv_service = conv_service.clone();
+        move |conv_id: String| conv_service.toggle_pin(&conv_id)
+    };
+
+    let on_rename = {
+        let conv_service = conv_service.clone();
+        move |(conv_id, title): (String, String)| conv_service.rename_conversation(&conv_id, &title)
+    };
+
+    let on_archive = {
+        let conv_service = conv_service.clone();
+        move |(conv_id, archived): (String, bool)| conv_service.set_archived(&conv_id, archived)
+    };
+
+    let on_delete = {
+        let conv_service = conv_service.clone();
+        move |conv_id: String| conv_service.delete_conversation(&conv_id)
+    };
+
+    let on_reorder_pinned = {
+        let conv_service = conv_service.clone();
+        move |(conv_id, index): (String, usize)| conv_service.move_pinned(&conv_id, index)
+    };
+
     rsx! {
         div {
-            class: "h-screen h-dvh flex bg-bg-primary text-text-primary font-sans",
+            class: "h-screen h-dvh flex flex-col bg-bg-primary text-text-primary font-sans {motion_class} {density_class}",
+            style: "{theme_vars}",
+
+            OfflineBanner {}
 
-            // Sidebar - conversation list
-            aside {
-                class: "w-sidebar min-w-sidebar border-r border-border flex flex-col bg-bg-secondary",
+            div {
+                class: "flex-1 flex min-h-0",
 
-                // Header with title
-                SidebarHeader {}
+                // Sidebar - conversation list
+                aside {
+                    class: if compact_sidebar {
+                        "w-sidebar-compact min-w-sidebar-compact border-r border-border flex flex-col bg-bg-secondary"
+                    } else {
+                        "w-sidebar min-w-sidebar border-r border-border flex flex-col bg-bg-secondary"
+                    },
 
-                // Real conversation list
-                ConversationList {
-                    conversations: conv_state.sorted_conversations(),
-                    loading: conv_state.is_loading(),
-                    on_select: on_select,
-                    on_new: on_new,
+                    // Header with title and settings button
+                    SidebarHeader {
+                        on_settings: move |_| show_settings.set(true),
+                    }
+
+                    SearchBar {}
+
+                    if search_state.is_active() {
+                        SearchResults { on_open: on_open_search_result }
+                    } else {
+                        // Real conversation list
+                        ConversationList {
+                            conversations: conv_state.sorted_conversations(),
+                            loading: conv_state.is_loading(),
+                            on_select: on_select,
+                            on_new: on_new,
+                            activity: conv_state.recent_activity(&chat_state, ACTIVITY_FEED_LIMIT),
+                            on_open_activity: on_open_activity,
+                            on_reorder_pinned: on_reorder_pinned,
+                            on_mark_unread: on_mark_unread,
+                            on_toggle_pin: on_toggle_pin,
+                            on_rename: on_rename,
+                            on_archive: on_archive,
+                            on_delete: on_delete,
+                            popup_menu: true,
+                            keyboard_nav: true,
+                        }
+                    }
                 }
-            }
 
-            // Main content - chat panel
-            main {
-                class: "flex-1 flex flex-col overflow-hidden bg-bg-primary",
-
-                if let Some(conv_id) = current_conv_id {
-                    // Get conversation title
-                    {
-                        let title = conv_state
-                            .get_conversation(&conv_id)
-                            .map(|c| if c.title.is_empty() { "New Chat".to_string() } else { c.title.clone() })
-                            .unwrap_or_else(|| "Chat".to_string());
-
-                        rsx! {
-                            ChatScreen {
-                                conv_id: conv_id.clone(),
-                                title: title,
-                                status: connection_status.read().clone(),
-                                on_back: move |_| {
-                                    // On desktop, back just deselects (no navigation needed)
-                                    tracing::info!("Back pressed on desktop (no-op)");
-                                },
-                                on_status_tap: move |_| {
-                                    tracing::info!("Status indicator tapped");
-                                },
+                // Main content - chat panel
+                main {
+                    class: "flex-1 flex flex-col overflow-hidden bg-bg-primary",
+
+                    if let Some(conv_id) = current_conv_id {
+                        // Get conversation title
+                        {
+                            let title = conv_state
+                                .get_conversation(&conv_id)
+                                .map(|c| c.display_title())
+                                .unwrap_or_else(|| "Chat".to_string());
+
+                            rsx! {
+                                ChatScreen {
+                                    // Remount per conversation so message hooks follow the switch
+                                    key: "{conv_id}",
+                                    conv_id: conv_id.clone(),
+                                    title: title,
+                                    status: connection_status.read().clone(),
+                                    on_back: move |_| {
+                                        // On desktop, back just deselects (no navigation needed)
+                                        tracing::info!("Back pressed on desktop (no-op)");
+                                    },
+                                    on_status_tap: move |_| show_settings.set(true),
+                                }
                             }
                         }
+                    } else {
+                        // No conversation selected - show placeholder
+                        EmptyState {
+                            icon: "💬",
+                            title: "No conversation selected",
+                            subtitle: "Select a conversation from the sidebar or create a new one",
+                        }
                     }
-                } else {
-                    // No conversation selected - show placeholder
-                    EmptyState {}
                 }
             }
+
+            if settings_state.indicator_placement() == IndicatorPlacement::StatusBar {
+                ConnectionStatusBar {
+                    EditableConnectionIndicator {
+                        status: connection_status(),
+                        open_upward: true,
+                    }
+                }
+            }
+
+            ToastStack {}
+
+            if show_settings() {
+                SettingsModal {
+                    on_close: move |_| show_settings.set(false),
+                }
+            }
+
+            ConnectLinkPrompt {}
         }
     }
 }
 
-/// Sidebar header with title
+/// Sidebar header with title and settings button
 #[component]
-fn SidebarHeader() -> Element {
+fn SidebarHeader(on_settings: EventHandler<()>) -> Element {
+    let settings_state: SettingsState = use_context();
+    let connection_status: Signal<ConnectionStatus> = use_context();
+    let show_status = settings_state.indicator_placement() == IndicatorPlacement::Header;
+
     rsx! {
         div {
             class: "shrink-0 p-4 border-b border-border flex justify-between items-center",
@@ -131,30 +251,20 @@ fn SidebarHeader() -> Element {
                 class: "m-0 text-lg font-semibold text-text-white",
                 "Conversations"
             }
-        }
-    }
-}
 
-/// Empty state when no conversation is selected
-#[component]
-fn EmptyState() -> Element {
-    rsx! {
-        div {
-            class: "flex-1 flex flex-col items-center justify-center text-text-muted",
+            TypingSummary {}
 
-            // Placeholder icon
-            div {
-                class: "text-6xl mb-6 opacity-50",
-                "💬"
+            if show_status {
+                EditableConnectionIndicator {
+                    status: connection_status(),
+                }
             }
 
-            p {
-                class: "text-lg m-0 mb-2 text-text-secondary",
-                "No conversation selected"
-            }
-            p {
-                class: "text-sm m-0",
-                "Select a conversation from the sidebar or create a new one"
+            button {
+                onclick: move |_| on_settings.call(()),
+                class: "bg-transparent border-none text-text-muted cursor-pointer text-lg leading-none p-1 hover:text-text-white",
+                title: "Settings",
+                "⚙"
             }
         }
     }
@@ -8,12 +8,15 @@
 //! - `Chat`: Full-screen chat view with back button to return to list
 
 use dioxus::prelude::*;
-use prsnl_core::ConnectionStatus;
+use prsnl_core::{ConnectionStatus, SearchResult};
 
 use crate::features::{
     ConversationList, ConversationsService, ConversationsState,
-    ChatScreen,
+    ChatScreen, ChatState, ConnectLinkPrompt, EditableConnectionIndicator, IndicatorPlacement, SearchBar, SearchResults,
+    SearchService, SearchState, SettingsModal, SettingsService, SettingsState, ToastStack, TypingSummary,
 };
+use crate::features::conversations::{ActivityEntry, ACTIVITY_FEED_LIMIT};
+use crate::shared::{use_theme, ConnectionIndicator, ConnectionStatusBar, OfflineBanner};
 
 /// Mobile view state
 #[derive(Clone, Debug, PartialEq)]
@@ -55,11 +58,34 @@ pub fn MobileShell() -> Element {
     // Get state and services from context
     let conv_state: ConversationsState = use_context();
     let conv_service: ConversationsService = use_context();
+    let chat_state: ChatState = use_context();
+    let search_state: SearchState = use_context();
+    let search_service: SearchService = use_context();
+    let settings_state: SettingsState = use_context();
+    let settings_service: SettingsService = use_context();
     let connection_status: Signal<ConnectionStatus> = use_context();
 
+    let motion_class = if settings_state.reduce_motion() { "reduce-motion" } else { "" };
+    let density_class = settings_state.density().class();
+    let theme_vars = use_theme().css_vars();
+
     // Local view state for navigation (separate from ConversationsState.view for mobile-specific behavior)
     let mut view = use_signal(MobileView::default);
 
+    // Follow the open conversation when it changes from within a chat
+    // (e.g. "start fresh" moves the user into a newly created conversation)
+    use_effect(move || {
+        if let Some(id) = conv_state.current_conversation_id() {
+            let switched = matches!(
+                &*view.peek(),
+                MobileView::Chat { conversation_id } if *conversation_id != id
+            );
+            if switched {
+                view.set(MobileView::Chat { conversation_id: id });
+            }
+        }
+    });
+
     // Navigation callbacks
     let on_select = {
         let conv_service = conv_service.clone();
@@ -76,6 +102,41 @@ pub fn MobileShell() -> Element {
         }
     };
 
+    let on_open_activity = {
+        let conv_service = conv_service.clone();
+        move |entry: ActivityEntry| {
+            match entry.message_id {
+                Some(msg_id) => conv_service.open_message(&entry.conv_id, &msg_id),
+                None => conv_service.select_conversation(&entry.conv_id),
+            }
+            view.set(MobileView::Chat { conversation_id: entry.conv_id });
+        }
+    };
+
+    let on_open_search_result = {
+        let conv_service = conv_service.clone();
+        move |result: SearchResult| {
+            search_service.focus_result(&result);
+            conv_service.select_conversation(&result.conv_id);
+            view.set(MobileView::Chat { conversation_id: result.conv_id });
+        }
+    };
+
+    let on_mark_unread = {
+        let conv_service = conv_service.clone();
+        move |conv_id: String| conv_service.mark_unread(&conv_id)
+    };
+
+    let on_toggle_pin = {
+        let conv_service = conv_service.clone();
+        move |conv_id: String| conv_service.toggle_pin(&conv_id)
+    };
+
+    let on_reorder_pinned = {
+        let conv_service = conv_service.clone();
+        move |(conv_id, index): (String, usize)| conv_service.move_pinned(&conv_id, index)
+    };
+
     let on_back = {
         let conv_service = conv_service.clone();
         move |_| {
@@ -86,31 +147,46 @@ pub fn MobileShell() -> Element {
 
     rsx! {
         div {
-            class: "h-screen h-dvh flex flex-col bg-bg-primary text-text-primary font-sans",
+            class: "h-screen h-dvh flex flex-col bg-bg-primary text-text-primary font-sans {motion_class} {density_class}",
+            style: "{theme_vars}",
+
+            OfflineBanner {}
 
             match view.read().clone() {
                 MobileView::ConversationList => rsx! {
                     // Header
                     MobileListHeader {}
 
-                    // Real conversation list from features
-                    ConversationList {
-                        conversations: conv_state.sorted_conversations(),
-                        loading: conv_state.is_loading(),
-                        on_select: on_select,
-                        on_new: on_new,
+                    SearchBar {}
+
+                    if search_state.is_active() {
+                        SearchResults { on_open: on_open_search_result }
+                    } else {
+                        // Real conversation list from features
+                        ConversationList {
+                            conversations: conv_state.sorted_conversations(),
+                            loading: conv_state.is_loading(),
+                            on_select: on_select,
+                            on_new: on_new,
+                            activity: conv_state.recent_activity(&chat_state, ACTIVITY_FEED_LIMIT),
+                            on_open_activity: on_open_activity,
+                            on_reorder_pinned: on_reorder_pinned,
+                            on_mark_unread: on_mark_unread,
+                            on_toggle_pin: on_toggle_pin,
+                        }
                     }
                 },
                 MobileView::Chat { conversation_id } => {
                     // Get conversation title
                     let title = conv_state
                         .get_conversation(&conversation_id)
-                        .map(|c| if c.title.is_empty() { "New Chat".to_string() } else { c.title.clone() })
+                        .map(|c| c.display_title())
                         .unwrap_or_else(|| "Chat".to_string());
 
                     rsx! {
                         // Real chat screen from features
                         ChatScreen {
+                            key: "{conversation_id}",
                             conv_id: conversation_id.clone(),
                             title: title,
                             status: connection_status.read().clone(),
@@ -122,13 +198,39 @@ pub fn MobileShell() -> Element {
                     }
                 },
             }
+
+            if settings_state.indicator_placement() == IndicatorPlacement::StatusBar {
+                ConnectionStatusBar {
+                    EditableConnectionIndicator {
+                        status: connection_status(),
+                        open_upward: true,
+                    }
+                }
+            }
+
+            ToastStack {}
+
+            if settings_state.is_modal_open() {
+                SettingsModal {
+                    on_close: move |_| settings_service.close_modal(),
+                }
+            }
+
+            ConnectLinkPrompt {}
         }
     }
 }
 
 /// Header for mobile list view
+///
+/// Tapping the connection indicator opens the settings modal.
 #[component]
 fn MobileListHeader() -> Element {
+    let settings_state: SettingsState = use_context();
+    let settings_service: SettingsService = use_context();
+    let connection_status: Signal<ConnectionStatus> = use_context();
+    let show_status = settings_state.indicator_placement() == IndicatorPlacement::Header;
+
     rsx! {
         header {
             class: "shrink-0 p-4 border-b border-border flex justify-between items-center bg-bg-secondary",
@@ -138,7 +240,14 @@ fn MobileListHeader() -> Element {
                 "Conversations"
             }
 
-            // Connection indicator could go here
+            TypingSummary {}
+
+            if show_status {
+                ConnectionIndicator {
+                    status: connection_status(),
+                    on_tap: move |_| settings_service.open_modal(),
+                }
+            }
         }
     }
 }
@@ -5,33 +5,65 @@
 //!
 //! Platform behavior:
 //! - **Android/iOS**: Always uses MobileShell (navigation-based)
-//! - **Web**: Uses viewport detection at 768px breakpoint
+//! - **Web**: Uses viewport detection at a breakpoint (768px unless configured),
+//!   optionally with a narrower-sidebar tablet layout above it
 //! - **Desktop (Linux/macOS/Windows)**: Uses DesktopShell (side-by-side)
 
+use std::time::Duration;
+
 use dioxus::prelude::*;
 use dioxus::document::Link;
 
+use crate::shared::{use_reconnect_provider, use_theme_provider, RECONNECT_COOLDOWN};
+
 // Conditionally import shells based on what's needed for each platform
 #[cfg(any(target_os = "android", target_os = "ios", target_arch = "wasm32"))]
 use super::MobileShell;
 #[cfg(any(target_arch = "wasm32", all(not(target_os = "android"), not(target_os = "ios"))))]
 use super::DesktopShell;
 
-/// Breakpoint for switching between mobile and desktop layouts (in pixels)
-#[allow(dead_code)]
+/// Default breakpoint for switching between mobile and desktop layouts (in pixels)
 const BREAKPOINT_WIDTH: u32 = 768;
 
+/// Layout picked for a viewport width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutMode {
+    /// One screen at a time (`MobileShell`)
+    Mobile,
+    /// Sidebar layout with a narrower sidebar
+    Tablet,
+    /// Sidebar layout
+    Desktop,
+}
+
+/// Layout for a viewport `width`
+///
+/// Widths below `breakpoint` get the mobile layout. With a
+/// `tablet_breakpoint`, widths from `breakpoint` up to it get the tablet
+/// layout.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+fn layout_for_width(width: u32, breakpoint: u32, tablet_breakpoint: Option<u32>) -> LayoutMode {
+    if width < breakpoint {
+        LayoutMode::Mobile
+    } else if tablet_breakpoint.is_some_and(|tablet| width < tablet) {
+        LayoutMode::Tablet
+    } else {
+        LayoutMode::Desktop
+    }
+}
+
 /// Hook to detect viewport width with automatic updates on resize (Web)
 ///
-/// Returns the current viewport width in pixels.
+/// Returns the current viewport width in pixels, or `fallback` when it
+/// can't be read.
 #[cfg(target_arch = "wasm32")]
-fn use_viewport_width() -> Signal<u32> {
+fn use_viewport_width(fallback: u32) -> Signal<u32> {
     use std::cell::RefCell;
     use std::rc::Rc;
     use wasm_bindgen::closure::Closure;
     use wasm_bindgen::JsCast;
 
-    let width = use_signal(|| get_window_width());
+    let width = use_signal(|| get_window_width().unwrap_or(fallback));
 
     // Set up resize listener on mount
     use_effect(move || {
@@ -49,8 +81,9 @@ fn use_viewport_width() -> Signal<u32> {
 
         // Create closure for resize event - use Fn since we use RefCell
         let closure = Closure::wrap(Box::new(move || {
-            let new_width = get_window_width();
-            width_for_closure.borrow_mut().set(new_width);
+            if let Some(new_width) = get_window_width() {
+                width_for_closure.borrow_mut().set(new_width);
+            }
         }) as Box<dyn Fn()>);
 
         // Add event listener
@@ -73,19 +106,20 @@ fn use_viewport_width() -> Signal<u32> {
 
 /// Get the current window inner width (Web)
 #[cfg(target_arch = "wasm32")]
-fn get_window_width() -> u32 {
+fn get_window_width() -> Option<u32> {
     web_sys::window()
         .and_then(|w| w.inner_width().ok())
         .and_then(|v| v.as_f64())
         .map(|f| f as u32)
-        .unwrap_or(BREAKPOINT_WIDTH) // Default to breakpoint if detection fails
 }
 
 /// Responsive application shell that automatically switches layout based on platform/viewport
 ///
 /// # Platform Behavior
 /// - **Android/iOS**: Always uses `MobileShell` (navigation-based layout)
-/// - **Web**: Uses viewport detection - `MobileShell` for < 768px, `DesktopShell` for >= 768px
+/// - **Web**: Uses viewport detection - `MobileShell` below `breakpoint`
+///   (768px by default), `DesktopShell` from there on; with a
+///   `tablet_breakpoint`, the sidebar is narrower up to that width
 /// - **Desktop (Linux/macOS/Windows)**: Always uses `DesktopShell` (side-by-side layout)
 ///
 /// # Example
@@ -100,8 +134,30 @@ fn get_window_width() -> u32 {
 ///     });
 /// }
 /// ```
+///
+/// Tablets that should get the sidebar from 600px, narrower up to 1024px:
+///
+/// ```rust,ignore
+/// rsx! { ResponsiveApp { breakpoint: 600, tablet_breakpoint: 1024 } }
+/// ```
 #[component]
-pub fn ResponsiveApp() -> Element {
+pub fn ResponsiveApp(
+    /// Viewport width (px) from which the sidebar layout is used (Web)
+    #[props(default = BREAKPOINT_WIDTH)]
+    breakpoint: u32,
+    /// Viewport width (px) up to which the sidebar layout uses a narrower
+    /// sidebar (Web); None skips the tablet layout
+    #[props(default, into)]
+    tablet_breakpoint: Option<u32>,
+    /// How long the reconnect buttons stay disabled after each press
+    #[props(default = RECONNECT_COOLDOWN)]
+    reconnect_cooldown: Duration,
+) -> Element {
+    // Palette for the chosen theme, applied by the shells
+    use_theme_provider();
+    // One manual-reconnect cooldown for every reconnect button
+    use_reconnect_provider(reconnect_cooldown);
+
     // On Android/iOS, always use mobile layout
     #[cfg(any(target_os = "android", target_os = "ios"))]
     {
@@ -116,18 +172,18 @@ pub fn ResponsiveApp() -> Element {
     // On Web, use viewport-based detection
     #[cfg(target_arch = "wasm32")]
     {
-        let width = use_viewport_width();
-        let is_mobile = *width.read() < BREAKPOINT_WIDTH;
+        let width = use_viewport_width(breakpoint);
+        let layout = layout_for_width(*width.read(), breakpoint, tablet_breakpoint);
 
-        tracing::debug!("ResponsiveApp: Web platform, viewport width: {}px, is_mobile: {}", *width.read(), is_mobile);
+        tracing::debug!("ResponsiveApp: Web platform, viewport width: {}px, layout: {:?}", *width.read(), layout);
 
         rsx! {
             // Include Tailwind CSS
             Link { rel: "stylesheet", href: asset!("/assets/tailwind.css") }
-            if is_mobile {
-                MobileShell {}
-            } else {
-                DesktopShell {}
+            match layout {
+                LayoutMode::Mobile => rsx! { MobileShell {} },
+                LayoutMode::Tablet => rsx! { DesktopShell { compact_sidebar: true } },
+                LayoutMode::Desktop => rsx! { DesktopShell {} },
             }
         }
     }
@@ -155,5 +211,20 @@ mod tests {
     #[test]
     fn test_breakpoint_value() {
         assert_eq!(BREAKPOINT_WIDTH, 768);
+        for breakpoint in [BREAKPOINT_WIDTH, 600] {
+            assert_eq!(layout_for_width(breakpoint - 1, breakpoint, None), LayoutMode::Mobile);
+            assert_eq!(layout_for_width(breakpoint, breakpoint, None), LayoutMode::Desktop);
+        }
+    }
+
+    #[test]
+    fn test_tablet_layout_sits_between_the_breakpoints() {
+        assert_eq!(layout_for_width(599, 600, Some(1024)), LayoutMode::Mobile);
+        assert_eq!(layout_for_width(600, 600, Some(1024)), LayoutMode::Tablet);
+        assert_eq!(layout_for_width(1023, 600, Some(1024)), LayoutMode::Tablet);
+        assert_eq!(layout_for_width(1024, 600, Some(1024)), LayoutMode::Desktop);
+        // A tablet breakpoint below the main one leaves no tablet range
+        assert_eq!(layout_for_width(700, 768, Some(700)), LayoutMode::Mobile);
+        assert_eq!(layout_for_width(800, 768, Some(700)), LayoutMode::Desktop);
     }
 }
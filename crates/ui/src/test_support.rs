@@ -0,0 +1,130 @@
+//! Shared helpers for unit tests in the UI crate
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dioxus::dioxus_core::{NoOpMutations, ScopeId, VirtualDom};
+use dioxus::prelude::*;
+use futures::channel::mpsc;
+use prsnl_core::testing::MockTransport;
+use prsnl_core::{AppEvent, EventBus, EventStream, WSClientMessage};
+
+use crate::features::chat::{provide_chat_feature, ChatService, ChatState, DEFAULT_MESSAGE_LIMIT};
+
+/// Run a closure inside a Dioxus runtime so feature state signals can be created and written
+pub fn with_runtime<R>(f: impl FnOnce() -> R) -> R {
+    let dom = VirtualDom::new(|| rsx! {});
+    dom.in_scope(ScopeId::ROOT, f)
+}
+
+/// Poll spawned tasks until the dom has no more work for a short while
+pub async fn settle(dom: &mut VirtualDom) {
+    for _ in 0..100 {
+        if tokio::time::timeout(Duration::from_millis(20), dom.wait_for_work()).await.is_err() {
+            return;
+        }
+        dom.render_immediate(&mut NoOpMutations);
+    }
+}
+
+/// Chat feature running on a `MockTransport` inside its own dom
+pub struct ChatFixture {
+    pub dom: VirtualDom,
+    pub bus: Arc<ChannelEventBus>,
+    pub transport: Arc<MockTransport>,
+    pub state: ChatState,
+    pub service: ChatService,
+}
+
+impl ChatFixture {
+    /// Run a closure inside the fixture's runtime
+    pub fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.dom.in_scope(ScopeId::ROOT, f)
+    }
+
+    /// Let the service and its spawned sends finish
+    pub async fn settle(&mut self) {
+        settle(&mut self.dom).await;
+    }
+}
+
+/// Chat feature subscribed to a live bus, with conversation "native-a" open
+pub fn chat_fixture() -> ChatFixture {
+    let bus = Arc::new(ChannelEventBus::new());
+    let transport = Arc::new(MockTransport::new(bus.clone()));
+    let mut dom = VirtualDom::new(|| rsx! {});
+    dom.rebuild_in_place();
+    let (state, service) = dom.in_scope(ScopeId::ROOT, || {
+        let (mut state, service) = provide_chat_feature(bus.clone(), transport.clone(), DEFAULT_MESSAGE_LIMIT);
+        service.subscribe_to_events();
+        state.set_current_conversation(Some("native-a".to_string()));
+        (state, service)
+    });
+    ChatFixture { dom, bus, transport, state, service }
+}
+
+/// Wire ids of the chat frames sent so far, oldest first
+pub fn sent_chat_ids(transport: &MockTransport) -> Vec<String> {
+    transport
+        .sent_messages()
+        .into_iter()
+        .filter_map(|frame| match frame {
+            WSClientMessage::Chat { id, .. } => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Event bus that records every published event and never delivers to subscribers
+#[derive(Default)]
+pub struct RecordingEventBus {
+    events: Mutex<Vec<AppEvent>>,
+}
+
+impl RecordingEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of all events published so far
+    pub fn published(&self) -> Vec<AppEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl EventBus for RecordingEventBus {
+    fn publish(&self, event: AppEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn subscribe(&self) -> EventStream {
+        Box::pin(futures::stream::empty())
+    }
+}
+
+/// Event bus that delivers every published event to all current subscribers
+#[derive(Default)]
+pub struct ChannelEventBus {
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<AppEvent>>>,
+}
+
+impl ChannelEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventBus for ChannelEventBus {
+    fn publish(&self, event: AppEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+    }
+
+    fn subscribe(&self) -> EventStream {
+        let (tx, rx) = mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(tx);
+        Box::pin(rx)
+    }
+}